@@ -5,10 +5,12 @@ use bitcoin::WPubkeyHash;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use dlc::create_dlc_transactions;
 use dlc::DlcTransactions;
+use dlc::EnumerationPayout;
 use dlc::PartyParams;
 use dlc::Payout;
 use dlc::TxInputInfo;
 use dlc_manager::contract::contract_info::ContractInfo;
+use dlc_manager::contract::enum_descriptor::EnumDescriptor;
 use dlc_manager::contract::numerical_descriptor::DifferenceParams;
 use dlc_manager::contract::numerical_descriptor::NumericalDescriptor;
 use dlc_manager::contract::ContractDescriptor;
@@ -19,6 +21,7 @@ use dlc_manager::payout_curve::PolynomialPayoutCurvePiece;
 use dlc_manager::payout_curve::RoundingInterval;
 use dlc_manager::payout_curve::RoundingIntervals;
 use dlc_messages::oracle_msgs::DigitDecompositionEventDescriptor;
+use dlc_messages::oracle_msgs::EnumEventDescriptor;
 use dlc_messages::oracle_msgs::EventDescriptor;
 use dlc_messages::oracle_msgs::OracleAnnouncement;
 use dlc_messages::oracle_msgs::OracleEvent;
@@ -50,6 +53,8 @@ const THRESHOLD: usize = 2;
 const EVENT_ID: &str = "Test";
 /// The total collateral value locked in the contract.
 const TOTAL_COLLATERAL: u64 = 200000000;
+/// The number of outcomes of the enumeration contract.
+const NB_ENUM_OUTCOMES: usize = 100;
 
 fn max_value() -> u32 {
     BASE.pow(NB_DIGITS as u32) - 1
@@ -125,7 +130,9 @@ fn create_contract_descriptor() -> ContractDescriptor {
             nb_digits: std::iter::repeat(NB_DIGITS)
                 .take(NB_ORACLES)
                 .collect::<Vec<_>>(),
+            oracle_bases: None,
         },
+        outcome_params: None,
         difference_params,
     })
 }
@@ -171,6 +178,50 @@ fn create_contract_info() -> ContractInfo {
     }
 }
 
+fn create_enum_outcomes() -> Vec<String> {
+    (0..NB_ENUM_OUTCOMES).map(|i| i.to_string()).collect()
+}
+
+fn create_enum_oracle_announcements() -> Vec<OracleAnnouncement> {
+    (0..NB_ORACLES).map(|_| {
+            OracleAnnouncement {
+            announcement_signature: Signature::from_str("859833d34b9cbd7c0a898693a289af434c74ad1d65e15c67d1b1d3bf74d9ee85cbd5258da5e91815da9989185c8bc9b026ce6f6598c1b2fb127c1bb1a6bef74a").unwrap(),
+            oracle_public_key: get_schnorr_pubkey(),
+            oracle_event: OracleEvent{
+                event_descriptor: EventDescriptor::EnumEvent(EnumEventDescriptor {
+                outcomes: create_enum_outcomes(),
+            }),
+                oracle_nonces: vec![get_schnorr_pubkey()],
+                event_maturity_epoch: 1234567,
+                event_id: EVENT_ID.to_string(),
+        }}}).collect()
+}
+
+fn create_enum_contract_info() -> ContractInfo {
+    let outcome_payouts = create_enum_outcomes()
+        .into_iter()
+        .enumerate()
+        .map(|(i, outcome)| {
+            let offer = TOTAL_COLLATERAL / (NB_ENUM_OUTCOMES as u64) * (i as u64);
+            EnumerationPayout {
+                outcome,
+                payout: Payout {
+                    offer,
+                    accept: TOTAL_COLLATERAL - offer,
+                },
+            }
+        })
+        .collect();
+    ContractInfo {
+        contract_descriptor: ContractDescriptor::Enum(EnumDescriptor {
+            outcome_payouts,
+            oracle_outcome_mappings: None,
+        }),
+        oracle_announcements: create_enum_oracle_announcements(),
+        threshold: THRESHOLD,
+    }
+}
+
 fn create_txinputinfo_vec() -> Vec<TxInputInfo> {
     let tx_input_info = TxInputInfo {
         outpoint: OutPoint::default(),
@@ -218,14 +269,12 @@ fn offer_seckey() -> SecretKey {
         .unwrap()
 }
 
-/// Benchmark to measure the adaptor signature creation time.
-pub fn sign_bench(c: &mut Criterion) {
-    let contract_info = create_contract_info();
+fn bench_sign(c: &mut Criterion, name: &str, contract_info: ContractInfo) {
     let dlc_transactions = create_transactions(&contract_info.get_payouts(200000000).unwrap());
     let fund_output_value = dlc_transactions.get_fund_output().value;
 
     let seckey = accept_seckey();
-    c.bench_function("sign", |b| {
+    c.bench_function(name, |b| {
         b.iter(|| {
             black_box(
                 contract_info
@@ -244,9 +293,7 @@ pub fn sign_bench(c: &mut Criterion) {
     });
 }
 
-/// Benchmark to measure the adaptor signature verification time.
-pub fn verify_bench(c: &mut Criterion) {
-    let contract_info = create_contract_info();
+fn bench_verify(c: &mut Criterion, name: &str, contract_info: ContractInfo) {
     let dlc_transactions = create_transactions(&contract_info.get_payouts(200000000).unwrap());
     let fund_output_value = dlc_transactions.get_fund_output().value;
 
@@ -264,7 +311,7 @@ pub fn verify_bench(c: &mut Criterion) {
         )
         .unwrap();
     let adaptor_signatures = &adaptor_info.1;
-    c.bench_function("verify", |b| {
+    c.bench_function(name, |b| {
         b.iter(|| {
             black_box(
                 contract_info
@@ -284,9 +331,31 @@ pub fn verify_bench(c: &mut Criterion) {
     });
 }
 
+/// Benchmark to measure the adaptor signature creation time.
+pub fn sign_bench(c: &mut Criterion) {
+    bench_sign(c, "sign", create_contract_info());
+}
+
+/// Benchmark to measure the adaptor signature verification time.
+pub fn verify_bench(c: &mut Criterion) {
+    bench_verify(c, "verify", create_contract_info());
+}
+
+/// Benchmark to measure the adaptor signature creation time for an
+/// enumeration contract.
+pub fn enum_sign_bench(c: &mut Criterion) {
+    bench_sign(c, "enum_sign", create_enum_contract_info());
+}
+
+/// Benchmark to measure the adaptor signature verification time for an
+/// enumeration contract.
+pub fn enum_verify_bench(c: &mut Criterion) {
+    bench_verify(c, "enum_verify", create_enum_contract_info());
+}
+
 criterion_group! {
     name = sign_verify_bench;
     config = Criterion::default().measurement_time(std::time::Duration::new(120, 0)).sample_size(10);
-    targets = sign_bench, verify_bench
+    targets = sign_bench, verify_bench, enum_sign_bench, enum_verify_bench
 }
 criterion_main!(sign_verify_bench);
@@ -0,0 +1,1536 @@
+//! #AsyncManager an asynchronous flavour of the [`crate::manager::Manager`],
+//! for applications whose wallet, blockchain, oracle and storage access is
+//! asynchronous.
+//!
+//! The state transitions of on-chain contracts are shared with the
+//! [`crate::manager::Manager`] through [`crate::contract_updater`], this module
+//! only performing the I/O around them. DLC channels, sub channels and the fee
+//! bumping of closing transactions are not supported and require the
+//! [`crate::manager::Manager`].
+
+use crate::chain_monitor::ChainMonitor;
+use crate::contract::{
+    accepted_contract::AcceptedContract,
+    adaptor_point_cache::{get_announcements_hashes, AdaptorPointCache},
+    contract_info::ContractInfo,
+    contract_input::ContractInput,
+    contract_input::OracleInput,
+    offered_contract::OfferedContract,
+    signed_contract::SignedContract,
+    Contract, FailedAcceptContract, FailedSignContract, FundingInputInfo, PreClosedContract,
+    PreRefundedContract,
+};
+use crate::contract_updater::{
+    accept_contract_with_party_params, verify_accepted_and_sign_contract, FundingSpend,
+};
+use crate::error::Error;
+use crate::events::{Event, EventHandler};
+use crate::manager::ManagerConfig;
+use crate::utils::{build_party_params, get_party_funding_fee};
+use crate::{AsyncBlockchain, AsyncOracle, AsyncStorage, AsyncWallet, ContractId, Time};
+use bitcoin::hashes::hex::ToHex;
+use bitcoin::{Address, OutPoint, Transaction};
+use dlc::{FeeConfig, PartyParams, FUND_TX_BASE_WEIGHT};
+use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation};
+use dlc_messages::{
+    AcceptDlc, CloseAccept, CloseOffer, Message as DlcMessage, OfferDlc, OnChainMessage, SignDlc,
+};
+use log::{error, warn};
+use secp256k1_zkp::{All, PublicKey, Secp256k1, SecretKey, XOnlyPublicKey};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ops::Deref;
+use std::string::ToString;
+use std::sync::Mutex;
+
+/// Used to create and update on-chain DLCs using asynchronous providers.
+pub struct AsyncManager<W: Deref, B: Deref, S: Deref, O: Deref, T: Deref>
+where
+    W::Target: AsyncWallet,
+    B::Target: AsyncBlockchain,
+    S::Target: AsyncStorage,
+    O::Target: AsyncOracle,
+    T::Target: Time,
+{
+    oracles: HashMap<XOnlyPublicKey, O>,
+    wallet: W,
+    blockchain: B,
+    store: S,
+    secp: Secp256k1<All>,
+    time: T,
+    config: ManagerConfig,
+    chain_monitor: Mutex<ChainMonitor>,
+    pending_events: Mutex<Vec<Event>>,
+    adaptor_point_cache: Mutex<AdaptorPointCache>,
+}
+
+macro_rules! get_contract_in_state {
+    ($manager: expr, $contract_id: expr, $state: ident, $peer_id: expr) => {{
+        match $manager.store.get_contract($contract_id).await? {
+            Some(c) => match $peer_id as Option<PublicKey> {
+                Some(p) if c.get_counter_party_id() != p => Err(Error::InvalidParameters(format!(
+                    "Peer {:02x?} is not involved with contract {:02x?}.",
+                    $peer_id, $contract_id
+                ))),
+                _ => match c {
+                    Contract::$state(s) => Ok(s),
+                    _ => Err(Error::InvalidState(format!(
+                        "Invalid state {:?} expected {}.",
+                        c,
+                        stringify!($state),
+                    ))),
+                },
+            },
+            None => Err(Error::InvalidParameters("Unknown contract id.".to_string())),
+        }
+    }};
+}
+
+impl<W: Deref, B: Deref, S: Deref, O: Deref, T: Deref> AsyncManager<W, B, S, O, T>
+where
+    W::Target: AsyncWallet,
+    B::Target: AsyncBlockchain,
+    S::Target: AsyncStorage,
+    O::Target: AsyncOracle,
+    T::Target: Time,
+{
+    /// Create a new AsyncManager struct using the default [`ManagerConfig`],
+    /// loading the adaptor points persisted in the store.
+    pub async fn new(
+        wallet: W,
+        blockchain: B,
+        store: S,
+        oracles: HashMap<XOnlyPublicKey, O>,
+        time: T,
+    ) -> Result<Self, Error> {
+        Self::new_with_config(
+            wallet,
+            blockchain,
+            store,
+            oracles,
+            time,
+            ManagerConfig::default(),
+        )
+        .await
+    }
+
+    /// Create a new AsyncManager struct using the given [`ManagerConfig`],
+    /// loading the adaptor points persisted in the store.
+    pub async fn new_with_config(
+        wallet: W,
+        blockchain: B,
+        store: S,
+        oracles: HashMap<XOnlyPublicKey, O>,
+        time: T,
+        config: ManagerConfig,
+    ) -> Result<Self, Error> {
+        config.validate()?;
+        let chain_monitor = match store.get_chain_monitor().await? {
+            Some(chain_monitor) => chain_monitor,
+            None => ChainMonitor::new(blockchain.get_blockchain_height().await?),
+        };
+        let adaptor_point_cache = AdaptorPointCache::from_points(store.get_adaptor_points().await?);
+
+        Ok(AsyncManager {
+            secp: secp256k1_zkp::Secp256k1::new(),
+            wallet,
+            store,
+            oracles,
+            time,
+            blockchain,
+            config,
+            chain_monitor: Mutex::new(chain_monitor),
+            pending_events: Mutex::new(Vec::new()),
+            adaptor_point_cache: Mutex::new(adaptor_point_cache),
+        })
+    }
+
+    /// Calls `f` with the cache of adaptor points and persists the points that
+    /// `f` added to it. Failing to persist the points is only logged, as they
+    /// can always be recomputed.
+    async fn with_adaptor_point_cache<R>(&self, f: impl FnOnce(&mut AdaptorPointCache) -> R) -> R {
+        let (res, new_points) = {
+            let mut adaptor_point_cache = self.adaptor_point_cache.lock().unwrap();
+            let res = f(&mut adaptor_point_cache);
+            (res, adaptor_point_cache.take_new_points())
+        };
+        if !new_points.is_empty() {
+            if let Err(e) = self.store.upsert_adaptor_points(&new_points).await {
+                warn!("Could not persist the adaptor point cache: {}", e);
+            }
+        }
+        res
+    }
+
+    /// Removes the adaptor points that were computed for contracts that are
+    /// now closed from the cache and from the store. Points only used by
+    /// contracts in accepted state are recomputed if needed.
+    async fn prune_adaptor_point_cache(&self) -> Result<(), Error> {
+        if self.adaptor_point_cache.lock().unwrap().is_empty() {
+            return Ok(());
+        }
+
+        let offered_contracts = self.store.get_contract_offers().await?;
+        let signed_contracts = self.store.get_signed_contracts().await?;
+        let confirmed_contracts = self.store.get_confirmed_contracts().await?;
+        let live_announcements = get_announcements_hashes(
+            offered_contracts
+                .iter()
+                .chain(
+                    signed_contracts
+                        .iter()
+                        .chain(confirmed_contracts.iter())
+                        .map(|c| &c.accepted_contract.offered_contract),
+                )
+                .flat_map(|c| c.contract_info.iter()),
+        );
+
+        let pruned = self
+            .adaptor_point_cache
+            .lock()
+            .unwrap()
+            .prune(&live_announcements);
+        if !pruned.is_empty() {
+            self.store.delete_adaptor_points(&pruned).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies `f` to the [`ChainMonitor`] and persists it if `f` returns true.
+    async fn update_chain_monitor(
+        &self,
+        f: impl FnOnce(&mut ChainMonitor) -> bool,
+    ) -> Result<(), Error> {
+        let updated_chain_monitor = {
+            let mut chain_monitor = self.chain_monitor.lock().unwrap();
+            if f(&mut chain_monitor) {
+                Some(chain_monitor.clone())
+            } else {
+                None
+            }
+        };
+        if let Some(chain_monitor) = updated_chain_monitor {
+            self.store.persist_chain_monitor(&chain_monitor).await?;
+        }
+        Ok(())
+    }
+
+    /// Get the store from the AsyncManager to access contracts.
+    pub fn get_store(&self) -> &S {
+        &self.store
+    }
+
+    /// Return the [`ManagerConfig`] used by this manager.
+    pub fn get_config(&self) -> &ManagerConfig {
+        &self.config
+    }
+
+    /// Returns the [`Event`]s generated since the last call and clears them.
+    /// Note that pending events are not persisted and are lost if the manager
+    /// is dropped before they are retrieved.
+    pub fn get_and_clear_pending_events(&self) -> Vec<Event> {
+        std::mem::take(&mut *self.pending_events.lock().unwrap())
+    }
+
+    /// Passes all the pending [`Event`]s to the given [`EventHandler`] and
+    /// clears them.
+    pub fn process_pending_events<H: Deref>(&self, handler: H)
+    where
+        H::Target: EventHandler,
+    {
+        for event in self.get_and_clear_pending_events() {
+            handler.handle_event(event);
+        }
+    }
+
+    fn push_event(&self, event: Event) {
+        log::debug!("Generated event {:?}", event);
+        self.pending_events.lock().unwrap().push(event);
+    }
+
+    fn push_contract_event(&self, contract: &Contract) {
+        if let Some(event) = Event::from_contract(contract) {
+            self.push_event(event);
+        }
+    }
+
+    /// Function called to pass a DlcMessage to the AsyncManager. Only on-chain
+    /// contract messages are supported.
+    pub async fn on_dlc_message(
+        &self,
+        msg: &DlcMessage,
+        counter_party: PublicKey,
+    ) -> Result<Option<DlcMessage>, Error> {
+        match msg {
+            DlcMessage::OnChain(on_chain) => match on_chain {
+                OnChainMessage::Offer(o) => {
+                    self.on_offer_message(o, counter_party).await?;
+                    Ok(None)
+                }
+                OnChainMessage::Accept(a) => {
+                    Ok(Some(self.on_accept_message(a, &counter_party).await?))
+                }
+                OnChainMessage::Sign(s) => {
+                    self.on_sign_message(s, &counter_party).await?;
+                    Ok(None)
+                }
+                OnChainMessage::CloseOffer(c) => {
+                    self.on_close_offer(c, &counter_party).await?;
+                    Ok(None)
+                }
+                OnChainMessage::CloseAccept(c) => {
+                    self.on_close_accept(c, &counter_party).await?;
+                    Ok(None)
+                }
+            },
+            DlcMessage::Channel(_) => Err(Error::InvalidParameters(
+                "Channel messages not supported".to_string(),
+            )),
+            DlcMessage::SubChannel(_) => Err(Error::InvalidParameters(
+                "SubChannel messages not supported".to_string(),
+            )),
+        }
+    }
+
+    /// Function called to create a new DLC. The offered contract will be stored
+    /// and an OfferDlc message returned.
+    pub async fn send_offer(
+        &self,
+        contract_input: &ContractInput,
+        counter_party: PublicKey,
+    ) -> Result<OfferDlc, Error> {
+        contract_input.validate()?;
+
+        let mut oracle_announcements = Vec::with_capacity(contract_input.contract_infos.len());
+        for contract_info in &contract_input.contract_infos {
+            oracle_announcements.push(
+                self.get_oracle_announcements(&contract_info.oracles)
+                    .await?,
+            );
+        }
+
+        let (party_params, _, funding_inputs_info) = get_party_params(
+            &self.secp,
+            contract_input.offer_collateral,
+            contract_input.fee_rate,
+            &self.wallet,
+            &self.blockchain,
+            true,
+        )
+        .await?;
+
+        let (offered_contract, offer_msg) =
+            crate::contract_updater::offer_contract_with_party_params(
+                contract_input,
+                oracle_announcements,
+                self.config.refund_delay,
+                &counter_party,
+                &party_params,
+                &funding_inputs_info,
+                self.time.unix_time_now(),
+            );
+
+        offered_contract.validate()?;
+
+        self.store.create_contract(&offered_contract).await?;
+
+        Ok(offer_msg)
+    }
+
+    /// Function to call to accept a DLC for which an offer was received.
+    pub async fn accept_contract_offer(
+        &self,
+        contract_id: &ContractId,
+    ) -> Result<(ContractId, PublicKey, AcceptDlc), Error> {
+        let offered_contract =
+            get_contract_in_state!(self, contract_id, Offered, None as Option<PublicKey>)?;
+
+        let counter_party = offered_contract.counter_party;
+
+        let (accept_params, fund_secret_key, funding_inputs) = get_party_params(
+            &self.secp,
+            offered_contract.total_collateral - offered_contract.offer_params.collateral,
+            offered_contract.fee_rate_per_vb,
+            &self.wallet,
+            &self.blockchain,
+            false,
+        )
+        .await?;
+
+        let (accepted_contract, accept_msg) = self
+            .with_adaptor_point_cache(|adaptor_point_cache| {
+                accept_contract_with_party_params(
+                    &self.secp,
+                    adaptor_point_cache,
+                    &offered_contract,
+                    &accept_params,
+                    &fund_secret_key,
+                    &funding_inputs,
+                )
+            })
+            .await?;
+
+        let network = self.blockchain.get_network().await?;
+        self.wallet
+            .import_address(&Address::p2wsh(
+                &accepted_contract.dlc_transactions.funding_script_pubkey,
+                network,
+            ))
+            .await?;
+
+        let contract_id = accepted_contract.get_contract_id();
+
+        self.store
+            .update_contract(&Contract::Accepted(accepted_contract))
+            .await?;
+
+        Ok((contract_id, counter_party, accept_msg))
+    }
+
+    /// Returns a [`CloseOffer`] message to be sent to the counter party of the
+    /// given confirmed on-chain contract, see
+    /// [`crate::manager::Manager::offer_contract_close`].
+    pub async fn offer_contract_close(
+        &self,
+        contract_id: &ContractId,
+        counter_payout: u64,
+        fee_rate_per_vb: u64,
+    ) -> Result<(CloseOffer, PublicKey), Error> {
+        let signed_contract =
+            get_contract_in_state!(self, contract_id, Confirmed, None as Option<PublicKey>)?;
+
+        if signed_contract.channel_id.is_some() {
+            return Err(Error::InvalidParameters(
+                "Contracts within a channel are closed through the channel".to_string(),
+            ));
+        }
+
+        let expiry = self
+            .time
+            .unix_time_now()
+            .saturating_add(self.config.peer_timeout);
+        let close_offer = crate::contract_updater::offer_close(
+            &self.secp,
+            &signed_contract,
+            counter_payout,
+            fee_rate_per_vb,
+            u32::try_from(expiry).unwrap_or(u32::MAX),
+            &self.wallet,
+        )?;
+
+        Ok((
+            close_offer,
+            signed_contract
+                .accepted_contract
+                .offered_contract
+                .counter_party,
+        ))
+    }
+
+    /// Accepts a [`CloseOffer`] received from the counter party of a contract,
+    /// as notified by an [`Event::ContractCloseOffered`], if it has not expired.
+    /// The closing transaction is broadcast and a [`CloseAccept`] message
+    /// returned to be sent to the counter party.
+    pub async fn accept_contract_close(
+        &self,
+        close_offer: &CloseOffer,
+        counter_party: &PublicKey,
+    ) -> Result<CloseAccept, Error> {
+        let signed_contract = get_contract_in_state!(
+            self,
+            &close_offer.contract_id,
+            Confirmed,
+            Some(*counter_party)
+        )?;
+
+        if signed_contract.channel_id.is_some() {
+            return Err(Error::InvalidParameters(
+                "Contracts within a channel are closed through the channel".to_string(),
+            ));
+        }
+
+        let (close_tx, close_accept) = crate::contract_updater::accept_close(
+            &self.secp,
+            &signed_contract,
+            close_offer,
+            self.time.unix_time_now(),
+            &self.wallet,
+        )?;
+
+        let closed_contract = self
+            .close_contract(&signed_contract, close_tx, None)
+            .await?;
+        self.store.update_contract(&closed_contract).await?;
+        self.push_contract_event(&closed_contract);
+
+        Ok(close_accept)
+    }
+
+    /// Function to call to check the state of the currently executing DLCs and
+    /// update them if possible.
+    pub async fn periodic_check(&self) -> Result<(), Error> {
+        self.check_transaction_confirmations().await;
+        self.check_signed_contracts().await?;
+        self.check_funding_spends().await?;
+        self.check_confirmed_contracts().await?;
+        self.check_preclosed_contracts().await?;
+        self.check_prerefunded_contracts().await?;
+        self.prune_adaptor_point_cache().await?;
+
+        Ok(())
+    }
+
+    /// Records the confirmed transactions spending the transaction outputs
+    /// watched by the [`ChainMonitor`]. Only funding outputs of on-chain
+    /// contracts are watched by the `AsyncManager`.
+    async fn check_transaction_confirmations(&self) {
+        let txos = self.chain_monitor.lock().unwrap().get_watched_txos();
+
+        for txo in txos {
+            let (confirmations, txid) = match self.blockchain.get_txo_confirmations(&txo).await {
+                Ok(Some((confirmations, txid))) => (confirmations, txid),
+                Ok(None) => continue,
+                Err(e) => {
+                    log::error!("Failed to get transaction confirmations for {txo}: {e}");
+                    continue;
+                }
+            };
+
+            if confirmations > 0 {
+                let tx = match self.blockchain.get_transaction(&txid).await {
+                    Ok(tx) => tx,
+                    Err(e) => {
+                        log::error!("Failed to get transaction for {txid}: {e}");
+                        continue;
+                    }
+                };
+
+                self.chain_monitor.lock().unwrap().confirm_txo(&txo, tx);
+            }
+        }
+    }
+
+    async fn on_offer_message(
+        &self,
+        offered_message: &OfferDlc,
+        counter_party: PublicKey,
+    ) -> Result<(), Error> {
+        let (min_refund_delay, max_refund_delay) = self.config.get_refund_delay_range()?;
+        offered_message.validate(&self.secp, min_refund_delay, max_refund_delay)?;
+        let contract: OfferedContract =
+            OfferedContract::try_from_offer_dlc(offered_message, counter_party)?;
+        contract.validate()?;
+
+        if self.store.get_contract(&contract.id).await?.is_some() {
+            return Err(Error::InvalidParameters(
+                "Contract with identical id already exists".to_string(),
+            ));
+        }
+
+        self.store.create_contract(&contract).await?;
+
+        Ok(())
+    }
+
+    async fn on_accept_message(
+        &self,
+        accept_msg: &AcceptDlc,
+        counter_party: &PublicKey,
+    ) -> Result<DlcMessage, Error> {
+        let offered_contract = get_contract_in_state!(
+            self,
+            &accept_msg.temporary_contract_id,
+            Offered,
+            Some(*counter_party)
+        )?;
+
+        let res = self
+            .with_adaptor_point_cache(|adaptor_point_cache| {
+                verify_accepted_and_sign_contract(
+                    &self.secp,
+                    adaptor_point_cache,
+                    &offered_contract,
+                    accept_msg,
+                    &self.wallet,
+                )
+            })
+            .await;
+        let (signed_contract, signed_msg) = match res {
+            Ok(contract) => contract,
+            Err(e) => {
+                return self
+                    .accept_fail_on_error(offered_contract, accept_msg.clone(), e)
+                    .await
+            }
+        };
+
+        let network = self.blockchain.get_network().await?;
+        self.wallet
+            .import_address(&Address::p2wsh(
+                &signed_contract
+                    .accepted_contract
+                    .dlc_transactions
+                    .funding_script_pubkey,
+                network,
+            ))
+            .await?;
+
+        self.store
+            .update_contract(&Contract::Signed(signed_contract))
+            .await?;
+
+        Ok(DlcMessage::OnChain(OnChainMessage::Sign(signed_msg)))
+    }
+
+    async fn on_sign_message(
+        &self,
+        sign_message: &SignDlc,
+        peer_id: &PublicKey,
+    ) -> Result<(), Error> {
+        let accepted_contract =
+            get_contract_in_state!(self, &sign_message.contract_id, Accepted, Some(*peer_id))?;
+
+        let res = self
+            .with_adaptor_point_cache(|adaptor_point_cache| {
+                crate::contract_updater::verify_signed_contract(
+                    &self.secp,
+                    adaptor_point_cache,
+                    &accepted_contract,
+                    sign_message,
+                    &self.wallet,
+                )
+            })
+            .await;
+        let (signed_contract, fund_tx) = match res {
+            Ok(contract) => contract,
+            Err(e) => {
+                return self
+                    .sign_fail_on_error(accepted_contract, sign_message.clone(), e)
+                    .await
+            }
+        };
+
+        self.store
+            .update_contract(&Contract::Signed(signed_contract))
+            .await?;
+
+        self.blockchain.send_transaction(&fund_tx).await?;
+
+        Ok(())
+    }
+
+    async fn on_close_offer(
+        &self,
+        close_offer: &CloseOffer,
+        peer_id: &PublicKey,
+    ) -> Result<(), Error> {
+        let signed_contract =
+            get_contract_in_state!(self, &close_offer.contract_id, Confirmed, Some(*peer_id))?;
+
+        if signed_contract.channel_id.is_some() {
+            return Err(Error::InvalidParameters(
+                "Received close offer for a contract within a channel".to_string(),
+            ));
+        }
+
+        crate::contract_updater::verify_close_offer(
+            &self.secp,
+            &signed_contract,
+            close_offer,
+            self.time.unix_time_now(),
+        )?;
+
+        self.push_event(Event::ContractCloseOffered {
+            contract_id: close_offer.contract_id,
+            counter_party: *peer_id,
+            close_offer: close_offer.clone(),
+        });
+
+        Ok(())
+    }
+
+    async fn on_close_accept(
+        &self,
+        close_accept: &CloseAccept,
+        peer_id: &PublicKey,
+    ) -> Result<(), Error> {
+        let signed_contract =
+            get_contract_in_state!(self, &close_accept.contract_id, Confirmed, Some(*peer_id))?;
+
+        let close_tx =
+            crate::contract_updater::on_close_accept(&self.secp, &signed_contract, close_accept)?;
+
+        // The accepting party broadcasts the closing transaction itself, so
+        // failing to do so here is expected.
+        if let Err(e) = self.blockchain.send_transaction(&close_tx).await {
+            warn!(
+                "Could not broadcast closing transaction {} of contract {}: {}",
+                close_tx.txid(),
+                close_accept.contract_id.to_hex(),
+                e
+            );
+        }
+
+        let preclosed_contract = Contract::PreClosed(PreClosedContract {
+            signed_contract,
+            attestations: None,
+            signed_cet: close_tx,
+        });
+        self.store.update_contract(&preclosed_contract).await?;
+        self.push_contract_event(&preclosed_contract);
+
+        Ok(())
+    }
+
+    async fn get_oracle_announcements(
+        &self,
+        oracle_inputs: &OracleInput,
+    ) -> Result<Vec<OracleAnnouncement>, Error> {
+        let mut announcements = Vec::new();
+        for pubkey in &oracle_inputs.public_keys {
+            let oracle = self
+                .oracles
+                .get(pubkey)
+                .ok_or_else(|| Error::InvalidParameters("Unknown oracle public key".to_string()))?;
+            announcements.push(
+                oracle
+                    .get_announcement(oracle_inputs.get_event_id(pubkey))
+                    .await?,
+            );
+        }
+
+        Ok(announcements)
+    }
+
+    async fn sign_fail_on_error<R>(
+        &self,
+        accepted_contract: AcceptedContract,
+        sign_message: SignDlc,
+        e: Error,
+    ) -> Result<R, Error> {
+        error!("Error in on_sign {}", e);
+        self.store
+            .update_contract(&Contract::FailedSign(FailedSignContract {
+                accepted_contract,
+                sign_message,
+                error_message: e.to_string(),
+            }))
+            .await?;
+        Err(e)
+    }
+
+    async fn accept_fail_on_error<R>(
+        &self,
+        offered_contract: OfferedContract,
+        accept_message: AcceptDlc,
+        e: Error,
+    ) -> Result<R, Error> {
+        error!("Error in on_accept {}", e);
+        self.store
+            .update_contract(&Contract::FailedAccept(FailedAcceptContract {
+                offered_contract,
+                accept_message,
+                error_message: e.to_string(),
+            }))
+            .await?;
+        Err(e)
+    }
+
+    async fn check_signed_contract(&self, contract: &SignedContract) -> Result<(), Error> {
+        let confirmations = self
+            .blockchain
+            .get_transaction_confirmations(&contract.accepted_contract.dlc_transactions.fund.txid())
+            .await?;
+        if confirmations >= self.config.nb_confirmations {
+            self.store
+                .update_contract(&Contract::Confirmed(contract.clone()))
+                .await?;
+            self.push_event(Event::ContractConfirmed {
+                contract_id: contract.accepted_contract.get_contract_id(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn check_signed_contracts(&self) -> Result<(), Error> {
+        for c in self.store.get_signed_contracts().await? {
+            if let Err(e) = self.check_signed_contract(&c).await {
+                error!(
+                    "Error checking confirmed contract {}: {}",
+                    c.accepted_contract.get_contract_id_string(),
+                    e
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_confirmed_contracts(&self) -> Result<(), Error> {
+        for c in self.store.get_confirmed_contracts().await? {
+            // Confirmed contracts from channel are handled by the `Manager`.
+            if c.channel_id.is_some() {
+                continue;
+            }
+            if let Err(e) = self.watch_funding_output(&c).await {
+                error!(
+                    "Error watching funding output of contract {}: {}",
+                    c.accepted_contract.get_contract_id_string(),
+                    e
+                )
+            }
+            if let Err(e) = self.check_confirmed_contract(&c).await {
+                error!(
+                    "Error checking confirmed contract {}: {}",
+                    c.accepted_contract.get_contract_id_string(),
+                    e
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn watch_funding_output(&self, contract: &SignedContract) -> Result<(), Error> {
+        let dlc_transactions = &contract.accepted_contract.dlc_transactions;
+        let outpoint = OutPoint {
+            txid: dlc_transactions.fund.txid(),
+            vout: dlc_transactions.get_fund_output_index() as u32,
+        };
+
+        self.update_chain_monitor(|chain_monitor| {
+            chain_monitor.add_funding_txo(outpoint, contract.accepted_contract.get_contract_id())
+        })
+        .await
+    }
+
+    async fn check_funding_spends(&self) -> Result<(), Error> {
+        let funding_spends = self
+            .chain_monitor
+            .lock()
+            .unwrap()
+            .confirmed_funding_spends();
+
+        for (outpoint, tx, contract_id) in funding_spends {
+            if let Err(e) = self.on_funding_spent(&contract_id, &tx).await {
+                error!(
+                    "Error processing spending of funding output of contract {}: {}",
+                    contract_id.to_hex(),
+                    e
+                );
+                continue;
+            }
+
+            self.update_chain_monitor(|chain_monitor| {
+                chain_monitor.remove_txo(&outpoint);
+                true
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Updates the state of an on-chain contract whose funding output was spent by
+    /// the given transaction, in case it was not spent by the local party.
+    async fn on_funding_spent(
+        &self,
+        contract_id: &ContractId,
+        tx: &Transaction,
+    ) -> Result<(), Error> {
+        let signed_contract = match self
+            .store
+            .get_contract(contract_id)
+            .await?
+            .and_then(|c| crate::contract_updater::get_contract_spent_by(c, tx))
+        {
+            Some(c) => c,
+            // Spent by our own CET or refund transaction, nothing to do.
+            None => return Ok(()),
+        };
+
+        let txid = tx.txid();
+
+        let attestations = match crate::contract_updater::get_funding_spend(&signed_contract, tx) {
+            FundingSpend::Refund => {
+                log::info!(
+                    "Refund transaction {} broadcast by counter party for contract {}",
+                    txid,
+                    contract_id.to_hex()
+                );
+                let refunded_contract = self.refund_contract(&signed_contract, tx.clone()).await?;
+                self.store.update_contract(&refunded_contract).await?;
+                self.push_contract_event(&refunded_contract);
+                return Ok(());
+            }
+            FundingSpend::CollaborativeClose => {
+                log::info!(
+                    "Contract {} collaboratively closed by transaction {}",
+                    contract_id.to_hex(),
+                    txid
+                );
+                None
+            }
+            FundingSpend::Cet => {
+                log::info!(
+                    "CET {} broadcast by counter party for contract {}",
+                    txid,
+                    contract_id.to_hex()
+                );
+                match crate::contract_updater::recover_attestation_from_cet(
+                    &self.secp,
+                    &signed_contract,
+                    tx,
+                ) {
+                    Some(attestation) => Some(vec![attestation]),
+                    // The CET was decrypted using several oracle signatures, find
+                    // out which of the ones published by the oracles were used.
+                    None => crate::contract_updater::verify_cet_attestations(
+                        &signed_contract,
+                        tx,
+                        &self.get_all_oracle_attestations(&signed_contract).await,
+                    ),
+                }
+            }
+        };
+
+        let closed_contract = self
+            .close_contract(&signed_contract, tx.clone(), attestations)
+            .await?;
+        self.store.update_contract(&closed_contract).await?;
+        self.push_contract_event(&closed_contract);
+
+        Ok(())
+    }
+
+    /// Returns the attestations available from the oracles of the given contract
+    /// info whose event has matured, together with the index of the oracle in
+    /// the announcements of the contract info.
+    async fn get_oracle_attestations(
+        &self,
+        contract_info: &ContractInfo,
+    ) -> Vec<(usize, OracleAttestation)> {
+        let mut attestations = Vec::new();
+        for (i, announcement) in crate::contract_updater::get_matured_announcements(
+            contract_info,
+            self.time.unix_time_now(),
+        ) {
+            let oracle = match self.oracles.get(&announcement.oracle_public_key) {
+                Some(oracle) => oracle,
+                None => continue,
+            };
+            if let Ok(attestation) = oracle
+                .get_attestation(&announcement.oracle_event.event_id)
+                .await
+            {
+                attestations.push((i, attestation));
+            }
+        }
+        attestations
+    }
+
+    /// Returns the attestations available for each of the contract infos of the
+    /// given contract, see [`Self::get_oracle_attestations`].
+    async fn get_all_oracle_attestations(
+        &self,
+        contract: &SignedContract,
+    ) -> Vec<Vec<(usize, OracleAttestation)>> {
+        let mut attestations = Vec::new();
+        for contract_info in &contract.accepted_contract.offered_contract.contract_info {
+            attestations.push(self.get_oracle_attestations(contract_info).await);
+        }
+        attestations
+    }
+
+    async fn check_confirmed_contract(&self, contract: &SignedContract) -> Result<(), Error> {
+        let closable_contract_info = crate::contract_updater::get_closable_contract_info(
+            contract,
+            self.get_all_oracle_attestations(contract).await,
+        );
+        if let Some((contract_info, adaptor_info, attestations)) = closable_contract_info {
+            let cet = crate::contract_updater::get_signed_cet(
+                &self.secp,
+                contract,
+                contract_info,
+                adaptor_info,
+                &attestations,
+                &self.wallet,
+            )?;
+            match self
+                .close_contract(
+                    contract,
+                    cet,
+                    Some(attestations.iter().map(|x| x.1.clone()).collect()),
+                )
+                .await
+            {
+                Ok(closed_contract) => {
+                    self.store.update_contract(&closed_contract).await?;
+                    self.push_contract_event(&closed_contract);
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to close contract {}: {}",
+                        contract.accepted_contract.get_contract_id_string(),
+                        e
+                    );
+                    return Err(e);
+                }
+            }
+        }
+
+        self.check_refund(contract).await?;
+
+        Ok(())
+    }
+
+    async fn check_preclosed_contracts(&self) -> Result<(), Error> {
+        for c in self.store.get_preclosed_contracts().await? {
+            if let Err(e) = self.check_preclosed_contract(&c).await {
+                error!(
+                    "Error checking pre-closed contract {}: {}",
+                    c.signed_contract.accepted_contract.get_contract_id_string(),
+                    e
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_preclosed_contract(&self, contract: &PreClosedContract) -> Result<(), Error> {
+        let confirmations = self
+            .blockchain
+            .get_transaction_confirmations(&contract.signed_cet.txid())
+            .await?;
+        if let Some(closed_contract) = crate::contract_updater::on_preclosed_confirmations(
+            contract,
+            confirmations,
+            self.config.nb_confirmations,
+        ) {
+            self.store.update_contract(&closed_contract).await?;
+            self.push_contract_event(&closed_contract);
+        }
+
+        Ok(())
+    }
+
+    async fn close_contract(
+        &self,
+        contract: &SignedContract,
+        signed_cet: Transaction,
+        attestations: Option<Vec<OracleAttestation>>,
+    ) -> Result<Contract, Error> {
+        let confirmations = self
+            .blockchain
+            .get_transaction_confirmations(&signed_cet.txid())
+            .await?;
+
+        if confirmations < 1 {
+            // If this fails because another transaction spending the funding
+            // output is already in mempool or blockchain, it will be picked up
+            // by `check_funding_spends` once confirmed.
+            self.blockchain.send_transaction(&signed_cet).await?;
+        }
+
+        Ok(crate::contract_updater::get_closing_contract(
+            contract,
+            signed_cet,
+            attestations,
+            confirmations,
+            self.config.nb_confirmations,
+        ))
+    }
+
+    async fn check_refund(&self, contract: &SignedContract) -> Result<(), Error> {
+        if crate::contract_updater::is_refundable(contract, self.time.unix_time_now()) {
+            let signed_refund =
+                crate::contract_updater::get_signed_refund(&self.secp, contract, &self.wallet)?;
+            let refunded_contract = self.refund_contract(contract, signed_refund).await?;
+            self.store.update_contract(&refunded_contract).await?;
+            self.push_contract_event(&refunded_contract);
+        }
+
+        Ok(())
+    }
+
+    async fn refund_contract(
+        &self,
+        contract: &SignedContract,
+        signed_refund: Transaction,
+    ) -> Result<Contract, Error> {
+        let confirmations = self
+            .blockchain
+            .get_transaction_confirmations(&signed_refund.txid())
+            .await?;
+
+        if confirmations < 1 {
+            self.blockchain.send_transaction(&signed_refund).await?;
+        }
+
+        Ok(crate::contract_updater::get_refunding_contract(
+            contract,
+            signed_refund,
+            confirmations,
+            self.config.nb_confirmations,
+        ))
+    }
+
+    async fn check_prerefunded_contracts(&self) -> Result<(), Error> {
+        for c in self.store.get_prerefunded_contracts().await? {
+            if let Err(e) = self.check_prerefunded_contract(&c).await {
+                error!(
+                    "Error checking pre-refunded contract {}: {}",
+                    c.signed_contract.accepted_contract.get_contract_id_string(),
+                    e
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_prerefunded_contract(
+        &self,
+        contract: &PreRefundedContract,
+    ) -> Result<(), Error> {
+        let refund_txid = contract.signed_refund.txid();
+        let confirmations = self
+            .blockchain
+            .get_transaction_confirmations(&refund_txid)
+            .await?;
+
+        if confirmations < 1 {
+            // The refund transaction might have been evicted from the mempool.
+            if let Err(e) = self
+                .blockchain
+                .send_transaction(&contract.signed_refund)
+                .await
+            {
+                warn!(
+                    "Failed to re-broadcast refund transaction {}: {}",
+                    refund_txid, e
+                );
+            }
+        }
+
+        if let Some(refunded_contract) = crate::contract_updater::on_prerefunded_confirmations(
+            contract,
+            confirmations,
+            self.config.nb_confirmations,
+        ) {
+            self.store.update_contract(&refunded_contract).await?;
+            self.push_contract_event(&refunded_contract);
+        }
+
+        Ok(())
+    }
+}
+
+/// Asynchronous version of [`crate::utils::get_party_params`], restricted to
+/// parties funding an on-chain contract with evenly split fees.
+async fn get_party_params<W: Deref, B: Deref>(
+    secp: &Secp256k1<All>,
+    own_collateral: u64,
+    fee_rate: u64,
+    wallet: &W,
+    blockchain: &B,
+    is_offer: bool,
+) -> Result<(PartyParams, SecretKey, Vec<FundingInputInfo>), Error>
+where
+    W::Target: AsyncWallet,
+    B::Target: AsyncBlockchain,
+{
+    let funding_privkey = wallet.get_new_secret_key().await?;
+    let funding_pubkey = PublicKey::from_secret_key(secp, &funding_privkey);
+
+    let payout_spk = wallet.get_new_address().await?.script_pubkey();
+    let change_spk = wallet.get_new_address().await?.script_pubkey();
+
+    let tx_fees = get_party_funding_fee(fee_rate, &payout_spk, 0, is_offer, FeeConfig::EvenSplit)?;
+
+    let mut utxos = Vec::new();
+    for utxo in wallet
+        .get_utxos_for_amount(
+            own_collateral + tx_fees,
+            Some(fee_rate),
+            (FUND_TX_BASE_WEIGHT / 2) as u64,
+            true,
+        )
+        .await?
+    {
+        let prev_tx = blockchain.get_transaction(&utxo.outpoint.txid).await?;
+        utxos.push((utxo, prev_tx));
+    }
+
+    let (party_params, funding_inputs_info) = build_party_params(
+        funding_pubkey,
+        own_collateral,
+        payout_spk,
+        change_spk,
+        utxos,
+    )?;
+
+    Ok((party_params, funding_privkey, funding_inputs_info))
+}
+
+#[cfg(test)]
+mod test {
+    use dlc::{EnumerationPayout, Payout};
+    use dlc_messages::{
+        oracle_msgs::{EnumEventDescriptor, EventDescriptor, OracleAttestation},
+        Message, OnChainMessage,
+    };
+    use mocks::{
+        dlc_manager::{
+            async_manager::AsyncManager,
+            contract::{
+                contract_input::{ContractInput, ContractInputInfo, OracleInput},
+                enum_descriptor::EnumDescriptor,
+                Contract, ContractDescriptor,
+            },
+            events::Event,
+            manager::ManagerConfig,
+            AsyncStorage, ContractId, Oracle, SyncProvider,
+        },
+        memory_storage_provider::MemoryStorage,
+        mock_blockchain::{MockBlockchain, MockBroadcaster},
+        mock_oracle_provider::MockOracle,
+        mock_time::MockTime,
+        mock_wallet::MockWallet,
+    };
+    use secp256k1_zkp::{PublicKey, SecretKey, SECP256K1};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    const EVENT_ID: &str = "event";
+    const EVENT_MATURITY: u32 = 1623133104;
+    const COLLATERAL: u64 = 1000000;
+    const NB_CONFIRMATIONS: u32 = 6;
+
+    type TestBlockchain = MockBlockchain<Arc<MockBroadcaster>>;
+
+    type TestManager = AsyncManager<
+        Arc<SyncProvider<Arc<MockWallet>>>,
+        Arc<SyncProvider<Arc<TestBlockchain>>>,
+        Arc<SyncProvider<Arc<MemoryStorage>>>,
+        Arc<SyncProvider<Arc<MockOracle>>>,
+        Arc<MockTime>,
+    >;
+
+    async fn create_manager(
+        blockchain: &Arc<TestBlockchain>,
+        oracles: &[Arc<MockOracle>],
+        config: ManagerConfig,
+    ) -> TestManager {
+        let oracles = oracles
+            .iter()
+            .map(|x| (x.get_public_key(), Arc::new(SyncProvider(x.clone()))))
+            .collect::<HashMap<_, _>>();
+        AsyncManager::new_with_config(
+            Arc::new(SyncProvider(Arc::new(MockWallet::new(blockchain, 10)))),
+            Arc::new(SyncProvider(blockchain.clone())),
+            Arc::new(SyncProvider(Arc::new(MemoryStorage::new()))),
+            oracles,
+            Arc::new(MockTime {}),
+            config,
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn get_manager() -> TestManager {
+        let blockchain = Arc::new(MockBlockchain::new(Arc::new(MockBroadcaster {})));
+        let oracles = (0..5)
+            .map(|_| Arc::new(MockOracle::new()))
+            .collect::<Vec<_>>();
+
+        mocks::mock_time::set_time(0);
+
+        create_manager(&blockchain, &oracles, ManagerConfig::default()).await
+    }
+
+    /// Returns a shared blockchain and two managers using it, the first one
+    /// being the one offering contracts to the second one.
+    async fn get_counter_parties(
+        oracle: &Arc<MockOracle>,
+    ) -> (Arc<TestBlockchain>, TestManager, TestManager) {
+        let blockchain = Arc::new(MockBlockchain::new(Arc::new(MockBroadcaster {})));
+        let config = || ManagerConfig {
+            nb_confirmations: NB_CONFIRMATIONS,
+            ..Default::default()
+        };
+        let alice = create_manager(&blockchain, &[oracle.clone()], config()).await;
+        let bob = create_manager(&blockchain, &[oracle.clone()], config()).await;
+
+        mocks::mock_time::set_time(EVENT_MATURITY as u64 - 1);
+
+        (blockchain, alice, bob)
+    }
+
+    fn pubkey() -> PublicKey {
+        "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166"
+            .parse()
+            .unwrap()
+    }
+
+    fn node_id(seed: u8) -> PublicKey {
+        PublicKey::from_secret_key(SECP256K1, &SecretKey::from_slice(&[seed; 32]).unwrap())
+    }
+
+    fn get_oracle(outcome: Option<&str>) -> Arc<MockOracle> {
+        let mut oracle = MockOracle::new();
+        let event = EnumEventDescriptor {
+            outcomes: vec!["a".to_string(), "b".to_string()],
+        };
+        oracle.add_event(EVENT_ID, &EventDescriptor::EnumEvent(event), EVENT_MATURITY);
+        if let Some(outcome) = outcome {
+            oracle.add_attestation(EVENT_ID, &[outcome.to_string()]);
+        }
+        Arc::new(oracle)
+    }
+
+    /// Returns a contract paying the whole collateral to the offer party if
+    /// "a" is attested, and to the accept party if "b" is.
+    fn get_contract_input(oracle: &MockOracle) -> ContractInput {
+        let outcome_payouts = vec![
+            EnumerationPayout {
+                outcome: "a".to_string(),
+                payout: Payout {
+                    offer: 2 * COLLATERAL,
+                    accept: 0,
+                },
+            },
+            EnumerationPayout {
+                outcome: "b".to_string(),
+                payout: Payout {
+                    offer: 0,
+                    accept: 2 * COLLATERAL,
+                },
+            },
+        ];
+        ContractInput {
+            offer_collateral: COLLATERAL,
+            accept_collateral: COLLATERAL,
+            fee_rate: 2,
+            contract_infos: vec![ContractInputInfo {
+                contract_descriptor: ContractDescriptor::Enum(EnumDescriptor {
+                    outcome_payouts,
+                    oracle_outcome_mappings: None,
+                }),
+                oracles: OracleInput {
+                    public_keys: vec![oracle.get_public_key()],
+                    event_id: EVENT_ID.to_string(),
+                    event_ids: Vec::new(),
+                    threshold: 1,
+                },
+            }],
+        }
+    }
+
+    async fn periodic_check(managers: &[&TestManager]) {
+        for manager in managers {
+            manager
+                .periodic_check()
+                .await
+                .expect("To be able to do the periodic check");
+        }
+    }
+
+    /// Checks that exactly the `expected` events are pending and that they are
+    /// cleared once retrieved.
+    fn assert_events(manager: &TestManager, expected: &[Event]) {
+        assert_eq!(expected, manager.get_and_clear_pending_events().as_slice());
+        assert!(manager.get_and_clear_pending_events().is_empty());
+    }
+
+    async fn get_contract(manager: &TestManager, contract_id: &ContractId) -> Contract {
+        manager
+            .get_store()
+            .get_contract(contract_id)
+            .await
+            .unwrap()
+            .expect("To find the contract")
+    }
+
+    /// Establishes a contract through the offer, accept and sign messages and
+    /// confirms its fund transaction.
+    async fn get_confirmed_contract(
+        blockchain: &TestBlockchain,
+        alice: &TestManager,
+        bob: &TestManager,
+        contract_input: &ContractInput,
+    ) -> ContractId {
+        let offer = alice
+            .send_offer(contract_input, node_id(2))
+            .await
+            .expect("To create an offer");
+        bob.on_dlc_message(
+            &Message::OnChain(OnChainMessage::Offer(offer.clone())),
+            node_id(1),
+        )
+        .await
+        .expect("To process the offer");
+        let (contract_id, _, accept) = bob
+            .accept_contract_offer(&offer.temporary_contract_id)
+            .await
+            .expect("To accept the offer");
+        let sign = alice
+            .on_dlc_message(
+                &Message::OnChain(OnChainMessage::Accept(accept)),
+                node_id(2),
+            )
+            .await
+            .expect("To process the accept")
+            .expect("To get a sign message");
+        bob.on_dlc_message(&sign, node_id(1))
+            .await
+            .expect("To process the sign");
+
+        assert!(matches!(
+            get_contract(alice, &contract_id).await,
+            Contract::Signed(_)
+        ));
+        assert!(matches!(
+            get_contract(bob, &contract_id).await,
+            Contract::Signed(_)
+        ));
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[alice, bob]).await;
+        assert_events(alice, &[Event::ContractConfirmed { contract_id }]);
+        assert_events(bob, &[Event::ContractConfirmed { contract_id }]);
+
+        contract_id
+    }
+
+    /// Returns the attestations of the given closed contract.
+    fn get_closed_attestations(contract: Contract) -> Option<Vec<OracleAttestation>> {
+        match contract {
+            Contract::Closed(c) => c.attestations,
+            c => panic!("Unexpected contract state {:?}", c),
+        }
+    }
+
+    #[tokio::test]
+    async fn reject_offer_with_existing_contract_id() {
+        let offer_message = Message::OnChain(OnChainMessage::Offer(
+            serde_json::from_str(include_str!("../test_inputs/offer_contract.json")).unwrap(),
+        ));
+
+        let manager = get_manager().await;
+
+        manager
+            .on_dlc_message(&offer_message, pubkey())
+            .await
+            .expect("To accept the first offer message");
+
+        manager
+            .on_dlc_message(&offer_message, pubkey())
+            .await
+            .expect_err("To reject the second offer message");
+    }
+
+    #[tokio::test]
+    async fn contract_execution() {
+        let oracle = get_oracle(Some("a"));
+        let (blockchain, alice, bob) = get_counter_parties(&oracle).await;
+        let contract_id =
+            get_confirmed_contract(&blockchain, &alice, &bob, &get_contract_input(&oracle)).await;
+
+        mocks::mock_time::set_time(EVENT_MATURITY as u64);
+        periodic_check(&[&alice]).await;
+        assert!(matches!(
+            get_contract(&alice, &contract_id).await,
+            Contract::PreClosed(_)
+        ));
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        // Bob learns about the CET through the spending of the funding output
+        // and recovers the attestation from it.
+        periodic_check(&[&alice, &bob]).await;
+        let expected = Some(vec![oracle.get_attestation(EVENT_ID).unwrap()]);
+        assert_eq!(
+            expected,
+            get_closed_attestations(get_contract(&alice, &contract_id).await)
+        );
+        assert_eq!(
+            expected,
+            get_closed_attestations(get_contract(&bob, &contract_id).await)
+        );
+    }
+
+    #[tokio::test]
+    async fn contract_collaborative_close() {
+        let oracle = get_oracle(None);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle).await;
+        let contract_id =
+            get_confirmed_contract(&blockchain, &alice, &bob, &get_contract_input(&oracle)).await;
+
+        let (close_offer, _) = bob
+            .offer_contract_close(&contract_id, COLLATERAL, 2)
+            .await
+            .expect("To create a close offer");
+        alice
+            .on_dlc_message(
+                &Message::OnChain(OnChainMessage::CloseOffer(close_offer.clone())),
+                node_id(2),
+            )
+            .await
+            .expect("To process the close offer");
+        assert_events(
+            &alice,
+            &[Event::ContractCloseOffered {
+                contract_id,
+                counter_party: node_id(2),
+                close_offer: close_offer.clone(),
+            }],
+        );
+
+        let close_accept = alice
+            .accept_contract_close(&close_offer, &node_id(2))
+            .await
+            .expect("To accept the close offer");
+        bob.on_dlc_message(
+            &Message::OnChain(OnChainMessage::CloseAccept(close_accept)),
+            node_id(1),
+        )
+        .await
+        .expect("To process the close accept");
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[&alice, &bob]).await;
+        assert_eq!(
+            None,
+            get_closed_attestations(get_contract(&alice, &contract_id).await)
+        );
+        assert_eq!(
+            None,
+            get_closed_attestations(get_contract(&bob, &contract_id).await)
+        );
+    }
+
+    #[tokio::test]
+    async fn contract_refund() {
+        let oracle = get_oracle(None);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle).await;
+        let contract_id =
+            get_confirmed_contract(&blockchain, &alice, &bob, &get_contract_input(&oracle)).await;
+
+        mocks::mock_time::set_time(EVENT_MATURITY as u64 + alice.get_config().refund_delay as u64);
+        periodic_check(&[&alice]).await;
+        assert!(matches!(
+            get_contract(&alice, &contract_id).await,
+            Contract::PreRefunded(_)
+        ));
+        assert_events(&alice, &[]);
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        // Bob learns about the refund through the spending of the funding output.
+        periodic_check(&[&alice, &bob]).await;
+        assert_events(&alice, &[Event::ContractRefunded { contract_id }]);
+        assert_events(&bob, &[Event::ContractRefunded { contract_id }]);
+    }
+}
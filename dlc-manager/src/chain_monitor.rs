@@ -11,10 +11,11 @@ use lightning::ln::msgs::DecodeError;
 use lightning::util::ser::{Readable, Writeable, Writer};
 use secp256k1_zkp::EcdsaAdaptorSignature;
 
+use crate::contract::ser::VersionedSerializable;
 
 /// A `ChainMonitor` keeps a list of transaction ids to watch for in the blockchain,
 /// and some associated information used to apply an action when the id is seen.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ChainMonitor {
     pub(crate) watched_tx: HashMap<Txid, WatchState>,
     pub(crate) watched_txo: HashMap<OutPoint, WatchState>,
@@ -23,9 +24,12 @@ pub struct ChainMonitor {
 
 impl_dlc_writeable!(ChainMonitor, { (watched_tx, { cb_writeable, write_hash_map, read_hash_map}), (watched_txo, { cb_writeable, write_hash_map, read_hash_map}), (last_height, writeable) });
 
+impl VersionedSerializable for ChainMonitor {}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) struct ChannelInfo {
-    /// The identifier for _either_ a Lightning channel or a DLC channel.
+    /// The identifier for _either_ a Lightning channel or a DLC channel, or the
+    /// contract id of an on-chain contract.
     pub channel_id: [u8; 32],
     pub tx_type: TxType,
 }
@@ -49,6 +53,9 @@ pub(crate) enum TxType {
     SettleTx2 {
         is_offer: bool,
     },
+    /// A transaction spending the funding output of an on-chain contract,
+    /// either a CET or the refund transaction.
+    FundingSpend,
 }
 
 impl_dlc_writeable_enum!(TxType,;
@@ -61,7 +68,7 @@ impl_dlc_writeable_enum!(TxType,;
     (6, SettleTx2, {
         (is_offer, writeable)
     });;
-    (1, BufferTx), (2, CollaborativeClose), (3, SplitTx), (4, SettleTx), (5, Cet)
+    (1, BufferTx), (2, CollaborativeClose), (3, SplitTx), (4, SettleTx), (5, Cet), (7, FundingSpend)
 );
 
 #[derive(Clone, Debug, PartialEq, Eq, Copy)]
@@ -118,6 +125,25 @@ impl ChainMonitor {
             .insert(outpoint, WatchState::new(channel_info));
     }
 
+    /// Watches the funding output of an on-chain contract so that a CET or refund
+    /// transaction broadcast by the counter party can be detected. Does nothing if
+    /// the output is already being watched.
+    pub(crate) fn add_funding_txo(&mut self, outpoint: OutPoint, contract_id: [u8; 32]) -> bool {
+        if self.watched_txo.contains_key(&outpoint) {
+            return false;
+        }
+
+        self.add_txo(
+            outpoint,
+            ChannelInfo {
+                channel_id: contract_id,
+                tx_type: TxType::FundingSpend,
+            },
+        );
+
+        true
+    }
+
     pub(crate) fn cleanup_channel(&mut self, channel_id: [u8; 32]) {
         log::debug!("Cleaning up data related to channel {channel_id:?}");
 
@@ -133,6 +159,11 @@ impl ChainMonitor {
         self.watched_tx.remove(txid);
     }
 
+    pub(crate) fn remove_txo(&mut self, txo: &OutPoint) {
+        log::debug!("Stopped watching transaction output {txo}");
+        self.watched_txo.remove(txo);
+    }
+
     pub(crate) fn get_watched_txs(&self) -> Vec<Txid> {
         self.watched_tx.keys().cloned().collect()
     }
@@ -186,16 +217,38 @@ impl ChainMonitor {
         })
     }
 
-    /// All the currently watched transactions which have been confirmed.
+    /// All the currently watched transactions related to channels which have been
+    /// confirmed.
     pub(crate) fn confirmed_txs(&self) -> Vec<(Transaction, ChannelInfo)> {
         (self.watched_tx.values())
             .chain(self.watched_txo.values())
             .filter_map(|state| match state {
-                WatchState::Registered { .. } => None,
                 WatchState::Confirmed {
                     channel_info,
                     transaction,
-                } => Some((transaction.clone(), *channel_info)),
+                } if channel_info.tx_type != TxType::FundingSpend => {
+                    Some((transaction.clone(), *channel_info))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// All the confirmed transactions spending the funding output of an on-chain
+    /// contract, together with the spent outpoint and the id of the contract.
+    pub(crate) fn confirmed_funding_spends(&self) -> Vec<(OutPoint, Transaction, [u8; 32])> {
+        self.watched_txo
+            .iter()
+            .filter_map(|(outpoint, state)| match state {
+                WatchState::Confirmed {
+                    channel_info:
+                        ChannelInfo {
+                            channel_id,
+                            tx_type: TxType::FundingSpend,
+                        },
+                    transaction,
+                } => Some((*outpoint, transaction.clone(), *channel_id)),
+                _ => None,
             })
             .collect()
     }
@@ -79,7 +79,54 @@ impl std::fmt::Debug for Channel {
     }
 }
 
+/// The type of state a [`Channel`] is in, used to filter channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelStateType {
+    /// See [`Channel::Offered`].
+    Offered,
+    /// See [`Channel::Accepted`].
+    Accepted,
+    /// See [`Channel::Signed`].
+    Signed,
+    /// See [`Channel::Closing`].
+    Closing,
+    /// See [`Channel::SettledClosing`].
+    SettledClosing,
+    /// See [`Channel::Closed`].
+    Closed,
+    /// See [`Channel::CounterClosed`].
+    CounterClosed,
+    /// See [`Channel::ClosedPunished`].
+    ClosedPunished,
+    /// See [`Channel::CollaborativelyClosed`].
+    CollaborativelyClosed,
+    /// See [`Channel::FailedAccept`].
+    FailedAccept,
+    /// See [`Channel::FailedSign`].
+    FailedSign,
+    /// See [`Channel::Cancelled`].
+    Cancelled,
+}
+
 impl Channel {
+    /// Returns the type of state the channel is in.
+    pub fn get_state_type(&self) -> ChannelStateType {
+        match self {
+            Channel::Offered(_) => ChannelStateType::Offered,
+            Channel::Accepted(_) => ChannelStateType::Accepted,
+            Channel::Signed(_) => ChannelStateType::Signed,
+            Channel::Closing(_) => ChannelStateType::Closing,
+            Channel::SettledClosing(_) => ChannelStateType::SettledClosing,
+            Channel::Closed(_) => ChannelStateType::Closed,
+            Channel::CounterClosed(_) => ChannelStateType::CounterClosed,
+            Channel::ClosedPunished(_) => ChannelStateType::ClosedPunished,
+            Channel::CollaborativelyClosed(_) => ChannelStateType::CollaborativelyClosed,
+            Channel::FailedAccept(_) => ChannelStateType::FailedAccept,
+            Channel::FailedSign(_) => ChannelStateType::FailedSign,
+            Channel::Cancelled(_) => ChannelStateType::Cancelled,
+        }
+    }
+
     /// Returns the public key of the counter party's node.
     pub fn get_counter_party_id(&self) -> PublicKey {
         match self {
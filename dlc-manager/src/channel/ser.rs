@@ -5,6 +5,7 @@ use super::party_points::PartyBasePoints;
 use super::signed_channel::{SignedChannel, SignedChannelState};
 use super::{ClosedChannel, ClosedPunishedChannel, ClosingChannel, FailedAccept, FailedSign, SettledClosingChannel};
 
+use crate::contract::ser::VersionedSerializable;
 use dlc_messages::ser_impls::{
     read_ecdsa_adaptor_signature, read_string, write_ecdsa_adaptor_signature, write_string,
 };
@@ -94,3 +95,13 @@ impl_dlc_writeable!(SettledClosingChannel, {
 });
 impl_dlc_writeable!(ClosedChannel, {(channel_id, writeable), (counter_party, writeable), (temporary_channel_id, writeable), (reference_id, option), (closing_txid, writeable)});
 impl_dlc_writeable!(ClosedPunishedChannel, {(channel_id, writeable), (counter_party, writeable), (temporary_channel_id, writeable), (punish_txid, writeable), (reference_id, option)});
+
+impl VersionedSerializable for OfferedChannel {}
+impl VersionedSerializable for AcceptedChannel {}
+impl VersionedSerializable for SignedChannel {}
+impl VersionedSerializable for FailedAccept {}
+impl VersionedSerializable for FailedSign {}
+impl VersionedSerializable for ClosingChannel {}
+impl VersionedSerializable for SettledClosingChannel {}
+impl VersionedSerializable for ClosedChannel {}
+impl VersionedSerializable for ClosedPunishedChannel {}
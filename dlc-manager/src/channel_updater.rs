@@ -9,13 +9,13 @@ use crate::{chain_monitor::{ChainMonitor, ChannelInfo, TxType}, channel::{
     signed_channel::{SignedChannel, SignedChannelState},
     Channel, ClosedChannel, SettledClosingChannel,
 }, contract::{
-    accepted_contract::AcceptedContract, contract_info::ContractInfo,
+    accepted_contract::AcceptedContract, adaptor_point_cache::AdaptorPointCache, contract_info::ContractInfo,
     contract_input::ContractInput, offered_contract::OfferedContract,
     signed_contract::SignedContract, AdaptorInfo,
 }, contract_updater::{
     accept_contract_internal, verify_accepted_and_sign_contract_internal,
     verify_signed_contract_internal,
-}, error::Error, manager::CET_NSEQUENCE, subchannel::{ClosingSubChannel, SubChannel}, Blockchain, ContractId, DlcChannelId, ReferenceId, Signer, Time, Wallet};
+}, error::Error, subchannel::{ClosingSubChannel, SubChannel}, Blockchain, ContractId, DlcChannelId, ReferenceId, Signer, Time, Wallet};
 use bitcoin::{OutPoint, Script, Sequence, Transaction, Address};
 use dlc::{
     channel::{get_tx_adaptor_signature, verify_tx_adaptor_signature, DlcChannelTransactions}, util::dlc_channel_extra_fee, PartyParams, FeeConfig
@@ -170,6 +170,7 @@ where
 /// message to be sent to the counter party.
 pub fn accept_channel_offer<W: Deref, B: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     offered_channel: &OfferedChannel,
     offered_contract: &OfferedContract,
     wallet: &W,
@@ -182,6 +183,7 @@ where
 {
     accept_channel_offer_internal(
         secp,
+        adaptor_point_cache,
         offered_channel,
         offered_contract,
         wallet,
@@ -194,6 +196,7 @@ where
 
 pub(crate) fn accept_channel_offer_internal<W: Deref, B: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     offered_channel: &OfferedChannel,
     offered_contract: &OfferedContract,
     wallet: &W,
@@ -286,9 +289,9 @@ where
             offered_contract.refund_locktime,
             offered_contract.fee_rate_per_vb,
             offered_contract.cet_locktime,
-            Sequence(crate::manager::CET_NSEQUENCE),
+            Sequence(offered_channel.cet_nsequence),
             Some(1),
-            Some(Sequence(crate::manager::CET_NSEQUENCE)),
+            Some(Sequence(offered_channel.cet_nsequence)),
         )?;
         (
             txs,
@@ -346,6 +349,7 @@ where
 
     let (accepted_contract, adaptor_sigs) = accept_contract_internal(
         secp,
+        adaptor_point_cache,
         offered_contract,
         &accept_params,
         &funding_inputs,
@@ -389,6 +393,7 @@ where
 /// [`SignChannel`] to be sent to the counter party.
 pub fn verify_and_sign_accepted_channel<S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     offered_channel: &OfferedChannel,
     offered_contract: &OfferedContract,
     accept_channel: &AcceptChannel,
@@ -402,6 +407,7 @@ where
 {
     verify_and_sign_accepted_channel_internal(
         secp,
+        adaptor_point_cache,
         offered_channel,
         offered_contract,
         accept_channel,
@@ -415,6 +421,7 @@ where
 
 pub(crate) fn verify_and_sign_accepted_channel_internal<S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     offered_channel: &OfferedChannel,
     offered_contract: &OfferedContract,
     accept_channel: &AcceptChannel,
@@ -500,9 +507,9 @@ where
             offered_contract.refund_locktime,
             offered_contract.fee_rate_per_vb,
             offered_contract.cet_locktime,
-            Sequence(crate::manager::CET_NSEQUENCE),
+            Sequence(cet_nsequence),
             Some(1),
-            Some(Sequence(crate::manager::CET_NSEQUENCE)),
+            Some(Sequence(cet_nsequence)),
         )?;
         (
             txs,
@@ -560,6 +567,7 @@ where
 
     let (signed_contract, cet_adaptor_signatures) = verify_accepted_and_sign_contract_internal(
         secp,
+        adaptor_point_cache,
         offered_contract,
         &accept_params,
         &accept_channel
@@ -660,6 +668,7 @@ where
 /// to a [`SignedChannel`] and [`SignedContract`], and returning them.
 pub fn verify_signed_channel<S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     accepted_channel: &AcceptedChannel,
     accepted_contract: &AcceptedContract,
     sign_channel: &SignChannel,
@@ -671,6 +680,7 @@ where
 {
     verify_signed_channel_internal(
         secp,
+        adaptor_point_cache,
         accepted_channel,
         accepted_contract,
         sign_channel,
@@ -682,6 +692,7 @@ where
 
 pub(crate) fn verify_signed_channel_internal<S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     accepted_channel: &AcceptedChannel,
     accepted_contract: &AcceptedContract,
     sign_channel: &SignChannel,
@@ -741,6 +752,7 @@ where
 
     let (signed_contract, signed_fund_tx) = verify_signed_contract_internal(
         secp,
+        adaptor_point_cache,
         accepted_contract,
         &sign_channel.refund_signature,
         &cet_adaptor_signatures,
@@ -1576,6 +1588,7 @@ pub fn on_renew_offer<T: Deref>(
 /// state.
 pub fn accept_channel_renewal<S: Deref, T: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     signed_channel: &mut SignedChannel,
     offered_contract: &OfferedContract,
     cet_nsequence: u32,
@@ -1589,6 +1602,7 @@ where
 {
     accept_channel_renewal_internal(
         secp,
+        adaptor_point_cache,
         signed_channel,
         offered_contract,
         cet_nsequence,
@@ -1600,6 +1614,7 @@ where
 
 pub(crate) fn accept_channel_renewal_internal<S: Deref, T: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     signed_channel: &mut SignedChannel,
     offered_contract: &OfferedContract,
     cet_nsequence: u32,
@@ -1650,7 +1665,7 @@ where
     );
 
     let (fund_vout, buffer_nsequence) = if signed_channel.is_sub_channel() {
-        (Some(1), Some(Sequence(crate::manager::CET_NSEQUENCE)))
+        (Some(1), Some(Sequence(cet_nsequence)))
     } else {
         (None, None)
     };
@@ -1679,6 +1694,7 @@ where
 
     let (accepted_contract, adaptor_sigs) = accept_contract_internal(
         secp,
+        adaptor_point_cache,
         offered_contract,
         &signed_channel.own_params,
         &[],
@@ -1717,6 +1733,7 @@ where
 /// [`SignedChannelState::RenewOffered`] state.
 pub fn verify_renew_accept_and_confirm<S: Deref, T: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     renew_accept: &RenewAccept,
     signed_channel: &mut SignedChannel,
     offered_contract: &OfferedContract,
@@ -1731,6 +1748,7 @@ where
 {
     verify_renew_accept_and_confirm_internal(
         secp,
+        adaptor_point_cache,
         renew_accept,
         signed_channel,
         offered_contract,
@@ -1744,6 +1762,7 @@ where
 
 pub(crate) fn verify_renew_accept_and_confirm_internal<S: Deref, T: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     renew_accept: &RenewAccept,
     signed_channel: &mut SignedChannel,
     offered_contract: &OfferedContract,
@@ -1781,7 +1800,7 @@ where
     let own_payout =
         total_collateral - get_signed_channel_state!(signed_channel, RenewOffered, counter_payout)?;
     let (fund_vout, buffer_nsequence) = if signed_channel.is_sub_channel() {
-        (Some(1), Some(Sequence(crate::manager::CET_NSEQUENCE)))
+        (Some(1), Some(Sequence(cet_nsequence)))
     } else {
         (None, None)
     };
@@ -1811,6 +1830,7 @@ where
 
     let (signed_contract, cet_adaptor_signatures) = verify_accepted_and_sign_contract_internal(
         secp,
+        adaptor_point_cache,
         offered_contract,
         &signed_channel.counter_params,
         &[],
@@ -1872,6 +1892,7 @@ where
 /// [`SignedChannelState::RenewAccepted`] state.
 pub fn verify_renew_confirm_and_finalize<T: Deref, S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     signed_channel: &mut SignedChannel,
     accepted_contract: &AcceptedContract,
     renew_confirm: &RenewConfirm,
@@ -1886,6 +1907,7 @@ where
 {
     verify_renew_confirm_and_finalize_internal(
         secp,
+        adaptor_point_cache,
         signed_channel,
         accepted_contract,
         renew_confirm,
@@ -1900,6 +1922,7 @@ where
 
 pub(crate) fn verify_renew_confirm_and_finalize_internal<S: Deref, T: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     signed_channel: &mut SignedChannel,
     accepted_contract: &AcceptedContract,
     renew_confirm: &RenewConfirm,
@@ -1953,6 +1976,7 @@ where
     let cet_adaptor_signatures: Vec<_> = (&renew_confirm.cet_adaptor_signatures).into();
     let (signed_contract, _) = verify_signed_contract_internal(
         secp,
+        adaptor_point_cache,
         accepted_contract,
         &renew_confirm.refund_signature,
         &cet_adaptor_signatures,
@@ -2217,6 +2241,7 @@ pub fn offer_collaborative_close<C: Signing, S: Deref, T: Deref>(
     secp: &Secp256k1<C>,
     signed_channel: &mut SignedChannel,
     counter_payout: u64,
+    peer_timeout: u64,
     signer: &S,
     time: &T,
     reference_id: Option<ReferenceId>
@@ -2265,7 +2290,7 @@ where
         counter_payout,
         offer_signature: close_signature,
         close_tx: close_tx.clone(),
-        timeout: time.unix_time_now() + super::manager::PEER_TIMEOUT,
+        timeout: time.unix_time_now() + peer_timeout,
         is_offer: true
     };
     std::mem::swap(&mut state, &mut signed_channel.state);
@@ -2854,6 +2879,7 @@ pub fn finalize_unilateral_close_settled_channel<S: Deref>(
     signed_channel: &SignedChannel,
     destination_address: &Address,
     fee_rate_per_vb: u64,
+    cet_nsequence: u32,
     signer: &S,
     is_initiator: bool,
 ) -> Result<(Transaction, Channel), Error>
@@ -2888,7 +2914,7 @@ where
         &own_sk,
         settle_transaction,
         destination_address,
-        CET_NSEQUENCE,
+        cet_nsequence,
         0,
         fee_rate_per_vb,
     )?;
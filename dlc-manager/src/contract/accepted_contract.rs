@@ -23,7 +23,9 @@ pub struct AcceptedContract {
     /// the relation between adaptor signatures and outcomes.
     pub adaptor_infos: Vec<AdaptorInfo>,
     /// The adaptor signatures of the accepting party. Note that the accepting
-    /// party does not keep them thus an option is used.
+    /// party only keeps them for on-chain contracts (to be able to recover the
+    /// oracle attestation from a CET broadcast by the counter party), thus an
+    /// option is used.
     pub adaptor_signatures: Option<Vec<EcdsaAdaptorSignature>>,
     /// The signature for the refund transaction from the accepting party.
     pub accept_refund_signature: Signature,
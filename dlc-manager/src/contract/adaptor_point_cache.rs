@@ -0,0 +1,468 @@
+//! #AdaptorPointCache
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use super::contract_info::ContractInfo;
+use super::numerical_descriptor::convert_signed_path;
+use super::ser::VersionedSerializable;
+use crate::error::Error;
+use dlc_trie::AdaptorPointProvider;
+use lightning::ln::msgs::DecodeError;
+use lightning::util::ser::{Readable, Writeable, Writer};
+use secp256k1_zkp::hashes::{sha256, Hash, HashEngine};
+use secp256k1_zkp::{All, PublicKey, Secp256k1};
+
+/// Caches the adaptor points used to create and verify the adaptor signatures
+/// of numerical contracts, so that they are not recomputed from the oracle
+/// announcements every time a contract using the same events is signed,
+/// verified or renewed. Each point is keyed by the announcements of the oracles
+/// and the digit prefixes it is computed from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdaptorPointCache {
+    points: HashMap<[u8; 32], ([u8; 32], PublicKey)>,
+    new_points: Vec<[u8; 32]>,
+}
+
+/// An adaptor point of an [`AdaptorPointCache`], as persisted in a
+/// [`crate::Storage`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedAdaptorPoint {
+    /// The key of the point, committing to the oracle announcements and the
+    /// digit prefixes it is computed from.
+    pub key: [u8; 32],
+    /// The hash of the oracle announcements of the contract the point was
+    /// computed for, used to prune the point once the contract is closed.
+    pub announcements_hash: [u8; 32],
+    /// The adaptor point.
+    pub point: PublicKey,
+}
+
+impl_dlc_writeable!(CachedAdaptorPoint, { (key, writeable), (announcements_hash, writeable), (point, writeable) });
+
+impl VersionedSerializable for CachedAdaptorPoint {}
+
+impl AdaptorPointCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a cache containing the given points, typically read from a
+    /// [`crate::Storage`].
+    pub fn from_points(points: Vec<CachedAdaptorPoint>) -> Self {
+        AdaptorPointCache {
+            points: points
+                .into_iter()
+                .map(|x| (x.key, (x.announcements_hash, x.point)))
+                .collect(),
+            new_points: Vec::new(),
+        }
+    }
+
+    /// Returns the number of adaptor points in the cache.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the points added to the cache since the last call, so that they
+    /// can be persisted.
+    pub fn take_new_points(&mut self) -> Vec<CachedAdaptorPoint> {
+        let points = &self.points;
+        self.new_points
+            .drain(..)
+            .filter_map(|key| {
+                points
+                    .get(&key)
+                    .map(|(announcements_hash, point)| CachedAdaptorPoint {
+                        key,
+                        announcements_hash: *announcements_hash,
+                        point: *point,
+                    })
+            })
+            .collect()
+    }
+
+    /// Removes the points computed for contracts whose announcements hash is
+    /// not in `live_announcements`, returning the keys of the removed points.
+    pub fn prune(&mut self, live_announcements: &HashSet<[u8; 32]>) -> Vec<[u8; 32]> {
+        let pruned: Vec<[u8; 32]> = self
+            .points
+            .iter()
+            .filter(|(_, (hash, _))| !live_announcements.contains(hash))
+            .map(|(key, _)| *key)
+            .collect();
+        for key in &pruned {
+            self.points.remove(key);
+        }
+        let points = &self.points;
+        self.new_points.retain(|key| points.contains_key(key));
+        pruned
+    }
+
+    fn insert(&mut self, key: [u8; 32], announcements_hash: [u8; 32], point: PublicKey) {
+        if self
+            .points
+            .insert(key, (announcements_hash, point))
+            .is_none()
+        {
+            self.new_points.push(key);
+        }
+    }
+}
+
+fn get_announcement_hashes(contract_info: &ContractInfo) -> Vec<[u8; 32]> {
+    contract_info
+        .oracle_announcements
+        .iter()
+        .map(|x| sha256::Hash::hash(&x.encode()).into_inner())
+        .collect()
+}
+
+fn hash_announcement_hashes(announcement_hashes: &[[u8; 32]]) -> [u8; 32] {
+    let mut engine = sha256::Hash::engine();
+    for hash in announcement_hashes {
+        engine.input(hash);
+    }
+    sha256::Hash::from_engine(engine).into_inner()
+}
+
+/// Returns the hashes of the oracle announcements of the given contract infos,
+/// identifying the adaptor points in an [`AdaptorPointCache`] that are still
+/// needed.
+pub(crate) fn get_announcements_hashes<'a>(
+    contract_infos: impl Iterator<Item = &'a ContractInfo>,
+) -> HashSet<[u8; 32]> {
+    contract_infos
+        .map(|x| hash_announcement_hashes(&get_announcement_hashes(x)))
+        .collect()
+}
+
+/// Provides the adaptor points of a contract from an [`AdaptorPointCache`],
+/// computing the ones missing from it. The signature points of the oracles are
+/// only computed if a point is missing, and the computed points can then be
+/// added to the cache with [`CachedAdaptorPoints::into_new_points`].
+pub(crate) struct CachedAdaptorPoints<'a> {
+    secp: &'a Secp256k1<All>,
+    contract_info: &'a ContractInfo,
+    cache: &'a AdaptorPointCache,
+    announcement_hashes: Vec<[u8; 32]>,
+    announcements_hash: [u8; 32],
+    signed_base: Option<usize>,
+    precomputed_points: Mutex<Option<Arc<Vec<Vec<Vec<PublicKey>>>>>>,
+    new_points: Mutex<Vec<([u8; 32], PublicKey)>>,
+}
+
+impl<'a> CachedAdaptorPoints<'a> {
+    pub(crate) fn new(
+        secp: &'a Secp256k1<All>,
+        contract_info: &'a ContractInfo,
+        cache: &'a AdaptorPointCache,
+    ) -> Self {
+        let announcement_hashes = get_announcement_hashes(contract_info);
+        CachedAdaptorPoints {
+            secp,
+            contract_info,
+            cache,
+            announcements_hash: hash_announcement_hashes(&announcement_hashes),
+            announcement_hashes,
+            signed_base: contract_info.get_signed_base(),
+            precomputed_points: Mutex::new(None),
+            new_points: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the adaptor points that were computed because they were missing
+    /// from the cache.
+    pub(crate) fn into_new_points(self) -> Vec<([u8; 32], PublicKey)> {
+        self.new_points.into_inner().unwrap()
+    }
+
+    fn get_key(&self, indexes: &[usize], paths: &[Vec<usize>]) -> [u8; 32] {
+        let mut engine = sha256::Hash::engine();
+        for (index, path) in indexes.iter().zip(paths) {
+            engine.input(&self.announcement_hashes[*index]);
+            engine.input(&(path.len() as u64).to_be_bytes());
+            for digit in path {
+                engine.input(&(*digit as u64).to_be_bytes());
+            }
+        }
+        sha256::Hash::from_engine(engine).into_inner()
+    }
+
+    fn get_precomputed_points(&self) -> Result<Arc<Vec<Vec<Vec<PublicKey>>>>, dlc::Error> {
+        let mut precomputed_points = self.precomputed_points.lock().unwrap();
+        if let Some(points) = precomputed_points.as_ref() {
+            return Ok(Arc::clone(points));
+        }
+        let points = Arc::new(self.contract_info.precompute_points(self.secp).map_err(
+            |e| match e {
+                Error::DlcError(e) => e,
+                e => dlc::Error::InvalidArgument(e.to_string()),
+            },
+        )?);
+        *precomputed_points = Some(Arc::clone(&points));
+        Ok(points)
+    }
+}
+
+impl<'a> AdaptorPointProvider for CachedAdaptorPoints<'a> {
+    fn get_adaptor_point(
+        &self,
+        indexes: &[usize],
+        paths: &[Vec<usize>],
+    ) -> Result<PublicKey, dlc::Error> {
+        // The paths of the tries differ from the digits attested by oracles
+        // attesting to signed values.
+        let paths = match self.signed_base {
+            Some(base) => Cow::Owned(paths.iter().map(|x| convert_signed_path(x, base)).collect()),
+            None => Cow::Borrowed(paths),
+        };
+        let key = self.get_key(indexes, &paths);
+        if let Some((_, point)) = self.cache.points.get(&key) {
+            return Ok(*point);
+        }
+        let point = self
+            .get_precomputed_points()?
+            .get_adaptor_point(indexes, &paths)?;
+        self.new_points.lock().unwrap().push((key, point));
+        Ok(point)
+    }
+}
+
+/// Calls `f` with the adaptor points of the given contract, taken from the
+/// given cache when available, and adds the ones that had to be computed to it.
+pub(crate) fn with_cached_adaptor_points<T, F>(
+    secp: &Secp256k1<All>,
+    contract_info: &ContractInfo,
+    cache: &mut AdaptorPointCache,
+    f: F,
+) -> Result<T, Error>
+where
+    F: FnOnce(&CachedAdaptorPoints) -> Result<T, Error>,
+{
+    let adaptor_points = CachedAdaptorPoints::new(secp, contract_info, cache);
+    let res = f(&adaptor_points);
+    let announcements_hash = adaptor_points.announcements_hash;
+    for (key, point) in adaptor_points.into_new_points() {
+        cache.insert(key, announcements_hash, point);
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::contract::numerical_descriptor::NumericalDescriptor;
+    use crate::contract::ser::{Serializable, SERIALIZATION_VERSION};
+    use crate::contract::ContractDescriptor;
+    use crate::payout_curve::builders::BinaryOption;
+    use dlc_messages::oracle_msgs::{
+        DigitDecompositionEventDescriptor, EventDescriptor, OracleAnnouncement, OracleEvent,
+    };
+    use dlc_trie::OracleNumericInfo;
+    use secp256k1_zkp::{schnorr::Signature, KeyPair, XOnlyPublicKey};
+
+    const NB_DIGITS: usize = 10;
+
+    fn random_public_key(secp: &Secp256k1<All>) -> XOnlyPublicKey {
+        XOnlyPublicKey::from_keypair(&KeyPair::new(secp, &mut secp256k1_zkp::rand::thread_rng())).0
+    }
+
+    fn get_announcement(secp: &Secp256k1<All>) -> OracleAnnouncement {
+        OracleAnnouncement {
+            announcement_signature: Signature::from_slice(&[1; 64]).unwrap(),
+            oracle_public_key: random_public_key(secp),
+            oracle_event: OracleEvent {
+                oracle_nonces: (0..NB_DIGITS).map(|_| random_public_key(secp)).collect(),
+                event_maturity_epoch: 0,
+                event_descriptor: EventDescriptor::DigitDecompositionEvent(
+                    DigitDecompositionEventDescriptor {
+                        base: 2,
+                        is_signed: false,
+                        unit: "btc/usd".to_string(),
+                        precision: 0,
+                        nb_digits: NB_DIGITS as u16,
+                    },
+                ),
+                event_id: "btcusd".to_string(),
+            },
+        }
+    }
+
+    fn get_contract_info(secp: &Secp256k1<All>, nb_oracles: usize) -> ContractInfo {
+        let (payout_function, rounding_intervals) = BinaryOption {
+            strike_price: 512,
+            offer_collateral: 1000000,
+            accept_collateral: 1000000,
+            offer_wins_above: true,
+        }
+        .build((1 << NB_DIGITS) - 1, 1)
+        .unwrap();
+        ContractInfo {
+            contract_descriptor: ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function,
+                rounding_intervals,
+                difference_params: None,
+                oracle_numeric_infos: OracleNumericInfo {
+                    base: 2,
+                    nb_digits: vec![NB_DIGITS; nb_oracles],
+                    oracle_bases: None,
+                },
+                outcome_params: None,
+            }),
+            oracle_announcements: (0..nb_oracles).map(|_| get_announcement(secp)).collect(),
+            threshold: nb_oracles,
+        }
+    }
+
+    #[test]
+    fn cached_adaptor_points_are_reused() {
+        let secp = Secp256k1::new();
+        let contract_info = get_contract_info(&secp, 2);
+        let precomputed_points = contract_info.precompute_points(&secp).unwrap();
+        let indexes = vec![0, 1];
+        let paths = vec![vec![1, 0, 1], vec![1]];
+        let expected = precomputed_points
+            .get_adaptor_point(&indexes, &paths)
+            .unwrap();
+
+        let mut cache = AdaptorPointCache::new();
+        let adaptor_points = CachedAdaptorPoints::new(&secp, &contract_info, &cache);
+        assert_eq!(
+            expected,
+            adaptor_points.get_adaptor_point(&indexes, &paths).unwrap()
+        );
+        let announcements_hash = adaptor_points.announcements_hash;
+        let new_points = adaptor_points.into_new_points();
+        assert_eq!(1, new_points.len());
+        for (key, point) in new_points {
+            cache.insert(key, announcements_hash, point);
+        }
+
+        let adaptor_points = CachedAdaptorPoints::new(&secp, &contract_info, &cache);
+        assert_eq!(
+            expected,
+            adaptor_points.get_adaptor_point(&indexes, &paths).unwrap()
+        );
+        assert!(adaptor_points.precomputed_points.lock().unwrap().is_none());
+        assert!(adaptor_points.into_new_points().is_empty());
+    }
+
+    #[test]
+    fn adaptor_points_are_keyed_by_announcements_and_prefixes() {
+        let secp = Secp256k1::new();
+        let contract_info = get_contract_info(&secp, 2);
+        let other_contract_info = get_contract_info(&secp, 2);
+        let cache = AdaptorPointCache::new();
+        let adaptor_points = CachedAdaptorPoints::new(&secp, &contract_info, &cache);
+        let other_adaptor_points = CachedAdaptorPoints::new(&secp, &other_contract_info, &cache);
+
+        let key = adaptor_points.get_key(&[0, 1], &[vec![1, 0], vec![1]]);
+        assert_ne!(key, adaptor_points.get_key(&[1, 0], &[vec![1, 0], vec![1]]));
+        assert_ne!(key, adaptor_points.get_key(&[0, 1], &[vec![1], vec![0, 1]]));
+        assert_ne!(key, adaptor_points.get_key(&[0, 1], &[vec![1, 0], vec![0]]));
+        assert_ne!(
+            key,
+            other_adaptor_points.get_key(&[0, 1], &[vec![1, 0], vec![1]])
+        );
+    }
+
+    fn fill_cache(
+        secp: &Secp256k1<All>,
+        contract_info: &ContractInfo,
+        cache: &mut AdaptorPointCache,
+    ) {
+        with_cached_adaptor_points(secp, contract_info, cache, |adaptor_points| {
+            for path in [vec![0], vec![1, 0], vec![1, 1, 0]] {
+                adaptor_points.get_adaptor_point(&[0], &[path])?;
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn new_points_are_taken_once_and_restored() {
+        let secp = Secp256k1::new();
+        let contract_info = get_contract_info(&secp, 1);
+        let mut cache = AdaptorPointCache::new();
+        fill_cache(&secp, &contract_info, &mut cache);
+        assert_eq!(3, cache.len());
+
+        let new_points = cache.take_new_points();
+        assert_eq!(3, new_points.len());
+        assert!(cache.take_new_points().is_empty());
+
+        let serialized = new_points
+            .iter()
+            .map(|x| x.serialize().unwrap())
+            .collect::<Vec<_>>();
+        let deserialized = serialized
+            .iter()
+            .map(|x| CachedAdaptorPoint::deserialize(&mut std::io::Cursor::new(x)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(new_points, deserialized);
+
+        let mut restored = AdaptorPointCache::from_points(deserialized);
+        assert_eq!(3, restored.len());
+        fill_cache(&secp, &contract_info, &mut restored);
+        assert!(restored.take_new_points().is_empty());
+    }
+
+    #[test]
+    fn points_of_closed_contracts_are_pruned() {
+        let secp = Secp256k1::new();
+        let contract_info = get_contract_info(&secp, 1);
+        let other_contract_info = get_contract_info(&secp, 1);
+        let mut cache = AdaptorPointCache::new();
+        fill_cache(&secp, &contract_info, &mut cache);
+        fill_cache(&secp, &other_contract_info, &mut cache);
+        assert_eq!(6, cache.len());
+
+        let live = get_announcements_hashes(std::iter::once(&other_contract_info));
+        let pruned = cache.prune(&live);
+        assert_eq!(3, pruned.len());
+        assert_eq!(3, cache.len());
+        assert!(cache
+            .take_new_points()
+            .iter()
+            .all(|x| !pruned.contains(&x.key)));
+
+        fill_cache(&secp, &other_contract_info, &mut cache);
+        assert!(cache.take_new_points().is_empty());
+        assert_eq!(3, cache.prune(&HashSet::new()).len());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn versioned_points_can_be_read_back() {
+        let secp = Secp256k1::new();
+        let contract_info = get_contract_info(&secp, 1);
+        let mut cache = AdaptorPointCache::new();
+        fill_cache(&secp, &contract_info, &mut cache);
+        let point = cache.take_new_points().remove(0);
+
+        let versioned = point.serialize_versioned().unwrap();
+        assert_eq!(SERIALIZATION_VERSION, versioned[0]);
+        assert_eq!(point.serialize().unwrap()[..], versioned[1..]);
+        assert_eq!(
+            point,
+            CachedAdaptorPoint::deserialize_versioned(&mut versioned.as_slice()).unwrap()
+        );
+        assert_eq!(
+            point,
+            CachedAdaptorPoint::deserialize_version(0, &mut &versioned[1..]).unwrap()
+        );
+
+        let mut newer = versioned;
+        newer[0] = SERIALIZATION_VERSION + 1;
+        assert!(CachedAdaptorPoint::deserialize_versioned(&mut newer.as_slice()).is_err());
+    }
+}
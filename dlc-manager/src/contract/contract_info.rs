@@ -1,5 +1,7 @@
 //! #ContractInfo
 
+use super::adaptor_point_cache::{with_cached_adaptor_points, AdaptorPointCache};
+use super::numerical_descriptor::convert_signed_path;
 use super::AdaptorInfo;
 use super::ContractDescriptor;
 use crate::error::Error;
@@ -69,6 +71,30 @@ impl ContractInfo {
         funding_script_pubkey: &Script,
         fund_output_value: u64,
         cets: &[Transaction],
+    ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
+        self.get_adaptor_signatures_with_cache(
+            secp,
+            &mut AdaptorPointCache::new(),
+            adaptor_info,
+            fund_privkey,
+            funding_script_pubkey,
+            fund_output_value,
+            cets,
+        )
+    }
+
+    /// Uses the provided AdaptorInfo and SecretKey to generate the set of
+    /// adaptor signatures for the contract, using and updating the given cache
+    /// of adaptor points.
+    pub fn get_adaptor_signatures_with_cache(
+        &self,
+        secp: &Secp256k1<All>,
+        adaptor_point_cache: &mut AdaptorPointCache,
+        adaptor_info: &AdaptorInfo,
+        fund_privkey: &SecretKey,
+        funding_script_pubkey: &Script,
+        fund_output_value: u64,
+        cets: &[Transaction],
     ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
         match adaptor_info {
             AdaptorInfo::Enum => match &self.contract_descriptor {
@@ -83,22 +109,42 @@ impl ContractInfo {
                 ),
                 _ => unreachable!(),
             },
-            AdaptorInfo::Numerical(trie) => Ok(trie.sign(
-                secp,
-                fund_privkey,
-                funding_script_pubkey,
-                fund_output_value,
-                cets,
-                &self.precompute_points(secp)?,
-            )?),
-            AdaptorInfo::NumericalWithDifference(trie) => Ok(trie.sign(
-                secp,
-                fund_privkey,
-                funding_script_pubkey,
-                fund_output_value,
-                cets,
-                &self.precompute_points(secp)?,
-            )?),
+            AdaptorInfo::Numerical(trie) => {
+                with_cached_adaptor_points(secp, self, adaptor_point_cache, |points| {
+                    Ok(trie.sign(
+                        secp,
+                        fund_privkey,
+                        funding_script_pubkey,
+                        fund_output_value,
+                        cets,
+                        points,
+                    )?)
+                })
+            }
+            AdaptorInfo::NumericalWithDifference(trie) => {
+                with_cached_adaptor_points(secp, self, adaptor_point_cache, |points| {
+                    Ok(trie.sign(
+                        secp,
+                        fund_privkey,
+                        funding_script_pubkey,
+                        fund_output_value,
+                        cets,
+                        points,
+                    )?)
+                })
+            }
+            AdaptorInfo::NumericalMultiBase(trie) => {
+                with_cached_adaptor_points(secp, self, adaptor_point_cache, |points| {
+                    Ok(trie.sign(
+                        secp,
+                        fund_privkey,
+                        funding_script_pubkey,
+                        fund_output_value,
+                        cets,
+                        points,
+                    )?)
+                })
+            }
         }
     }
 
@@ -114,6 +160,34 @@ impl ContractInfo {
         cets: &[Transaction],
         adaptor_sigs: &[EcdsaAdaptorSignature],
         adaptor_sig_start: usize,
+    ) -> Result<(AdaptorInfo, usize), Error> {
+        self.verify_and_get_adaptor_info_with_cache(
+            secp,
+            &mut AdaptorPointCache::new(),
+            total_collateral,
+            fund_pubkey,
+            funding_script_pubkey,
+            fund_output_value,
+            cets,
+            adaptor_sigs,
+            adaptor_sig_start,
+        )
+    }
+
+    /// Generate the AdaptorInfo for the contract while verifying the provided
+    /// set of adaptor signatures, using and updating the given cache of adaptor
+    /// points.
+    pub fn verify_and_get_adaptor_info_with_cache(
+        &self,
+        secp: &Secp256k1<All>,
+        adaptor_point_cache: &mut AdaptorPointCache,
+        total_collateral: u64,
+        fund_pubkey: &PublicKey,
+        funding_script_pubkey: &Script,
+        fund_output_value: u64,
+        cets: &[Transaction],
+        adaptor_sigs: &[EcdsaAdaptorSignature],
+        adaptor_sig_start: usize,
     ) -> Result<(AdaptorInfo, usize), Error> {
         let oracle_infos = self.get_oracle_infos();
         match &self.contract_descriptor {
@@ -128,18 +202,22 @@ impl ContractInfo {
                 adaptor_sigs,
                 adaptor_sig_start,
             )?),
-            ContractDescriptor::Numerical(n) => Ok(n.verify_and_get_adaptor_info(
-                secp,
-                total_collateral,
-                fund_pubkey,
-                funding_script_pubkey,
-                fund_output_value,
-                self.threshold,
-                &self.precompute_points(secp)?,
-                cets,
-                adaptor_sigs,
-                adaptor_sig_start,
-            )?),
+            ContractDescriptor::Numerical(n) => {
+                with_cached_adaptor_points(secp, self, adaptor_point_cache, |points| {
+                    n.verify_and_get_adaptor_info(
+                        secp,
+                        total_collateral,
+                        fund_pubkey,
+                        funding_script_pubkey,
+                        fund_output_value,
+                        self.threshold,
+                        points,
+                        cets,
+                        adaptor_sigs,
+                        adaptor_sig_start,
+                    )
+                })
+            }
         }
     }
 
@@ -161,20 +239,24 @@ impl ContractInfo {
                 _ => unreachable!(),
             },
             AdaptorInfo::Numerical(n) => {
-                let res = n.look_up(&outcomes_to_digits(outcomes))?;
+                let res = n.look_up(&self.outcomes_to_digits(outcomes))?;
                 Some((
                     res.1.iter().map(|(x, y)| (*x, y.len())).collect(),
                     res.0.clone(),
                 ))
             }
             AdaptorInfo::NumericalWithDifference(n) => {
-                let res = n.multi_trie.look_up(&outcomes_to_digits(outcomes))?;
+                let res = n.multi_trie.look_up(&self.outcomes_to_digits(outcomes))?;
 
                 Some((
                     res.1.iter().map(|(x, y)| (*x, y.len())).collect(),
                     res.0.clone(),
                 ))
             }
+            AdaptorInfo::NumericalMultiBase(n) => {
+                let res = n.look_up(&self.outcomes_to_digits(outcomes))?;
+                Some((res.1.iter().map(|(x, y)| (*x, y.len())).collect(), res.0))
+            }
         }
     }
 
@@ -190,6 +272,33 @@ impl ContractInfo {
         adaptor_sigs: &[EcdsaAdaptorSignature],
         adaptor_sig_start: usize,
         adaptor_info: &AdaptorInfo,
+    ) -> Result<usize, Error> {
+        self.verify_adaptor_info_with_cache(
+            secp,
+            &mut AdaptorPointCache::new(),
+            fund_pubkey,
+            funding_script_pubkey,
+            fund_output_value,
+            cets,
+            adaptor_sigs,
+            adaptor_sig_start,
+            adaptor_info,
+        )
+    }
+
+    /// Verifies the given adaptor signatures are valid with respect to the given
+    /// adaptor info, using and updating the given cache of adaptor points.
+    pub fn verify_adaptor_info_with_cache(
+        &self,
+        secp: &Secp256k1<All>,
+        adaptor_point_cache: &mut AdaptorPointCache,
+        fund_pubkey: &PublicKey,
+        funding_script_pubkey: &Script,
+        fund_output_value: u64,
+        cets: &[Transaction],
+        adaptor_sigs: &[EcdsaAdaptorSignature],
+        adaptor_sig_start: usize,
+        adaptor_info: &AdaptorInfo,
     ) -> Result<usize, Error> {
         let oracle_infos = self.get_oracle_infos();
         match &self.contract_descriptor {
@@ -206,24 +315,45 @@ impl ContractInfo {
             )?),
             ContractDescriptor::Numerical(_) => match adaptor_info {
                 AdaptorInfo::Enum => unreachable!(),
-                AdaptorInfo::Numerical(trie) => Ok(trie.verify(
-                    secp,
-                    fund_pubkey,
-                    funding_script_pubkey,
-                    fund_output_value,
-                    adaptor_sigs,
-                    cets,
-                    &self.precompute_points(secp)?,
-                )?),
-                AdaptorInfo::NumericalWithDifference(trie) => Ok(trie.verify(
-                    secp,
-                    fund_pubkey,
-                    funding_script_pubkey,
-                    fund_output_value,
-                    adaptor_sigs,
-                    cets,
-                    &self.precompute_points(secp)?,
-                )?),
+                AdaptorInfo::Numerical(trie) => {
+                    with_cached_adaptor_points(secp, self, adaptor_point_cache, |points| {
+                        Ok(trie.verify(
+                            secp,
+                            fund_pubkey,
+                            funding_script_pubkey,
+                            fund_output_value,
+                            adaptor_sigs,
+                            cets,
+                            points,
+                        )?)
+                    })
+                }
+                AdaptorInfo::NumericalWithDifference(trie) => {
+                    with_cached_adaptor_points(secp, self, adaptor_point_cache, |points| {
+                        Ok(trie.verify(
+                            secp,
+                            fund_pubkey,
+                            funding_script_pubkey,
+                            fund_output_value,
+                            adaptor_sigs,
+                            cets,
+                            points,
+                        )?)
+                    })
+                }
+                AdaptorInfo::NumericalMultiBase(trie) => {
+                    with_cached_adaptor_points(secp, self, adaptor_point_cache, |points| {
+                        Ok(trie.verify(
+                            secp,
+                            fund_pubkey,
+                            funding_script_pubkey,
+                            fund_output_value,
+                            adaptor_sigs,
+                            cets,
+                            points,
+                        )?)
+                    })
+                }
             },
         }
     }
@@ -238,6 +368,31 @@ impl ContractInfo {
         fund_output_value: u64,
         cets: &[Transaction],
         adaptor_index_start: usize,
+    ) -> Result<(AdaptorInfo, Vec<EcdsaAdaptorSignature>), Error> {
+        self.get_adaptor_info_with_cache(
+            secp,
+            &mut AdaptorPointCache::new(),
+            total_collateral,
+            fund_priv_key,
+            funding_script_pubkey,
+            fund_output_value,
+            cets,
+            adaptor_index_start,
+        )
+    }
+
+    /// Generate the adaptor info and adaptor signatures for the contract, using
+    /// and updating the given cache of adaptor points.
+    pub fn get_adaptor_info_with_cache(
+        &self,
+        secp: &Secp256k1<All>,
+        adaptor_point_cache: &mut AdaptorPointCache,
+        total_collateral: u64,
+        fund_priv_key: &SecretKey,
+        funding_script_pubkey: &Script,
+        fund_output_value: u64,
+        cets: &[Transaction],
+        adaptor_index_start: usize,
     ) -> Result<(AdaptorInfo, Vec<EcdsaAdaptorSignature>), Error> {
         match &self.contract_descriptor {
             ContractDescriptor::Enum(e) => {
@@ -252,21 +407,25 @@ impl ContractInfo {
                     cets,
                 )?)
             }
-            ContractDescriptor::Numerical(n) => Ok(n.get_adaptor_info(
-                secp,
-                total_collateral,
-                fund_priv_key,
-                funding_script_pubkey,
-                fund_output_value,
-                self.threshold,
-                &self.precompute_points(secp)?,
-                cets,
-                adaptor_index_start,
-            )?),
+            ContractDescriptor::Numerical(n) => {
+                with_cached_adaptor_points(secp, self, adaptor_point_cache, |points| {
+                    n.get_adaptor_info(
+                        secp,
+                        total_collateral,
+                        fund_priv_key,
+                        funding_script_pubkey,
+                        fund_output_value,
+                        self.threshold,
+                        points,
+                        cets,
+                        adaptor_index_start,
+                    )
+                })
+            }
         }
     }
 
-    fn precompute_points<C: Verification>(
+    pub(super) fn precompute_points<C: Verification>(
         &self,
         secp: &Secp256k1<C>,
     ) -> Result<Vec<Vec<Vec<PublicKey>>>, Error> {
@@ -279,12 +438,29 @@ impl ContractInfo {
                     EventDescriptor::DigitDecompositionEvent(d) => {
                         let base = d.base as usize;
                         let nb_digits = d.nb_digits as usize;
-                        if nb_digits != nonces.len() {
+                        if nb_digits + d.is_signed as usize != nonces.len() {
                             return Err(Error::InvalidParameters(
                                 "Number of digits and nonces must be equal".to_string(),
                             ));
                         }
-                        let mut d_points = Vec::with_capacity(nb_digits);
+                        let mut d_points = Vec::with_capacity(nonces.len());
+                        let mut nonces = nonces.iter();
+                        if d.is_signed {
+                            // The sign is attested using the first nonce, and
+                            // represented by the digits 0 for "-" and 1 for "+".
+                            let nonce = nonces.next().expect("to have a sign nonce");
+                            let points = ["-", "+"]
+                                .iter()
+                                .map(|sign| {
+                                    let msg =
+                                        Message::from_hashed_data::<sha256::Hash>(sign.as_bytes());
+                                    dlc::secp_utils::schnorrsig_compute_sig_point(
+                                        secp, pubkey, nonce, &msg,
+                                    )
+                                })
+                                .collect::<Result<Vec<_>, _>>()?;
+                            d_points.push(points);
+                        }
                         for nonce in nonces {
                             let mut points = Vec::with_capacity(base);
                             for j in 0..base {
@@ -307,6 +483,44 @@ impl ContractInfo {
             })
             .collect::<Result<Vec<Vec<Vec<PublicKey>>>, Error>>()
     }
+
+    /// Returns the base of the oracles if they attest to signed values, in
+    /// which case the digits they attest to need to be converted to get the
+    /// paths of the tries.
+    pub(super) fn get_signed_base(&self) -> Option<usize> {
+        match &self.contract_descriptor {
+            ContractDescriptor::Numerical(n) if n.is_signed() => Some(n.oracle_numeric_infos.base),
+            _ => None,
+        }
+    }
+
+    fn outcomes_to_digits(&self, outcomes: &[(usize, &Vec<String>)]) -> Vec<(usize, Vec<usize>)> {
+        let signed_base = self.get_signed_base();
+        outcomes
+            .iter()
+            .filter_map(|(x, path)| {
+                let digits = match signed_base {
+                    Some(base) => {
+                        let (sign, digits) = path.split_first()?;
+                        let sign = match sign.as_str() {
+                            "-" => 0,
+                            "+" => 1,
+                            _ => return None,
+                        };
+                        let digits: Vec<usize> = std::iter::once(sign)
+                            .chain(get_digits_outcome(digits).ok()?)
+                            .collect();
+                        if digits.iter().skip(1).any(|x| *x >= base) {
+                            return None;
+                        }
+                        convert_signed_path(&digits, base)
+                    }
+                    None => get_digits_outcome(path).ok()?,
+                };
+                Some((*x, digits))
+            })
+            .collect()
+    }
 }
 
 fn get_digits_outcome(input: &[String]) -> Result<Vec<usize>, crate::error::Error> {
@@ -321,10 +535,3 @@ fn get_digits_outcome(input: &[String]) -> Result<Vec<usize>, crate::error::Erro
         })
         .collect::<Result<Vec<usize>, crate::error::Error>>()
 }
-
-fn outcomes_to_digits(outcomes: &[(usize, &Vec<String>)]) -> Vec<(usize, Vec<usize>)> {
-    outcomes
-        .iter()
-        .filter_map(|(x, path)| Some((*x, get_digits_outcome(path).ok()?)))
-        .collect()
-}
@@ -1,12 +1,44 @@
 //! #ContractInput
 
+use crate::conversion_utils::get_ser_contract_info;
 use crate::error::Error;
 
-use super::ContractDescriptor;
-use secp256k1_zkp::XOnlyPublicKey;
+use super::contract_info::ContractInfo;
+use super::{ContractDescriptor, ContractPreview};
+use dlc_messages::oracle_msgs::{
+    DigitDecompositionEventDescriptor, EnumEventDescriptor, EventDescriptor, OracleAnnouncement,
+    OracleEvent,
+};
+use dlc_messages::ser_impls::BigSize;
+use lightning::util::ser::Writeable;
+use secp256k1_zkp::{schnorr::Signature, XOnlyPublicKey};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Size of the fields of an offer message other than the contract info, with no
+/// funding input and P2WPKH payout and change scripts: type (2), protocol
+/// version (4), contract flags (1), chain hash (32), temporary contract id (32),
+/// funding public key (33), payout script (24), payout serial id (8),
+/// collateral (8), empty funding inputs (1), change script (24), change serial
+/// id (8), fund output serial id (8), fee rate (8), CET and refund locktimes (8).
+const OFFER_BASE_SIZE: usize = 201;
+
+/// Size of the fields of an accept message other than the adaptor signatures,
+/// with no funding input and P2WPKH payout and change scripts: type (2),
+/// protocol version (4), temporary contract id (32), collateral (8), funding
+/// public key (33), payout script (24), payout serial id (8), empty funding
+/// inputs (1), change script (24), change serial id (8), refund signature (64),
+/// absent negotiation fields (1).
+const ACCEPT_BASE_SIZE: usize = 209;
+
+/// Size of the fields of a sign message other than the adaptor signatures, with
+/// no funding signature: type (2), protocol version (4), contract id (32),
+/// refund signature (64), empty funding signatures (1).
+const SIGN_BASE_SIZE: usize = 103;
+
+/// Size of a serialized ECDSA adaptor signature.
+const ADAPTOR_SIGNATURE_SIZE: usize = 162;
+
 /// Oracle information required for the initial creation of a contract.
 #[derive(Debug, Clone)]
 #[cfg_attr(
@@ -17,9 +49,14 @@ use serde::{Deserialize, Serialize};
 pub struct OracleInput {
     /// The set of public keys for each of the used oracles.
     pub public_keys: Vec<XOnlyPublicKey>,
-    /// The id of the event being used for the contract. Note that at the moment
-    /// a single event id is used, while multiple ids would be preferable.
+    /// The id of the event being used for the contract, for the oracles that
+    /// are not listed in `event_ids`.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub event_id: String,
+    /// The ids of the event used by specific oracles, for oracles that name the
+    /// same event differently.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub event_ids: Vec<(XOnlyPublicKey, String)>,
     /// The number of oracles that need to provide attestations satisfying the
     /// contract conditions to be able to close the contract.
     pub threshold: u16,
@@ -40,8 +77,42 @@ impl OracleInput {
             ));
         }
 
+        for (i, (public_key, _)) in self.event_ids.iter().enumerate() {
+            if !self.public_keys.contains(public_key) {
+                return Err(Error::InvalidParameters(
+                    "Event id provided for an oracle that is not used.".to_string(),
+                ));
+            }
+
+            if self.event_ids[..i].iter().any(|(x, _)| x == public_key) {
+                return Err(Error::InvalidParameters(
+                    "Multiple event ids provided for the same oracle.".to_string(),
+                ));
+            }
+        }
+
+        if self
+            .public_keys
+            .iter()
+            .any(|x| self.get_event_id(x).is_empty())
+        {
+            return Err(Error::InvalidParameters(
+                "Event id cannot be empty.".to_string(),
+            ));
+        }
+
         Ok(())
     }
+
+    /// Returns the id of the event to use for the oracle with the given public
+    /// key.
+    pub fn get_event_id(&self, public_key: &XOnlyPublicKey) -> &str {
+        self.event_ids
+            .iter()
+            .find(|(x, _)| x == public_key)
+            .map(|(_, event_id)| event_id.as_str())
+            .unwrap_or(&self.event_id)
+    }
 }
 
 /// Represents the contract specifications.
@@ -77,7 +148,87 @@ pub struct ContractInput {
     pub contract_infos: Vec<ContractInputInfo>,
 }
 
+/// Information about the CETs, adaptor signatures and messages that a contract
+/// input will produce.
+#[derive(Clone, Debug)]
+pub struct ContractInputPreview {
+    /// The preview of each of the contract infos, in the same order.
+    pub contract_previews: Vec<ContractPreview>,
+    /// The total number of CETs.
+    pub nb_cets: usize,
+    /// The total number of adaptor signatures that each party will generate
+    /// and verify.
+    pub nb_adaptor_signatures: usize,
+    /// The estimated size in bytes of the offer message, without funding inputs.
+    pub estimated_offer_size: usize,
+    /// The estimated size in bytes of the accept message, without funding
+    /// inputs.
+    pub estimated_accept_size: usize,
+    /// The estimated size in bytes of the sign message, without funding
+    /// signatures.
+    pub estimated_sign_size: usize,
+}
+
 impl ContractInput {
+    /// Returns information about the CETs, adaptor signatures and messages that
+    /// the contract will produce, without requiring the oracle announcements.
+    /// Message sizes do not include the funding inputs and signatures, which
+    /// depend on the wallet, and assume P2WPKH payout and change scripts and
+    /// numerical events without unit.
+    pub fn get_preview(&self) -> Result<ContractInputPreview, Error> {
+        self.validate()?;
+        let total_collateral = self
+            .offer_collateral
+            .checked_add(self.accept_collateral)
+            .ok_or_else(|| Error::InvalidParameters("Invalid total collateral.".to_string()))?;
+
+        let contract_previews = self
+            .contract_infos
+            .iter()
+            .map(|x| {
+                x.contract_descriptor.get_preview(
+                    total_collateral,
+                    x.oracles.public_keys.len(),
+                    x.oracles.threshold as usize,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let nb_cets = contract_previews.iter().map(|x| x.nb_cets).sum();
+        let nb_adaptor_signatures = contract_previews
+            .iter()
+            .map(|x| x.nb_adaptor_signatures)
+            .sum();
+
+        let contract_infos: Vec<ContractInfo> = self
+            .contract_infos
+            .iter()
+            .map(|x| ContractInfo {
+                contract_descriptor: x.contract_descriptor.clone(),
+                oracle_announcements: x
+                    .oracles
+                    .public_keys
+                    .iter()
+                    .enumerate()
+                    .map(|(i, public_key)| get_placeholder_announcement(x, i, public_key))
+                    .collect(),
+                threshold: x.oracles.threshold as usize,
+            })
+            .collect();
+        let contract_info_size =
+            get_ser_contract_info(&contract_infos, total_collateral).serialized_length();
+        let adaptor_signatures_size = BigSize(nb_adaptor_signatures as u64).serialized_length()
+            + nb_adaptor_signatures * ADAPTOR_SIGNATURE_SIZE;
+
+        Ok(ContractInputPreview {
+            contract_previews,
+            nb_cets,
+            nb_adaptor_signatures,
+            estimated_offer_size: OFFER_BASE_SIZE + contract_info_size,
+            estimated_accept_size: ACCEPT_BASE_SIZE + adaptor_signatures_size,
+            estimated_sign_size: SIGN_BASE_SIZE + adaptor_signatures_size,
+        })
+    }
+
     /// Validate the contract input parameters
     pub fn validate(&self) -> Result<(), Error> {
         if self.contract_infos.is_empty() {
@@ -88,6 +239,15 @@ impl ContractInput {
 
         for contract_info in &self.contract_infos {
             contract_info.oracles.validate()?;
+            if let ContractDescriptor::Enum(e) = &contract_info.contract_descriptor {
+                if e.oracle_outcome_mappings.as_ref().map_or(false, |x| {
+                    x.len() != contract_info.oracles.public_keys.len()
+                }) {
+                    return Err(Error::InvalidParameters(
+                        "Expected an outcome mapping for each oracle.".to_string(),
+                    ));
+                }
+            }
         }
 
         dlc::util::validate_fee_rate(self.fee_rate)
@@ -95,12 +255,74 @@ impl ContractInput {
     }
 }
 
+/// Returns an announcement with the same serialized size as the one that the
+/// oracle at `index` is expected to publish for the contract, apart from the
+/// unit of numerical events.
+fn get_placeholder_announcement(
+    contract_input_info: &ContractInputInfo,
+    index: usize,
+    public_key: &XOnlyPublicKey,
+) -> OracleAnnouncement {
+    let (event_descriptor, nb_nonces) = match &contract_input_info.contract_descriptor {
+        ContractDescriptor::Enum(e) => (
+            EventDescriptor::EnumEvent(EnumEventDescriptor {
+                outcomes: match e
+                    .oracle_outcome_mappings
+                    .as_ref()
+                    .and_then(|x| x.get(index))
+                {
+                    Some(mapping) => mapping.clone(),
+                    None => e
+                        .outcome_payouts
+                        .iter()
+                        .map(|x| x.outcome.clone())
+                        .collect(),
+                },
+            }),
+            1,
+        ),
+        ContractDescriptor::Numerical(n) => {
+            let nb_digits = n.oracle_numeric_infos.nb_digits[index];
+            let outcome_params = n.get_outcome_params();
+            (
+                EventDescriptor::DigitDecompositionEvent(DigitDecompositionEventDescriptor {
+                    base: n.oracle_numeric_infos.get_base(index) as u16,
+                    is_signed: outcome_params.is_signed,
+                    unit: String::new(),
+                    precision: outcome_params.precision,
+                    nb_digits: nb_digits as u16,
+                }),
+                nb_digits + outcome_params.is_signed as usize,
+            )
+        }
+    };
+
+    OracleAnnouncement {
+        announcement_signature: Signature::from_slice(&[1; 64])
+            .expect("to have a valid signature length"),
+        oracle_public_key: *public_key,
+        oracle_event: OracleEvent {
+            oracle_nonces: vec![*public_key; nb_nonces],
+            event_maturity_epoch: 0,
+            event_descriptor,
+            event_id: contract_input_info
+                .oracles
+                .get_event_id(public_key)
+                .to_string(),
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use dlc::{EnumerationPayout, Payout};
     use secp256k1_zkp::{KeyPair, SECP256K1};
 
     use crate::contract::enum_descriptor::EnumDescriptor;
+    use crate::contract::numerical_descriptor::{DifferenceParams, NumericalDescriptor};
+    use crate::contract::ser::Serializable;
+    use crate::payout_curve::builders::BinaryOption;
+    use dlc_trie::OracleNumericInfo;
 
     use super::*;
 
@@ -127,6 +349,7 @@ mod tests {
                             },
                         },
                     ],
+                    oracle_outcome_mappings: None,
                 }),
                 oracles: OracleInput {
                     public_keys: vec![
@@ -137,6 +360,7 @@ mod tests {
                         .0,
                     ],
                     event_id: "1234".to_string(),
+                    event_ids: Vec::new(),
                     threshold: 1,
                 },
             }],
@@ -184,4 +408,219 @@ mod tests {
             .validate()
             .expect_err("the contract input to be invalid.");
     }
+
+    fn other_public_key() -> XOnlyPublicKey {
+        XOnlyPublicKey::from_keypair(&KeyPair::from_secret_key(
+            SECP256K1,
+            &secp256k1_zkp::SecretKey::from_slice(&[2; 32]).unwrap(),
+        ))
+        .0
+    }
+
+    #[test]
+    fn per_oracle_event_ids_contract_input_is_valid() {
+        let mut input = get_base_input();
+        let oracles = &mut input.contract_infos[0].oracles;
+        let other = other_public_key();
+        oracles.public_keys.push(other);
+        oracles.event_ids.push((other, "btcusd-5678".to_string()));
+        input.validate().expect("the contract input to be valid.");
+
+        let oracles = &input.contract_infos[0].oracles;
+        assert_eq!("1234", oracles.get_event_id(&oracles.public_keys[0]));
+        assert_eq!("btcusd-5678", oracles.get_event_id(&other));
+    }
+
+    #[test]
+    fn unknown_oracle_event_id_contract_input_is_not_valid() {
+        let mut input = get_base_input();
+        input.contract_infos[0]
+            .oracles
+            .event_ids
+            .push((other_public_key(), "5678".to_string()));
+        input
+            .validate()
+            .expect_err("the contract input to be invalid.");
+    }
+
+    #[test]
+    fn duplicate_oracle_event_id_contract_input_is_not_valid() {
+        let mut input = get_base_input();
+        let oracles = &mut input.contract_infos[0].oracles;
+        let public_key = oracles.public_keys[0];
+        oracles.event_ids.push((public_key, "5678".to_string()));
+        oracles.event_ids.push((public_key, "9012".to_string()));
+        input
+            .validate()
+            .expect_err("the contract input to be invalid.");
+    }
+
+    #[test]
+    fn missing_event_id_contract_input_is_not_valid() {
+        let mut input = get_base_input();
+        let oracles = &mut input.contract_infos[0].oracles;
+        oracles.event_id = String::new();
+        oracles.public_keys.push(other_public_key());
+        oracles
+            .event_ids
+            .push((oracles.public_keys[0], "5678".to_string()));
+        input
+            .validate()
+            .expect_err("the contract input to be invalid.");
+    }
+
+    #[test]
+    fn enum_contract_input_preview_test() {
+        let mut input = get_base_input();
+        let preview = input.get_preview().expect("to be able to get a preview");
+        assert_eq!(2, preview.nb_cets);
+        assert_eq!(2, preview.nb_adaptor_signatures);
+        assert_eq!(
+            ACCEPT_BASE_SIZE + 1 + 2 * ADAPTOR_SIGNATURE_SIZE,
+            preview.estimated_accept_size
+        );
+        assert_eq!(
+            SIGN_BASE_SIZE + 1 + 2 * ADAPTOR_SIGNATURE_SIZE,
+            preview.estimated_sign_size
+        );
+
+        // Two out of three oracles give three combinations for each outcome.
+        let oracles = &mut input.contract_infos[0].oracles;
+        oracles.public_keys.push(other_public_key());
+        oracles.public_keys.push(
+            XOnlyPublicKey::from_keypair(&KeyPair::new(
+                SECP256K1,
+                &mut secp256k1_zkp::rand::thread_rng(),
+            ))
+            .0,
+        );
+        oracles.threshold = 2;
+        let multi_preview = input.get_preview().expect("to be able to get a preview");
+        assert_eq!(2, multi_preview.nb_cets);
+        assert_eq!(6, multi_preview.nb_adaptor_signatures);
+        assert!(multi_preview.estimated_offer_size > preview.estimated_offer_size);
+    }
+
+    #[test]
+    fn numerical_contract_input_preview_test() {
+        let (payout_function, rounding_intervals) = BinaryOption {
+            strike_price: 512,
+            offer_collateral: 1000000,
+            accept_collateral: 2000000,
+            offer_wins_above: true,
+        }
+        .build(1023, 1)
+        .unwrap();
+        let mut input = get_base_input();
+        input.contract_infos[0].contract_descriptor =
+            ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function,
+                rounding_intervals,
+                difference_params: None,
+                oracle_numeric_infos: OracleNumericInfo {
+                    base: 2,
+                    nb_digits: vec![10],
+                    oracle_bases: None,
+                },
+                outcome_params: None,
+            });
+
+        let preview = input.get_preview().expect("to be able to get a preview");
+        // Outcomes under the strike price all start with a 0 and outcomes above
+        // with a 1, so a single adaptor signature is needed for each CET.
+        assert_eq!(2, preview.nb_cets);
+        assert_eq!(2, preview.nb_adaptor_signatures);
+        assert_eq!(
+            0,
+            preview.contract_previews[0].range_payouts[0].payout.offer
+        );
+        assert_eq!(512, preview.contract_previews[0].range_payouts[1].start);
+
+        input.contract_infos[0]
+            .oracles
+            .public_keys
+            .push(other_public_key());
+        input
+            .get_preview()
+            .expect_err("number of oracles should match the numeric infos");
+    }
+
+    #[test]
+    fn multi_base_numerical_contract_input_preview_test() {
+        // Both oracles can represent outcomes up to 999.
+        let (payout_function, rounding_intervals) = BinaryOption {
+            strike_price: 512,
+            offer_collateral: 1000000,
+            accept_collateral: 2000000,
+            offer_wins_above: true,
+        }
+        .build(999, 1)
+        .unwrap();
+        let oracle_numeric_infos = OracleNumericInfo {
+            base: 10,
+            nb_digits: vec![3, 10],
+            oracle_bases: Some(vec![10, 2]),
+        };
+        let mut input = get_base_input();
+        input.contract_infos[0].contract_descriptor =
+            ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function,
+                rounding_intervals,
+                difference_params: None,
+                oracle_numeric_infos,
+                outcome_params: None,
+            });
+        input.contract_infos[0]
+            .oracles
+            .public_keys
+            .push(other_public_key());
+        input.contract_infos[0].oracles.threshold = 2;
+
+        let preview = input.get_preview().expect("to be able to get a preview");
+        assert_eq!(2, preview.nb_cets);
+        // Outcome prefixes cannot be shared between the oracles, so more than
+        // one adaptor signature is needed for each CET.
+        assert!(preview.nb_adaptor_signatures > preview.nb_cets);
+
+        let placeholder = get_placeholder_announcement(
+            &input.contract_infos[0],
+            1,
+            &input.contract_infos[0].oracles.public_keys[1],
+        );
+        match placeholder.oracle_event.event_descriptor {
+            EventDescriptor::DigitDecompositionEvent(d) => {
+                assert_eq!(2, d.base);
+                assert_eq!(10, d.nb_digits);
+            }
+            _ => panic!("Expected a digit decomposition event."),
+        }
+
+        let serialized = input.contract_infos[0]
+            .contract_descriptor
+            .serialize()
+            .unwrap();
+        match ContractDescriptor::deserialize(&mut std::io::Cursor::new(serialized)).unwrap() {
+            ContractDescriptor::Numerical(n) => {
+                assert_eq!(10, n.oracle_numeric_infos.base);
+                assert_eq!(vec![3, 10], n.oracle_numeric_infos.nb_digits);
+                assert_eq!(Some(vec![10, 2]), n.oracle_numeric_infos.oracle_bases);
+            }
+            _ => panic!("Expected a numerical descriptor."),
+        }
+
+        // Allowing differences between the oracles requires additional
+        // adaptor signatures to cover the outcomes of the second oracle.
+        if let ContractDescriptor::Numerical(n) = &mut input.contract_infos[0].contract_descriptor {
+            n.difference_params = Some(DifferenceParams {
+                max_error_exp: 4,
+                min_support_exp: 2,
+                maximize_coverage: false,
+            });
+        }
+        let diff_preview = input
+            .get_preview()
+            .expect("to be able to get a preview with differences");
+        assert_eq!(2, diff_preview.nb_cets);
+        assert!(diff_preview.nb_adaptor_signatures > preview.nb_adaptor_signatures);
+    }
 }
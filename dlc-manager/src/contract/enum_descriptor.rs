@@ -5,10 +5,12 @@ use super::utils::{get_majority_combination, unordered_equal};
 use super::AdaptorInfo;
 use crate::error::Error;
 use bitcoin::{Script, Transaction};
-use dlc::OracleInfo;
+use dlc::{AdaptorSigVerificationMode, OracleInfo};
 use dlc::{EnumerationPayout, Payout};
 use dlc_messages::oracle_msgs::EnumEventDescriptor;
 use dlc_trie::{combination_iterator::CombinationIterator, RangeInfo};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use secp256k1_zkp::{
     All, EcdsaAdaptorSignature, Message, PublicKey, Secp256k1, SecretKey, Verification,
 };
@@ -25,6 +27,13 @@ use serde::{Deserialize, Serialize};
 pub struct EnumDescriptor {
     /// The set of outcomes.
     pub outcome_payouts: Vec<EnumerationPayout>,
+    /// For oracles using different labels for the outcomes of the event, the
+    /// outcome attested by each oracle (in the order of the oracles of the
+    /// contract) for each of the outcome payouts (in the same order). If
+    /// `None`, all oracles are expected to attest to the outcomes of
+    /// `outcome_payouts`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub oracle_outcome_mappings: Option<Vec<Vec<String>>>,
 }
 
 impl EnumDescriptor {
@@ -55,6 +64,88 @@ impl EnumDescriptor {
         }
     }
 
+    /// Validate that the descriptor covers all possible outcomes of each of the
+    /// given oracle event descriptors. Unless outcome mappings are provided, all
+    /// the oracles are required to have the same outcomes.
+    pub fn validate_oracle_events(
+        &self,
+        enum_event_descriptors: &[&EnumEventDescriptor],
+    ) -> Result<(), Error> {
+        let mappings = match &self.oracle_outcome_mappings {
+            Some(mappings) => mappings,
+            None => {
+                let first = enum_event_descriptors.first().ok_or_else(|| {
+                    Error::InvalidParameters("Expected at least one oracle.".to_string())
+                })?;
+                if enum_event_descriptors
+                    .iter()
+                    .any(|x| !unordered_equal(&first.outcomes, &x.outcomes))
+                {
+                    return Err(Error::InvalidParameters(
+                        "Oracles don't have same enum outcomes.".to_string(),
+                    ));
+                }
+                return self.validate(first);
+            }
+        };
+
+        if mappings.len() != enum_event_descriptors.len() {
+            return Err(Error::InvalidParameters(
+                "Expected an outcome mapping for each oracle.".to_string(),
+            ));
+        }
+
+        let outcomes: Vec<_> = self.outcome_payouts.iter().map(|x| &x.outcome).collect();
+        let mut unique_outcomes = outcomes.clone();
+        unique_outcomes.sort();
+        unique_outcomes.dedup();
+        if unique_outcomes.len() != outcomes.len() {
+            return Err(Error::InvalidParameters(
+                "Outcomes of the contract must be unique.".to_string(),
+            ));
+        }
+
+        for (mapping, enum_event_descriptor) in mappings.iter().zip(enum_event_descriptors) {
+            if mapping.len() != self.outcome_payouts.len()
+                || !unordered_equal(&enum_event_descriptor.outcomes, mapping)
+            {
+                return Err(Error::InvalidParameters(
+                    "Oracle outcomes do not each map to a single payout.".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the outcome attested by the oracle at `oracle_index` for the
+    /// outcome payout at `outcome_index`.
+    pub(crate) fn get_oracle_outcome(&self, oracle_index: usize, outcome_index: usize) -> &str {
+        match &self.oracle_outcome_mappings {
+            Some(mappings) => &mappings[oracle_index][outcome_index],
+            None => &self.outcome_payouts[outcome_index].outcome,
+        }
+    }
+
+    /// Returns the outcome of the contract corresponding to the given outcome
+    /// attested by the oracle at `oracle_index`, if any.
+    fn get_contract_outcome(&self, oracle_index: usize, oracle_outcome: &str) -> Option<&String> {
+        match &self.oracle_outcome_mappings {
+            Some(mappings) => {
+                let pos = mappings
+                    .get(oracle_index)?
+                    .iter()
+                    .position(|x| x == oracle_outcome)?;
+                Some(&self.outcome_payouts.get(pos)?.outcome)
+            }
+            None => self
+                .outcome_payouts
+                .iter()
+                .map(|x| &x.outcome)
+                .find(|x| *x == oracle_outcome),
+        }
+    }
+
     /// Returns the `RangeInfo` that matches the given set of outcomes if any.
     pub fn get_range_info_for_outcome(
         &self,
@@ -67,11 +158,16 @@ impl EnumDescriptor {
             return None;
         }
 
-        let filtered_outcomes: Vec<(usize, &Vec<String>)> = outcomes
+        let contract_outcomes: Vec<(usize, Vec<String>)> = outcomes
             .iter()
             .filter(|x| x.1.len() == 1)
-            .cloned()
+            .filter_map(|(i, x)| {
+                let outcome = self.get_contract_outcome(*i, &x[0])?;
+                Some((*i, vec![outcome.clone()]))
+            })
             .collect();
+        let filtered_outcomes: Vec<(usize, &Vec<String>)> =
+            contract_outcomes.iter().map(|(i, x)| (*i, x)).collect();
         let (mut outcome, mut actual_combination) = get_majority_combination(&filtered_outcomes)?;
         let outcome = outcome.remove(0);
 
@@ -121,26 +217,40 @@ impl EnumDescriptor {
         adaptor_sigs: &[EcdsaAdaptorSignature],
         adaptor_sig_start: usize,
     ) -> Result<usize, dlc::Error> {
-        let mut adaptor_sig_index = adaptor_sig_start;
-        let mut callback =
-            |adaptor_point: &PublicKey, cet_index: usize| -> Result<(), dlc::Error> {
-                let sig = adaptor_sigs[adaptor_sig_index];
-                adaptor_sig_index += 1;
-                dlc::verify_cet_adaptor_sig_from_point(
-                    secp,
-                    &sig,
-                    &cets[cet_index],
-                    adaptor_point,
-                    fund_pubkey,
-                    funding_script_pubkey,
-                    fund_output_value,
-                )?;
-                Ok(())
-            };
+        let inputs = self.map_outcomes(
+            secp,
+            oracle_infos,
+            threshold,
+            |adaptor_index, adaptor_point, cet_index| {
+                let adaptor_sig = adaptor_sigs
+                    .get(adaptor_sig_start + adaptor_index)
+                    .ok_or_else(|| {
+                        dlc::Error::InvalidArgument("Missing adaptor signature".to_string())
+                    })?;
+                Ok((cet_index, *adaptor_point, *adaptor_sig))
+            },
+        )?;
+        let nb_adaptor_sigs = inputs.len();
 
-        self.iter_outcomes(secp, oracle_infos, threshold, &mut callback)?;
+        // Report the indexes of the invalid signatures within `adaptor_sigs`
+        // rather than within `inputs`.
+        dlc::verify_cet_adaptor_sigs_from_points(
+            secp,
+            cets,
+            &inputs,
+            fund_pubkey,
+            funding_script_pubkey,
+            fund_output_value,
+            AdaptorSigVerificationMode::EarlyAbort,
+        )
+        .map_err(|e| match e {
+            dlc::Error::InvalidAdaptorSignatures(indexes) => dlc::Error::InvalidAdaptorSignatures(
+                indexes.into_iter().map(|i| adaptor_sig_start + i).collect(),
+            ),
+            e => e,
+        })?;
 
-        Ok(adaptor_sig_index)
+        Ok(adaptor_sig_start + nb_adaptor_sigs)
     }
 
     /// Verify the given set of adaptor signature and generates the adaptor info.
@@ -206,71 +316,278 @@ impl EnumDescriptor {
         funding_script_pubkey: &Script,
         fund_output_value: u64,
     ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
-        let mut adaptor_sigs = Vec::new();
-        let mut callback =
-            |adaptor_point: &PublicKey, cet_index: usize| -> Result<(), dlc::Error> {
-                let sig = dlc::create_cet_adaptor_sig_from_point(
+        let adaptor_sigs = self.map_outcomes(
+            secp,
+            oracle_infos,
+            threshold,
+            |_, adaptor_point, cet_index| {
+                dlc::create_cet_adaptor_sig_from_point(
                     secp,
                     &cets[cet_index],
                     adaptor_point,
                     fund_privkey,
                     funding_script_pubkey,
                     fund_output_value,
-                )?;
-                adaptor_sigs.push(sig);
-                Ok(())
-            };
-
-        self.iter_outcomes(secp, oracle_infos, threshold, &mut callback)?;
+                )
+            },
+        )?;
 
         Ok(adaptor_sigs)
     }
 
-    fn iter_outcomes<C: Verification, F>(
+    /// Computes the adaptor point of every outcome and oracle combination, in
+    /// the order of their adaptor signatures, and maps each of them using `f`
+    /// together with their adaptor signature index (relative to the first
+    /// signature of the contract) and the index of their CET. Adaptor points
+    /// are computed and mapped in parallel if the `parallel` feature is enabled.
+    fn map_outcomes<C: Verification, T, F>(
         &self,
         secp: &Secp256k1<C>,
         oracle_infos: &[OracleInfo],
         threshold: usize,
-        callback: &mut F,
-    ) -> Result<(), dlc::Error>
+        f: F,
+    ) -> Result<Vec<T>, dlc::Error>
     where
-        F: FnMut(&PublicKey, usize) -> Result<(), dlc::Error>,
+        T: Send,
+        F: Fn(usize, &PublicKey, usize) -> Result<T, dlc::Error> + Sync,
     {
-        let messages: Vec<Vec<Vec<Message>>> = self
-            .outcome_payouts
+        // The signature point of each oracle for each outcome is shared by the
+        // adaptor points of all the combinations of oracles including it.
+        let sig_points: Vec<Vec<PublicKey>> = oracle_infos
             .iter()
-            .map(|x| {
-                let message = vec![Message::from_hashed_data::<
-                    secp256k1_zkp::hashes::sha256::Hash,
-                >(x.outcome.as_bytes())];
-                std::iter::repeat(message).take(threshold).collect()
+            .enumerate()
+            .map(|(i, oracle_info)| {
+                (0..self.outcome_payouts.len())
+                    .map(|j| {
+                        let message =
+                            Message::from_hashed_data::<secp256k1_zkp::hashes::sha256::Hash>(
+                                self.get_oracle_outcome(i, j).as_bytes(),
+                            );
+                        dlc::get_oracle_sig_point(secp, oracle_info, &[message])
+                    })
+                    .collect()
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
         let combination_iter = CombinationIterator::new(oracle_infos.len(), threshold);
         let combinations: Vec<Vec<usize>> = combination_iter.collect();
+        let outcome_combinations: Vec<(usize, &Vec<usize>)> = (0..self.outcome_payouts.len())
+            .flat_map(|i| combinations.iter().map(move |selector| (i, selector)))
+            .collect();
 
-        for (i, outcome_messages) in messages.iter().enumerate() {
-            for selector in &combinations {
-                let cur_oracle_infos: Vec<_> = oracle_infos
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(i, x)| {
-                        if selector.contains(&i) {
-                            Some(x.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                let adaptor_point = dlc::get_adaptor_point_from_oracle_info(
-                    secp,
-                    &cur_oracle_infos,
-                    outcome_messages,
-                )?;
-                callback(&adaptor_point, i)?;
-            }
+        let map = |(adaptor_index, (cet_index, selector)): (usize, (usize, &Vec<usize>))| {
+            let cur_sig_points: Vec<&PublicKey> = selector
+                .iter()
+                .map(|i| &sig_points[*i][cet_index])
+                .collect();
+            let adaptor_point = PublicKey::combine_keys(&cur_sig_points)?;
+            f(adaptor_index, &adaptor_point, cet_index)
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let outcome_combinations = outcome_combinations.into_iter();
+        #[cfg(feature = "parallel")]
+        let outcome_combinations = outcome_combinations.into_par_iter();
+        outcome_combinations.enumerate().map(map).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::contract::ser::Serializable;
+    use crate::contract::ContractDescriptor;
+    use bitcoin::{PackedLockTime, TxIn};
+    use secp256k1_zkp::{KeyPair, XOnlyPublicKey};
+
+    fn to_strings(outcomes: &[&str]) -> Vec<String> {
+        outcomes.iter().map(|x| x.to_string()).collect()
+    }
+
+    fn get_descriptor(oracle_outcome_mappings: Option<Vec<Vec<String>>>) -> EnumDescriptor {
+        EnumDescriptor {
+            outcome_payouts: ["a", "b", "c"]
+                .iter()
+                .enumerate()
+                .map(|(i, x)| EnumerationPayout {
+                    outcome: x.to_string(),
+                    payout: Payout {
+                        offer: i as u64 * 100,
+                        accept: 200 - i as u64 * 100,
+                    },
+                })
+                .collect(),
+            oracle_outcome_mappings,
+        }
+    }
+
+    fn get_mappings() -> Vec<Vec<String>> {
+        vec![
+            to_strings(&["a", "b", "c"]),
+            to_strings(&["yes", "no", "draw"]),
+        ]
+    }
+
+    fn get_event_descriptor(outcomes: &[&str]) -> EnumEventDescriptor {
+        EnumEventDescriptor {
+            outcomes: to_strings(outcomes),
         }
+    }
 
-        Ok(())
+    fn random_public_key(secp: &Secp256k1<All>) -> XOnlyPublicKey {
+        XOnlyPublicKey::from_keypair(&KeyPair::new(secp, &mut secp256k1_zkp::rand::thread_rng())).0
+    }
+
+    #[test]
+    fn validate_oracle_events_without_mappings_test() {
+        let descriptor = get_descriptor(None);
+        let first = get_event_descriptor(&["a", "b", "c"]);
+        let second = get_event_descriptor(&["c", "a", "b"]);
+        let other = get_event_descriptor(&["yes", "no", "draw"]);
+
+        descriptor
+            .validate_oracle_events(&[&first, &second])
+            .expect("to be valid");
+        descriptor
+            .validate_oracle_events(&[&first, &other])
+            .expect_err("oracles should have the same outcomes");
+    }
+
+    #[test]
+    fn validate_oracle_events_with_mappings_test() {
+        let first = get_event_descriptor(&["c", "a", "b"]);
+        let second = get_event_descriptor(&["draw", "yes", "no"]);
+
+        get_descriptor(Some(get_mappings()))
+            .validate_oracle_events(&[&first, &second])
+            .expect("to be valid");
+        get_descriptor(Some(get_mappings()))
+            .validate_oracle_events(&[&first])
+            .expect_err("each oracle should have a mapping");
+        get_descriptor(Some(get_mappings()))
+            .validate_oracle_events(&[&first, &get_event_descriptor(&["yes", "no", "maybe"])])
+            .expect_err("oracle outcomes should match the mapping");
+        get_descriptor(Some(vec![
+            to_strings(&["a", "b", "c"]),
+            to_strings(&["yes", "no", "no"]),
+        ]))
+        .validate_oracle_events(&[&first, &get_event_descriptor(&["yes", "no"])])
+        .expect_err("oracle outcomes should each map to a single payout");
+
+        let mut descriptor = get_descriptor(Some(get_mappings()));
+        descriptor.outcome_payouts[2].outcome = "a".to_string();
+        descriptor
+            .validate_oracle_events(&[&first, &second])
+            .expect_err("contract outcomes should be unique");
+    }
+
+    #[test]
+    fn get_range_info_for_mapped_outcomes_test() {
+        let descriptor = get_descriptor(Some(get_mappings()));
+        let first = to_strings(&["b"]);
+        let second = to_strings(&["no"]);
+
+        let (oracle_indexes, range_info) = descriptor
+            .get_range_info_for_outcome(2, 2, &[(0, &first), (1, &second)], 0)
+            .expect("to find the range info");
+        assert_eq!(vec![(0, 1), (1, 1)], oracle_indexes);
+        assert_eq!(1, range_info.cet_index);
+        assert_eq!(1, range_info.adaptor_index);
+
+        let disagreeing = to_strings(&["yes"]);
+        assert!(descriptor
+            .get_range_info_for_outcome(2, 2, &[(0, &first), (1, &disagreeing)], 0)
+            .is_none());
+        assert!(descriptor
+            .get_range_info_for_outcome(2, 2, &[(0, &first), (1, &first)], 0)
+            .is_none());
+    }
+
+    #[test]
+    fn mapped_adaptor_signatures_test() {
+        let secp = Secp256k1::new();
+        let descriptor = get_descriptor(Some(get_mappings()));
+        let oracle_infos: Vec<OracleInfo> = (0..2)
+            .map(|_| OracleInfo {
+                public_key: random_public_key(&secp),
+                nonces: vec![random_public_key(&secp)],
+            })
+            .collect();
+        let cets: Vec<Transaction> = (0..3)
+            .map(|i| Transaction {
+                version: 2,
+                lock_time: PackedLockTime(i),
+                input: vec![TxIn::default()],
+                output: Vec::new(),
+            })
+            .collect();
+        let fund_privkey = SecretKey::new(&mut secp256k1_zkp::rand::thread_rng());
+        let fund_pubkey = PublicKey::from_secret_key(&secp, &fund_privkey);
+        let funding_script_pubkey = Script::new();
+
+        let adaptor_sigs = descriptor
+            .get_adaptor_signatures(
+                &secp,
+                &oracle_infos,
+                1,
+                &cets,
+                &fund_privkey,
+                &funding_script_pubkey,
+                1000,
+            )
+            .unwrap();
+        assert_eq!(6, adaptor_sigs.len());
+        assert_eq!(
+            6,
+            descriptor
+                .verify_adaptor_info(
+                    &secp,
+                    &oracle_infos,
+                    1,
+                    &fund_pubkey,
+                    &funding_script_pubkey,
+                    1000,
+                    &cets,
+                    &adaptor_sigs,
+                    0,
+                )
+                .unwrap()
+        );
+
+        // The signature for the third outcome attested by the second oracle
+        // uses the label of that oracle.
+        let message =
+            Message::from_hashed_data::<secp256k1_zkp::hashes::sha256::Hash>("draw".as_bytes());
+        let adaptor_point =
+            dlc::get_adaptor_point_from_oracle_info(&secp, &oracle_infos[1..], &[vec![message]])
+                .unwrap();
+        dlc::verify_cet_adaptor_sig_from_point(
+            &secp,
+            &adaptor_sigs[5],
+            &cets[2],
+            &adaptor_point,
+            &fund_pubkey,
+            &funding_script_pubkey,
+            1000,
+        )
+        .expect("the signature to use the label of the oracle");
+    }
+    #[test]
+    fn contract_descriptor_serialization_test() {
+        for mappings in [None, Some(get_mappings())] {
+            let descriptor = get_descriptor(mappings.clone());
+            let serialized = ContractDescriptor::Enum(descriptor.clone())
+                .serialize()
+                .unwrap();
+            // Descriptors without mappings keep their previous encoding.
+            assert_eq!(if mappings.is_some() { 2 } else { 0 }, serialized[0]);
+
+            match ContractDescriptor::deserialize(&mut std::io::Cursor::new(serialized)).unwrap() {
+                ContractDescriptor::Enum(e) => {
+                    assert_eq!(mappings, e.oracle_outcome_mappings);
+                    assert_eq!(descriptor.get_payouts(), e.get_payouts());
+                }
+                _ => panic!("Expected an enum descriptor."),
+            }
+        }
     }
 }
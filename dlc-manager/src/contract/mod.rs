@@ -3,20 +3,23 @@
 use crate::error::Error;
 use crate::ContractId;
 use bitcoin::{Address, Transaction};
+use dlc::RangePayout;
 use dlc_messages::{
     oracle_msgs::{EventDescriptor, OracleAnnouncement, OracleAttestation},
     AcceptDlc, FundingInput, SignDlc,
 };
+use dlc_trie::combination_iterator::CombinationIterator;
+use dlc_trie::multi_base_oracle_trie::MultiBaseOracleTrie;
 use dlc_trie::multi_oracle_trie::MultiOracleTrie;
 use dlc_trie::multi_oracle_trie_with_diff::MultiOracleTrieWithDiff;
+use dlc_trie::DlcTrie;
 use secp256k1_zkp::PublicKey;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use signed_contract::SignedContract;
 
-use self::utils::unordered_equal;
-
 pub mod accepted_contract;
+pub mod adaptor_point_cache;
 pub mod contract_info;
 pub mod contract_input;
 pub mod enum_descriptor;
@@ -41,7 +44,10 @@ pub enum Contract {
     PreClosed(PreClosedContract),
     /// A contract for which a CET was confirmed to blockchain
     Closed(ClosedContract),
-    /// A contract whose refund transaction was broadcast.
+    /// A contract for which the refund transaction was broadcast, but not
+    /// necessarily confirmed to blockchain.
+    PreRefunded(PreRefundedContract),
+    /// A contract whose refund transaction was confirmed to blockchain.
     Refunded(signed_contract::SignedContract),
     /// A contract that failed when verifying information from an accept message.
     FailedAccept(FailedAcceptContract),
@@ -60,6 +66,7 @@ impl std::fmt::Debug for Contract {
             Contract::Confirmed(_) => "confirmed",
             Contract::PreClosed(_) => "pre-closed",
             Contract::Closed(_) => "closed",
+            Contract::PreRefunded(_) => "pre-refunded",
             Contract::Refunded(_) => "refunded",
             Contract::FailedAccept(_) => "failed accept",
             Contract::FailedSign(_) => "failed sign",
@@ -69,7 +76,75 @@ impl std::fmt::Debug for Contract {
     }
 }
 
+/// The type of state a [`Contract`] is in, used to filter contracts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContractStateType {
+    /// See [`Contract::Offered`].
+    Offered,
+    /// See [`Contract::Accepted`].
+    Accepted,
+    /// See [`Contract::Signed`].
+    Signed,
+    /// See [`Contract::Confirmed`].
+    Confirmed,
+    /// See [`Contract::PreClosed`].
+    PreClosed,
+    /// See [`Contract::Closed`].
+    Closed,
+    /// See [`Contract::PreRefunded`].
+    PreRefunded,
+    /// See [`Contract::Refunded`].
+    Refunded,
+    /// See [`Contract::FailedAccept`].
+    FailedAccept,
+    /// See [`Contract::FailedSign`].
+    FailedSign,
+    /// See [`Contract::Rejected`].
+    Rejected,
+}
+
 impl Contract {
+    /// Returns the type of state the contract is in.
+    pub fn get_state_type(&self) -> ContractStateType {
+        match self {
+            Contract::Offered(_) => ContractStateType::Offered,
+            Contract::Accepted(_) => ContractStateType::Accepted,
+            Contract::Signed(_) => ContractStateType::Signed,
+            Contract::Confirmed(_) => ContractStateType::Confirmed,
+            Contract::PreClosed(_) => ContractStateType::PreClosed,
+            Contract::Closed(_) => ContractStateType::Closed,
+            Contract::PreRefunded(_) => ContractStateType::PreRefunded,
+            Contract::Refunded(_) => ContractStateType::Refunded,
+            Contract::FailedAccept(_) => ContractStateType::FailedAccept,
+            Contract::FailedSign(_) => ContractStateType::FailedSign,
+            Contract::Rejected(_) => ContractStateType::Rejected,
+        }
+    }
+
+    /// Returns the latest maturity of the oracle events the contract is based on,
+    /// as a unix timestamp. Returns `None` for closed contracts, which do not
+    /// keep the contract information.
+    pub fn get_maturity(&self) -> Option<u64> {
+        let offered_contract = match self {
+            Contract::Offered(o) | Contract::Rejected(o) => o,
+            Contract::Accepted(a) => &a.offered_contract,
+            Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => {
+                &s.accepted_contract.offered_contract
+            }
+            Contract::PreClosed(c) => &c.signed_contract.accepted_contract.offered_contract,
+            Contract::PreRefunded(c) => &c.signed_contract.accepted_contract.offered_contract,
+            Contract::FailedAccept(f) => &f.offered_contract,
+            Contract::FailedSign(f) => &f.accepted_contract.offered_contract,
+            Contract::Closed(_) => return None,
+        };
+        offered_contract
+            .contract_info
+            .iter()
+            .flat_map(|info| info.oracle_announcements.iter())
+            .map(|announcement| announcement.oracle_event.event_maturity_epoch as u64)
+            .max()
+    }
+
     /// Get the id of a contract. Returns the temporary contract id for offered
     /// and failed accept contracts.
     pub fn get_id(&self) -> ContractId {
@@ -82,6 +157,7 @@ impl Contract {
             Contract::FailedAccept(c) => c.offered_contract.id,
             Contract::FailedSign(c) => c.accepted_contract.get_contract_id(),
             Contract::PreClosed(c) => c.signed_contract.accepted_contract.get_contract_id(),
+            Contract::PreRefunded(c) => c.signed_contract.accepted_contract.get_contract_id(),
             Contract::Closed(c) => c.contract_id,
         }
     }
@@ -97,6 +173,7 @@ impl Contract {
             Contract::FailedAccept(c) => c.offered_contract.id,
             Contract::FailedSign(c) => c.accepted_contract.offered_contract.id,
             Contract::PreClosed(c) => c.signed_contract.accepted_contract.offered_contract.id,
+            Contract::PreRefunded(c) => c.signed_contract.accepted_contract.offered_contract.id,
             Contract::Closed(c) => c.temporary_contract_id,
         }
     }
@@ -115,6 +192,12 @@ impl Contract {
                     .offered_contract
                     .counter_party
             }
+            Contract::PreRefunded(c) => {
+                c.signed_contract
+                    .accepted_contract
+                    .offered_contract
+                    .counter_party
+            }
             Contract::Closed(c) => c.counter_party_id,
             Contract::FailedAccept(f) => f.offered_contract.counter_party,
             Contract::FailedSign(f) => f.accepted_contract.offered_contract.counter_party,
@@ -169,6 +252,16 @@ pub struct PreClosedContract {
     pub signed_cet: Transaction,
 }
 
+/// Information about a contract that is almost refunded by a broadcasted, but not confirmed
+/// refund transaction.
+#[derive(Clone)]
+pub struct PreRefundedContract {
+    /// The signed contract that is being refunded.
+    pub signed_contract: SignedContract,
+    /// The signed version of the refund transaction that was broadcast.
+    pub signed_refund: Transaction,
+}
+
 /// Information about a contract that was closed by a CET that was confirmed on the blockchain.
 #[derive(Clone)]
 pub struct ClosedContract {
@@ -197,6 +290,10 @@ pub enum AdaptorInfo {
     /// For numerical outcome DLC where oracles are allowed to diverge to some
     /// extent in the outcome value, a trie of trie is used to store the information.
     NumericalWithDifference(MultiOracleTrieWithDiff),
+    /// For numerical outcome DLC where oracles use different bases, or are
+    /// allowed to diverge without using base 2, the outcome prefixes of each
+    /// oracle are stored for every adaptor signature.
+    NumericalMultiBase(MultiBaseOracleTrie),
 }
 
 /// The descriptor of a contract.
@@ -213,7 +310,100 @@ pub enum ContractDescriptor {
     Numerical(numerical_descriptor::NumericalDescriptor),
 }
 
+/// Information about the CETs and adaptor signatures that a contract descriptor
+/// will require, computed without any key or oracle announcement.
+#[derive(Clone, Debug)]
+pub struct ContractPreview {
+    /// The payouts of the contract. For enumerated outcome contracts, each
+    /// range covers a single outcome whose value is the index of the outcome in
+    /// the descriptor.
+    pub range_payouts: Vec<RangePayout>,
+    /// The number of CETs.
+    pub nb_cets: usize,
+    /// The number of adaptor signatures that each party will generate and
+    /// verify, taking into account all the combinations of oracles.
+    pub nb_adaptor_signatures: usize,
+}
+
 impl ContractDescriptor {
+    /// Returns information about the CETs and adaptor signatures required by
+    /// the descriptor when used with `nb_oracles` oracles out of which
+    /// `threshold` are required to close the contract.
+    pub fn get_preview(
+        &self,
+        total_collateral: u64,
+        nb_oracles: usize,
+        threshold: usize,
+    ) -> Result<ContractPreview, Error> {
+        if threshold == 0 || threshold > nb_oracles {
+            return Err(Error::InvalidParameters(
+                "Threshold must be between one and the number of oracles.".to_string(),
+            ));
+        }
+
+        match self {
+            ContractDescriptor::Enum(e) => {
+                let range_payouts: Vec<RangePayout> = e
+                    .outcome_payouts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| RangePayout {
+                        start: i,
+                        count: 1,
+                        payout: x.payout.clone(),
+                    })
+                    .collect();
+                let nb_combinations = CombinationIterator::new(nb_oracles, threshold).count();
+                Ok(ContractPreview {
+                    nb_cets: range_payouts.len(),
+                    nb_adaptor_signatures: range_payouts.len() * nb_combinations,
+                    range_payouts,
+                })
+            }
+            ContractDescriptor::Numerical(n) => {
+                if n.oracle_numeric_infos.nb_digits.len() != nb_oracles {
+                    return Err(Error::InvalidParameters(
+                        "Number of oracles does not match the oracle numeric infos.".to_string(),
+                    ));
+                }
+                let range_payouts = n.get_range_payouts(total_collateral)?;
+                let trie_range_payouts = n.get_trie_range_payouts(total_collateral)?;
+                let oracle_numeric_infos = n.get_trie_numeric_infos();
+                let nb_adaptor_signatures = match &n.difference_params {
+                    Some(params) if n.uses_multi_base_trie() => MultiBaseOracleTrie::new_with_diff(
+                        &oracle_numeric_infos,
+                        threshold,
+                        params.min_support_exp,
+                        params.max_error_exp,
+                    )?
+                    .generate(0, &trie_range_payouts)?
+                    .len(),
+                    Some(params) => MultiOracleTrieWithDiff::new(
+                        &oracle_numeric_infos,
+                        threshold,
+                        params.min_support_exp,
+                        params.max_error_exp,
+                    )?
+                    .generate(0, &trie_range_payouts)?
+                    .len(),
+                    None if n.uses_multi_base_trie() => {
+                        MultiBaseOracleTrie::new(&oracle_numeric_infos, threshold)?
+                            .generate(0, &trie_range_payouts)?
+                            .len()
+                    }
+                    None => MultiOracleTrie::new(&oracle_numeric_infos, threshold)?
+                        .generate(0, &trie_range_payouts)?
+                        .len(),
+                };
+                Ok(ContractPreview {
+                    nb_cets: range_payouts.len(),
+                    nb_adaptor_signatures,
+                    range_payouts,
+                })
+            }
+        }
+    }
+
     /// Get the parameters on allowed divergence between oracle if any.
     pub fn get_oracle_params(&self) -> Option<numerical_descriptor::DifferenceParams> {
         match self {
@@ -232,15 +422,12 @@ impl ContractDescriptor {
             .first()
             .expect("to have at least one element.");
         match &first.oracle_event.event_descriptor {
-            EventDescriptor::EnumEvent(ee) => {
+            EventDescriptor::EnumEvent(_) => {
+                let mut enum_event_descriptors = Vec::with_capacity(announcements.len());
                 for announcement in announcements {
                     match &announcement.oracle_event.event_descriptor {
                         EventDescriptor::EnumEvent(enum_desc) => {
-                            if !unordered_equal(&ee.outcomes, &enum_desc.outcomes) {
-                                return Err(Error::InvalidParameters(
-                                    "Oracles don't have same enum outcomes.".to_string(),
-                                ));
-                            }
+                            enum_event_descriptors.push(enum_desc);
                         }
                         _ => {
                             return Err(Error::InvalidParameters(
@@ -250,7 +437,9 @@ impl ContractDescriptor {
                     }
                 }
                 match self {
-                    ContractDescriptor::Enum(ed) => ed.validate(ee),
+                    ContractDescriptor::Enum(ed) => {
+                        ed.validate_oracle_events(&enum_event_descriptors)
+                    }
                     _ => Err(Error::InvalidParameters(
                         "Event descriptor from contract and oracle differ.".to_string(),
                     )),
@@ -258,15 +447,35 @@ impl ContractDescriptor {
             }
             EventDescriptor::DigitDecompositionEvent(_) => match self {
                 ContractDescriptor::Numerical(n) => {
-                    let min_nb_digits = n.oracle_numeric_infos.get_min_nb_digits();
-                    let max_value = n
-                        .oracle_numeric_infos
-                        .base
-                        .checked_pow(min_nb_digits as u32)
-                        .ok_or_else(|| {
-                            Error::InvalidParameters("Could not compute max value".to_string())
-                        })?;
-                    n.validate((max_value - 1) as u64)
+                    let oracle_numeric_infos = &n.oracle_numeric_infos;
+                    let outcome_params = n.get_outcome_params();
+                    if oracle_numeric_infos.nb_digits.len() != announcements.len() {
+                        return Err(Error::InvalidParameters(
+                            "Number of oracles does not match the oracle numeric infos."
+                                .to_string(),
+                        ));
+                    }
+                    for (i, announcement) in announcements.iter().enumerate() {
+                        match &announcement.oracle_event.event_descriptor {
+                            EventDescriptor::DigitDecompositionEvent(d)
+                                if d.base as usize == oracle_numeric_infos.get_base(i)
+                                    && d.nb_digits as usize
+                                        == oracle_numeric_infos.nb_digits[i]
+                                    && d.is_signed == outcome_params.is_signed
+                                    && (n.outcome_params.is_none()
+                                        || d.precision == outcome_params.precision) => {}
+                            _ => {
+                                return Err(Error::InvalidParameters(
+                                    "Oracle event does not match the oracle numeric infos."
+                                        .to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    let max_outcome = n.get_max_outcome().ok_or_else(|| {
+                        Error::InvalidParameters("Could not compute max value".to_string())
+                    })?;
+                    n.validate(max_outcome)
                 }
                 _ => Err(Error::InvalidParameters(
                     "Event descriptor from contract and oracle differ.".to_string(),
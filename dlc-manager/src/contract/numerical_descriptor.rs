@@ -5,9 +5,10 @@ use crate::error::Error;
 use crate::payout_curve::{PayoutFunction, RoundingIntervals};
 use bitcoin::{Script, Transaction};
 use dlc::{Payout, RangePayout};
+use dlc_trie::multi_base_oracle_trie::MultiBaseOracleTrie;
 use dlc_trie::multi_oracle_trie::MultiOracleTrie;
 use dlc_trie::multi_oracle_trie_with_diff::MultiOracleTrieWithDiff;
-use dlc_trie::{DlcTrie, OracleNumericInfo};
+use dlc_trie::{AdaptorPointProvider, DlcTrie, OracleNumericInfo};
 use secp256k1_zkp::{All, EcdsaAdaptorSignature, PublicKey, Secp256k1, SecretKey};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -31,6 +32,103 @@ pub struct DifferenceParams {
     pub maximize_coverage: bool,
 }
 
+/// Information about how the oracles represent the outcome values, as given by
+/// the digit decomposition event descriptor of their announcements. All oracles
+/// must attest to values in the same unit and with the same precision, as
+/// values are not scaled between oracles.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct OutcomeParams {
+    /// Whether the oracles attest to signed values. The outcomes of the
+    /// contract are then offset by the largest value the oracles can attest to,
+    /// so that outcome zero represents the lowest negative value.
+    pub is_signed: bool,
+    /// The precision of the values attested by the oracles, an attested value
+    /// `v` representing `v * 10^precision` in the unit of the event.
+    pub precision: i32,
+}
+
+impl OutcomeParams {
+    /// Returns the outcome of the contract representing an attested value of
+    /// zero.
+    pub fn get_outcome_offset(&self, oracle_numeric_infos: &OracleNumericInfo) -> Option<u64> {
+        if self.is_signed {
+            oracle_numeric_infos.get_max_value().map(|x| x as u64)
+        } else {
+            Some(0)
+        }
+    }
+
+    /// Returns the largest outcome of the contract.
+    pub fn get_max_outcome(&self, oracle_numeric_infos: &OracleNumericInfo) -> Option<u64> {
+        let max_value = oracle_numeric_infos.get_max_value()? as u64;
+        self.get_outcome_offset(oracle_numeric_infos)?
+            .checked_add(max_value)
+    }
+
+    /// Returns the outcome of the contract for the given value expressed in
+    /// the unit of the event, rounded to the precision of the oracles.
+    pub fn get_outcome(
+        &self,
+        oracle_numeric_infos: &OracleNumericInfo,
+        value: f64,
+    ) -> Result<u64, Error> {
+        let (offset, max_outcome) = self.get_offset_and_max_outcome(oracle_numeric_infos)?;
+        let outcome = (value / 10_f64.powi(self.precision)).round() + offset as f64;
+        if !(0.0..=max_outcome as f64).contains(&outcome) {
+            return Err(Error::InvalidParameters(
+                "Value cannot be attested by the oracles.".to_string(),
+            ));
+        }
+        Ok(outcome as u64)
+    }
+
+    /// Returns the value, expressed in the unit of the event, represented by
+    /// the given outcome of the contract.
+    pub fn get_value(
+        &self,
+        oracle_numeric_infos: &OracleNumericInfo,
+        outcome: u64,
+    ) -> Result<f64, Error> {
+        let (offset, max_outcome) = self.get_offset_and_max_outcome(oracle_numeric_infos)?;
+        if outcome > max_outcome {
+            return Err(Error::InvalidParameters(
+                "Outcome is larger than the maximum outcome.".to_string(),
+            ));
+        }
+        Ok((outcome as f64 - offset as f64) * 10_f64.powi(self.precision))
+    }
+
+    fn get_offset_and_max_outcome(
+        &self,
+        oracle_numeric_infos: &OracleNumericInfo,
+    ) -> Result<(u64, u64), Error> {
+        let offset = self.get_outcome_offset(oracle_numeric_infos);
+        let max_outcome = self.get_max_outcome(oracle_numeric_infos);
+        offset
+            .zip(max_outcome)
+            .ok_or_else(|| Error::InvalidParameters("Could not compute max outcome.".to_string()))
+    }
+}
+
+/// Converts between the digits attested by a signed oracle, whose first digit
+/// is 0 for a negative value and 1 for a positive one, and the path of the
+/// outcome in the tries. As negative values decrease when their magnitude
+/// increases, their digits are complemented, which makes the conversion its
+/// own inverse.
+pub(crate) fn convert_signed_path(path: &[usize], base: usize) -> Vec<usize> {
+    match path.split_first() {
+        Some((0, digits)) => std::iter::once(0)
+            .chain(digits.iter().map(|x| base - 1 - x))
+            .collect(),
+        _ => path.to_vec(),
+    }
+}
+
 #[derive(Clone, Debug)]
 #[cfg_attr(
     feature = "serde",
@@ -50,19 +148,123 @@ pub struct NumericalDescriptor {
     pub difference_params: Option<DifferenceParams>,
     /// Information about base and number of digits for each oracle.
     pub oracle_numeric_infos: OracleNumericInfo,
+    /// How the oracles represent the outcome values. If None, the oracles
+    /// attest to unsigned values with a precision of zero.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub outcome_params: Option<OutcomeParams>,
 }
 
 impl NumericalDescriptor {
     /// Returns the set of RangePayout for the descriptor generated from the
-    /// payout function.
+    /// payout function, evaluated with [`crate::payout_curve::EvaluationMode::Exact`]
+    /// so that both parties compute the same set of CETs.
     pub fn get_range_payouts(&self, total_collateral: u64) -> Result<Vec<RangePayout>, Error> {
         self.payout_function
             .to_range_payouts(total_collateral, &self.rounding_intervals)
     }
 
+    /// Returns whether the oracles attest to signed values.
+    pub fn is_signed(&self) -> bool {
+        self.outcome_params.as_ref().map_or(false, |x| x.is_signed)
+    }
+
+    /// Returns how the oracles represent the outcome values.
+    pub fn get_outcome_params(&self) -> OutcomeParams {
+        self.outcome_params.clone().unwrap_or_default()
+    }
+
+    /// Returns the largest outcome of the contract.
+    pub fn get_max_outcome(&self) -> Option<u64> {
+        self.get_outcome_params()
+            .get_max_outcome(&self.oracle_numeric_infos)
+    }
+
+    /// Returns the payout for the given value, expressed in the unit of the
+    /// event and rounded to the precision of the oracles.
+    pub fn get_payout_for_value(&self, value: f64, total_collateral: u64) -> Result<Payout, Error> {
+        let outcome = self
+            .get_outcome_params()
+            .get_outcome(&self.oracle_numeric_infos, value)? as usize;
+        self.get_range_payouts(total_collateral)?
+            .into_iter()
+            .find(|x| x.start <= outcome && outcome < x.start + x.count)
+            .map(|x| x.payout)
+            .ok_or_else(|| Error::InvalidParameters("No payout for the given value.".to_string()))
+    }
+
+    /// Returns the numeric infos used to generate the tries, in which the sign
+    /// of a signed value is an additional leading digit.
+    pub(crate) fn get_trie_numeric_infos(&self) -> OracleNumericInfo {
+        let mut oracle_numeric_infos = self.oracle_numeric_infos.clone();
+        if self.is_signed() {
+            for nb_digits in oracle_numeric_infos.nb_digits.iter_mut() {
+                *nb_digits += 1;
+            }
+        }
+        oracle_numeric_infos
+    }
+
+    /// Returns the range payouts used to generate the tries. With signed
+    /// values, the outcome representing zero can be attested either as a
+    /// positive or as a negative value and covers two outcomes of the tries.
+    pub(crate) fn get_trie_range_payouts(
+        &self,
+        total_collateral: u64,
+    ) -> Result<Vec<RangePayout>, Error> {
+        let range_payouts = self.get_range_payouts(total_collateral)?;
+        if !self.is_signed() {
+            return Ok(range_payouts);
+        }
+        let zero = self
+            .oracle_numeric_infos
+            .get_max_value()
+            .ok_or_else(|| Error::InvalidParameters("Could not compute max value.".to_string()))?;
+        Ok(range_payouts
+            .into_iter()
+            .map(|x| {
+                let end = x.start + x.count - 1;
+                let start = if x.start <= zero {
+                    x.start
+                } else {
+                    x.start + 1
+                };
+                let end = if end < zero { end } else { end + 1 };
+                RangePayout {
+                    start,
+                    count: end - start + 1,
+                    payout: x.payout,
+                }
+            })
+            .collect())
+    }
+
+    /// Returns whether the adaptor signatures are stored in a
+    /// [`MultiBaseOracleTrie`], which is the case when the oracles use
+    /// different bases, or when outcome differences are allowed and the
+    /// oracles don't use base 2.
+    pub(crate) fn uses_multi_base_trie(&self) -> bool {
+        self.oracle_numeric_infos.has_diff_bases()
+            || (self.difference_params.is_some() && self.oracle_numeric_infos.base != 2)
+    }
+
     /// Validate that the descriptor covers all possible outcomes of the given
     /// digit decomposition event descriptor.
     pub fn validate(&self, max_value: u64) -> Result<(), Error> {
+        if self.is_signed() {
+            if self.difference_params.is_some() {
+                return Err(Error::InvalidParameters(
+                    "Outcome differences are not supported for signed values.".to_string(),
+                ));
+            }
+            if self.oracle_numeric_infos.has_diff_bases()
+                || self.oracle_numeric_infos.has_diff_nb_digits()
+            {
+                return Err(Error::InvalidParameters(
+                    "Oracles attesting to signed values must use the same base and number of digits."
+                        .to_string(),
+                ));
+            }
+        }
         self.rounding_intervals.validate()?;
         self.payout_function.validate(max_value)
     }
@@ -78,7 +280,7 @@ impl NumericalDescriptor {
     }
 
     /// Verify the given set of adaptor signatures and generate the adaptor info.
-    pub fn verify_and_get_adaptor_info(
+    pub fn verify_and_get_adaptor_info<P: AdaptorPointProvider + ?Sized>(
         &self,
         secp: &Secp256k1<All>,
         total_collateral: u64,
@@ -86,15 +288,36 @@ impl NumericalDescriptor {
         funding_script_pubkey: &Script,
         fund_output_value: u64,
         threshold: usize,
-        precomputed_points: &[Vec<Vec<PublicKey>>],
+        adaptor_points: &P,
         cets: &[Transaction],
         adaptor_pairs: &[EcdsaAdaptorSignature],
         adaptor_index_start: usize,
     ) -> Result<(AdaptorInfo, usize), Error> {
+        let oracle_numeric_infos = self.get_trie_numeric_infos();
         match &self.difference_params {
+            Some(params) if self.uses_multi_base_trie() => {
+                let mut trie = MultiBaseOracleTrie::new_with_diff(
+                    &oracle_numeric_infos,
+                    threshold,
+                    params.min_support_exp,
+                    params.max_error_exp,
+                )?;
+                let index = trie.generate_verify(
+                    secp,
+                    fund_pubkey,
+                    funding_script_pubkey,
+                    fund_output_value,
+                    &self.get_trie_range_payouts(total_collateral)?,
+                    cets,
+                    adaptor_points,
+                    adaptor_pairs,
+                    adaptor_index_start,
+                )?;
+                Ok((AdaptorInfo::NumericalMultiBase(trie), index))
+            }
             Some(params) => {
                 let mut multi_trie = MultiOracleTrieWithDiff::new(
-                    &self.oracle_numeric_infos,
+                    &oracle_numeric_infos,
                     threshold,
                     params.min_support_exp,
                     params.max_error_exp,
@@ -104,24 +327,39 @@ impl NumericalDescriptor {
                     fund_pubkey,
                     funding_script_pubkey,
                     fund_output_value,
-                    &self.get_range_payouts(total_collateral)?,
+                    &self.get_trie_range_payouts(total_collateral)?,
                     cets,
-                    precomputed_points,
+                    adaptor_points,
                     adaptor_pairs,
                     adaptor_index_start,
                 )?;
                 Ok((AdaptorInfo::NumericalWithDifference(multi_trie), index))
             }
+            None if self.uses_multi_base_trie() => {
+                let mut trie = MultiBaseOracleTrie::new(&oracle_numeric_infos, threshold)?;
+                let index = trie.generate_verify(
+                    secp,
+                    fund_pubkey,
+                    funding_script_pubkey,
+                    fund_output_value,
+                    &self.get_trie_range_payouts(total_collateral)?,
+                    cets,
+                    adaptor_points,
+                    adaptor_pairs,
+                    adaptor_index_start,
+                )?;
+                Ok((AdaptorInfo::NumericalMultiBase(trie), index))
+            }
             None => {
-                let mut trie = MultiOracleTrie::new(&self.oracle_numeric_infos, threshold)?;
+                let mut trie = MultiOracleTrie::new(&oracle_numeric_infos, threshold)?;
                 let index = trie.generate_verify(
                     secp,
                     fund_pubkey,
                     funding_script_pubkey,
                     fund_output_value,
-                    &self.get_range_payouts(total_collateral)?,
+                    &self.get_trie_range_payouts(total_collateral)?,
                     cets,
-                    precomputed_points,
+                    adaptor_points,
                     adaptor_pairs,
                     adaptor_index_start,
                 )?;
@@ -131,7 +369,7 @@ impl NumericalDescriptor {
     }
 
     /// Generate the set of adaptor signatures and the adaptor info.
-    pub fn get_adaptor_info(
+    pub fn get_adaptor_info<P: AdaptorPointProvider + ?Sized>(
         &self,
         secp: &Secp256k1<All>,
         total_collateral: u64,
@@ -139,14 +377,34 @@ impl NumericalDescriptor {
         funding_script_pubkey: &Script,
         fund_output_value: u64,
         threshold: usize,
-        precomputed_points: &[Vec<Vec<PublicKey>>],
+        adaptor_points: &P,
         cets: &[Transaction],
         adaptor_index_start: usize,
     ) -> Result<(AdaptorInfo, Vec<EcdsaAdaptorSignature>), Error> {
+        let oracle_numeric_infos = self.get_trie_numeric_infos();
         match &self.difference_params {
+            Some(params) if self.uses_multi_base_trie() => {
+                let mut trie = MultiBaseOracleTrie::new_with_diff(
+                    &oracle_numeric_infos,
+                    threshold,
+                    params.min_support_exp,
+                    params.max_error_exp,
+                )?;
+                let sigs = trie.generate_sign(
+                    secp,
+                    fund_priv_key,
+                    funding_script_pubkey,
+                    fund_output_value,
+                    &self.get_trie_range_payouts(total_collateral)?,
+                    cets,
+                    adaptor_points,
+                    adaptor_index_start,
+                )?;
+                Ok((AdaptorInfo::NumericalMultiBase(trie), sigs))
+            }
             Some(params) => {
                 let mut multi_trie = MultiOracleTrieWithDiff::new(
-                    &self.oracle_numeric_infos,
+                    &oracle_numeric_infos,
                     threshold,
                     params.min_support_exp,
                     params.max_error_exp,
@@ -156,9 +414,9 @@ impl NumericalDescriptor {
                     fund_priv_key,
                     funding_script_pubkey,
                     fund_output_value,
-                    &self.get_range_payouts(total_collateral)?,
+                    &self.get_trie_range_payouts(total_collateral)?,
                     cets,
-                    precomputed_points,
+                    adaptor_points,
                     adaptor_index_start,
                 )?;
                 Ok((
@@ -166,17 +424,31 @@ impl NumericalDescriptor {
                     adaptor_pairs,
                 ))
             }
+            None if self.uses_multi_base_trie() => {
+                let mut trie = MultiBaseOracleTrie::new(&oracle_numeric_infos, threshold)?;
+                let sigs = trie.generate_sign(
+                    secp,
+                    fund_priv_key,
+                    funding_script_pubkey,
+                    fund_output_value,
+                    &self.get_trie_range_payouts(total_collateral)?,
+                    cets,
+                    adaptor_points,
+                    adaptor_index_start,
+                )?;
+                Ok((AdaptorInfo::NumericalMultiBase(trie), sigs))
+            }
 
             None => {
-                let mut trie = MultiOracleTrie::new(&self.oracle_numeric_infos, threshold)?;
+                let mut trie = MultiOracleTrie::new(&oracle_numeric_infos, threshold)?;
                 let sigs = trie.generate_sign(
                     secp,
                     fund_priv_key,
                     funding_script_pubkey,
                     fund_output_value,
-                    &self.get_range_payouts(total_collateral)?,
+                    &self.get_trie_range_payouts(total_collateral)?,
                     cets,
-                    precomputed_points,
+                    adaptor_points,
                     adaptor_index_start,
                 )?;
                 Ok((AdaptorInfo::Numerical(trie), sigs))
@@ -184,3 +456,222 @@ impl NumericalDescriptor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::adaptor_point_cache::{with_cached_adaptor_points, AdaptorPointCache};
+    use crate::contract::contract_info::ContractInfo;
+    use crate::contract::ContractDescriptor;
+    use crate::payout_curve::builders::BinaryOption;
+    use bitcoin::{PackedLockTime, TxIn};
+    use dlc_messages::oracle_msgs::{
+        DigitDecompositionEventDescriptor, EventDescriptor, OracleAnnouncement, OracleEvent,
+    };
+    use dlc_trie::digit_decomposition::decompose_value;
+    use secp256k1_zkp::hashes::sha256;
+    use secp256k1_zkp::{schnorr::Signature, KeyPair, Message, XOnlyPublicKey};
+
+    const NB_DIGITS: usize = 4;
+
+    fn random_public_key(secp: &Secp256k1<All>) -> XOnlyPublicKey {
+        XOnlyPublicKey::from_keypair(&KeyPair::new(secp, &mut secp256k1_zkp::rand::thread_rng())).0
+    }
+
+    fn get_signed_contract_info(secp: &Secp256k1<All>) -> ContractInfo {
+        let oracle_numeric_infos = OracleNumericInfo {
+            base: 2,
+            nb_digits: vec![NB_DIGITS],
+            oracle_bases: None,
+        };
+        let outcome_params = OutcomeParams {
+            is_signed: true,
+            precision: 0,
+        };
+        let (payout_function, rounding_intervals) = BinaryOption {
+            strike_price: outcome_params
+                .get_outcome(&oracle_numeric_infos, 3.0)
+                .unwrap(),
+            offer_collateral: 1000,
+            accept_collateral: 1000,
+            offer_wins_above: true,
+        }
+        .build(
+            outcome_params
+                .get_max_outcome(&oracle_numeric_infos)
+                .unwrap(),
+            1,
+        )
+        .unwrap();
+        ContractInfo {
+            contract_descriptor: ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function,
+                rounding_intervals,
+                difference_params: None,
+                oracle_numeric_infos,
+                outcome_params: Some(outcome_params),
+            }),
+            oracle_announcements: vec![OracleAnnouncement {
+                announcement_signature: Signature::from_slice(&[1; 64]).unwrap(),
+                oracle_public_key: random_public_key(secp),
+                oracle_event: OracleEvent {
+                    oracle_nonces: (0..NB_DIGITS + 1)
+                        .map(|_| random_public_key(secp))
+                        .collect(),
+                    event_maturity_epoch: 0,
+                    event_descriptor: EventDescriptor::DigitDecompositionEvent(
+                        DigitDecompositionEventDescriptor {
+                            base: 2,
+                            is_signed: true,
+                            unit: "celsius".to_string(),
+                            precision: 0,
+                            nb_digits: NB_DIGITS as u16,
+                        },
+                    ),
+                    event_id: "temperature".to_string(),
+                },
+            }],
+            threshold: 1,
+        }
+    }
+
+    fn get_attested_outcome(sign: &str, value: usize) -> Vec<String> {
+        std::iter::once(sign.to_string())
+            .chain(
+                decompose_value(value, 2, NB_DIGITS)
+                    .iter()
+                    .map(|x| x.to_string()),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn convert_signed_path_test() {
+        assert_eq!(vec![0, 8, 7], convert_signed_path(&[0, 1, 2], 10));
+        assert_eq!(vec![0, 1, 2], convert_signed_path(&[0, 8, 7], 10));
+        assert_eq!(vec![1, 1, 2], convert_signed_path(&[1, 1, 2], 10));
+        assert_eq!(vec![0], convert_signed_path(&[0], 10));
+    }
+
+    #[test]
+    fn outcome_value_conversion_test() {
+        let oracle_numeric_infos = OracleNumericInfo {
+            base: 10,
+            nb_digits: vec![3],
+            oracle_bases: None,
+        };
+        let signed = OutcomeParams {
+            is_signed: true,
+            precision: -1,
+        };
+        assert_eq!(Some(1998), signed.get_max_outcome(&oracle_numeric_infos));
+        assert_eq!(0, signed.get_outcome(&oracle_numeric_infos, -99.9).unwrap());
+        assert_eq!(999, signed.get_outcome(&oracle_numeric_infos, 0.0).unwrap());
+        assert_eq!(
+            1122,
+            signed.get_outcome(&oracle_numeric_infos, 12.34).unwrap()
+        );
+        signed
+            .get_outcome(&oracle_numeric_infos, 100.0)
+            .expect_err("value cannot be attested");
+        let value = signed.get_value(&oracle_numeric_infos, 1122).unwrap();
+        assert!((value - 12.3).abs() < 1e-9);
+        let value = signed.get_value(&oracle_numeric_infos, 0).unwrap();
+        assert!((value + 99.9).abs() < 1e-9);
+        signed
+            .get_value(&oracle_numeric_infos, 1999)
+            .expect_err("outcome is too large");
+
+        let unsigned = OutcomeParams::default();
+        assert_eq!(Some(999), unsigned.get_max_outcome(&oracle_numeric_infos));
+        assert_eq!(5, unsigned.get_outcome(&oracle_numeric_infos, 5.0).unwrap());
+        unsigned
+            .get_outcome(&oracle_numeric_infos, -1.0)
+            .expect_err("value cannot be attested");
+    }
+
+    #[test]
+    fn signed_outcomes_adaptor_signatures_test() {
+        let secp = Secp256k1::new();
+        let contract_info = get_signed_contract_info(&secp);
+        contract_info.validate().expect("to be valid");
+        let descriptor = match &contract_info.contract_descriptor {
+            ContractDescriptor::Numerical(n) => n,
+            _ => unreachable!(),
+        };
+        let range_payouts = descriptor.get_range_payouts(2000).unwrap();
+        let trie_range_payouts = descriptor.get_trie_range_payouts(2000).unwrap();
+        assert_eq!(range_payouts.len(), trie_range_payouts.len());
+        assert_eq!(
+            2 << NB_DIGITS,
+            trie_range_payouts.iter().map(|x| x.count).sum::<usize>()
+        );
+
+        let cets: Vec<Transaction> = (0..range_payouts.len())
+            .map(|i| Transaction {
+                version: 2,
+                lock_time: PackedLockTime(i as u32),
+                input: vec![TxIn::default()],
+                output: Vec::new(),
+            })
+            .collect();
+        let fund_privkey = SecretKey::new(&mut secp256k1_zkp::rand::thread_rng());
+        let fund_pubkey = PublicKey::from_secret_key(&secp, &fund_privkey);
+        let funding_script_pubkey = Script::new();
+        let mut cache = AdaptorPointCache::new();
+        let (adaptor_info, adaptor_sigs) =
+            with_cached_adaptor_points(&secp, &contract_info, &mut cache, |points| {
+                descriptor.get_adaptor_info(
+                    &secp,
+                    2000,
+                    &fund_privkey,
+                    &funding_script_pubkey,
+                    1000,
+                    1,
+                    points,
+                    &cets,
+                    0,
+                )
+            })
+            .unwrap();
+
+        let mut attested_outcomes = vec![(-0.0, get_attested_outcome("-", 0))];
+        for value in 0..(1 << NB_DIGITS) {
+            attested_outcomes.push((value as f64, get_attested_outcome("+", value)));
+            attested_outcomes.push((-(value as f64), get_attested_outcome("-", value)));
+        }
+        for (value, outcome) in attested_outcomes {
+            let (prefixes, range_info) = contract_info
+                .get_range_info_for_outcome(&adaptor_info, &[(0, &outcome)], 0)
+                .expect("to find the outcome");
+            assert_eq!(
+                descriptor.get_payout_for_value(value, 2000).unwrap(),
+                range_payouts[range_info.cet_index].payout
+            );
+
+            // The adaptor signature can be decrypted using the signatures of
+            // the oracle over the attested outcome.
+            let msgs: Vec<Message> = outcome
+                .iter()
+                .take(prefixes[0].1)
+                .map(|x| Message::from_hashed_data::<sha256::Hash>(x.as_bytes()))
+                .collect();
+            let adaptor_point = dlc::get_adaptor_point_from_oracle_info(
+                &secp,
+                &contract_info.get_oracle_infos(),
+                &[msgs],
+            )
+            .unwrap();
+            dlc::verify_cet_adaptor_sig_from_point(
+                &secp,
+                &adaptor_sigs[range_info.adaptor_index],
+                &cets[range_info.cet_index],
+                &adaptor_point,
+                &fund_pubkey,
+                &funding_script_pubkey,
+                1000,
+            )
+            .expect("the adaptor signature to use the attested outcome");
+        }
+    }
+}
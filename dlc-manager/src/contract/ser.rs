@@ -4,24 +4,28 @@
 use crate::contract::accepted_contract::AcceptedContract;
 use crate::contract::contract_info::ContractInfo;
 use crate::contract::enum_descriptor::EnumDescriptor;
-use crate::contract::numerical_descriptor::{DifferenceParams, NumericalDescriptor};
+use crate::contract::numerical_descriptor::{DifferenceParams, NumericalDescriptor, OutcomeParams};
 use crate::contract::offered_contract::OfferedContract;
 use crate::contract::signed_contract::SignedContract;
 use crate::contract::AdaptorInfo;
 use crate::contract::{
     ClosedContract, ContractDescriptor, FailedAcceptContract, FailedSignContract, FundingInputInfo,
-    PreClosedContract,
+    PreClosedContract, PreRefundedContract,
 };
 use crate::payout_curve::{
-    HyperbolaPayoutCurvePiece, PayoutFunction, PayoutFunctionPiece, PayoutPoint,
-    PolynomialPayoutCurvePiece, RoundingInterval, RoundingIntervals,
+    HyperbolaPayoutCurvePiece, LinearPayoutCurvePiece, PayoutFunction, PayoutFunctionPiece,
+    PayoutPoint, PolynomialPayoutCurvePiece, RoundingInterval, RoundingIntervals,
 };
 use dlc::DlcTransactions;
 use dlc_messages::ser_impls::{
-    read_ecdsa_adaptor_signatures, read_option_cb, read_usize, read_vec, read_vec_cb,
-    write_ecdsa_adaptor_signatures, write_option_cb, write_usize, write_vec, write_vec_cb,
+    read_ecdsa_adaptor_signatures, read_i32, read_option, read_option_cb, read_strings, read_usize,
+    read_vec, read_vec_cb, write_ecdsa_adaptor_signatures, write_i32, write_option,
+    write_option_cb, write_strings, write_usize, write_vec, write_vec_cb,
 };
 use dlc_trie::digit_trie::{DigitNodeData, DigitTrieDump};
+use dlc_trie::multi_base_oracle_trie::{
+    MultiBaseOracleTrie, MultiBaseOracleTrieDump, MultiBaseTrieEntry,
+};
 use dlc_trie::multi_oracle_trie::{MultiOracleTrie, MultiOracleTrieDump};
 use dlc_trie::multi_oracle_trie_with_diff::{MultiOracleTrieWithDiff, MultiOracleTrieWithDiffDump};
 use dlc_trie::multi_trie::{MultiTrieDump, MultiTrieNodeData, TrieNodeInfo};
@@ -56,16 +60,54 @@ where
     }
 }
 
+/// The version of the serialization format of the objects persisted by storage
+/// providers, written before each of them by
+/// [`VersionedSerializable::serialize_versioned`].
+pub const SERIALIZATION_VERSION: u8 = 1;
+
+/// Trait implemented by the objects persisted by storage providers, whose
+/// serialization is prefixed with the version of its format so that objects
+/// written by previous releases can still be decoded.
+pub trait VersionedSerializable: Writeable + Readable {
+    /// Serialize the object, prefixed with [`SERIALIZATION_VERSION`].
+    fn serialize_versioned(&self) -> Result<Vec<u8>, ::std::io::Error> {
+        let mut buffer = vec![SERIALIZATION_VERSION];
+        self.write(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Deserialize an object prefixed with the version of its format.
+    fn deserialize_versioned<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let version: u8 = Readable::read(reader)?;
+        Self::deserialize_version(version, reader)
+    }
+
+    /// Deserialize an object written with the given version of the format,
+    /// version 0 designating the objects written without version prefix by the
+    /// releases predating [`SERIALIZATION_VERSION`], which storage providers
+    /// prefix with it when migrating them. Types whose format changes must
+    /// override this function to keep decoding the previous versions.
+    fn deserialize_version<R: Read>(version: u8, reader: &mut R) -> Result<Self, DecodeError> {
+        match version {
+            0..=SERIALIZATION_VERSION => Readable::read(reader),
+            _ => Err(DecodeError::UnknownVersion),
+        }
+    }
+}
+
 impl_dlc_writeable!(PayoutPoint, { (event_outcome, writeable), (outcome_payout, writeable), (extra_precision, writeable) });
 impl_dlc_writeable_enum!(
     PayoutFunctionPiece,
     (0, PolynomialPayoutCurvePiece),
-    (1, HyperbolaPayoutCurvePiece);;;
+    (1, HyperbolaPayoutCurvePiece),
+    (2, LinearPayoutCurvePiece);;;
 );
 impl_dlc_writeable!(RoundingInterval, { (begin_interval, writeable), (rounding_mod, writeable) });
 impl_dlc_writeable!(PayoutFunction, { (payout_function_pieces, vec) });
-impl_dlc_writeable!(NumericalDescriptor, { (payout_function, writeable), (rounding_intervals, writeable), (difference_params, option), (oracle_numeric_infos, {cb_writeable, oracle_params::write, oracle_params::read}) });
+impl_dlc_writeable!(NumericalDescriptor, { (payout_function, writeable), (rounding_intervals, writeable), (difference_params, option), (oracle_numeric_infos, {cb_writeable, oracle_params::write, oracle_params::read}), (outcome_params, option) });
+impl_dlc_writeable!(OutcomeParams, { (is_signed, writeable), (precision, {cb_writeable, write_i32, read_i32}) });
 impl_dlc_writeable!(PolynomialPayoutCurvePiece, { (payout_points, vec) });
+impl_dlc_writeable!(LinearPayoutCurvePiece, { (left_end_point, writeable), (right_end_point, writeable) });
 impl_dlc_writeable!(RoundingIntervals, { (intervals, vec) });
 impl_dlc_writeable!(DifferenceParams, { (max_error_exp, usize), (min_support_exp, usize), (maximize_coverage, writeable) });
 impl_dlc_writeable!(HyperbolaPayoutCurvePiece, {
@@ -79,14 +121,14 @@ impl_dlc_writeable!(HyperbolaPayoutCurvePiece, {
     (c, float),
     (d, float)
 });
-impl_dlc_writeable_enum!(ContractDescriptor, (0, Enum), (1, Numerical);;;);
 impl_dlc_writeable!(ContractInfo, { (contract_descriptor, writeable), (oracle_announcements, vec), (threshold, usize)});
 impl_dlc_writeable!(FundingInputInfo, { (funding_input, writeable), (address, {option_cb, dlc_messages::ser_impls::write_address, dlc_messages::ser_impls::read_address}) });
 impl_dlc_writeable!(EnumDescriptor, {
     (
         outcome_payouts,
         {vec_cb, dlc_messages::ser_impls::enum_payout::write, dlc_messages::ser_impls::enum_payout::read}
-    )
+    ),
+    (oracle_outcome_mappings, {option_cb, write_oracle_outcome_mappings, read_oracle_outcome_mappings})
 });
 impl_dlc_writeable!(OfferedContract, {
     (id, writeable),
@@ -102,7 +144,7 @@ impl_dlc_writeable!(OfferedContract, {
     (counter_party, writeable)
 });
 impl_dlc_writeable_external!(RangeInfo, range_info, { (cet_index, usize), (adaptor_index, usize)});
-impl_dlc_writeable_enum!(AdaptorInfo,;; (0, Numerical, write_multi_oracle_trie, read_multi_oracle_trie), (1, NumericalWithDifference, write_multi_oracle_trie_with_diff, read_multi_oracle_trie_with_diff); (2, Enum));
+impl_dlc_writeable_enum!(AdaptorInfo,;; (0, Numerical, write_multi_oracle_trie, read_multi_oracle_trie), (1, NumericalWithDifference, write_multi_oracle_trie_with_diff, read_multi_oracle_trie_with_diff), (3, NumericalMultiBase, write_multi_base_oracle_trie, read_multi_base_oracle_trie); (2, Enum));
 impl_dlc_writeable_external!(
     DlcTransactions, dlc_transactions,
     { (fund, writeable),
@@ -131,6 +173,10 @@ impl_dlc_writeable!(PreClosedContract, {
     (attestations, {option_cb, write_vec, read_vec}),
     (signed_cet, writeable)
 });
+impl_dlc_writeable!(PreRefundedContract, {
+    (signed_contract, writeable),
+    (signed_refund, writeable)
+});
 impl_dlc_writeable!(ClosedContract, {
     (attestations, {option_cb, write_vec, read_vec}),
     (signed_cet, writeable),
@@ -142,11 +188,21 @@ impl_dlc_writeable!(ClosedContract, {
 impl_dlc_writeable!(FailedAcceptContract, {(offered_contract, writeable), (accept_message, writeable), (error_message, string)});
 impl_dlc_writeable!(FailedSignContract, {(accepted_contract, writeable), (sign_message, writeable), (error_message, string)});
 
+impl VersionedSerializable for OfferedContract {}
+impl VersionedSerializable for AcceptedContract {}
+impl VersionedSerializable for SignedContract {}
+impl VersionedSerializable for PreClosedContract {}
+impl VersionedSerializable for PreRefundedContract {}
+impl VersionedSerializable for ClosedContract {}
+impl VersionedSerializable for FailedAcceptContract {}
+impl VersionedSerializable for FailedSignContract {}
+
 impl_dlc_writeable_external!(DigitTrieDump<Vec<RangeInfo> >, digit_trie_dump_vec_range, { (node_data, {vec_cb, write_digit_node_data_vec_range, read_digit_node_data_vec_range}), (root, {option_cb, write_usize, read_usize}), (base, usize)});
 impl_dlc_writeable_external!(DigitTrieDump<RangeInfo>, digit_trie_dump_range, { (node_data, {vec_cb, write_digit_node_data_range, read_digit_node_data_range}), (root, {option_cb, write_usize, read_usize}), (base, usize)});
 impl_dlc_writeable_external!(DigitTrieDump<Vec<TrieNodeInfo> >, digit_trie_dump_trie, { (node_data, {vec_cb, write_digit_node_data_trie, read_digit_node_data_trie}), (root, {option_cb, write_usize, read_usize}), (base, usize)});
 impl_dlc_writeable_external!(MultiOracleTrieDump, multi_oracle_trie_dump, { (digit_trie_dump, {cb_writeable, digit_trie_dump_vec_range::write, digit_trie_dump_vec_range::read}), (threshold, usize), (oracle_numeric_infos, {cb_writeable, oracle_params::write, oracle_params::read}), (extra_cover_trie_dump, {option_cb, multi_trie_dump::write, multi_trie_dump::read}) });
-impl_dlc_writeable_external!(OracleNumericInfo, oracle_params, { (base, usize), (nb_digits, {vec_cb, write_usize, read_usize}) });
+impl_dlc_writeable_external!(MultiBaseTrieEntry, multi_base_trie_entry, { (indexes, {vec_cb, write_usize, read_usize}), (paths, {vec_cb, write_usize_vec, read_usize_vec}), (range_info, {cb_writeable, range_info::write, range_info::read}) });
+impl_dlc_writeable_external!(MultiBaseOracleTrieDump, multi_base_oracle_trie_dump, { (threshold, usize), (oracle_numeric_infos, {cb_writeable, oracle_params::write, oracle_params::read}), (entries, {vec_cb, multi_base_trie_entry::write, multi_base_trie_entry::read}), (difference_exps, {option_cb, write_difference_exps, read_difference_exps}) });
 impl_dlc_writeable_external_enum!(
     MultiTrieNodeData<RangeInfo>,
     multi_trie_node_data,
@@ -157,6 +213,154 @@ impl_dlc_writeable_external!(MultiTrieDump<RangeInfo>, multi_trie_dump, { (node_
 impl_dlc_writeable_external!(MultiOracleTrieWithDiffDump, multi_oracle_trie_with_diff_dump, { (multi_trie_dump, {cb_writeable, multi_trie_dump::write, multi_trie_dump::read}), (oracle_numeric_infos, {cb_writeable, oracle_params::write, oracle_params::read}) });
 impl_dlc_writeable_external!(TrieNodeInfo, trie_node_info, { (trie_index, usize), (store_index, usize) });
 
+// Enum descriptors without outcome mappings and numerical descriptors without
+// outcome parameters are written without them under the variant id that was
+// used before they were introduced, so that contracts stored by previous
+// versions can still be read.
+impl Writeable for ContractDescriptor {
+    fn write<W: Writer>(&self, w: &mut W) -> Result<(), ::std::io::Error> {
+        match self {
+            ContractDescriptor::Enum(e) if e.oracle_outcome_mappings.is_none() => {
+                0_u8.write(w)?;
+                write_vec_cb(
+                    &e.outcome_payouts,
+                    w,
+                    &dlc_messages::ser_impls::enum_payout::write,
+                )
+            }
+            ContractDescriptor::Enum(e) => {
+                2_u8.write(w)?;
+                e.write(w)
+            }
+            ContractDescriptor::Numerical(n) if n.outcome_params.is_none() => {
+                1_u8.write(w)?;
+                n.payout_function.write(w)?;
+                n.rounding_intervals.write(w)?;
+                write_option(&n.difference_params, w)?;
+                oracle_params::write(&n.oracle_numeric_infos, w)
+            }
+            ContractDescriptor::Numerical(n) => {
+                3_u8.write(w)?;
+                n.write(w)
+            }
+        }
+    }
+}
+
+impl Readable for ContractDescriptor {
+    fn read<R: Read>(r: &mut R) -> Result<Self, DecodeError> {
+        let id: u8 = Readable::read(r)?;
+        match id {
+            0 => Ok(ContractDescriptor::Enum(EnumDescriptor {
+                outcome_payouts: read_vec_cb(r, &dlc_messages::ser_impls::enum_payout::read)?,
+                oracle_outcome_mappings: None,
+            })),
+            1 => Ok(ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function: Readable::read(r)?,
+                rounding_intervals: Readable::read(r)?,
+                difference_params: read_option(r)?,
+                oracle_numeric_infos: oracle_params::read(r)?,
+                outcome_params: None,
+            })),
+            2 => Ok(ContractDescriptor::Enum(Readable::read(r)?)),
+            3 => Ok(ContractDescriptor::Numerical(Readable::read(r)?)),
+            _ => Err(DecodeError::UnknownRequiredFeature),
+        }
+    }
+}
+
+#[allow(clippy::ptr_arg)] // Need to have Vec to work with callbacks.
+fn write_oracle_outcome_mappings<W: Writer>(
+    mappings: &Vec<Vec<String>>,
+    writer: &mut W,
+) -> Result<(), ::std::io::Error> {
+    let cb = |x: &Vec<String>, writer: &mut W| -> Result<(), ::std::io::Error> {
+        write_strings(x, writer)
+    };
+    write_vec_cb(mappings, writer, &cb)
+}
+
+fn read_oracle_outcome_mappings<R: Read>(reader: &mut R) -> Result<Vec<Vec<String>>, DecodeError> {
+    read_vec_cb(reader, &read_strings)
+}
+
+/// Module containing write and read functions for oracle_params. Oracles
+/// using different bases are written with a zero base followed by the base of
+/// each oracle, so that the information written before oracles could use
+/// different bases can still be read.
+pub mod oracle_params {
+    use super::*;
+
+    /// Function to write oracle_params
+    pub fn write<W: Writer>(
+        oracle_params: &OracleNumericInfo,
+        w: &mut W,
+    ) -> Result<(), ::std::io::Error> {
+        match &oracle_params.oracle_bases {
+            Some(bases) if oracle_params.has_diff_bases() => {
+                write_usize(&0, w)?;
+                write_vec_cb(bases, w, &write_usize)?;
+            }
+            _ => write_usize(&oracle_params.base, w)?,
+        }
+        write_vec_cb(&oracle_params.nb_digits, w, &write_usize)
+    }
+
+    /// Function to read oracle_params
+    pub fn read<R: Read>(r: &mut R) -> Result<OracleNumericInfo, DecodeError> {
+        let (base, oracle_bases) = match read_usize(r)? {
+            0 => {
+                let bases = read_vec_cb(r, &read_usize)?;
+                (
+                    *bases.first().ok_or(DecodeError::InvalidValue)?,
+                    Some(bases),
+                )
+            }
+            base => (base, None),
+        };
+        Ok(OracleNumericInfo {
+            base,
+            nb_digits: read_vec_cb(r, &read_usize)?,
+            oracle_bases,
+        })
+    }
+}
+
+#[allow(clippy::ptr_arg)] // Need to have Vec to work with callbacks.
+fn write_usize_vec<W: Writer>(input: &Vec<usize>, writer: &mut W) -> Result<(), ::std::io::Error> {
+    write_vec_cb(input, writer, &write_usize)
+}
+
+fn read_usize_vec<R: Read>(reader: &mut R) -> Result<Vec<usize>, DecodeError> {
+    read_vec_cb(reader, &read_usize)
+}
+
+fn write_difference_exps<W: Writer>(
+    input: &(usize, usize),
+    writer: &mut W,
+) -> Result<(), ::std::io::Error> {
+    write_usize(&input.0, writer)?;
+    write_usize(&input.1, writer)
+}
+
+fn read_difference_exps<R: Read>(reader: &mut R) -> Result<(usize, usize), DecodeError> {
+    Ok((read_usize(reader)?, read_usize(reader)?))
+}
+
+fn write_multi_base_oracle_trie<W: Writer>(
+    trie: &MultiBaseOracleTrie,
+    w: &mut W,
+) -> Result<(), ::std::io::Error> {
+    multi_base_oracle_trie_dump::write(&trie.dump(), w)
+}
+
+fn read_multi_base_oracle_trie<R: Read>(
+    reader: &mut R,
+) -> Result<MultiBaseOracleTrie, DecodeError> {
+    let dump = multi_base_oracle_trie_dump::read(reader)?;
+    Ok(MultiBaseOracleTrie::from_dump(dump))
+}
+
 fn write_digit_node_data_trie<W: Writer>(
     input: &DigitNodeData<Vec<TrieNodeInfo>>,
     writer: &mut W,
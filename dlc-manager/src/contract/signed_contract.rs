@@ -16,7 +16,8 @@ use secp256k1_zkp::EcdsaAdaptorSignature;
 pub struct SignedContract {
     /// The accepted contract that was signed.
     pub accepted_contract: AcceptedContract,
-    /// The adaptor signatures of the offering party (None if offering party).
+    /// The adaptor signatures of the offering party (None if offering party,
+    /// unless the contract is an on-chain one).
     pub adaptor_signatures: Option<Vec<EcdsaAdaptorSignature>>,
     /// The refund signature of the offering party.
     pub offer_refund_signature: Signature,
@@ -3,23 +3,28 @@
 use std::ops::Deref;
 
 use bitcoin::psbt::PartiallySignedTransaction;
-use bitcoin::{consensus::Decodable, Script, Transaction, Witness};
+use bitcoin::{consensus::Decodable, OutPoint, Script, Transaction, Witness};
 use dlc::{DlcTransactions, PartyParams, FeeConfig};
 use dlc_messages::{
     oracle_msgs::{OracleAnnouncement, OracleAttestation},
-    AcceptDlc, FundingSignature, FundingSignatures, OfferDlc, SignDlc, WitnessElement,
+    AcceptDlc, CloseAccept, CloseOffer, FundingSignature, FundingSignatures, OfferDlc, SignDlc,
+    WitnessElement,
 };
+use dlc_trie::combination_iterator::CombinationIterator;
 use secp256k1_zkp::{
-    ecdsa::Signature, All, EcdsaAdaptorSignature, PublicKey, Secp256k1, SecretKey, Signing,
+    ecdsa::Signature, hashes::sha256, schnorr::Signature as SchnorrSignature, All,
+    EcdsaAdaptorSignature, Message, PublicKey, Secp256k1, SecretKey, Signing,
 };
 
 use crate::{
     contract::{
-        accepted_contract::AcceptedContract, contract_info::ContractInfo,
+        accepted_contract::AcceptedContract, adaptor_point_cache::AdaptorPointCache,
+        contract_info::ContractInfo,
         contract_input::ContractInput, offered_contract::OfferedContract,
-        signed_contract::SignedContract, AdaptorInfo, FundingInputInfo,
+        signed_contract::SignedContract, AdaptorInfo, ClosedContract, Contract,
+        ContractDescriptor, FundingInputInfo, PreClosedContract, PreRefundedContract,
     },
-    conversion_utils::get_tx_input_infos,
+    conversion_utils::{get_tx_input_infos, PROTOCOL_VERSION},
     error::Error,
     Blockchain, DlcChannelId, Signer, Time, Wallet,
 };
@@ -55,26 +60,49 @@ where
         FeeConfig::EvenSplit
     )?;
 
-    let offered_contract = OfferedContract::new(
+    Ok(offer_contract_with_party_params(
         contract_input,
         oracle_announcements,
+        refund_delay,
+        counter_party,
         &party_params,
         &funding_inputs_info,
+        time.unix_time_now(),
+    ))
+}
+
+/// Same as [`offer_contract`], using the provided offering party parameters
+/// instead of requesting them from the wallet.
+pub(crate) fn offer_contract_with_party_params(
+    contract_input: &ContractInput,
+    oracle_announcements: Vec<Vec<OracleAnnouncement>>,
+    refund_delay: u32,
+    counter_party: &PublicKey,
+    party_params: &PartyParams,
+    funding_inputs_info: &[FundingInputInfo],
+    now: u64,
+) -> (OfferedContract, OfferDlc) {
+    let offered_contract = OfferedContract::new(
+        contract_input,
+        oracle_announcements,
+        party_params,
+        funding_inputs_info,
         counter_party,
         refund_delay,
-        time.unix_time_now() as u32,
+        now as u32,
         crate::utils::get_new_temporary_id(),
     );
 
     let offer_msg: OfferDlc = (&offered_contract).into();
 
-    Ok((offered_contract, offer_msg))
+    (offered_contract, offer_msg)
 }
 
 /// Creates an [`AcceptedContract`] and produces
 /// the accepting party's cet adaptor signatures.
 pub fn accept_contract<W: Deref, B: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     offered_contract: &OfferedContract,
     wallet: &W,
     blockchain: &B,
@@ -97,9 +125,31 @@ where
         FeeConfig::EvenSplit,
     )?;
 
+    accept_contract_with_party_params(
+        secp,
+        adaptor_point_cache,
+        offered_contract,
+        &accept_params,
+        &fund_secret_key,
+        &funding_inputs,
+    )
+}
+
+/// Same as [`accept_contract`], using the provided accepting party parameters
+/// instead of requesting them from the wallet.
+pub(crate) fn accept_contract_with_party_params(
+    secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
+    offered_contract: &OfferedContract,
+    accept_params: &PartyParams,
+    fund_secret_key: &SecretKey,
+    funding_inputs: &[FundingInputInfo],
+) -> Result<(AcceptedContract, AcceptDlc), crate::Error> {
+    let total_collateral = offered_contract.total_collateral;
+
     let dlc_transactions = dlc::create_dlc_transactions(
         &offered_contract.offer_params,
-        &accept_params,
+        accept_params,
         &offered_contract.contract_info[0].get_payouts(total_collateral)?,
         offered_contract.refund_locktime,
         offered_contract.fee_rate_per_vb,
@@ -111,17 +161,22 @@ where
 
     let fund_output_value = dlc_transactions.get_fund_output().value;
 
-    let (accepted_contract, adaptor_sigs) = accept_contract_internal(
+    let (mut accepted_contract, adaptor_sigs) = accept_contract_internal(
         secp,
+        adaptor_point_cache,
         offered_contract,
-        &accept_params,
-        &funding_inputs,
-        &fund_secret_key,
+        accept_params,
+        funding_inputs,
+        fund_secret_key,
         fund_output_value,
         None,
         &dlc_transactions,
     )?;
 
+    // Keep own adaptor signatures so that the oracle attestation can be
+    // recovered if the counter party broadcasts a CET.
+    accepted_contract.adaptor_signatures = Some(adaptor_sigs.clone());
+
     let accept_msg: AcceptDlc = accepted_contract.get_accept_contract_msg(&adaptor_sigs);
 
     Ok((accepted_contract, accept_msg))
@@ -129,6 +184,7 @@ where
 
 pub(crate) fn accept_contract_internal(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     offered_contract: &OfferedContract,
     accept_params: &PartyParams,
     funding_inputs: &[FundingInputInfo],
@@ -144,15 +200,17 @@ pub(crate) fn accept_contract_internal(
 
     let cet_input = dlc_transactions.cets[0].input[0].clone();
 
-    let (adaptor_info, adaptor_sig) = offered_contract.contract_info[0].get_adaptor_info(
-        secp,
-        offered_contract.total_collateral,
-        adaptor_secret_key,
-        &input_script_pubkey,
-        input_value,
-        &dlc_transactions.cets,
-        0,
-    )?;
+    let (adaptor_info, adaptor_sig) = offered_contract.contract_info[0]
+        .get_adaptor_info_with_cache(
+            secp,
+            adaptor_point_cache,
+            offered_contract.total_collateral,
+            adaptor_secret_key,
+            &input_script_pubkey,
+            input_value,
+            &dlc_transactions.cets,
+            0,
+        )?;
     let mut adaptor_infos = vec![adaptor_info];
     let mut adaptor_sigs = adaptor_sig;
 
@@ -178,8 +236,9 @@ pub(crate) fn accept_contract_internal(
             0,
         );
 
-        let (adaptor_info, adaptor_sig) = contract_info.get_adaptor_info(
+        let (adaptor_info, adaptor_sig) = contract_info.get_adaptor_info_with_cache(
             secp,
+            adaptor_point_cache,
             offered_contract.total_collateral,
             adaptor_secret_key,
             &input_script_pubkey,
@@ -213,7 +272,8 @@ pub(crate) fn accept_contract_internal(
     let accepted_contract = AcceptedContract {
         offered_contract: offered_contract.clone(),
         adaptor_infos,
-        // Drop own adaptor signatures as no point keeping them.
+        // Own adaptor signatures are only kept for on-chain contracts, see
+        // `accept_contract`.
         adaptor_signatures: None,
         accept_params: accept_params.clone(),
         funding_inputs: funding_inputs.to_vec(),
@@ -228,6 +288,7 @@ pub(crate) fn accept_contract_internal(
 /// creates a [`SignedContract`], and generates the offering party CET adaptor signatures.
 pub fn verify_accepted_and_sign_contract<S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     offered_contract: &OfferedContract,
     accept_msg: &AcceptDlc,
     signer: &S,
@@ -271,8 +332,9 @@ where
     let fund_output_value = dlc_transactions.get_fund_output().value;
     let fund_privkey =
         signer.get_secret_key_for_pubkey(&offered_contract.offer_params.fund_pubkey)?;
-    let (signed_contract, adaptor_sigs) = verify_accepted_and_sign_contract_internal(
+    let (mut signed_contract, adaptor_sigs) = verify_accepted_and_sign_contract_internal(
         secp,
+        adaptor_point_cache,
         offered_contract,
         &accept_params,
         &accept_msg
@@ -291,6 +353,10 @@ where
         None,
     )?;
 
+    // Keep own adaptor signatures so that the oracle attestation can be
+    // recovered if the counter party broadcasts a CET.
+    signed_contract.adaptor_signatures = Some(adaptor_sigs.clone());
+
     let signed_msg: SignDlc = signed_contract.get_sign_dlc(adaptor_sigs);
 
     Ok((signed_contract, signed_msg))
@@ -323,6 +389,7 @@ fn populate_psbt(
 
 pub(crate) fn verify_accepted_and_sign_contract_internal<S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     offered_contract: &OfferedContract,
     accept_params: &PartyParams,
     funding_inputs_info: &[FundingInputInfo],
@@ -364,8 +431,9 @@ where
     )?;
 
     let (adaptor_info, mut adaptor_index) = offered_contract.contract_info[0]
-        .verify_and_get_adaptor_info(
+        .verify_and_get_adaptor_info_with_cache(
             secp,
+            adaptor_point_cache,
             offered_contract.total_collateral,
             &counter_adaptor_pk,
             &input_script_pubkey,
@@ -394,16 +462,18 @@ where
             0,
         );
 
-        let (adaptor_info, tmp_adaptor_index) = contract_info.verify_and_get_adaptor_info(
-            secp,
-            offered_contract.total_collateral,
-            &accept_params.fund_pubkey,
-            funding_script_pubkey,
-            input_value,
-            &tmp_cets,
-            cet_adaptor_signatures,
-            adaptor_index,
-        )?;
+        let (adaptor_info, tmp_adaptor_index) = contract_info
+            .verify_and_get_adaptor_info_with_cache(
+                secp,
+                adaptor_point_cache,
+                offered_contract.total_collateral,
+                &accept_params.fund_pubkey,
+                funding_script_pubkey,
+                input_value,
+                &tmp_cets,
+                cet_adaptor_signatures,
+                adaptor_index,
+            )?;
 
         adaptor_index = tmp_adaptor_index;
 
@@ -419,8 +489,9 @@ where
         .iter()
         .zip(adaptor_infos.iter())
     {
-        let sigs = contract_info.get_adaptor_signatures(
+        let sigs = contract_info.get_adaptor_signatures_with_cache(
             secp,
+            adaptor_point_cache,
             adaptor_info,
             adaptor_secret,
             &input_script_pubkey,
@@ -524,6 +595,7 @@ where
 /// signed fund transaction.
 pub fn verify_signed_contract<S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     accepted_contract: &AcceptedContract,
     sign_msg: &SignDlc,
     signer: &S,
@@ -534,6 +606,7 @@ where
     let cet_adaptor_signatures: Vec<_> = (&sign_msg.cet_adaptor_signatures).into();
     verify_signed_contract_internal(
         secp,
+        adaptor_point_cache,
         accepted_contract,
         &sign_msg.refund_signature,
         &cet_adaptor_signatures,
@@ -548,6 +621,7 @@ where
 
 pub(crate) fn verify_signed_contract_internal<S: Deref>(
     secp: &Secp256k1<All>,
+    adaptor_point_cache: &mut AdaptorPointCache,
     accepted_contract: &AcceptedContract,
     refund_signature: &Signature,
     cet_adaptor_signatures: &[EcdsaAdaptorSignature],
@@ -588,8 +662,9 @@ where
         .iter()
         .zip(offered_contract.contract_info.iter())
     {
-        adaptor_sig_start = contract_info.verify_adaptor_info(
+        adaptor_sig_start = contract_info.verify_adaptor_info_with_cache(
             secp,
+            adaptor_point_cache,
             &counter_adaptor_pk,
             &input_script_pubkey,
             input_value,
@@ -722,6 +797,179 @@ where
     Ok(cet)
 }
 
+/// Returns the local party's adaptor signatures for the CETs of the given
+/// contract, together with the signature it produced for the given CET, as
+/// decrypted and broadcast by the counter party.
+fn get_own_cet_signatures<'a>(
+    contract: &'a SignedContract,
+    cet: &Transaction,
+) -> Option<(&'a [EcdsaAdaptorSignature], Signature)> {
+    let accepted_contract = &contract.accepted_contract;
+    let offered_contract = &accepted_contract.offered_contract;
+
+    let (own_adaptor_signatures, fund_pubkey, other_fund_pubkey) =
+        if offered_contract.is_offer_party {
+            (
+                contract.adaptor_signatures.as_ref()?,
+                &offered_contract.offer_params.fund_pubkey,
+                &accepted_contract.accept_params.fund_pubkey,
+            )
+        } else {
+            (
+                accepted_contract.adaptor_signatures.as_ref()?,
+                &accepted_contract.accept_params.fund_pubkey,
+                &offered_contract.offer_params.fund_pubkey,
+            )
+        };
+
+    // Signatures in the witness are sorted by public key and followed by the
+    // sighash type.
+    let witness = cet.input.first()?.witness.to_vec();
+    let sig_data = if fund_pubkey < other_fund_pubkey {
+        witness.get(1)?
+    } else {
+        witness.get(2)?
+    };
+    let mut own_sig = Signature::from_der(sig_data.split_last()?.1).ok()?;
+    own_sig.normalize_s();
+
+    Some((own_adaptor_signatures.as_slice(), own_sig))
+}
+
+/// Tries to recover the oracle attestation that was used to decrypt the given CET,
+/// broadcast by the counter party, from the local party's own adaptor signature.
+/// This is only possible for enumeration contracts requiring a single oracle
+/// attestation, as otherwise the adaptor secret is the sum of several oracle
+/// signatures which cannot be separated. [`verify_cet_attestations`] can be used
+/// in other cases.
+pub fn recover_attestation_from_cet(
+    secp: &Secp256k1<All>,
+    contract: &SignedContract,
+    cet: &Transaction,
+) -> Option<OracleAttestation> {
+    let accepted_contract = &contract.accepted_contract;
+    let offered_contract = &accepted_contract.offered_contract;
+
+    // CETs and adaptor signatures of the different contract infos are
+    // concatenated, only the (common) case of a single one is handled.
+    if offered_contract.contract_info.len() != 1 {
+        return None;
+    }
+    let contract_info = &offered_contract.contract_info[0];
+    let enum_descriptor = match &contract_info.contract_descriptor {
+        ContractDescriptor::Enum(e) => e,
+        _ => return None,
+    };
+    if contract_info.threshold != 1 {
+        return None;
+    }
+
+    let cet_index = accepted_contract
+        .dlc_transactions
+        .cets
+        .iter()
+        .position(|x| x.txid() == cet.txid())?;
+    let (own_adaptor_signatures, own_sig) = get_own_cet_signatures(contract, cet)?;
+    let oracle_infos = contract_info.get_oracle_infos();
+
+    // With a threshold of one, each oracle has its own adaptor signature for
+    // each outcome, in the order of the oracles.
+    contract_info
+        .oracle_announcements
+        .iter()
+        .enumerate()
+        .find_map(|(oracle_index, announcement)| {
+            let own_adaptor_signature =
+                own_adaptor_signatures.get(cet_index * oracle_infos.len() + oracle_index)?;
+            let outcome = enum_descriptor.get_oracle_outcome(oracle_index, cet_index);
+            let msg = Message::from_hashed_data::<sha256::Hash>(outcome.as_bytes());
+            let adaptor_point = dlc::get_adaptor_point_from_oracle_info(
+                secp,
+                &oracle_infos[oracle_index..oracle_index + 1],
+                &[vec![msg]],
+            )
+            .ok()?;
+            let adaptor_secret = own_adaptor_signature
+                .recover(secp, &own_sig, &adaptor_point)
+                .ok()?;
+            let nonce = announcement.oracle_event.oracle_nonces.first()?;
+
+            [adaptor_secret, adaptor_secret.negate()]
+                .iter()
+                .find_map(|secret| {
+                    let mut sig_bytes = [0u8; 64];
+                    sig_bytes[..32].copy_from_slice(&nonce.serialize());
+                    sig_bytes[32..].copy_from_slice(secret.as_ref());
+                    let signature = SchnorrSignature::from_slice(&sig_bytes).ok()?;
+                    secp.verify_schnorr(&signature, &msg, &announcement.oracle_public_key)
+                        .ok()?;
+                    Some(signature)
+                })
+                .map(|signature| OracleAttestation {
+                    oracle_public_key: announcement.oracle_public_key,
+                    signatures: vec![signature],
+                    outcomes: vec![outcome.to_string()],
+                })
+        })
+}
+
+/// Returns the attestations, among the given ones, that were used to decrypt the
+/// given CET broadcast by the counter party, if any. The attestations are given
+/// for each of the contract infos of the contract, together with the index of
+/// the oracle that produced them in the announcements of the contract info.
+/// Contrary to [`recover_attestation_from_cet`], this works for all types of
+/// contracts, including numerical ones and ones requiring several oracles, by
+/// checking which of the combinations of attestations meeting the threshold
+/// decrypt the local party's adaptor signature into the one of the CET.
+pub fn verify_cet_attestations(
+    contract: &SignedContract,
+    cet: &Transaction,
+    attestations: &[Vec<(usize, OracleAttestation)>],
+) -> Option<Vec<OracleAttestation>> {
+    let accepted_contract = &contract.accepted_contract;
+    let contract_infos = &accepted_contract.offered_contract.contract_info;
+    let cets = &accepted_contract.dlc_transactions.cets;
+    let (own_adaptor_signatures, own_sig) = get_own_cet_signatures(contract, cet)?;
+    let cet_txid = cet.txid();
+
+    contract_infos
+        .iter()
+        .zip(accepted_contract.adaptor_infos.iter())
+        .zip(attestations.iter())
+        .find_map(|((contract_info, adaptor_info), attestations)| {
+            if attestations.len() < contract_info.threshold {
+                return None;
+            }
+            CombinationIterator::new(attestations.len(), contract_info.threshold).find_map(
+                |combination| {
+                    let selected: Vec<_> = combination
+                        .iter()
+                        .map(|i| attestations[*i].clone())
+                        .collect();
+                    let (range_info, sigs) = crate::utils::get_range_info_and_oracle_sigs(
+                        contract_info,
+                        adaptor_info,
+                        &selected,
+                    )
+                    .ok()?;
+                    if cets.get(range_info.cet_index)?.txid() != cet_txid {
+                        return None;
+                    }
+                    let adaptor_secret = dlc::signatures_to_secret(&sigs).ok()?;
+                    let mut sig = own_adaptor_signatures
+                        .get(range_info.adaptor_index)?
+                        .decrypt(&adaptor_secret)
+                        .ok()?;
+                    sig.normalize_s();
+                    if sig != own_sig {
+                        return None;
+                    }
+                    Some(selected.into_iter().map(|(_, x)| x).collect())
+                },
+            )
+        })
+}
+
 /// Signs and return the refund transaction to refund the contract.
 pub fn get_signed_refund<C: Signing, S: Deref>(
     secp: &Secp256k1<C>,
@@ -763,3 +1011,449 @@ where
     )?;
     Ok(refund)
 }
+
+/// Returns the fee of a transaction collaboratively closing the given contract
+/// at the given fee rate (in satoshis per virtual byte).
+pub fn get_close_fee(contract: &SignedContract, fee_rate_per_vb: u64) -> Result<u64, Error> {
+    let accepted_contract = &contract.accepted_contract;
+    let offer_params = &accepted_contract.offered_contract.offer_params;
+    let accept_params = &accepted_contract.accept_params;
+    Ok(dlc::util::cet_or_refund_base_fee(fee_rate_per_vb)?
+        + dlc::util::dlc_payout_spk_fee(&offer_params.payout_script_pubkey, fee_rate_per_vb)
+        + dlc::util::dlc_payout_spk_fee(&accept_params.payout_script_pubkey, fee_rate_per_vb))
+}
+
+/// Returns the transaction spending the funding output of the given contract to
+/// close it collaboratively, paying `counter_payout` to the party receiving the
+/// close offer, `fee` to the miners and the rest of the funding output to the
+/// one sending it.
+fn get_close_transaction(
+    contract: &SignedContract,
+    counter_payout: u64,
+    fee: u64,
+    is_close_offerer: bool,
+) -> Result<Transaction, Error> {
+    let accepted_contract = &contract.accepted_contract;
+    let offered_contract = &accepted_contract.offered_contract;
+    let dlc_transactions = &accepted_contract.dlc_transactions;
+    let fund_output_value = dlc_transactions.get_fund_output().value;
+
+    let min_fee = get_close_fee(contract, 1)?;
+    if fee < min_fee {
+        return Err(Error::InvalidParameters(format!(
+            "Close fee {} is lower than the minimum relay fee {}",
+            fee, min_fee
+        )));
+    }
+
+    let close_offerer_payout = fund_output_value
+        .checked_sub(fee)
+        .and_then(|x| x.checked_sub(counter_payout))
+        .ok_or_else(|| {
+            Error::InvalidParameters(
+                "Counter payout and fee are greater than the funding output value".to_string(),
+            )
+        })?;
+
+    let (own_payout, other_payout) = if is_close_offerer {
+        (close_offerer_payout, counter_payout)
+    } else {
+        (counter_payout, close_offerer_payout)
+    };
+    let (offer_payout, accept_payout) = if offered_contract.is_offer_party {
+        (own_payout, other_payout)
+    } else {
+        (other_payout, own_payout)
+    };
+
+    Ok(dlc::channel::create_collaborative_close_transaction(
+        &offered_contract.offer_params,
+        offer_payout,
+        &accepted_contract.accept_params,
+        accept_payout,
+        OutPoint {
+            txid: dlc_transactions.fund.txid(),
+            vout: dlc_transactions.get_fund_output_index() as u32,
+        },
+        fund_output_value,
+    ))
+}
+
+/// Returns the fund public keys of the local party and of its counter party.
+fn get_fund_pubkeys(contract: &SignedContract) -> (&PublicKey, &PublicKey) {
+    let accepted_contract = &contract.accepted_contract;
+    let offered_contract = &accepted_contract.offered_contract;
+    if offered_contract.is_offer_party {
+        (
+            &offered_contract.offer_params.fund_pubkey,
+            &accepted_contract.accept_params.fund_pubkey,
+        )
+    } else {
+        (
+            &accepted_contract.accept_params.fund_pubkey,
+            &offered_contract.offer_params.fund_pubkey,
+        )
+    }
+}
+
+/// Creates a [`CloseOffer`] message proposing to close the given contract by
+/// paying `counter_payout` to the counter party and the rest of the funding
+/// output to the local party, minus the fee of the closing transaction at
+/// `fee_rate_per_vb`. The counter party must not accept the offer from
+/// `expiry` onwards.
+pub fn offer_close<C: Signing, S: Deref>(
+    secp: &Secp256k1<C>,
+    contract: &SignedContract,
+    counter_payout: u64,
+    fee_rate_per_vb: u64,
+    expiry: u32,
+    signer: &S,
+) -> Result<CloseOffer, Error>
+where
+    S::Target: Signer,
+{
+    let dlc_transactions = &contract.accepted_contract.dlc_transactions;
+    let fee = get_close_fee(contract, fee_rate_per_vb)?;
+    let close_tx = get_close_transaction(contract, counter_payout, fee, true)?;
+    let (fund_pubkey, _) = get_fund_pubkeys(contract);
+    let fund_priv_key = signer.get_secret_key_for_pubkey(fund_pubkey)?;
+
+    let close_signature = dlc::util::get_raw_sig_for_tx_input(
+        secp,
+        &close_tx,
+        0,
+        &dlc_transactions.funding_script_pubkey,
+        dlc_transactions.get_fund_output().value,
+        &fund_priv_key,
+    )?;
+
+    Ok(CloseOffer {
+        protocol_version: PROTOCOL_VERSION,
+        contract_id: contract.accepted_contract.get_contract_id(),
+        counter_payout,
+        fee,
+        expiry,
+        close_signature,
+    })
+}
+
+/// Verifies that the given [`CloseOffer`] has not expired at time `now` and its
+/// signature, and returns the closing transaction that it proposes.
+pub fn verify_close_offer(
+    secp: &Secp256k1<All>,
+    contract: &SignedContract,
+    close_offer: &CloseOffer,
+    now: u64,
+) -> Result<Transaction, Error> {
+    if close_offer.expiry as u64 <= now {
+        return Err(Error::InvalidParameters(format!(
+            "Close offer expired at {}",
+            close_offer.expiry
+        )));
+    }
+
+    let dlc_transactions = &contract.accepted_contract.dlc_transactions;
+    let close_tx =
+        get_close_transaction(contract, close_offer.counter_payout, close_offer.fee, false)?;
+    let (_, other_fund_pubkey) = get_fund_pubkeys(contract);
+
+    dlc::verify_tx_input_sig(
+        secp,
+        &close_offer.close_signature,
+        &close_tx,
+        0,
+        &dlc_transactions.funding_script_pubkey,
+        dlc_transactions.get_fund_output().value,
+        other_fund_pubkey,
+    )?;
+
+    Ok(close_tx)
+}
+
+/// Accepts the given [`CloseOffer`] if it has not expired at time `now`,
+/// returning the fully signed closing transaction and the [`CloseAccept`]
+/// message to send to the counter party.
+pub fn accept_close<S: Deref>(
+    secp: &Secp256k1<All>,
+    contract: &SignedContract,
+    close_offer: &CloseOffer,
+    now: u64,
+    signer: &S,
+) -> Result<(Transaction, CloseAccept), Error>
+where
+    S::Target: Signer,
+{
+    let dlc_transactions = &contract.accepted_contract.dlc_transactions;
+    let mut close_tx = verify_close_offer(secp, contract, close_offer, now)?;
+    let (fund_pubkey, other_fund_pubkey) = get_fund_pubkeys(contract);
+    let fund_priv_key = signer.get_secret_key_for_pubkey(fund_pubkey)?;
+
+    let accept_signature = dlc::util::get_raw_sig_for_tx_input(
+        secp,
+        &close_tx,
+        0,
+        &dlc_transactions.funding_script_pubkey,
+        dlc_transactions.get_fund_output().value,
+        &fund_priv_key,
+    )?;
+
+    dlc::util::finalize_multi_sig_input_transaction(
+        &mut close_tx,
+        vec![
+            (*other_fund_pubkey, close_offer.close_signature),
+            (*fund_pubkey, accept_signature),
+        ],
+        &dlc_transactions.funding_script_pubkey,
+        0,
+    );
+
+    let close_accept = CloseAccept {
+        protocol_version: PROTOCOL_VERSION,
+        contract_id: close_offer.contract_id,
+        counter_payout: close_offer.counter_payout,
+        fee: close_offer.fee,
+        offer_signature: close_offer.close_signature,
+        accept_signature,
+    };
+
+    Ok((close_tx, close_accept))
+}
+
+/// Verifies the given [`CloseAccept`], received in response to a [`CloseOffer`]
+/// sent by the local party, and returns the fully signed closing transaction.
+/// The echoed offer signature is checked against the local fund public key so
+/// that only a split that was actually proposed can be accepted.
+pub fn on_close_accept(
+    secp: &Secp256k1<All>,
+    contract: &SignedContract,
+    close_accept: &CloseAccept,
+) -> Result<Transaction, Error> {
+    let dlc_transactions = &contract.accepted_contract.dlc_transactions;
+    let funding_script_pubkey = &dlc_transactions.funding_script_pubkey;
+    let fund_output_value = dlc_transactions.get_fund_output().value;
+    let mut close_tx = get_close_transaction(
+        contract,
+        close_accept.counter_payout,
+        close_accept.fee,
+        true,
+    )?;
+    let (fund_pubkey, other_fund_pubkey) = get_fund_pubkeys(contract);
+
+    dlc::verify_tx_input_sig(
+        secp,
+        &close_accept.offer_signature,
+        &close_tx,
+        0,
+        funding_script_pubkey,
+        fund_output_value,
+        fund_pubkey,
+    )
+    .map_err(|_| {
+        Error::InvalidParameters("Close accept does not match any sent close offer".to_string())
+    })?;
+
+    dlc::verify_tx_input_sig(
+        secp,
+        &close_accept.accept_signature,
+        &close_tx,
+        0,
+        funding_script_pubkey,
+        fund_output_value,
+        other_fund_pubkey,
+    )?;
+
+    dlc::util::finalize_multi_sig_input_transaction(
+        &mut close_tx,
+        vec![
+            (*fund_pubkey, close_accept.offer_signature),
+            (*other_fund_pubkey, close_accept.accept_signature),
+        ],
+        funding_script_pubkey,
+        0,
+    );
+
+    Ok(close_tx)
+}
+
+/// The contract info of a contract for which enough oracle attestations are
+/// available to close it, with its adaptor info and the attestations.
+pub(crate) type ClosableContractInfo<'a> = Option<(
+    &'a ContractInfo,
+    &'a AdaptorInfo,
+    Vec<(usize, OracleAttestation)>,
+)>;
+
+/// Returns the announcements of the given contract info whose event has matured
+/// at time `now`, together with their index in the contract info.
+pub(crate) fn get_matured_announcements(
+    contract_info: &ContractInfo,
+    now: u64,
+) -> impl Iterator<Item = (usize, &OracleAnnouncement)> {
+    contract_info
+        .oracle_announcements
+        .iter()
+        .enumerate()
+        .filter(move |(_, x)| (x.oracle_event.event_maturity_epoch as u64) <= now)
+}
+
+/// Returns the first contract info of the given contract that can be closed
+/// with the given attestations, provided for each of its contract infos.
+pub(crate) fn get_closable_contract_info(
+    contract: &SignedContract,
+    attestations: Vec<Vec<(usize, OracleAttestation)>>,
+) -> ClosableContractInfo<'_> {
+    let contract_infos = &contract.accepted_contract.offered_contract.contract_info;
+    let adaptor_infos = &contract.accepted_contract.adaptor_infos;
+    contract_infos
+        .iter()
+        .zip(adaptor_infos.iter())
+        .zip(attestations)
+        .find(|((contract_info, _), attestations)| attestations.len() >= contract_info.threshold)
+        .map(|((contract_info, adaptor_info), attestations)| {
+            (contract_info, adaptor_info, attestations)
+        })
+}
+
+/// Returns whether the refund transaction of the given contract is valid at
+/// time `now`.
+pub(crate) fn is_refundable(contract: &SignedContract, now: u64) -> bool {
+    contract
+        .accepted_contract
+        .dlc_transactions
+        .refund
+        .lock_time
+        .0 as u64
+        <= now
+}
+
+/// The transactions that can spend the funding output of an on-chain contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FundingSpend {
+    /// The refund transaction.
+    Refund,
+    /// One of the CETs.
+    Cet,
+    /// Any other transaction. As the funding output requires the signatures of
+    /// both parties, it is a collaborative close agreed upon through a
+    /// [`CloseOffer`].
+    CollaborativeClose,
+}
+
+/// Returns the kind of the given transaction spending the funding output of the
+/// given contract.
+pub(crate) fn get_funding_spend(contract: &SignedContract, tx: &Transaction) -> FundingSpend {
+    let txid = tx.txid();
+    let dlc_transactions = &contract.accepted_contract.dlc_transactions;
+    if txid == dlc_transactions.refund.txid() {
+        FundingSpend::Refund
+    } else if dlc_transactions.cets.iter().any(|x| x.txid() == txid) {
+        FundingSpend::Cet
+    } else {
+        FundingSpend::CollaborativeClose
+    }
+}
+
+/// Returns the signed contract whose state needs to be updated following the
+/// given transaction spending its funding output, which is the case if the
+/// contract is confirmed, or if the CET or refund transaction broadcast by the
+/// local party lost the race against `tx`. Returns `None` if `tx` is the local
+/// party's own closing transaction.
+pub(crate) fn get_contract_spent_by(
+    contract: Contract,
+    tx: &Transaction,
+) -> Option<SignedContract> {
+    let txid = tx.txid();
+    match contract {
+        Contract::Confirmed(c) => Some(c),
+        Contract::PreClosed(c) if c.signed_cet.txid() != txid => Some(c.signed_contract),
+        Contract::PreRefunded(c) if c.signed_refund.txid() != txid => Some(c.signed_contract),
+        _ => None,
+    }
+}
+
+/// Returns the [`ClosedContract`] for the given contract closed by `closing_tx`.
+pub(crate) fn get_closed_contract(
+    contract: &SignedContract,
+    closing_tx: Transaction,
+    attestations: Option<Vec<OracleAttestation>>,
+) -> ClosedContract {
+    ClosedContract {
+        attestations,
+        pnl: contract.accepted_contract.compute_pnl(&closing_tx),
+        signed_cet: Some(closing_tx),
+        contract_id: contract.accepted_contract.get_contract_id(),
+        temporary_contract_id: contract.accepted_contract.offered_contract.id,
+        counter_party_id: contract.accepted_contract.offered_contract.counter_party,
+    }
+}
+
+/// Returns the state of the given contract once `closing_tx`, which has
+/// `confirmations` confirmations, spends its funding output: pre-closed until
+/// the transaction is confirmed `nb_confirmations` times, and closed afterwards.
+/// Broadcasting the transaction if it is unconfirmed is left to the caller.
+pub(crate) fn get_closing_contract(
+    contract: &SignedContract,
+    closing_tx: Transaction,
+    attestations: Option<Vec<OracleAttestation>>,
+    confirmations: u32,
+    nb_confirmations: u32,
+) -> Contract {
+    if confirmations < nb_confirmations.max(1) {
+        return Contract::PreClosed(PreClosedContract {
+            signed_contract: contract.clone(),
+            attestations,
+            signed_cet: closing_tx,
+        });
+    }
+
+    Contract::Closed(get_closed_contract(contract, closing_tx, attestations))
+}
+
+/// Same as [`get_closing_contract`] for the refund transaction of the contract.
+pub(crate) fn get_refunding_contract(
+    contract: &SignedContract,
+    signed_refund: Transaction,
+    confirmations: u32,
+    nb_confirmations: u32,
+) -> Contract {
+    if confirmations < nb_confirmations.max(1) {
+        return Contract::PreRefunded(PreRefundedContract {
+            signed_contract: contract.clone(),
+            signed_refund,
+        });
+    }
+
+    Contract::Refunded(contract.clone())
+}
+
+/// Returns the closed contract if the closing transaction of the given
+/// pre-closed contract reached `nb_confirmations` confirmations.
+pub(crate) fn on_preclosed_confirmations(
+    contract: &PreClosedContract,
+    confirmations: u32,
+    nb_confirmations: u32,
+) -> Option<Contract> {
+    if confirmations < nb_confirmations {
+        return None;
+    }
+
+    Some(Contract::Closed(get_closed_contract(
+        &contract.signed_contract,
+        contract.signed_cet.clone(),
+        contract.attestations.clone(),
+    )))
+}
+
+/// Returns the refunded contract if the refund transaction of the given
+/// pre-refunded contract reached `nb_confirmations` confirmations.
+pub(crate) fn on_prerefunded_confirmations(
+    contract: &PreRefundedContract,
+    confirmations: u32,
+    nb_confirmations: u32,
+) -> Option<Contract> {
+    if confirmations < nb_confirmations {
+        return None;
+    }
+
+    Some(Contract::Refunded(contract.signed_contract.clone()))
+}
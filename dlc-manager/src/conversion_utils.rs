@@ -1,7 +1,7 @@
 use crate::contract::{
     contract_info::ContractInfo,
     enum_descriptor::EnumDescriptor,
-    numerical_descriptor::{DifferenceParams, NumericalDescriptor},
+    numerical_descriptor::{DifferenceParams, NumericalDescriptor, OutcomeParams},
     offered_contract::OfferedContract,
     ContractDescriptor, FundingInputInfo,
 };
@@ -12,7 +12,8 @@ use crate::payout_curve::{
 use bitcoin::{consensus::encode::Decodable, OutPoint, Transaction};
 use dlc::{EnumerationPayout, Payout, TxInputInfo};
 use dlc_messages::oracle_msgs::{
-    MultiOracleInfo, OracleInfo as SerOracleInfo, OracleParams, SingleOracleInfo,
+    MultiOracleInfo, OracleAnnouncement, OracleInfo as SerOracleInfo, OracleParams,
+    SingleOracleInfo,
 };
 use dlc_messages::FundingInput;
 use dlc_messages::{
@@ -20,9 +21,10 @@ use dlc_messages::{
         ContractDescriptor as SerContractDescriptor, ContractInfo as SerContractInfo,
         ContractInfoInner, ContractOutcome, DisjointContractInfo, EnumeratedContractDescriptor,
         HyperbolaPayoutCurvePiece as SerHyperbolaPayoutCurvePiece,
-        NumericOutcomeContractDescriptor, PayoutCurvePiece as SerPayoutCurvePiece,
-        PayoutFunction as SerPayoutFunction, PayoutFunctionPiece as SerPayoutFunctionPiece,
-        PayoutPoint as SerPayoutPoint, PolynomialPayoutCurvePiece as SerPolynomialPayoutCurvePiece,
+        MappedEnumeratedContractDescriptor, NumericOutcomeContractDescriptor, OracleOutcomeMapping,
+        PayoutCurvePiece as SerPayoutCurvePiece, PayoutFunction as SerPayoutFunction,
+        PayoutFunctionPiece as SerPayoutFunctionPiece, PayoutPoint as SerPayoutPoint,
+        PolynomialPayoutCurvePiece as SerPolynomialPayoutCurvePiece,
         RoundingInterval as SerRoundingInterval, RoundingIntervals as SerRoundingIntervals,
         SingleContractInfo,
     },
@@ -114,38 +116,25 @@ pub(crate) fn get_contract_info_and_announcements(
         let (descriptor, oracle_announcements, threshold) = match contract_info.contract_descriptor
         {
             SerContractDescriptor::EnumeratedContractDescriptor(enumerated) => {
-                let outcome_payouts = enumerated
-                    .payouts
-                    .iter()
-                    .map(|x| EnumerationPayout {
-                        outcome: x.outcome.clone(),
-                        payout: Payout {
-                            offer: x.offer_payout,
-                            accept: total_collateral - x.offer_payout,
-                        },
-                    })
+                get_enum_contract_info(
+                    &enumerated.payouts,
+                    None,
+                    contract_info.oracle_info,
+                    total_collateral,
+                )?
+            }
+            SerContractDescriptor::MappedEnumeratedContractDescriptor(mapped) => {
+                let oracle_outcome_mappings = mapped
+                    .oracle_outcome_mappings
+                    .into_iter()
+                    .map(|x| x.outcomes)
                     .collect();
-                let descriptor = ContractDescriptor::Enum(EnumDescriptor { outcome_payouts });
-                let mut threshold = 1;
-                let announcements = match contract_info.oracle_info {
-                    SerOracleInfo::Single(single) => vec![single.oracle_announcement],
-                    SerOracleInfo::Multi(multi) => {
-                        threshold = multi.threshold;
-                        multi.oracle_announcements
-                    }
-                };
-
-                if announcements
-                    .iter()
-                    .any(|x| match &x.oracle_event.event_descriptor {
-                        EventDescriptor::EnumEvent(_) => false,
-                        EventDescriptor::DigitDecompositionEvent(_) => true,
-                    })
-                {
-                    return Err(Error::InvalidParameters);
-                }
-
-                (descriptor, announcements, threshold)
+                get_enum_contract_info(
+                    &mapped.payouts,
+                    Some(oracle_outcome_mappings),
+                    contract_info.oracle_info,
+                    total_collateral,
+                )?
             }
             SerContractDescriptor::NumericOutcomeContractDescriptor(numeric) => {
                 let threshold;
@@ -170,34 +159,52 @@ pub(crate) fn get_contract_info_and_announcements(
                 if announcements.is_empty() {
                     return Err(Error::InvalidParameters);
                 }
-                let expected_base = if let EventDescriptor::DigitDecompositionEvent(d) =
-                    &announcements[0].oracle_event.event_descriptor
-                {
-                    d.base
-                } else {
-                    return Err(Error::InvalidParameters);
-                };
-                let nb_digits = announcements
+                let event_descriptors = announcements
                     .iter()
                     .map(|x| match &x.oracle_event.event_descriptor {
-                        EventDescriptor::DigitDecompositionEvent(d) => {
-                            if d.base == expected_base {
-                                Ok(d.nb_digits as usize)
-                            } else {
-                                Err(Error::InvalidParameters)
-                            }
-                        }
+                        EventDescriptor::DigitDecompositionEvent(d) => Ok(d),
                         _ => Err(Error::InvalidParameters),
                     })
                     .collect::<Result<Vec<_>, _>>()?;
+                let bases: Vec<usize> = event_descriptors.iter().map(|x| x.base as usize).collect();
+                let nb_digits = event_descriptors
+                    .iter()
+                    .map(|x| x.nb_digits as usize)
+                    .collect();
+                let base = bases[0];
+                let oracle_bases = if bases.iter().any(|x| *x != base) {
+                    Some(bases)
+                } else {
+                    None
+                };
+                let outcome_params = OutcomeParams {
+                    is_signed: event_descriptors[0].is_signed,
+                    precision: event_descriptors[0].precision,
+                };
+                // Values are not scaled between oracles, which must then all
+                // attest in the same unit and with the same precision.
+                if event_descriptors.iter().any(|x| {
+                    x.is_signed != outcome_params.is_signed
+                        || x.precision != outcome_params.precision
+                        || x.unit != event_descriptors[0].unit
+                }) {
+                    return Err(Error::InvalidParameters);
+                }
+                let outcome_params = if outcome_params == OutcomeParams::default() {
+                    None
+                } else {
+                    Some(outcome_params)
+                };
                 let descriptor = ContractDescriptor::Numerical(NumericalDescriptor {
                     payout_function: (&numeric.payout_function).into(),
                     rounding_intervals: (&numeric.rounding_intervals).into(),
                     difference_params,
                     oracle_numeric_infos: OracleNumericInfo {
-                        base: expected_base as usize,
+                        base,
                         nb_digits,
+                        oracle_bases,
                     },
+                    outcome_params,
                 });
                 (descriptor, announcements, threshold)
             }
@@ -212,66 +219,109 @@ pub(crate) fn get_contract_info_and_announcements(
     Ok(contract_infos)
 }
 
+fn get_enum_contract_info(
+    payouts: &[ContractOutcome],
+    oracle_outcome_mappings: Option<Vec<Vec<String>>>,
+    oracle_info: SerOracleInfo,
+    total_collateral: u64,
+) -> Result<(ContractDescriptor, Vec<OracleAnnouncement>, u16), Error> {
+    let outcome_payouts = payouts
+        .iter()
+        .map(|x| EnumerationPayout {
+            outcome: x.outcome.clone(),
+            payout: Payout {
+                offer: x.offer_payout,
+                accept: total_collateral - x.offer_payout,
+            },
+        })
+        .collect();
+    let descriptor = ContractDescriptor::Enum(EnumDescriptor {
+        outcome_payouts,
+        oracle_outcome_mappings,
+    });
+    let mut threshold = 1;
+    let announcements = match oracle_info {
+        SerOracleInfo::Single(single) => vec![single.oracle_announcement],
+        SerOracleInfo::Multi(multi) => {
+            threshold = multi.threshold;
+            multi.oracle_announcements
+        }
+    };
+
+    if announcements
+        .iter()
+        .any(|x| match &x.oracle_event.event_descriptor {
+            EventDescriptor::EnumEvent(_) => false,
+            EventDescriptor::DigitDecompositionEvent(_) => true,
+        })
+    {
+        return Err(Error::InvalidParameters);
+    }
+
+    Ok((descriptor, announcements, threshold))
+}
+
 impl From<&OfferedContract> for SerContractInfo {
     fn from(offered_contract: &OfferedContract) -> SerContractInfo {
-        let oracle_infos: Vec<SerOracleInfo> = offered_contract.into();
-        let mut contract_infos: Vec<ContractInfoInner> = offered_contract
-            .contract_info
-            .iter()
-            .zip(oracle_infos)
-            .map(|(c, o)| ContractInfoInner {
-                contract_descriptor: (&c.contract_descriptor).into(),
-                oracle_info: o,
-            })
-            .collect();
-        if contract_infos.len() == 1 {
-            SerContractInfo::SingleContractInfo(SingleContractInfo {
-                total_collateral: offered_contract.total_collateral,
-                contract_info: contract_infos.remove(0),
-            })
-        } else {
-            SerContractInfo::DisjointContractInfo(DisjointContractInfo {
-                total_collateral: offered_contract.total_collateral,
-                contract_infos,
-            })
-        }
+        get_ser_contract_info(
+            &offered_contract.contract_info,
+            offered_contract.total_collateral,
+        )
+    }
+}
+
+/// Returns the wire representation of the given contract infos.
+pub(crate) fn get_ser_contract_info(
+    contract_infos: &[ContractInfo],
+    total_collateral: u64,
+) -> SerContractInfo {
+    let mut contract_infos: Vec<ContractInfoInner> = contract_infos
+        .iter()
+        .map(|c| ContractInfoInner {
+            contract_descriptor: (&c.contract_descriptor).into(),
+            oracle_info: get_ser_oracle_info(c),
+        })
+        .collect();
+    if contract_infos.len() == 1 {
+        SerContractInfo::SingleContractInfo(SingleContractInfo {
+            total_collateral,
+            contract_info: contract_infos.remove(0),
+        })
+    } else {
+        SerContractInfo::DisjointContractInfo(DisjointContractInfo {
+            total_collateral,
+            contract_infos,
+        })
     }
 }
 
 impl From<&OfferedContract> for Vec<SerOracleInfo> {
     fn from(offered_contract: &OfferedContract) -> Vec<SerOracleInfo> {
-        let mut infos = Vec::new();
-        for contract_info in &offered_contract.contract_info {
-            let announcements = &contract_info.oracle_announcements;
-            if announcements.len() == 1 {
-                infos.push(SerOracleInfo::Single(SingleOracleInfo {
-                    oracle_announcement: announcements[0].clone(),
-                }));
-            } else {
-                if let ContractDescriptor::Numerical(n) = &contract_info.contract_descriptor {
-                    if let Some(params) = &n.difference_params {
-                        infos.push(SerOracleInfo::Multi(MultiOracleInfo {
-                            threshold: contract_info.threshold as u16,
-                            oracle_announcements: announcements.clone(),
-                            oracle_params: Some(OracleParams {
-                                max_error_exp: params.max_error_exp as u16,
-                                min_fail_exp: params.min_support_exp as u16,
-                                maximize_coverage: params.maximize_coverage,
-                            }),
-                        }));
-                        continue;
-                    }
-                }
-                infos.push(SerOracleInfo::Multi(MultiOracleInfo {
-                    threshold: contract_info.threshold as u16,
-                    oracle_announcements: announcements.clone(),
-                    oracle_params: None,
-                }))
-            }
-        }
+        offered_contract
+            .contract_info
+            .iter()
+            .map(get_ser_oracle_info)
+            .collect()
+    }
+}
 
-        infos
+fn get_ser_oracle_info(contract_info: &ContractInfo) -> SerOracleInfo {
+    let announcements = &contract_info.oracle_announcements;
+    if announcements.len() == 1 {
+        return SerOracleInfo::Single(SingleOracleInfo {
+            oracle_announcement: announcements[0].clone(),
+        });
     }
+
+    SerOracleInfo::Multi(MultiOracleInfo {
+        threshold: contract_info.threshold as u16,
+        oracle_announcements: announcements.clone(),
+        oracle_params: contract_info
+            .contract_descriptor
+            .get_oracle_params()
+            .as_ref()
+            .map(|params| params.into()),
+    })
 }
 
 impl From<&EnumDescriptor> for EnumeratedContractDescriptor {
@@ -306,9 +356,20 @@ impl From<&NumericalDescriptor> for NumericOutcomeContractDescriptor {
 impl From<&ContractDescriptor> for SerContractDescriptor {
     fn from(descriptor: &ContractDescriptor) -> SerContractDescriptor {
         match descriptor {
-            ContractDescriptor::Enum(e) => {
-                SerContractDescriptor::EnumeratedContractDescriptor(e.into())
-            }
+            ContractDescriptor::Enum(e) => match &e.oracle_outcome_mappings {
+                None => SerContractDescriptor::EnumeratedContractDescriptor(e.into()),
+                Some(mappings) => SerContractDescriptor::MappedEnumeratedContractDescriptor(
+                    MappedEnumeratedContractDescriptor {
+                        payouts: EnumeratedContractDescriptor::from(e).payouts,
+                        oracle_outcome_mappings: mappings
+                            .iter()
+                            .map(|x| OracleOutcomeMapping {
+                                outcomes: x.clone(),
+                            })
+                            .collect(),
+                    },
+                ),
+            },
             ContractDescriptor::Numerical(n) => {
                 SerContractDescriptor::NumericOutcomeContractDescriptor(n.into())
             }
@@ -342,6 +403,16 @@ impl From<&PayoutFunction> for SerPayoutFunction {
                             (&h.left_end_point).into(),
                             SerPayoutCurvePiece::HyperbolaPayoutCurvePiece(h.into()),
                         ),
+                        // The specification has no linear piece, so it is sent as a
+                        // polynomial piece without intermediate points.
+                        PayoutFunctionPiece::LinearPayoutCurvePiece(l) => (
+                            (&l.left_end_point).into(),
+                            SerPayoutCurvePiece::PolynomialPayoutCurvePiece(
+                                SerPolynomialPayoutCurvePiece {
+                                    payout_points: Vec::new(),
+                                },
+                            ),
+                        ),
                     };
                     SerPayoutFunctionPiece {
                         end_point: left,
@@ -349,17 +420,12 @@ impl From<&PayoutFunction> for SerPayoutFunction {
                     }
                 })
                 .collect(),
-            last_endpoint: {
-                let last_piece = payout_function.payout_function_pieces.last().unwrap();
-                match last_piece {
-                    PayoutFunctionPiece::PolynomialPayoutCurvePiece(p) => {
-                        p.payout_points.last().unwrap().into()
-                    }
-                    PayoutFunctionPiece::HyperbolaPayoutCurvePiece(h) => {
-                        (&h.right_end_point).into()
-                    }
-                }
-            },
+            last_endpoint: payout_function
+                .payout_function_pieces
+                .last()
+                .unwrap()
+                .get_last_point()
+                .into(),
         }
     }
 }
@@ -0,0 +1,183 @@
+//! Encryption at rest of the values persisted by storage providers.
+//!
+//! Encryption is implemented by each storage provider using the [`Cipher`] of
+//! this module, rather than by a wrapper encrypting the values given to any
+//! [`crate::Storage`] implementation: the providers filter and query records by
+//! properties which such a wrapper would have to either leave in plaintext or
+//! hide from them, and only they know how to bind an encrypted value to the
+//! location it is stored at and how to re-encrypt the stored values when the key
+//! is rotated.
+
+use crate::error::Error;
+use bitcoin::hashes::{sha256, Hash, HashEngine, Hmac, HmacEngine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use zeroize::Zeroize;
+
+const NONCE_LEN: usize = 24;
+/// Used to derive the key of [`Cipher::hash`] from the encryption key.
+const HASH_KEY_TAG: &[u8] = b"dlc-manager/encryption/hash-key";
+
+/// A 256 bit key used to encrypt the values persisted by storage providers using
+/// XChaCha20-Poly1305. It is up to the application to generate and keep it safe.
+/// The key is wiped from memory when dropped, but copies of the bytes it was
+/// created from are left to the application to wipe.
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+    /// Creates a new key from the given bytes.
+    pub fn new(key: [u8; 32]) -> Self {
+        EncryptionKey(key)
+    }
+}
+
+impl Drop for EncryptionKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl From<[u8; 32]> for EncryptionKey {
+    fn from(key: [u8; 32]) -> Self {
+        EncryptionKey::new(key)
+    }
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+/// Encrypts and authenticates values with an [`EncryptionKey`]. The keys derived
+/// from it are wiped from memory when the cipher is dropped.
+pub struct Cipher {
+    cipher: XChaCha20Poly1305,
+    hash_key: [u8; 32],
+}
+
+impl Cipher {
+    /// Creates a cipher using the given key.
+    pub fn new(key: &EncryptionKey) -> Self {
+        Cipher {
+            cipher: XChaCha20Poly1305::new(Key::from_slice(&key.0)),
+            hash_key: hmac(&key.0, HASH_KEY_TAG),
+        }
+    }
+
+    /// Returns a keyed hash of `data`, with which values can be looked up by `data`
+    /// without storing it in plaintext. The hash is deterministic, so equal data
+    /// can be told apart from different data without knowing the key.
+    pub fn hash(&self, data: &[u8]) -> [u8; 32] {
+        hmac(&self.hash_key, data)
+    }
+
+    /// Encrypts `plaintext`, authenticating `aad` along with it, and returns the
+    /// random nonce used followed by the cipher text.
+    pub fn encrypt(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut cipher_text = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| Error::StorageError("Error encrypting data".to_string()))?;
+        let mut res = Vec::with_capacity(NONCE_LEN + cipher_text.len());
+        res.extend_from_slice(&nonce);
+        res.append(&mut cipher_text);
+        Ok(res)
+    }
+
+    /// Decrypts data produced by [`Cipher::encrypt`] with the same `aad`.
+    pub fn decrypt(&self, aad: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+        if data.len() < NONCE_LEN {
+            return Err(Error::StorageError(
+                "Encrypted record is too short".to_string(),
+            ));
+        }
+        let (nonce, cipher_text) = data.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: cipher_text,
+                    aad,
+                },
+            )
+            .map_err(|_| {
+                Error::StorageError(
+                    "Error decrypting data, the encryption key is invalid or the data is corrupted"
+                        .to_string(),
+                )
+            })
+    }
+}
+
+impl Drop for Cipher {
+    fn drop(&mut self) {
+        self.hash_key.zeroize();
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut engine = HmacEngine::<sha256::Hash>::new(key);
+    engine.input(data);
+    Hmac::<sha256::Hash>::from_engine(engine).into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decrypt_encrypted_data_roundtrip() {
+        let cipher = Cipher::new(&EncryptionKey::new([1; 32]));
+        let encrypted = cipher.encrypt(&[1, 2], b"some data").unwrap();
+
+        assert_eq!(
+            b"some data".to_vec(),
+            cipher.decrypt(&[1, 2], &encrypted).unwrap()
+        );
+    }
+
+    #[test]
+    fn encrypt_uses_different_nonces() {
+        let cipher = Cipher::new(&EncryptionKey::new([1; 32]));
+
+        assert_ne!(
+            cipher.encrypt(&[], b"some data").unwrap(),
+            cipher.encrypt(&[], b"some data").unwrap()
+        );
+    }
+
+    #[test]
+    fn decrypt_with_other_key_fails() {
+        let cipher = Cipher::new(&EncryptionKey::new([1; 32]));
+        let other = Cipher::new(&EncryptionKey::new([2; 32]));
+        let encrypted = cipher.encrypt(&[], b"some data").unwrap();
+
+        assert!(other.decrypt(&[], &encrypted).is_err());
+    }
+
+    #[test]
+    fn decrypt_with_other_aad_fails() {
+        let cipher = Cipher::new(&EncryptionKey::new([1; 32]));
+        let encrypted = cipher.encrypt(&[1], b"some data").unwrap();
+
+        assert!(cipher.decrypt(&[2], &encrypted).is_err());
+    }
+
+    #[test]
+    fn hash_depends_on_key() {
+        let cipher = Cipher::new(&EncryptionKey::new([1; 32]));
+        let other = Cipher::new(&EncryptionKey::new([2; 32]));
+
+        assert_eq!(cipher.hash(b"some data"), cipher.hash(b"some data"));
+        assert_ne!(cipher.hash(b"some data"), cipher.hash(b"other data"));
+        assert_ne!(cipher.hash(b"some data"), other.hash(b"some data"));
+    }
+}
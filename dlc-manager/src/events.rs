@@ -0,0 +1,136 @@
+//! # Events emitted by the [`crate::manager::Manager`] and the
+//! [`crate::sub_channel_manager::SubChannelManager`] to notify the application
+//! about state transitions of contracts and channels, so that it does not have
+//! to poll the store to find them out.
+
+use bitcoin::Txid;
+use dlc_messages::CloseOffer;
+use lightning::ln::ChannelId;
+use secp256k1_zkp::PublicKey;
+
+use crate::contract::Contract;
+use crate::{ContractId, DlcChannelId};
+
+/// An event generated when a contract or channel moves to a new state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The fund transaction of a contract reached the required number of
+    /// confirmations.
+    ContractConfirmed {
+        /// The id of the contract.
+        contract_id: ContractId,
+    },
+    /// A CET, or a transaction collaboratively closing an on-chain contract,
+    /// was broadcast to close a contract.
+    CetBroadcast {
+        /// The id of the contract.
+        contract_id: ContractId,
+        /// The id of the broadcast transaction.
+        txid: Txid,
+    },
+    /// The counter party of an on-chain contract offered to close it
+    /// collaboratively. The offer can be accepted with
+    /// [`crate::manager::Manager::accept_contract_close`] until its expiry.
+    ContractCloseOffered {
+        /// The id of the contract.
+        contract_id: ContractId,
+        /// The id of the counter party that sent the offer.
+        counter_party: PublicKey,
+        /// The received offer.
+        close_offer: CloseOffer,
+    },
+    /// A contract was closed, either through a confirmed CET or collaboratively.
+    ContractClosed {
+        /// The id of the contract.
+        contract_id: ContractId,
+        /// The profit and loss of the local party for the contract.
+        pnl: i64,
+    },
+    /// The refund transaction of a contract reached the required number of
+    /// confirmations.
+    ContractRefunded {
+        /// The id of the contract.
+        contract_id: ContractId,
+    },
+    /// A DLC channel was set up and its fund transaction signed.
+    ChannelEstablished {
+        /// The id of the channel.
+        channel_id: DlcChannelId,
+    },
+    /// The balance of a DLC channel was settled.
+    ChannelSettled {
+        /// The id of the channel.
+        channel_id: DlcChannelId,
+        /// The amount held by the local party after the settlement.
+        own_payout: u64,
+    },
+    /// A DLC channel was closed.
+    ChannelClosed {
+        /// The id of the channel.
+        channel_id: DlcChannelId,
+        /// The id of the transaction that closed the channel.
+        closing_txid: Txid,
+    },
+    /// The counter party broadcast a revoked transaction and was punished by
+    /// the local party.
+    ChannelPunished {
+        /// The id of the channel.
+        channel_id: DlcChannelId,
+        /// The id of the punishment transaction.
+        punish_txid: Txid,
+    },
+    /// A DLC channel embedded within a Lightning channel was fully set up.
+    SubChannelSigned {
+        /// The id of the Lightning channel.
+        channel_id: ChannelId,
+    },
+    /// A DLC channel embedded within a Lightning channel was closed off-chain.
+    SubChannelClosed {
+        /// The id of the Lightning channel.
+        channel_id: ChannelId,
+    },
+    /// The counter party broadcast a revoked split transaction and was
+    /// punished by the local party.
+    SubChannelPunished {
+        /// The id of the Lightning channel.
+        channel_id: ChannelId,
+        /// The id of the punishment transaction.
+        punish_txid: Txid,
+    },
+}
+
+impl Event {
+    /// Returns the [`Event`] corresponding to the contract having transitioned
+    /// to its current state, if any.
+    pub(crate) fn from_contract(contract: &Contract) -> Option<Event> {
+        match contract {
+            Contract::PreClosed(c) => Some(Event::CetBroadcast {
+                contract_id: c.signed_contract.accepted_contract.get_contract_id(),
+                txid: c.signed_cet.txid(),
+            }),
+            Contract::Closed(c) => Some(Event::ContractClosed {
+                contract_id: c.contract_id,
+                pnl: c.pnl,
+            }),
+            Contract::Refunded(c) => Some(Event::ContractRefunded {
+                contract_id: c.accepted_contract.get_contract_id(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Trait to be implemented by the application to handle [`Event`]s.
+pub trait EventHandler {
+    /// Handles the given [`Event`].
+    fn handle_event(&self, event: Event);
+}
+
+impl<F> EventHandler for F
+where
+    F: Fn(Event),
+{
+    fn handle_event(&self, event: Event) {
+        self(event)
+    }
+}
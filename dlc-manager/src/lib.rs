@@ -1,5 +1,10 @@
 //! # Library providing data structures and functions supporting the execution
 //! and management of DLC.
+//!
+//! The `async` feature adds the `AsyncManager`, for applications whose wallet,
+//! blockchain, oracle and storage access is asynchronous. It only handles
+//! on-chain contracts: DLC channels, sub channels and the fee bumping of
+//! unconfirmed transactions require the synchronous [`manager::Manager`].
 
 #![crate_name = "dlc_manager"]
 // Coding conventions
@@ -17,36 +22,52 @@ extern crate bitcoin;
 extern crate dlc;
 #[macro_use]
 extern crate dlc_messages;
+#[cfg(feature = "encryption")]
+extern crate chacha20poly1305;
 extern crate core;
 extern crate dlc_trie;
 extern crate lightning;
 extern crate log;
 #[cfg(feature = "fuzztarget")]
 extern crate rand_chacha;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 extern crate secp256k1_zkp;
+#[cfg(feature = "encryption")]
+extern crate zeroize;
 
 #[macro_use]
 mod utils;
 
+#[cfg(feature = "async")]
+pub mod async_manager;
 pub mod chain_monitor;
 pub mod channel;
 pub mod channel_updater;
 pub mod contract;
 pub mod contract_updater;
 mod conversion_utils;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub mod error;
+pub mod events;
 pub mod manager;
 pub mod payout_curve;
+pub mod query;
 pub mod sub_channel_manager;
 pub mod subchannel;
 
+#[cfg(feature = "async")]
+use async_trait::async_trait;
 use bitcoin::psbt::PartiallySignedTransaction;
 use bitcoin::{Address, Block, OutPoint, Script, Transaction, TxOut, Txid};
 use chain_monitor::ChainMonitor;
 use channel::offered_channel::OfferedChannel;
 use channel::signed_channel::{SignedChannel, SignedChannelStateType};
 use channel::{Channel, SettledClosingChannel};
-use contract::PreClosedContract;
+use contract::adaptor_point_cache::CachedAdaptorPoint;
+use contract::ser::VersionedSerializable;
+use contract::{PreClosedContract, PreRefundedContract};
 use contract::{offered_contract::OfferedContract, signed_contract::SignedContract, Contract};
 use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation};
 use dlc_messages::ser_impls::{read_address, write_address};
@@ -54,8 +75,11 @@ use error::Error;
 use lightning::ln::msgs::DecodeError;
 use lightning::ln::ChannelId;
 use lightning::util::ser::{Readable, Writeable, Writer};
+use query::{ChannelQuery, ContractQuery, Page};
 use secp256k1_zkp::XOnlyPublicKey;
 use secp256k1_zkp::{PublicKey, SecretKey};
+#[cfg(feature = "async")]
+use std::ops::Deref;
 use sub_channel_manager::Action;
 use subchannel::SubChannel;
 
@@ -165,6 +189,22 @@ pub trait Storage {
     /// Returns the set of contracts whos broadcasted cet has not been verified to be confirmed on
     /// blockchain
     fn get_preclosed_contracts(&self) -> Result<Vec<PreClosedContract>, Error>;
+    /// Returns the set of contracts whose broadcast refund transaction has not been
+    /// verified to be confirmed on blockchain. The default implementation filters
+    /// the result of [`Storage::get_contracts`].
+    fn get_prerefunded_contracts(&self) -> Result<Vec<PreRefundedContract>, Error> {
+        Ok(self
+            .get_contracts()?
+            .into_iter()
+            .filter_map(|x| match x {
+                Contract::PreRefunded(c) => Some(c),
+                _ => None,
+            })
+            .collect())
+    }
+    /// Returns the contracts matching the given query, ordered by the time at which
+    /// they were first stored.
+    fn query_contracts(&self, query: &ContractQuery) -> Result<Page<Contract>, Error>;
     /// Update the state of the channel and optionally its associated contract
     /// atomically.
     fn upsert_channel(&self, channel: Channel, contract: Option<Contract>) -> Result<(), Error>;
@@ -174,6 +214,9 @@ pub trait Storage {
     fn get_channel(&self, channel_id: &DlcChannelId) -> Result<Option<Channel>, Error>;
     /// Returns all channels in the store.
     fn get_channels(&self) -> Result<Vec<Channel>, Error>;
+    /// Returns the channels matching the given query, ordered by the time at which
+    /// they were first stored.
+    fn query_channels(&self, query: &ChannelQuery) -> Result<Page<Channel>, Error>;
     /// Returns the set of [`SignedChannel`] in the store. Returns only the one
     /// with matching `channel_state` if set.
     fn get_signed_channels(
@@ -188,6 +231,38 @@ pub trait Storage {
     fn persist_chain_monitor(&self, monitor: &ChainMonitor) -> Result<(), Error>;
     /// Returns the latest [`ChainMonitor`] in the store if any.
     fn get_chain_monitor(&self) -> Result<Option<ChainMonitor>, Error>;
+    /// Writes the given points of the adaptor point cache to the store,
+    /// replacing the ones with the same key. The default implementation does
+    /// not persist anything, in which case the points are recomputed when
+    /// needed after a restart.
+    fn upsert_adaptor_points(&self, _points: &[CachedAdaptorPoint]) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Returns all the adaptor points in the store.
+    fn get_adaptor_points(&self) -> Result<Vec<CachedAdaptorPoint>, Error> {
+        Ok(Vec::new())
+    }
+    /// Deletes the adaptor points with the given keys from the store, once the
+    /// contracts they were computed for are closed.
+    fn delete_adaptor_points(&self, _keys: &[[u8; 32]]) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Records that the unconfirmed transaction with the given id was fee bumped
+    /// using the given child transaction, replacing any previous record. The
+    /// default implementation does not persist anything, in which case the
+    /// [`crate::manager::Manager`] tries to bump the fee again after a restart.
+    fn upsert_fee_bump(&self, _txid: &Txid, _fee_bump: &FeeBump) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Returns the latest fee bump of the transaction with the given id, if any.
+    fn get_fee_bump(&self, _txid: &Txid) -> Result<Option<FeeBump>, Error> {
+        Ok(None)
+    }
+    /// Deletes the record of the fee bump of the transaction with the given id if
+    /// any, once it does not need to be bumped anymore.
+    fn delete_fee_bump(&self, _txid: &Txid) -> Result<(), Error> {
+        Ok(())
+    }
     /// Creates or updates a [`SubChannel`].
     fn upsert_sub_channel(&self, subchannel: &SubChannel) -> Result<(), Error>;
     /// Returns the [`SubChannel`] with given [`ChannelId`] if it exists.
@@ -212,6 +287,322 @@ pub trait Oracle {
     fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, Error>;
 }
 
+/// Asynchronous version of the [`Wallet`] trait, used by the
+/// [`async_manager::AsyncManager`]. Synchronous wallets can be used through a
+/// [`SyncProvider`].
+#[cfg(feature = "async")]
+#[async_trait]
+pub trait AsyncWallet: Signer + Send + Sync {
+    /// Returns a new (unused) address.
+    async fn get_new_address(&self) -> Result<Address, Error>;
+    /// Generate a new secret key and store it in the wallet so that it can later
+    /// be retrieved.
+    async fn get_new_secret_key(&self) -> Result<SecretKey, Error>;
+    /// Get a set of UTXOs to fund the given amount, see
+    /// [`Wallet::get_utxos_for_amount`].
+    async fn get_utxos_for_amount(
+        &self,
+        amount: u64,
+        fee_rate: Option<u64>,
+        base_weight_wu: u64,
+        lock_utxos: bool,
+    ) -> Result<Vec<Utxo>, Error>;
+    /// Import the provided address.
+    async fn import_address(&self, address: &Address) -> Result<(), Error>;
+    /// Unlock reserved utxo
+    async fn unreserve_utxos(&self, outpoints: &[OutPoint]) -> Result<(), Error>;
+}
+
+/// Asynchronous version of the [`Blockchain`] trait, used by the
+/// [`async_manager::AsyncManager`]. Synchronous blockchain providers can be
+/// used through a [`SyncProvider`].
+#[cfg(feature = "async")]
+#[async_trait]
+pub trait AsyncBlockchain: Send + Sync {
+    /// Broadcast the given transaction to the bitcoin network.
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<(), Error>;
+    /// Returns the network currently used (mainnet, testnet or regtest).
+    async fn get_network(&self) -> Result<bitcoin::network::constants::Network, Error>;
+    /// Returns the height of the blockchain
+    async fn get_blockchain_height(&self) -> Result<u64, Error>;
+    /// Returns the block at given height
+    async fn get_block_at_height(&self, height: u64) -> Result<Block, Error>;
+    /// Get the transaction with given id.
+    async fn get_transaction(&self, tx_id: &Txid) -> Result<Transaction, Error>;
+    /// Get the number of confirmations for the transaction with given id.
+    async fn get_transaction_confirmations(&self, tx_id: &Txid) -> Result<u32, Error>;
+    /// Get the number of confirmations for the given transaction output.
+    ///
+    /// Also returns the [`Txid`] of the transaction where the transaction output is used as an
+    /// input.
+    async fn get_txo_confirmations(&self, txo: &OutPoint) -> Result<Option<(u32, Txid)>, Error>;
+}
+
+/// Asynchronous version of the contract related functionalities of the
+/// [`Storage`] trait, used by the [`async_manager::AsyncManager`]. Synchronous
+/// storages can be used through a [`SyncProvider`].
+#[cfg(feature = "async")]
+#[async_trait]
+pub trait AsyncStorage: Send + Sync {
+    /// Returns the contract with given id if found.
+    async fn get_contract(&self, id: &ContractId) -> Result<Option<Contract>, Error>;
+    /// Return all contracts
+    async fn get_contracts(&self) -> Result<Vec<Contract>, Error>;
+    /// Create a record for the given contract.
+    async fn create_contract(&self, contract: &OfferedContract) -> Result<(), Error>;
+    /// Delete the record for the contract with the given id.
+    async fn delete_contract(&self, id: &ContractId) -> Result<(), Error>;
+    /// Update the given contract.
+    async fn update_contract(&self, contract: &Contract) -> Result<(), Error>;
+    /// Returns the set of contracts in offered state.
+    async fn get_contract_offers(&self) -> Result<Vec<OfferedContract>, Error>;
+    /// Returns the set of contracts in signed state.
+    async fn get_signed_contracts(&self) -> Result<Vec<SignedContract>, Error>;
+    /// Returns the set of confirmed contracts.
+    async fn get_confirmed_contracts(&self) -> Result<Vec<SignedContract>, Error>;
+    /// Returns the set of contracts whos broadcasted cet has not been verified to be confirmed on
+    /// blockchain
+    async fn get_preclosed_contracts(&self) -> Result<Vec<PreClosedContract>, Error>;
+    /// Returns the set of contracts whose broadcast refund transaction has not been
+    /// verified to be confirmed on blockchain.
+    async fn get_prerefunded_contracts(&self) -> Result<Vec<PreRefundedContract>, Error>;
+    /// Writes the given points of the adaptor point cache to the store,
+    /// replacing the ones with the same key. The default implementation does
+    /// not persist anything.
+    async fn upsert_adaptor_points(&self, _points: &[CachedAdaptorPoint]) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Returns all the adaptor points in the store.
+    async fn get_adaptor_points(&self) -> Result<Vec<CachedAdaptorPoint>, Error> {
+        Ok(Vec::new())
+    }
+    /// Deletes the adaptor points with the given keys from the store.
+    async fn delete_adaptor_points(&self, _keys: &[[u8; 32]]) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Returns the contracts matching the given query, ordered by the time at which
+    /// they were first stored.
+    async fn query_contracts(&self, query: &ContractQuery) -> Result<Page<Contract>, Error>;
+    /// Writes the [`ChainMonitor`] data to the store.
+    async fn persist_chain_monitor(&self, monitor: &ChainMonitor) -> Result<(), Error>;
+    /// Returns the latest [`ChainMonitor`] in the store if any.
+    async fn get_chain_monitor(&self) -> Result<Option<ChainMonitor>, Error>;
+}
+
+/// Asynchronous version of the [`Oracle`] trait, used by the
+/// [`async_manager::AsyncManager`]. Synchronous oracle clients can be used
+/// through a [`SyncProvider`].
+#[cfg(feature = "async")]
+#[async_trait]
+pub trait AsyncOracle: Send + Sync {
+    /// Returns the public key of the oracle.
+    fn get_public_key(&self) -> XOnlyPublicKey;
+    /// Returns the announcement for the event with the given id if found.
+    async fn get_announcement(&self, event_id: &str) -> Result<OracleAnnouncement, Error>;
+    /// Returns the attestation for the event with the given id if found.
+    async fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, Error>;
+}
+
+/// Makes a synchronous [`Wallet`], [`Blockchain`], [`Storage`] or [`Oracle`]
+/// usable where the corresponding asynchronous trait is required. The wrapped
+/// provider is called directly from the returned futures, which therefore
+/// block the executor until the call completes. This is only suitable for
+/// providers that do not perform network I/O, such as in memory or embedded
+/// storages and wallets; network based providers should implement the
+/// asynchronous traits natively. A wrapper is used rather than blanket
+/// implementations of the asynchronous traits so that blocking providers are
+/// opted in explicitly.
+#[cfg(feature = "async")]
+pub struct SyncProvider<P>(pub P);
+
+#[cfg(feature = "async")]
+impl<P: Deref> Signer for SyncProvider<P>
+where
+    P::Target: Signer,
+{
+    fn sign_psbt_input(
+        &self,
+        psbt: &mut PartiallySignedTransaction,
+        input_index: usize,
+    ) -> Result<(), Error> {
+        self.0.sign_psbt_input(psbt, input_index)
+    }
+
+    fn get_secret_key_for_pubkey(&self, pubkey: &PublicKey) -> Result<SecretKey, Error> {
+        self.0.get_secret_key_for_pubkey(pubkey)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl<P: Deref + Send + Sync> AsyncWallet for SyncProvider<P>
+where
+    P::Target: Wallet,
+{
+    async fn get_new_address(&self) -> Result<Address, Error> {
+        self.0.get_new_address()
+    }
+
+    async fn get_new_secret_key(&self) -> Result<SecretKey, Error> {
+        self.0.get_new_secret_key()
+    }
+
+    async fn get_utxos_for_amount(
+        &self,
+        amount: u64,
+        fee_rate: Option<u64>,
+        base_weight_wu: u64,
+        lock_utxos: bool,
+    ) -> Result<Vec<Utxo>, Error> {
+        self.0
+            .get_utxos_for_amount(amount, fee_rate, base_weight_wu, lock_utxos)
+    }
+
+    async fn import_address(&self, address: &Address) -> Result<(), Error> {
+        self.0.import_address(address)
+    }
+
+    async fn unreserve_utxos(&self, outpoints: &[OutPoint]) -> Result<(), Error> {
+        self.0.unreserve_utxos(outpoints)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl<P: Deref + Send + Sync> AsyncBlockchain for SyncProvider<P>
+where
+    P::Target: Blockchain,
+{
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<(), Error> {
+        self.0.send_transaction(transaction)
+    }
+
+    async fn get_network(&self) -> Result<bitcoin::network::constants::Network, Error> {
+        self.0.get_network()
+    }
+
+    async fn get_blockchain_height(&self) -> Result<u64, Error> {
+        self.0.get_blockchain_height()
+    }
+
+    async fn get_block_at_height(&self, height: u64) -> Result<Block, Error> {
+        self.0.get_block_at_height(height)
+    }
+
+    async fn get_transaction(&self, tx_id: &Txid) -> Result<Transaction, Error> {
+        self.0.get_transaction(tx_id)
+    }
+
+    async fn get_transaction_confirmations(&self, tx_id: &Txid) -> Result<u32, Error> {
+        self.0.get_transaction_confirmations(tx_id)
+    }
+
+    async fn get_txo_confirmations(&self, txo: &OutPoint) -> Result<Option<(u32, Txid)>, Error> {
+        self.0.get_txo_confirmations(txo)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl<P: Deref + Send + Sync> AsyncStorage for SyncProvider<P>
+where
+    P::Target: Storage,
+{
+    async fn get_contract(&self, id: &ContractId) -> Result<Option<Contract>, Error> {
+        self.0.get_contract(id)
+    }
+
+    async fn get_contracts(&self) -> Result<Vec<Contract>, Error> {
+        self.0.get_contracts()
+    }
+
+    async fn create_contract(&self, contract: &OfferedContract) -> Result<(), Error> {
+        self.0.create_contract(contract)
+    }
+
+    async fn delete_contract(&self, id: &ContractId) -> Result<(), Error> {
+        self.0.delete_contract(id)
+    }
+
+    async fn update_contract(&self, contract: &Contract) -> Result<(), Error> {
+        self.0.update_contract(contract)
+    }
+
+    async fn get_contract_offers(&self) -> Result<Vec<OfferedContract>, Error> {
+        self.0.get_contract_offers()
+    }
+
+    async fn get_signed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
+        self.0.get_signed_contracts()
+    }
+
+    async fn get_confirmed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
+        self.0.get_confirmed_contracts()
+    }
+
+    async fn get_preclosed_contracts(&self) -> Result<Vec<PreClosedContract>, Error> {
+        self.0.get_preclosed_contracts()
+    }
+
+    async fn get_prerefunded_contracts(&self) -> Result<Vec<PreRefundedContract>, Error> {
+        self.0.get_prerefunded_contracts()
+    }
+
+    async fn query_contracts(&self, query: &ContractQuery) -> Result<Page<Contract>, Error> {
+        self.0.query_contracts(query)
+    }
+
+    async fn persist_chain_monitor(&self, monitor: &ChainMonitor) -> Result<(), Error> {
+        self.0.persist_chain_monitor(monitor)
+    }
+
+    async fn get_chain_monitor(&self) -> Result<Option<ChainMonitor>, Error> {
+        self.0.get_chain_monitor()
+    }
+
+    async fn upsert_adaptor_points(&self, points: &[CachedAdaptorPoint]) -> Result<(), Error> {
+        self.0.upsert_adaptor_points(points)
+    }
+
+    async fn get_adaptor_points(&self) -> Result<Vec<CachedAdaptorPoint>, Error> {
+        self.0.get_adaptor_points()
+    }
+
+    async fn delete_adaptor_points(&self, keys: &[[u8; 32]]) -> Result<(), Error> {
+        self.0.delete_adaptor_points(keys)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait]
+impl<P: Deref + Send + Sync> AsyncOracle for SyncProvider<P>
+where
+    P::Target: Oracle,
+{
+    fn get_public_key(&self) -> XOnlyPublicKey {
+        self.0.get_public_key()
+    }
+
+    async fn get_announcement(&self, event_id: &str) -> Result<OracleAnnouncement, Error> {
+        self.0.get_announcement(event_id)
+    }
+
+    async fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, Error> {
+        self.0.get_attestation(event_id)
+    }
+}
+
+/// A child transaction bumping the fee of an unconfirmed transaction (CPFP).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeBump {
+    /// The fee rate (in satoshis per virtual byte) paid by the transaction and
+    /// the child together.
+    pub fee_rate: u64,
+    /// The id of the child transaction, which is replaced if the fee needs to be
+    /// bumped again.
+    pub child_txid: Txid,
+}
+
 /// Represents a UTXO.
 #[derive(Clone, Debug)]
 pub struct Utxo {
@@ -235,3 +626,5 @@ impl_dlc_writeable!(Utxo, {
     (redeem_script, writeable),
     (reserved, writeable)
 });
+
+impl VersionedSerializable for Utxo {}
@@ -8,22 +8,26 @@ use crate::channel::{Channel, ClosedChannel, ClosedPunishedChannel, SettledClosi
 use crate::channel_updater::{get_unix_time_now, verify_signed_channel};
 use crate::channel_updater::{self, get_signed_channel_state};
 use crate::contract::{
-    accepted_contract::AcceptedContract, contract_info::ContractInfo,
+    accepted_contract::AcceptedContract,
+    adaptor_point_cache::{get_announcements_hashes, AdaptorPointCache},
+    contract_info::ContractInfo,
     contract_input::ContractInput, contract_input::OracleInput, offered_contract::OfferedContract,
-    signed_contract::SignedContract, AdaptorInfo, ClosedContract, Contract, FailedAcceptContract,
-    FailedSignContract, PreClosedContract,
+    signed_contract::SignedContract, ClosedContract, Contract, FailedAcceptContract,
+    FailedSignContract, PreClosedContract, PreRefundedContract,
 };
-use crate::contract_updater::{accept_contract, verify_accepted_and_sign_contract};
+use crate::contract_updater::{accept_contract, verify_accepted_and_sign_contract, FundingSpend};
 use crate::error::Error;
+use crate::events::{Event, EventHandler};
 use crate::sub_channel_manager::get_sub_channel_in_state;
 use crate::subchannel::{ClosingSubChannel, SubChannel, SubChannelState};
-use crate::utils::get_object_in_state;
-use crate::{ContractId, DlcChannelId, ReferenceId, Signer};
+use crate::utils::{get_object_in_state, VBYTES_PER_KILO_WEIGHT};
+use crate::{ContractId, DlcChannelId, FeeBump, ReferenceId, Signer};
 use bitcoin::consensus::encode::serialize_hex;
-use bitcoin::{Address, OutPoint, Txid};
+use bitcoin::psbt::PartiallySignedTransaction;
+use bitcoin::{Address, OutPoint, Script, Txid};
 use bitcoin::Transaction;
 use bitcoin::hashes::hex::ToHex;
-use dlc::FeeConfig;
+use dlc::{FeeConfig, PartyParams};
 use dlc_messages::channel::{
     AcceptChannel, CollaborativeCloseOffer, OfferChannel, Reject, RenewAccept, RenewConfirm,
     RenewFinalize, RenewOffer, RenewRevoke, SettleAccept, SettleConfirm, SettleFinalize,
@@ -31,7 +35,8 @@ use dlc_messages::channel::{
 };
 use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation};
 use dlc_messages::{
-    AcceptDlc, ChannelMessage, Message as DlcMessage, OfferDlc, OnChainMessage, SignDlc,
+    AcceptDlc, ChannelMessage, CloseAccept, CloseOffer, Message as DlcMessage, OfferDlc,
+    OnChainMessage, SignDlc,
 };
 use lightning::chain::chaininterface::{FeeEstimator, ConfirmationTarget};
 use lightning::ln::chan_utils::{
@@ -41,9 +46,10 @@ use log::{error, warn};
 use secp256k1_zkp::{ecdsa::Signature, All, PublicKey, Secp256k1, SecretKey};
 use secp256k1_zkp::{EcdsaAdaptorSignature, XOnlyPublicKey};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::ops::Deref;
 use std::string::ToString;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use bitcoin::consensus::Decodable;
 use bitcoin::hashes::Hash;
 
@@ -56,12 +62,111 @@ pub const CET_NSEQUENCE: u32 = 288;
 /// Timeout in seconds when waiting for a peer's reply, after which a DLC channel
 /// is forced closed.
 pub const PEER_TIMEOUT: u64 = 3600;
+/// The maximum fee rate (in satoshis per virtual byte) up to which unconfirmed
+/// funding and closing transactions are fee bumped. Fee bumping is disabled by
+/// default.
+pub const MAX_FEE_RATE: u64 = 0;
+
+/// Parameters of a [`Manager`] that can be adjusted depending on the network
+/// it is deployed on. The default values are the ones defined by the constants
+/// of this module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ManagerConfig {
+    /// The number of confirmations required before moving a [`Contract`] to the
+    /// confirmed state, and a closing transaction to be considered final.
+    pub nb_confirmations: u32,
+    /// The delay (in seconds) after the latest event maturity at which the
+    /// refund transaction becomes valid.
+    pub refund_delay: u32,
+    /// The nSequence value used for CETs in DLC channels.
+    pub cet_nsequence: u32,
+    /// Timeout in seconds when waiting for a peer's reply, after which a DLC
+    /// channel is considered timed out and a close offer for an on-chain
+    /// contract expires.
+    pub peer_timeout: u64,
+    /// The maximum fee rate (in satoshis per virtual byte) that an unconfirmed
+    /// funding, CET, refund or collaborative close transaction of an on-chain
+    /// contract is bumped to, using a child transaction spending the local
+    /// party's change or payout output (CPFP). Zero disables fee bumping.
+    /// Channel transactions have no output owned by a single party and cannot
+    /// be bumped this way. Anchor outputs letting either party bump any of the
+    /// transactions are not supported, as they would change the transactions
+    /// that both parties sign and thus require a protocol change.
+    pub max_fee_rate: u64,
+}
+
+impl Default for ManagerConfig {
+    fn default() -> Self {
+        ManagerConfig {
+            nb_confirmations: NB_CONFIRMATIONS,
+            refund_delay: REFUND_DELAY,
+            cet_nsequence: CET_NSEQUENCE,
+            peer_timeout: PEER_TIMEOUT,
+            max_fee_rate: MAX_FEE_RATE,
+        }
+    }
+}
+
+impl ManagerConfig {
+    /// Checks that the parameters can be used by a [`Manager`]. The refund
+    /// delays accepted in offers go up to twice `refund_delay`, which must thus
+    /// not overflow, and `cet_nsequence` must be a non zero relative lock time
+    /// expressed in blocks (BIP 68).
+    pub fn validate(&self) -> Result<(), Error> {
+        self.get_refund_delay_range()?;
+        if self.cet_nsequence == 0 || self.cet_nsequence > u16::MAX as u32 {
+            return Err(Error::InvalidParameters(format!(
+                "Invalid CET nSequence {}, expected a value between 1 and {}",
+                self.cet_nsequence,
+                u16::MAX
+            )));
+        }
+        self.get_cet_nsequence_range()?;
+        Ok(())
+    }
 
-type ClosableContractInfo<'a> = Option<(
-    &'a ContractInfo,
-    &'a AdaptorInfo,
-    Vec<(usize, OracleAttestation)>,
-)>;
+    /// Returns the minimum and maximum refund delays accepted in an offer
+    /// received from a counter party.
+    pub(crate) fn get_refund_delay_range(&self) -> Result<(u32, u32), Error> {
+        let max = self.refund_delay.checked_mul(2).ok_or_else(|| {
+            Error::InvalidParameters(format!("Refund delay {} is too large", self.refund_delay))
+        })?;
+        Ok((self.refund_delay, max))
+    }
+
+    /// Returns the minimum and maximum CET nSequence values accepted in a
+    /// channel offer received from a counter party.
+    pub(crate) fn get_cet_nsequence_range(&self) -> Result<(u32, u32), Error> {
+        let max = self.cet_nsequence.checked_mul(2).ok_or_else(|| {
+            Error::InvalidParameters(format!("CET nSequence {} is too large", self.cet_nsequence))
+        })?;
+        Ok((self.cet_nsequence, max))
+    }
+}
+
+/// Defines how the [`Manager`] reacts when the counter party of a DLC channel
+/// does not reply to a channel update before the state timeout expires.
+#[derive(Clone, Default)]
+pub enum ChannelTimeoutPolicy {
+    /// Only log a warning and leave the channel in its current state.
+    #[default]
+    LogOnly,
+    /// Force close the channel by broadcasting the latest agreed upon state.
+    ForceClose,
+    /// Hand the timed out channel to the application, which decides whether
+    /// the channel should be force closed by returning `true`.
+    Callback(Arc<dyn Fn(&SignedChannel) -> bool + Send + Sync>),
+}
+
+impl std::fmt::Debug for ChannelTimeoutPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelTimeoutPolicy::LogOnly => f.write_str("LogOnly"),
+            ChannelTimeoutPolicy::ForceClose => f.write_str("ForceClose"),
+            ChannelTimeoutPolicy::Callback(_) => f.write_str("Callback"),
+        }
+    }
+}
 
 /// Used to create and update DLCs.
 pub struct Manager<W: Deref, B: Deref, S: Deref, O: Deref, T: Deref, F: Deref>
@@ -79,8 +184,12 @@ where
     store: S,
     secp: Secp256k1<All>,
     chain_monitor: Mutex<ChainMonitor>,
+    adaptor_point_cache: Mutex<AdaptorPointCache>,
     time: T,
     fee_estimator: F,
+    config: ManagerConfig,
+    channel_timeout_policy: ChannelTimeoutPolicy,
+    pending_events: Mutex<Vec<Event>>,
 }
 
 macro_rules! get_contract_in_state {
@@ -132,37 +241,48 @@ macro_rules! check_for_timed_out_channels {
             if let SignedChannelState::$state { timeout, .. } = channel.state {
                 let is_timed_out = timeout < $manager.time.unix_time_now();
                 if is_timed_out {
-                    let _sub_channel: Option<SubChannel> = if channel.is_sub_channel() {
+                    // The receiver of a renew or collaborative close offer is the
+                    // one expected to reply, so only the offerer can time out.
+                    match channel.state {
+                        SignedChannelState::RenewOffered { is_offer: false, .. }
+                        | SignedChannelState::CollaborativeCloseOffered { is_offer: false, .. } => {
+                            continue;
+                        }
+                        _ => {}
+                    }
+
+                    if channel.is_sub_channel() {
                         log::info!(
                             "Skipping force-closure of subchannel {}: not supported",
                             bitcoin::hashes::hex::ToHex::to_hex(&channel.channel_id[..])
                         );
                         continue;
+                    }
 
-                        // TODO: Implement subchannel force closing
-                        // let s = get_sub_channel_in_state!(
-                        //     $manager,
-                        //     channel.channel_id,
-                        //     Signed,
-                        //     None::<PublicKey>
-                        // )?;
-                        // Some(s)
-                    } else {
-                        None
+                    let should_force_close = match &$manager.channel_timeout_policy {
+                        ChannelTimeoutPolicy::LogOnly => {
+                            log::warn!(
+                                "Dlc channel {} timed out in State {:?}. Skipping force-closure as per timeout policy.",
+                                bitcoin::hashes::hex::ToHex::to_hex(&channel.channel_id[..]),
+                                channel.state,
+                            );
+                            false
+                        }
+                        ChannelTimeoutPolicy::ForceClose => true,
+                        ChannelTimeoutPolicy::Callback(callback) => callback(&channel),
                     };
 
-                    log::warn!(
-                            "Dlc channel timed out in State {:?}. Skipping force-closure of dlc channel {} while in beta.",
-                            channel.state,
-                            bitcoin::hashes::hex::ToHex::to_hex(&channel.channel_id[..])
+                    if should_force_close {
+                        log::warn!(
+                            "Force closing channel {} that timed out. {} < {}",
+                            bitcoin::hashes::hex::ToHex::to_hex(&channel.channel_id[..]),
+                            timeout,
+                            $manager.time.unix_time_now()
                         );
-
-                    // TODO(holzeis): Enable that logic once out of beta!
-                    // log::warn!("Force closing channel that timed out. {} < {}", timeout, $manager.time.unix_time_now());
-                    // match $manager.force_close_channel_internal(channel, sub_channel, true) {
-                    //     Err(e) => error!("Error force closing channel {}", e),
-                    //     _ => {}
-                    // }
+                        if let Err(e) = $manager.force_close_channel_internal(channel, None, true, None) {
+                            error!("Error force closing channel {}", e);
+                        }
+                    }
                 }
             }
         }
@@ -178,7 +298,7 @@ where
     T::Target: Time,
     F::Target: FeeEstimator,
 {
-    /// Create a new Manager struct.
+    /// Create a new Manager struct using the default [`ManagerConfig`].
     pub fn new(
         wallet: W,
         blockchain: B,
@@ -187,9 +307,32 @@ where
         time: T,
         fee_estimator: F,
     ) -> Result<Self, Error> {
+        Self::new_with_config(
+            wallet,
+            blockchain,
+            store,
+            oracles,
+            time,
+            fee_estimator,
+            ManagerConfig::default(),
+        )
+    }
+
+    /// Create a new Manager struct using the given [`ManagerConfig`].
+    pub fn new_with_config(
+        wallet: W,
+        blockchain: B,
+        store: S,
+        oracles: HashMap<XOnlyPublicKey, O>,
+        time: T,
+        fee_estimator: F,
+        config: ManagerConfig,
+    ) -> Result<Self, Error> {
+        config.validate()?;
         let chain_monitor = store
             .get_chain_monitor()?
             .unwrap_or(ChainMonitor::new(blockchain.get_blockchain_height()?));
+        let adaptor_point_cache = AdaptorPointCache::from_points(store.get_adaptor_points()?);
 
         Ok(Manager {
             secp: secp256k1_zkp::Secp256k1::new(),
@@ -199,10 +342,73 @@ where
             time,
             fee_estimator,
             chain_monitor: Mutex::new(chain_monitor),
+            adaptor_point_cache: Mutex::new(adaptor_point_cache),
             blockchain,
+            config,
+            channel_timeout_policy: ChannelTimeoutPolicy::default(),
+            pending_events: Mutex::new(Vec::new()),
         })
     }
 
+    /// Set the [`ChannelTimeoutPolicy`] applied to DLC channels whose counter
+    /// party did not reply in time. Defaults to [`ChannelTimeoutPolicy::LogOnly`].
+    pub fn set_channel_timeout_policy(&mut self, policy: ChannelTimeoutPolicy) {
+        self.channel_timeout_policy = policy;
+    }
+
+    /// Calls `f` with the cache of adaptor points and persists the points that
+    /// `f` added to it. Failing to persist the points is only logged, as they
+    /// can always be recomputed.
+    pub(crate) fn with_adaptor_point_cache<R>(
+        &self,
+        f: impl FnOnce(&mut AdaptorPointCache) -> R,
+    ) -> R {
+        let mut adaptor_point_cache = self.adaptor_point_cache.lock().unwrap();
+        let res = f(&mut adaptor_point_cache);
+        let new_points = adaptor_point_cache.take_new_points();
+        if !new_points.is_empty() {
+            if let Err(e) = self.store.upsert_adaptor_points(&new_points) {
+                warn!("Could not persist the adaptor point cache: {}", e);
+            }
+        }
+        res
+    }
+
+    /// Removes the adaptor points that were computed for contracts that are
+    /// now closed from the cache and from the store. Points only used by
+    /// contracts in accepted state are recomputed if needed.
+    fn prune_adaptor_point_cache(&self) -> Result<(), Error> {
+        if self.adaptor_point_cache.lock().unwrap().is_empty() {
+            return Ok(());
+        }
+
+        let offered_contracts = self.store.get_contract_offers()?;
+        let signed_contracts = self.store.get_signed_contracts()?;
+        let confirmed_contracts = self.store.get_confirmed_contracts()?;
+        let live_announcements = get_announcements_hashes(
+            offered_contracts
+                .iter()
+                .chain(
+                    signed_contracts
+                        .iter()
+                        .chain(confirmed_contracts.iter())
+                        .map(|c| &c.accepted_contract.offered_contract),
+                )
+                .flat_map(|c| c.contract_info.iter()),
+        );
+
+        let pruned = self
+            .adaptor_point_cache
+            .lock()
+            .unwrap()
+            .prune(&live_announcements);
+        if !pruned.is_empty() {
+            self.store.delete_adaptor_points(&pruned)?;
+        }
+
+        Ok(())
+    }
+
     /// Get the store from the Manager to access contracts.
     pub fn get_store(&self) -> &S {
         &self.store
@@ -228,6 +434,56 @@ where
         &self.secp
     }
 
+    /// Return the [`ManagerConfig`] used by this manager.
+    pub fn get_config(&self) -> &ManagerConfig {
+        &self.config
+    }
+
+    /// Returns the [`Event`]s generated since the last call and clears them.
+    /// Note that pending events are not persisted and are lost if the manager
+    /// is dropped before they are retrieved.
+    pub fn get_and_clear_pending_events(&self) -> Vec<Event> {
+        std::mem::take(&mut *self.pending_events.lock().unwrap())
+    }
+
+    /// Passes all the pending [`Event`]s to the given [`EventHandler`] and
+    /// clears them.
+    pub fn process_pending_events<H: Deref>(&self, handler: H)
+    where
+        H::Target: EventHandler,
+    {
+        for event in self.get_and_clear_pending_events() {
+            handler.handle_event(event);
+        }
+    }
+
+    pub(crate) fn push_event(&self, event: Event) {
+        log::debug!("Generated event {:?}", event);
+        self.pending_events.lock().unwrap().push(event);
+    }
+
+    pub(crate) fn push_contract_event(&self, contract: &Contract) {
+        if let Some(event) = Event::from_contract(contract) {
+            self.push_event(event);
+        }
+    }
+
+    pub(crate) fn push_channel_closed_event(&self, channel: &Channel) {
+        match channel {
+            Channel::Closed(c) | Channel::CounterClosed(c) | Channel::CollaborativelyClosed(c) => {
+                self.push_event(Event::ChannelClosed {
+                    channel_id: c.channel_id,
+                    closing_txid: c.closing_txid,
+                })
+            }
+            Channel::ClosedPunished(c) => self.push_event(Event::ChannelPunished {
+                channel_id: c.channel_id,
+                punish_txid: c.punish_txid,
+            }),
+            _ => {}
+        }
+    }
+
     /// Return the chain monitor used to watch for relevant transactions on chain.
     pub fn get_chain_monitor(&self) -> &Mutex<ChainMonitor> {
         &self.chain_monitor
@@ -250,6 +506,14 @@ where
                     self.on_sign_message(s, &counter_party)?;
                     Ok(None)
                 }
+                OnChainMessage::CloseOffer(c) => {
+                    self.on_close_offer(c, &counter_party)?;
+                    Ok(None)
+                }
+                OnChainMessage::CloseAccept(c) => {
+                    self.on_close_accept(c, &counter_party)?;
+                    Ok(None)
+                }
             },
             DlcMessage::Channel(channel) => match channel {
                 ChannelMessage::Offer(o) => {
@@ -331,7 +595,7 @@ where
             &self.secp,
             contract_input,
             oracle_announcements,
-            REFUND_DELAY,
+            self.config.refund_delay,
             &counter_party,
             &self.wallet,
             &self.blockchain,
@@ -355,12 +619,15 @@ where
 
         let counter_party = offered_contract.counter_party;
 
-        let (accepted_contract, accept_msg) = accept_contract(
-            &self.secp,
-            &offered_contract,
-            &self.wallet,
-            &self.blockchain,
-        )?;
+        let (accepted_contract, accept_msg) = self.with_adaptor_point_cache(|adaptor_point_cache| {
+            accept_contract(
+                &self.secp,
+                adaptor_point_cache,
+                &offered_contract,
+                &self.wallet,
+                &self.blockchain,
+            )
+        })?;
 
         self.wallet.import_address(&Address::p2wsh(
             &accepted_contract.dlc_transactions.funding_script_pubkey,
@@ -375,14 +642,101 @@ where
         Ok((contract_id, counter_party, accept_msg))
     }
 
+    /// Returns a [`CloseOffer`] message to be sent to the counter party of the
+    /// given confirmed on-chain contract, proposing to close it before maturity
+    /// by paying `counter_payout` to the counter party and the rest of the
+    /// funding output to the local party, minus the fee of the closing
+    /// transaction at `fee_rate_per_vb`. The contract stays confirmed until the
+    /// counter party accepts the offer, and can still be closed through a CET
+    /// or refunded in the meantime.
+    ///
+    /// The offer expires after [`ManagerConfig::peer_timeout`] and is then
+    /// rejected by the counter party's manager. As the signature included in
+    /// the offer stays valid until the funding output is spent, a counter party
+    /// not following the protocol can still use it after that, so only offer
+    /// splits that remain acceptable until the contract is closed.
+    pub fn offer_contract_close(
+        &self,
+        contract_id: &ContractId,
+        counter_payout: u64,
+        fee_rate_per_vb: u64,
+    ) -> Result<(CloseOffer, PublicKey), Error> {
+        let signed_contract =
+            get_contract_in_state!(self, contract_id, Confirmed, None as Option<PublicKey>)?;
+
+        if signed_contract.channel_id.is_some() {
+            return Err(Error::InvalidParameters(
+                "Contracts within a channel are closed through the channel".to_string(),
+            ));
+        }
+
+        let expiry = self
+            .time
+            .unix_time_now()
+            .saturating_add(self.config.peer_timeout);
+        let close_offer = crate::contract_updater::offer_close(
+            &self.secp,
+            &signed_contract,
+            counter_payout,
+            fee_rate_per_vb,
+            u32::try_from(expiry).unwrap_or(u32::MAX),
+            &self.wallet,
+        )?;
+
+        Ok((
+            close_offer,
+            signed_contract.accepted_contract.offered_contract.counter_party,
+        ))
+    }
+
+    /// Accepts a [`CloseOffer`] received from the counter party of a contract,
+    /// as notified by an [`Event::ContractCloseOffered`], if it has not expired.
+    /// The closing transaction is broadcast and a [`CloseAccept`] message
+    /// returned to be sent to the counter party.
+    pub fn accept_contract_close(
+        &self,
+        close_offer: &CloseOffer,
+        counter_party: &PublicKey,
+    ) -> Result<CloseAccept, Error> {
+        let signed_contract = get_contract_in_state!(
+            self,
+            &close_offer.contract_id,
+            Confirmed,
+            Some(*counter_party)
+        )?;
+
+        if signed_contract.channel_id.is_some() {
+            return Err(Error::InvalidParameters(
+                "Contracts within a channel are closed through the channel".to_string(),
+            ));
+        }
+
+        let (close_tx, close_accept) = crate::contract_updater::accept_close(
+            &self.secp,
+            &signed_contract,
+            close_offer,
+            self.time.unix_time_now(),
+            &self.wallet,
+        )?;
+
+        let closed_contract = self.close_contract(&signed_contract, close_tx, None)?;
+        self.store.update_contract(&closed_contract)?;
+        self.push_contract_event(&closed_contract);
+
+        Ok(close_accept)
+    }
+
     /// Function to call to check the state of the currently executing DLCs and
     /// update them if possible.
     pub fn periodic_check(&self) -> Result<(), Error> {
         self.check_transaction_confirmations();
         self.check_signed_contracts()?;
+        self.check_funding_spends()?;
         self.check_confirmed_contracts()?;
         self.check_preclosed_contracts()?;
+        self.check_prerefunded_contracts()?;
         self.channel_checks()?;
+        self.prune_adaptor_point_cache()?;
 
         Ok(())
     }
@@ -446,12 +800,80 @@ where
         }
     }
 
+    fn on_close_offer(&self, close_offer: &CloseOffer, peer_id: &PublicKey) -> Result<(), Error> {
+        let signed_contract = get_contract_in_state!(
+            self,
+            &close_offer.contract_id,
+            Confirmed,
+            Some(*peer_id)
+        )?;
+
+        if signed_contract.channel_id.is_some() {
+            return Err(Error::InvalidParameters(
+                "Received close offer for a contract within a channel".to_string(),
+            ));
+        }
+
+        crate::contract_updater::verify_close_offer(
+            &self.secp,
+            &signed_contract,
+            close_offer,
+            self.time.unix_time_now(),
+        )?;
+
+        self.push_event(Event::ContractCloseOffered {
+            contract_id: close_offer.contract_id,
+            counter_party: *peer_id,
+            close_offer: close_offer.clone(),
+        });
+
+        Ok(())
+    }
+
+    fn on_close_accept(
+        &self,
+        close_accept: &CloseAccept,
+        peer_id: &PublicKey,
+    ) -> Result<(), Error> {
+        let signed_contract = get_contract_in_state!(
+            self,
+            &close_accept.contract_id,
+            Confirmed,
+            Some(*peer_id)
+        )?;
+
+        let close_tx =
+            crate::contract_updater::on_close_accept(&self.secp, &signed_contract, close_accept)?;
+
+        // The accepting party broadcasts the closing transaction itself, so
+        // failing to do so here is expected.
+        if let Err(e) = self.blockchain.send_transaction(&close_tx) {
+            warn!(
+                "Could not broadcast closing transaction {} of contract {}: {}",
+                close_tx.txid(),
+                close_accept.contract_id.to_hex(),
+                e
+            );
+        }
+
+        let preclosed_contract = Contract::PreClosed(PreClosedContract {
+            signed_contract,
+            attestations: None,
+            signed_cet: close_tx,
+        });
+        self.store.update_contract(&preclosed_contract)?;
+        self.push_contract_event(&preclosed_contract);
+
+        Ok(())
+    }
+
     fn on_offer_message(
         &self,
         offered_message: &OfferDlc,
         counter_party: PublicKey,
     ) -> Result<(), Error> {
-        offered_message.validate(&self.secp, REFUND_DELAY, REFUND_DELAY * 2)?;
+        let (min_refund_delay, max_refund_delay) = self.config.get_refund_delay_range()?;
+        offered_message.validate(&self.secp, min_refund_delay, max_refund_delay)?;
         let contract: OfferedContract =
             OfferedContract::try_from_offer_dlc(offered_message, counter_party)?;
         contract.validate()?;
@@ -479,12 +901,16 @@ where
             Some(*counter_party)
         )?;
 
-        let (signed_contract, signed_msg) = match verify_accepted_and_sign_contract(
-            &self.secp,
-            &offered_contract,
-            accept_msg,
-            &self.wallet,
-        ) {
+        let res = self.with_adaptor_point_cache(|adaptor_point_cache| {
+            verify_accepted_and_sign_contract(
+                &self.secp,
+                adaptor_point_cache,
+                &offered_contract,
+                accept_msg,
+                &self.wallet,
+            )
+        });
+        let (signed_contract, signed_msg) = match res {
             Ok(contract) => contract,
             Err(e) => return self.accept_fail_on_error(offered_contract, accept_msg.clone(), e),
         };
@@ -507,12 +933,16 @@ where
         let accepted_contract =
             get_contract_in_state!(self, &sign_message.contract_id, Accepted, Some(*peer_id))?;
 
-        let (signed_contract, fund_tx) = match crate::contract_updater::verify_signed_contract(
-            &self.secp,
-            &accepted_contract,
-            sign_message,
-            &self.wallet,
-        ) {
+        let res = self.with_adaptor_point_cache(|adaptor_point_cache| {
+            crate::contract_updater::verify_signed_contract(
+                &self.secp,
+                adaptor_point_cache,
+                &accepted_contract,
+                sign_message,
+                &self.wallet,
+            )
+        });
+        let (signed_contract, fund_tx) = match res {
             Ok(contract) => contract,
             Err(e) => return self.sign_fail_on_error(accepted_contract, sign_message.clone(), e),
         };
@@ -535,7 +965,7 @@ where
                 .oracles
                 .get(pubkey)
                 .ok_or_else(|| Error::InvalidParameters("Unknown oracle public key".to_string()))?;
-            announcements.push(oracle.get_announcement(&oracle_inputs.event_id)?.clone());
+            announcements.push(oracle.get_announcement(oracle_inputs.get_event_id(pubkey))?.clone());
         }
 
         Ok(announcements)
@@ -574,13 +1004,23 @@ where
     }
 
     fn check_signed_contract(&self, contract: &SignedContract) -> Result<(), Error> {
-        let confirmations = self.blockchain.get_transaction_confirmations(
-            &contract.accepted_contract.dlc_transactions.fund.txid(),
-        )?;
-        #[allow(clippy::absurd_extreme_comparisons)]
-        if confirmations >= NB_CONFIRMATIONS {
+        let fund_txid = contract.accepted_contract.dlc_transactions.fund.txid();
+        let confirmations = self.blockchain.get_transaction_confirmations(&fund_txid)?;
+        if confirmations < 1 && contract.channel_id.is_none() {
+            if let Err(e) = self.bump_funding_fee(contract) {
+                warn!(
+                    "Failed to bump fee of funding transaction {}: {}",
+                    fund_txid, e
+                );
+            }
+        }
+        if confirmations >= self.config.nb_confirmations {
+            self.store.delete_fee_bump(&fund_txid)?;
             self.store
                 .update_contract(&Contract::Confirmed(contract.clone()))?;
+            self.push_event(Event::ContractConfirmed {
+                contract_id: contract.accepted_contract.get_contract_id(),
+            });
         }
         Ok(())
     }
@@ -605,6 +1045,13 @@ where
             if c.channel_id.is_some() {
                 continue;
             }
+            if let Err(e) = self.watch_funding_output(&c) {
+                error!(
+                    "Error watching funding output of contract {}: {}",
+                    c.accepted_contract.get_contract_id_string(),
+                    e
+                )
+            }
             if let Err(e) = self.check_confirmed_contract(&c) {
                 error!(
                     "Error checking confirmed contract {}: {}",
@@ -617,44 +1064,148 @@ where
         Ok(())
     }
 
-    fn get_closable_contract_info<'a>(
-        &'a self,
-        contract: &'a SignedContract,
-    ) -> ClosableContractInfo<'a> {
-        let contract_infos = &contract.accepted_contract.offered_contract.contract_info;
-        let adaptor_infos = &contract.accepted_contract.adaptor_infos;
-        for (contract_info, adaptor_info) in contract_infos.iter().zip(adaptor_infos.iter()) {
-            let matured: Vec<_> = contract_info
-                .oracle_announcements
-                .iter()
-                .filter(|x| {
-                    (x.oracle_event.event_maturity_epoch as u64) <= self.time.unix_time_now()
-                })
-                .enumerate()
-                .collect();
-            if matured.len() >= contract_info.threshold {
-                let attestations: Vec<_> = matured
-                    .iter()
-                    .filter_map(|(i, announcement)| {
-                        let oracle = self.oracles.get(&announcement.oracle_public_key)?;
-                        Some((
-                            *i,
-                            oracle
-                                .get_attestation(&announcement.oracle_event.event_id)
-                                .ok()?,
-                        ))
-                    })
-                    .collect();
-                if attestations.len() >= contract_info.threshold {
-                    return Some((contract_info, adaptor_info, attestations));
-                }
+    fn watch_funding_output(&self, contract: &SignedContract) -> Result<(), Error> {
+        let dlc_transactions = &contract.accepted_contract.dlc_transactions;
+        let outpoint = OutPoint {
+            txid: dlc_transactions.fund.txid(),
+            vout: dlc_transactions.get_fund_output_index() as u32,
+        };
+
+        let mut chain_monitor = self.chain_monitor.lock().unwrap();
+        if chain_monitor.add_funding_txo(outpoint, contract.accepted_contract.get_contract_id()) {
+            self.store.persist_chain_monitor(&chain_monitor)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_funding_spends(&self) -> Result<(), Error> {
+        let funding_spends = self.chain_monitor.lock().unwrap().confirmed_funding_spends();
+
+        for (outpoint, tx, contract_id) in funding_spends {
+            if let Err(e) = self.on_funding_spent(&contract_id, &tx) {
+                error!(
+                    "Error processing spending of funding output of contract {}: {}",
+                    contract_id.to_hex(),
+                    e
+                );
+                continue;
             }
+
+            let mut chain_monitor = self.chain_monitor.lock().unwrap();
+            chain_monitor.remove_txo(&outpoint);
+            self.store.persist_chain_monitor(&chain_monitor)?;
         }
-        None
+
+        Ok(())
+    }
+
+    /// Updates the state of an on-chain contract whose funding output was spent by
+    /// the given transaction, in case it was not spent by the local party.
+    fn on_funding_spent(&self, contract_id: &ContractId, tx: &Transaction) -> Result<(), Error> {
+        let signed_contract = match self
+            .store
+            .get_contract(contract_id)?
+            .and_then(|c| crate::contract_updater::get_contract_spent_by(c, tx))
+        {
+            Some(c) => c,
+            // Spent by our own CET or refund transaction, nothing to do.
+            None => return Ok(()),
+        };
+
+        let txid = tx.txid();
+
+        let attestations = match crate::contract_updater::get_funding_spend(&signed_contract, tx) {
+            FundingSpend::Refund => {
+                log::info!(
+                    "Refund transaction {} broadcast by counter party for contract {}",
+                    txid,
+                    contract_id.to_hex()
+                );
+                let refunded_contract = self.refund_contract(&signed_contract, tx.clone())?;
+                self.store.update_contract(&refunded_contract)?;
+                self.push_contract_event(&refunded_contract);
+                return Ok(());
+            }
+            FundingSpend::CollaborativeClose => {
+                log::info!(
+                    "Contract {} collaboratively closed by transaction {}",
+                    contract_id.to_hex(),
+                    txid
+                );
+                None
+            }
+            FundingSpend::Cet => {
+                log::info!(
+                    "CET {} broadcast by counter party for contract {}",
+                    txid,
+                    contract_id.to_hex()
+                );
+                crate::contract_updater::recover_attestation_from_cet(
+                    &self.secp,
+                    &signed_contract,
+                    tx,
+                )
+                .map(|attestation| vec![attestation])
+                .or_else(|| {
+                    // The CET was decrypted using several oracle signatures, find
+                    // out which of the ones published by the oracles were used.
+                    crate::contract_updater::verify_cet_attestations(
+                        &signed_contract,
+                        tx,
+                        &self.get_all_oracle_attestations(&signed_contract),
+                    )
+                })
+            }
+        };
+
+        let closed_contract = self.close_contract(&signed_contract, tx.clone(), attestations)?;
+        self.store.update_contract(&closed_contract)?;
+        self.push_contract_event(&closed_contract);
+
+        Ok(())
+    }
+
+    /// Returns the attestations available from the oracles of the given contract
+    /// info whose event has matured, together with the index of the oracle in
+    /// the announcements of the contract info.
+    fn get_oracle_attestations(
+        &self,
+        contract_info: &ContractInfo,
+    ) -> Vec<(usize, OracleAttestation)> {
+        crate::contract_updater::get_matured_announcements(contract_info, self.time.unix_time_now())
+            .filter_map(|(i, announcement)| {
+                let oracle = self.oracles.get(&announcement.oracle_public_key)?;
+                Some((
+                    i,
+                    oracle
+                        .get_attestation(&announcement.oracle_event.event_id)
+                        .ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the attestations available for each of the contract infos of the
+    /// given contract, see [`Self::get_oracle_attestations`].
+    fn get_all_oracle_attestations(
+        &self,
+        contract: &SignedContract,
+    ) -> Vec<Vec<(usize, OracleAttestation)>> {
+        contract
+            .accepted_contract
+            .offered_contract
+            .contract_info
+            .iter()
+            .map(|x| self.get_oracle_attestations(x))
+            .collect()
     }
 
     fn check_confirmed_contract(&self, contract: &SignedContract) -> Result<(), Error> {
-        let closable_contract_info = self.get_closable_contract_info(contract);
+        let closable_contract_info = crate::contract_updater::get_closable_contract_info(
+            contract,
+            self.get_all_oracle_attestations(contract),
+        );
         if let Some((contract_info, adaptor_info, attestations)) = closable_contract_info {
             let cet = crate::contract_updater::get_signed_cet(
                 &self.secp,
@@ -667,10 +1218,11 @@ where
             match self.close_contract(
                 contract,
                 cet,
-                attestations.iter().map(|x| x.1.clone()).collect(),
+                Some(attestations.iter().map(|x| x.1.clone()).collect()),
             ) {
                 Ok(closed_contract) => {
                     self.store.update_contract(&closed_contract)?;
+                    self.push_contract_event(&closed_contract);
                     return Ok(());
                 }
                 Err(e) => {
@@ -700,39 +1252,154 @@ where
             }
         }
 
-        Ok(())
-    }
+        Ok(())
+    }
+
+    fn check_preclosed_contract(&self, contract: &PreClosedContract) -> Result<(), Error> {
+        let broadcasted_txid = contract.signed_cet.txid();
+        let confirmations = self
+            .blockchain
+            .get_transaction_confirmations(&broadcasted_txid)?;
+        if confirmations < 1 {
+            if let Err(e) = self.bump_fee(
+                &contract.signed_cet,
+                get_fund_output_value(&contract.signed_contract),
+                &get_own_party_params(&contract.signed_contract).payout_script_pubkey,
+            ) {
+                warn!(
+                    "Failed to bump fee of closing transaction {}: {}",
+                    broadcasted_txid, e
+                );
+            }
+        }
+        if let Some(closed_contract) = crate::contract_updater::on_preclosed_confirmations(
+            contract,
+            confirmations,
+            self.config.nb_confirmations,
+        ) {
+            self.store.delete_fee_bump(&broadcasted_txid)?;
+            self.store.update_contract(&closed_contract)?;
+            self.push_contract_event(&closed_contract);
+        }
+
+        Ok(())
+    }
+
+    /// Bumps the fee of the funding transaction of the given contract using the
+    /// change output of the local party, see [`Self::bump_fee`]. The signed
+    /// funding transaction is retrieved from the blockchain as the contract only
+    /// stores the unsigned one.
+    fn bump_funding_fee(&self, contract: &SignedContract) -> Result<(), Error> {
+        let accepted_contract = &contract.accepted_contract;
+        let fund_tx = self
+            .blockchain
+            .get_transaction(&accepted_contract.dlc_transactions.fund.txid())?;
+        let input_value = accepted_contract.offered_contract.offer_params.input_amount
+            + accepted_contract.accept_params.input_amount;
+        self.bump_fee(
+            &fund_tx,
+            input_value,
+            &get_own_party_params(contract).change_script_pubkey,
+        )
+    }
+
+    /// Broadcasts a transaction spending the local party's output of the given
+    /// unconfirmed transaction, so that both together pay the current high
+    /// priority fee rate estimate, capped at [`ManagerConfig::max_fee_rate`].
+    /// `input_value` is the sum of the values of the outputs spent by the
+    /// transaction. Does nothing if the transaction already pays enough fees or
+    /// was already bumped to the target fee rate by a child that is still known
+    /// to the blockchain, as recorded in the store. A child from a previous bump
+    /// spends the same output and is replaced by the new one, which pays a higher
+    /// fee as required by BIP 125 since its package fee rate is higher.
+    fn bump_fee(
+        &self,
+        tx: &Transaction,
+        input_value: u64,
+        own_script_pubkey: &Script,
+    ) -> Result<(), Error> {
+        if self.config.max_fee_rate == 0 {
+            return Ok(());
+        }
+
+        let estimated_fee_rate: u64 = (self
+            .fee_estimator
+            .get_est_sat_per_1000_weight(ConfirmationTarget::HighPriority)
+            / VBYTES_PER_KILO_WEIGHT)
+            .into();
+        let fee_rate = u64::min(estimated_fee_rate, self.config.max_fee_rate);
+
+        let txid = tx.txid();
+        let previous_child = match self.store.get_fee_bump(&txid)? {
+            Some(fee_bump) => {
+                // The child is unknown if it was evicted from the mempool, in
+                // which case the fee is bumped again.
+                let previous_child = self.blockchain.get_transaction(&fee_bump.child_txid).ok();
+                if previous_child.is_some() && fee_bump.fee_rate >= fee_rate {
+                    return Ok(());
+                }
+                previous_child
+            }
+            None => None,
+        };
+
+        let fee = input_value
+            .checked_sub(tx.output.iter().map(|x| x.value).sum())
+            .ok_or_else(|| Error::InvalidState("Transaction outputs exceed inputs".to_string()))?;
+
+        if fee >= dlc::util::weight_to_fee(tx.weight(), fee_rate)? {
+            return Ok(());
+        }
+
+        let vout = match dlc::util::get_output_for_script_pubkey(tx, own_script_pubkey) {
+            Some((vout, _)) => vout,
+            // The local party has no output (or a dust one) to spend from.
+            None => return Ok(()),
+        };
 
-    fn check_preclosed_contract(&self, contract: &PreClosedContract) -> Result<(), Error> {
-        let broadcasted_txid = contract.signed_cet.txid();
-        let confirmations = self
-            .blockchain
-            .get_transaction_confirmations(&broadcasted_txid)?;
-        #[allow(clippy::absurd_extreme_comparisons)]
-        if confirmations >= NB_CONFIRMATIONS {
-            let closed_contract = ClosedContract {
-                attestations: contract.attestations.clone(),
-                signed_cet: Some(contract.signed_cet.clone()),
-                contract_id: contract.signed_contract.accepted_contract.get_contract_id(),
-                temporary_contract_id: contract
-                    .signed_contract
-                    .accepted_contract
-                    .offered_contract
-                    .id,
-                counter_party_id: contract
-                    .signed_contract
-                    .accepted_contract
-                    .offered_contract
-                    .counter_party,
-                pnl: contract
-                    .signed_contract
-                    .accepted_contract
-                    .compute_pnl(&contract.signed_cet),
-            };
-            self.store
-                .update_contract(&Contract::Closed(closed_contract))?;
+        // The replacing child pays to the same address as the replaced one.
+        let destination = match &previous_child {
+            Some(previous_child) => previous_child.output[0].script_pubkey.clone(),
+            None => self.wallet.get_new_address()?.script_pubkey(),
+        };
+        let mut child = dlc::create_cpfp_transaction(tx, fee, vout as u32, &destination, fee_rate)?;
+
+        let mut psbt = PartiallySignedTransaction::from_unsigned_tx(child.clone())
+            .map_err(|_| Error::InvalidState("Tried to create PSBT from signed tx".to_string()))?;
+        psbt.inputs[0].witness_utxo = Some(tx.output[vout].clone());
+        self.wallet.sign_psbt_input(&mut psbt, 0)?;
+        child.input[0].witness = psbt.inputs[0]
+            .final_script_witness
+            .clone()
+            .ok_or_else(|| {
+                Error::InvalidState("No witness from signing psbt input".to_string())
+            })?;
+
+        if let Some(previous_child) = &previous_child {
+            log::info!(
+                "Replacing child transaction {} of transaction {}",
+                previous_child.txid(),
+                txid
+            );
         }
 
+        self.blockchain.send_transaction(&child)?;
+
+        log::info!(
+            "Bumped fee of transaction {} to {} sats/vbyte with child transaction {}",
+            txid,
+            fee_rate,
+            child.txid()
+        );
+
+        self.store.upsert_fee_bump(
+            &txid,
+            &FeeBump {
+                fee_rate,
+                child_txid: child.txid(),
+            },
+        )?;
+
         Ok(())
     }
 
@@ -740,72 +1407,109 @@ where
         &self,
         contract: &SignedContract,
         signed_cet: Transaction,
-        attestations: Vec<OracleAttestation>,
+        attestations: Option<Vec<OracleAttestation>>,
     ) -> Result<Contract, Error> {
         let confirmations = self
             .blockchain
             .get_transaction_confirmations(&signed_cet.txid())?;
 
-        #[allow(clippy::absurd_extreme_comparisons)]
         if confirmations < 1 {
-            // TODO(tibo): if this fails because another tx is already in
-            // mempool or blockchain, we might have been cheated. There is
-            // not much to be done apart from possibly extracting a fraud
-            // proof but ideally it should be handled.
+            // If this fails because another transaction spending the funding
+            // output is already in mempool or blockchain, it will be picked up
+            // by `check_funding_spends` once confirmed.
             self.blockchain.send_transaction(&signed_cet)?;
+        }
 
-            let preclosed_contract = PreClosedContract {
-                signed_contract: contract.clone(),
-                attestations: Some(attestations),
-                signed_cet,
-            };
+        Ok(crate::contract_updater::get_closing_contract(
+            contract,
+            signed_cet,
+            attestations,
+            confirmations,
+            self.config.nb_confirmations,
+        ))
+    }
 
-            return Ok(Contract::PreClosed(preclosed_contract));
-        } else if confirmations < NB_CONFIRMATIONS {
-            let preclosed_contract = PreClosedContract {
-                signed_contract: contract.clone(),
-                attestations: Some(attestations),
-                signed_cet,
-            };
+    fn check_refund(&self, contract: &SignedContract) -> Result<(), Error> {
+        if crate::contract_updater::is_refundable(contract, self.time.unix_time_now()) {
+            let signed_refund =
+                crate::contract_updater::get_signed_refund(&self.secp, contract, &self.wallet)?;
+            let refunded_contract = self.refund_contract(contract, signed_refund)?;
+            self.store.update_contract(&refunded_contract)?;
+            self.push_contract_event(&refunded_contract);
+        }
+
+        Ok(())
+    }
+
+    fn refund_contract(
+        &self,
+        contract: &SignedContract,
+        signed_refund: Transaction,
+    ) -> Result<Contract, Error> {
+        let confirmations = self
+            .blockchain
+            .get_transaction_confirmations(&signed_refund.txid())?;
 
-            return Ok(Contract::PreClosed(preclosed_contract));
+        if confirmations < 1 {
+            self.blockchain.send_transaction(&signed_refund)?;
         }
 
-        let closed_contract = ClosedContract {
-            attestations: Some(attestations.to_vec()),
-            pnl: contract.accepted_contract.compute_pnl(&signed_cet),
-            signed_cet: Some(signed_cet),
-            contract_id: contract.accepted_contract.get_contract_id(),
-            temporary_contract_id: contract.accepted_contract.offered_contract.id,
-            counter_party_id: contract.accepted_contract.offered_contract.counter_party,
-        };
+        Ok(crate::contract_updater::get_refunding_contract(
+            contract,
+            signed_refund,
+            confirmations,
+            self.config.nb_confirmations,
+        ))
+    }
+
+    fn check_prerefunded_contracts(&self) -> Result<(), Error> {
+        for c in self.store.get_prerefunded_contracts()? {
+            if let Err(e) = self.check_prerefunded_contract(&c) {
+                error!(
+                    "Error checking pre-refunded contract {}: {}",
+                    c.signed_contract.accepted_contract.get_contract_id_string(),
+                    e
+                )
+            }
+        }
 
-        Ok(Contract::Closed(closed_contract))
+        Ok(())
     }
 
-    fn check_refund(&self, contract: &SignedContract) -> Result<(), Error> {
-        // TODO(tibo): should check for confirmation of refund before updating state
-        if contract
-            .accepted_contract
-            .dlc_transactions
-            .refund
-            .lock_time
-            .0 as u64
-            <= self.time.unix_time_now()
-        {
-            let accepted_contract = &contract.accepted_contract;
-            let refund = accepted_contract.dlc_transactions.refund.clone();
-            let confirmations = self
-                .blockchain
-                .get_transaction_confirmations(&refund.txid())?;
-            if confirmations == 0 {
-                let refund =
-                    crate::contract_updater::get_signed_refund(&self.secp, contract, &self.wallet)?;
-                self.blockchain.send_transaction(&refund)?;
+    fn check_prerefunded_contract(&self, contract: &PreRefundedContract) -> Result<(), Error> {
+        let refund_txid = contract.signed_refund.txid();
+        let confirmations = self
+            .blockchain
+            .get_transaction_confirmations(&refund_txid)?;
+
+        if confirmations < 1 {
+            // The refund transaction might have been evicted from the mempool.
+            if let Err(e) = self.blockchain.send_transaction(&contract.signed_refund) {
+                warn!(
+                    "Failed to re-broadcast refund transaction {}: {}",
+                    refund_txid, e
+                );
+            }
+            if let Err(e) = self.bump_fee(
+                &contract.signed_refund,
+                get_fund_output_value(&contract.signed_contract),
+                &get_own_party_params(&contract.signed_contract).payout_script_pubkey,
+            ) {
+                warn!(
+                    "Failed to bump fee of refund transaction {}: {}",
+                    refund_txid, e
+                );
             }
+        }
 
-            self.store
-                .update_contract(&Contract::Refunded(contract.clone()))?;
+        if let Some(refunded_contract) = crate::contract_updater::on_prerefunded_confirmations(
+            contract,
+            confirmations,
+            self.config.nb_confirmations,
+        ) {
+            self.store.delete_fee_bump(&refund_txid)?;
+            self.store.update_contract(&refunded_contract)?;
+            self.push_contract_event(&refunded_contract);
         }
 
         Ok(())
@@ -841,8 +1545,8 @@ where
             contract_input,
             &counter_party,
             &oracle_announcements,
-            CET_NSEQUENCE,
-            REFUND_DELAY,
+            self.config.cet_nsequence,
+            self.config.refund_delay,
             &self.wallet,
             &self.blockchain,
             &self.time,
@@ -908,14 +1612,17 @@ where
         )?;
 
         let (accepted_channel, accepted_contract, accept_channel) =
-            crate::channel_updater::accept_channel_offer(
-                &self.secp,
-                &offered_channel,
-                &offered_contract,
-                &self.wallet,
-                &self.blockchain,
-                fee_config,
-            )?;
+            self.with_adaptor_point_cache(|adaptor_point_cache| {
+                crate::channel_updater::accept_channel_offer(
+                    &self.secp,
+                    adaptor_point_cache,
+                    &offered_channel,
+                    &offered_contract,
+                    &self.wallet,
+                    &self.blockchain,
+                    fee_config,
+                )
+            })?;
 
         self.wallet.import_address(&Address::p2wsh(
             &accepted_contract.dlc_transactions.funding_script_pubkey,
@@ -957,7 +1664,7 @@ where
             &self.secp,
             &mut signed_channel,
             counter_payout,
-            PEER_TIMEOUT,
+            self.config.peer_timeout,
             &self.wallet,
             &self.time,
             reference_id,
@@ -998,9 +1705,9 @@ where
         let msg = crate::channel_updater::settle_channel_accept_internal(
             &self.secp,
             &mut signed_channel,
-            CET_NSEQUENCE,
+            self.config.cet_nsequence,
             0,
-            PEER_TIMEOUT,
+            self.config.peer_timeout,
             &self.wallet,
             &self.time,
             own_settle_adaptor_sk,
@@ -1040,9 +1747,9 @@ where
             contract_input,
             oracle_announcements,
             counter_payout,
-            REFUND_DELAY,
-            PEER_TIMEOUT,
-            CET_NSEQUENCE,
+            self.config.refund_delay,
+            self.config.peer_timeout,
+            self.config.cet_nsequence,
             &self.wallet,
             &self.time,
             reference_id,
@@ -1078,15 +1785,18 @@ where
             None as Option<PublicKey>
         )?;
 
-        let (accepted_contract, msg) = crate::channel_updater::accept_channel_renewal_internal(
-            &self.secp,
-            &mut signed_channel,
-            &offered_contract,
-            CET_NSEQUENCE,
-            PEER_TIMEOUT,
-            &self.wallet,
-            &self.time,
-        )?;
+        let (accepted_contract, msg) = self.with_adaptor_point_cache(|adaptor_point_cache| {
+            crate::channel_updater::accept_channel_renewal_internal(
+                &self.secp,
+                adaptor_point_cache,
+                &mut signed_channel,
+                &offered_contract,
+                self.config.cet_nsequence,
+                self.config.peer_timeout,
+                &self.wallet,
+                &self.time,
+            )
+        })?;
 
         let counter_party = signed_channel.counter_party;
 
@@ -1169,6 +1879,7 @@ where
             &self.secp,
             &mut signed_channel,
             counter_payout,
+            self.config.peer_timeout,
             &self.wallet,
             &self.time,
             reference_id
@@ -1223,11 +1934,13 @@ where
 
         self.blockchain.send_transaction(&close_tx)?;
 
-        self.store.upsert_channel(closed_channel, None)?;
+        self.store.upsert_channel(closed_channel.clone(), None)?;
+        self.push_channel_closed_event(&closed_channel);
 
         if let Some(closed_contract) = closed_contract {
-            self.store
-                .update_contract(&Contract::Closed(closed_contract))?;
+            let closed_contract = Contract::Closed(closed_contract);
+            self.store.update_contract(&closed_contract)?;
+            self.push_contract_event(&closed_contract);
         }
 
         Ok(())
@@ -1248,7 +1961,7 @@ where
         if self
             .blockchain
             .get_transaction_confirmations(&buffer_tx.txid())?
-            >= CET_NSEQUENCE
+            >= self.config.cet_nsequence
         {
             log::info!(
                 "Buffer transaction for contract {} has enough confirmations to spend from it",
@@ -1279,7 +1992,7 @@ where
             let closed_contract = self.close_contract(
                 &confirmed_contract,
                 signed_cet,
-                attestations.iter().map(|x| &x.1).cloned().collect(),
+                Some(attestations.iter().map(|x| &x.1).cloned().collect()),
             )?;
 
             self.chain_monitor
@@ -1288,7 +2001,9 @@ where
                 .cleanup_channel(signed_channel.channel_id);
 
             self.store
-                .upsert_channel(closed_channel, Some(closed_contract))?;
+                .upsert_channel(closed_channel.clone(), Some(closed_contract.clone()))?;
+            self.push_channel_closed_event(&closed_channel);
+            self.push_contract_event(&closed_contract);
         }
 
         Ok(())
@@ -1308,7 +2023,7 @@ where
         if self
             .blockchain
             .get_transaction_confirmations(&settle_tx.txid())?
-            >= CET_NSEQUENCE
+            >= self.config.cet_nsequence
         {
             log::info!(
                 "Settle transaction {} for channel {} has enough confirmations to spend from it",
@@ -1323,7 +2038,7 @@ where
                         ConfirmationTarget::Background,
                     );
 
-                let fee_rate = fee_rate / 250;
+                let fee_rate = fee_rate / VBYTES_PER_KILO_WEIGHT;
 
                 fee_rate.into()
             };
@@ -1334,6 +2049,7 @@ where
                     &signed_channel,
                     &self.wallet.get_new_address()?,
                     fee_rate_per_vb,
+                    self.config.cet_nsequence,
                     &self.wallet,
                     is_initiator,
                 )?;
@@ -1362,10 +2078,9 @@ where
 
         // TODO(lucas): No need to send it again if it is in mempool, unless we want to bump the
         // fee.
-        #[allow(clippy::absurd_extreme_comparisons)]
         if confirmations < 1 {
             self.blockchain.send_transaction(claim_tx)?;
-        } else if confirmations >= NB_CONFIRMATIONS {
+        } else if confirmations >= self.config.nb_confirmations {
             self.chain_monitor
                 .lock()
                 .unwrap()
@@ -1398,7 +2113,8 @@ where
                 .cleanup_channel(channel.channel_id);
 
             self.store
-                .upsert_channel(closed_channel, None)?;
+                .upsert_channel(closed_channel.clone(), None)?;
+            self.push_channel_closed_event(&closed_channel);
         }
 
         Ok(())
@@ -1409,12 +2125,14 @@ where
         offer_channel: &OfferChannel,
         counter_party: PublicKey,
     ) -> Result<(), Error> {
+        let (min_refund_delay, max_refund_delay) = self.config.get_refund_delay_range()?;
+        let (min_cet_nsequence, max_cet_nsequence) = self.config.get_cet_nsequence_range()?;
         offer_channel.validate(
             &self.secp,
-            REFUND_DELAY,
-            REFUND_DELAY * 2,
-            CET_NSEQUENCE,
-            CET_NSEQUENCE * 2,
+            min_refund_delay,
+            max_refund_delay,
+            min_cet_nsequence,
+            max_cet_nsequence,
         )?;
 
         let (channel, contract) = OfferedChannel::from_offer_channel(offer_channel, counter_party)?;
@@ -1456,20 +2174,23 @@ where
         )?;
 
         let (signed_channel, signed_contract, sign_channel) = {
-            let res = crate::channel_updater::verify_and_sign_accepted_channel(
-                &self.secp,
-                &offered_channel,
-                &offered_contract,
-                accept_channel,
-                //TODO(tibo): this should be parameterizable.
-                CET_NSEQUENCE,
-                &self.wallet,
-                &self.chain_monitor,
-                offered_channel
-                    .fee_config
-                    .map(FeeConfig::from)
-                    .unwrap_or(FeeConfig::EvenSplit),
-            );
+            let res = self.with_adaptor_point_cache(|adaptor_point_cache| {
+                crate::channel_updater::verify_and_sign_accepted_channel(
+                    &self.secp,
+                    adaptor_point_cache,
+                    &offered_channel,
+                    &offered_contract,
+                    accept_channel,
+                    //TODO(tibo): this should be parameterizable.
+                    self.config.cet_nsequence,
+                    &self.wallet,
+                    &self.chain_monitor,
+                    offered_channel
+                        .fee_config
+                        .map(FeeConfig::from)
+                        .unwrap_or(FeeConfig::EvenSplit),
+                )
+            });
 
             match res {
                 Ok(res) => res,
@@ -1507,6 +2228,8 @@ where
             signed_contract.accepted_contract.get_contract_id().to_hex()
         );
 
+        let channel_id = signed_channel.channel_id;
+
         self.store.upsert_channel(
             Channel::Signed(signed_channel),
             Some(Contract::Signed(signed_contract)),
@@ -1515,6 +2238,8 @@ where
         self.store
             .persist_chain_monitor(&self.chain_monitor.lock().unwrap())?;
 
+        self.push_event(Event::ChannelEstablished { channel_id });
+
         Ok(sign_channel)
     }
 
@@ -1533,14 +2258,17 @@ where
         )?;
 
         let (signed_channel, signed_contract, signed_fund_tx) = {
-            let res = verify_signed_channel(
-                &self.secp,
-                &accepted_channel,
-                &accepted_contract,
-                sign_channel,
-                &self.wallet,
-                &self.chain_monitor,
-            );
+            let res = self.with_adaptor_point_cache(|adaptor_point_cache| {
+                verify_signed_channel(
+                    &self.secp,
+                    adaptor_point_cache,
+                    &accepted_channel,
+                    &accepted_contract,
+                    sign_channel,
+                    &self.wallet,
+                    &self.chain_monitor,
+                )
+            });
 
             match res {
                 Ok(res) => res,
@@ -1584,6 +2312,8 @@ where
 
         self.blockchain.send_transaction(&signed_fund_tx)?;
 
+        let channel_id = signed_channel.channel_id;
+
         self.store.upsert_channel(
             Channel::Signed(signed_channel),
             Some(Contract::Signed(signed_contract)),
@@ -1591,6 +2321,8 @@ where
         self.store
             .persist_chain_monitor(&self.chain_monitor.lock().unwrap())?;
 
+        self.push_event(Event::ChannelEstablished { channel_id });
+
         Ok(())
     }
 
@@ -1656,9 +2388,9 @@ where
             &self.secp,
             &mut signed_channel,
             settle_accept,
-            CET_NSEQUENCE,
+            self.config.cet_nsequence,
             0,
-            PEER_TIMEOUT,
+            self.config.peer_timeout,
             &self.wallet,
             &self.time,
             own_settle_adaptor_sk,
@@ -1738,11 +2470,19 @@ where
             true,
         )?);
 
+        let channel_id = signed_channel.channel_id;
+
         self.store
-            .upsert_channel(Channel::Signed(signed_channel), Some(closed_contract))?;
+            .upsert_channel(Channel::Signed(signed_channel), Some(closed_contract.clone()))?;
         self.store
             .persist_chain_monitor(&self.chain_monitor.lock().unwrap())?;
 
+        self.push_contract_event(&closed_contract);
+        self.push_event(Event::ChannelSettled {
+            channel_id,
+            own_payout,
+        });
+
         Ok(msg)
     }
 
@@ -1792,11 +2532,18 @@ where
             own_payout,
             true,
         )?);
+        let channel_id = signed_channel.channel_id;
         self.store
-            .upsert_channel(Channel::Signed(signed_channel), Some(closed_contract))?;
+            .upsert_channel(Channel::Signed(signed_channel), Some(closed_contract.clone()))?;
         self.store
             .persist_chain_monitor(&self.chain_monitor.lock().unwrap())?;
 
+        self.push_contract_event(&closed_contract);
+        self.push_event(Event::ChannelSettled {
+            channel_id,
+            own_payout,
+        });
+
         Ok(())
     }
 
@@ -1820,7 +2567,7 @@ where
         }
 
         let offered_contract =
-            crate::channel_updater::on_renew_offer(&mut signed_channel, renew_offer,PEER_TIMEOUT, &self.time)?;
+            crate::channel_updater::on_renew_offer(&mut signed_channel, renew_offer, self.config.peer_timeout, &self.time)?;
 
         self.store.create_contract(&offered_contract)?;
         self.store
@@ -1862,17 +2609,20 @@ where
         };
 
         let (signed_contract, msg) =
-            crate::channel_updater::verify_renew_accept_and_confirm_internal(
-                &self.secp,
-                renew_accept,
-                &mut signed_channel,
-                &offered_contract,
-                CET_NSEQUENCE,
-                PEER_TIMEOUT,
-                &self.wallet,
-                &self.time,
-                own_buffer_adaptor_sk,
-            )?;
+            self.with_adaptor_point_cache(|adaptor_point_cache| {
+                crate::channel_updater::verify_renew_accept_and_confirm_internal(
+                    &self.secp,
+                    adaptor_point_cache,
+                    renew_accept,
+                    &mut signed_channel,
+                    &offered_contract,
+                    self.config.cet_nsequence,
+                    self.config.peer_timeout,
+                    &self.wallet,
+                    &self.time,
+                    own_buffer_adaptor_sk,
+                )
+            })?;
 
         // Directly confirmed as we're in a channel the fund tx is already confirmed.
         self.store.upsert_channel(
@@ -1977,18 +2727,21 @@ where
         };
 
         let (signed_contract, msg) =
-            crate::channel_updater::verify_renew_confirm_and_finalize_internal(
-                &self.secp,
-                &mut signed_channel,
-                &accepted_contract,
-                renew_confirm,
-                PEER_TIMEOUT,
-                &self.time,
-                &self.wallet,
-                counter_buffer_adaptor_pk,
-                own_buffer_adaptor_sk,
-                &self.chain_monitor,
-            )?;
+            self.with_adaptor_point_cache(|adaptor_point_cache| {
+                crate::channel_updater::verify_renew_confirm_and_finalize_internal(
+                    &self.secp,
+                    adaptor_point_cache,
+                    &mut signed_channel,
+                    &accepted_contract,
+                    renew_confirm,
+                    self.config.peer_timeout,
+                    &self.time,
+                    &self.wallet,
+                    counter_buffer_adaptor_pk,
+                    own_buffer_adaptor_sk,
+                    &self.chain_monitor,
+                )
+            })?;
 
         self.chain_monitor.lock().unwrap().add_tx(
             prev_tx_id,
@@ -2009,6 +2762,7 @@ where
 
         if let Some(closed_contract) = closed_contract {
             self.store.update_contract(&closed_contract)?;
+            self.push_contract_event(&closed_contract);
         }
 
         Ok(msg)
@@ -2122,6 +2876,7 @@ where
 
         if let Some(closed_contract) = closed_contract {
             self.store.update_contract(&closed_contract)?;
+            self.push_contract_event(&closed_contract);
         }
 
         Ok(msg)
@@ -2156,7 +2911,7 @@ where
         crate::channel_updater::on_collaborative_close_offer(
             &mut signed_channel,
             close_offer,
-            PEER_TIMEOUT,
+            self.config.peer_timeout,
             &self.time,
         )?;
 
@@ -2268,6 +3023,7 @@ where
         check_for_timed_out_channels!(self, SettledOffered);
         check_for_timed_out_channels!(self, SettledAccepted);
         check_for_timed_out_channels!(self, SettledConfirmed);
+        check_for_timed_out_channels!(self, CollaborativeCloseOffered);
 
         Ok(())
     }
@@ -2406,7 +3162,7 @@ where
 
                     let fee_rate_per_vb: u64 = (self.fee_estimator.get_est_sat_per_1000_weight(
                         lightning::chain::chaininterface::ConfirmationTarget::HighPriority,
-                    ) / 250)
+                    ) / VBYTES_PER_KILO_WEIGHT)
                         .into();
 
                     let signed_tx = match revoked_tx_type {
@@ -2434,7 +3190,7 @@ where
                                 &counter_revocation_sk,
                                 &tx,
                                 &self.wallet.get_new_address()?,
-                                CET_NSEQUENCE,
+                                self.config.cet_nsequence,
                                 0,
                                 fee_rate_per_vb,
                                 is_offer,
@@ -2472,7 +3228,8 @@ where
                         .lock()
                         .unwrap()
                         .cleanup_channel(signed_channel.channel_id);
-                    self.store.upsert_channel(closed_channel, None)?;
+                    self.store.upsert_channel(closed_channel.clone(), None)?;
+                    self.push_channel_closed_event(&closed_channel);
                     true
                 }
                 TxType::CollaborativeClose => {
@@ -2490,8 +3247,9 @@ where
                             *counter_payout,
                             false,
                         )?;
-                        self.store
-                            .update_contract(&Contract::Closed(closed_contract))?;
+                        let closed_contract = Contract::Closed(closed_contract);
+                        self.store.update_contract(&closed_contract)?;
+                        self.push_contract_event(&closed_contract);
                     }
 
                     let closed_channel = Channel::CollaborativelyClosed(ClosedChannel {
@@ -2505,7 +3263,8 @@ where
                         .lock()
                         .unwrap()
                         .cleanup_channel(signed_channel.channel_id);
-                    self.store.upsert_channel(closed_channel, None)?;
+                    self.store.upsert_channel(closed_channel.clone(), None)?;
+                    self.push_channel_closed_event(&closed_channel);
                     true
                 }
                 TxType::SettleTx => {
@@ -2624,11 +3383,19 @@ where
                         .flatten();
 
                     self.store
-                        .upsert_channel(closed_channel, pre_closed_contract)?;
+                        .upsert_channel(closed_channel.clone(), pre_closed_contract.clone())?;
+
+                    self.push_channel_closed_event(&closed_channel);
+                    if let Some(pre_closed_contract) = &pre_closed_contract {
+                        self.push_contract_event(pre_closed_contract);
+                    }
 
                     true
                 }
                 TxType::SplitTx => false,
+                // Funding outputs of on-chain contracts are handled in
+                // `check_funding_spends`.
+                TxType::FundingSpend => false,
             };
 
             if persist {
@@ -2890,29 +3657,47 @@ where
     }
 }
 
+fn get_own_party_params(contract: &SignedContract) -> &PartyParams {
+    let accepted_contract = &contract.accepted_contract;
+    let offered_contract = &accepted_contract.offered_contract;
+    if offered_contract.is_offer_party {
+        &offered_contract.offer_params
+    } else {
+        &accepted_contract.accept_params
+    }
+}
+
+fn get_fund_output_value(contract: &SignedContract) -> u64 {
+    contract
+        .accepted_contract
+        .dlc_transactions
+        .get_fund_output()
+        .value
+}
+
 #[cfg(test)]
 mod test {
-    use dlc_messages::{ChannelMessage, Message, OnChainMessage};
+    use crate::utils::VBYTES_PER_KILO_WEIGHT;
+    use bitcoin::{OutPoint, Script, Transaction};
+    use dlc::FeeConfig;
+    use dlc_messages::{oracle_msgs::OracleAttestation, ChannelMessage, Message, OnChainMessage};
     use mocks::{
-        dlc_manager::{manager::Manager, Oracle},
+        dlc_manager::{
+            contract::Contract,
+            events::Event,
+            manager::{Manager, ManagerConfig},
+            Blockchain, ContractId, FeeBump, Oracle, Storage,
+        },
         memory_storage_provider::MemoryStorage,
         mock_blockchain::{MockBlockchain, MockBroadcaster},
         mock_oracle_provider::MockOracle,
         mock_time::MockTime,
         mock_wallet::MockWallet,
+        test_utils::*,
     };
     use secp256k1_zkp::PublicKey;
     use std::{collections::HashMap, rc::Rc};
 
-    type TestManager = Manager<
-        Rc<MockWallet>,
-        Rc<MockBlockchain<Rc<MockBroadcaster>>>,
-        Rc<MemoryStorage>,
-        Rc<MockOracle>,
-        Rc<MockTime>,
-        Rc<MockBlockchain<Rc<MockBroadcaster>>>,
-    >;
-
     fn get_manager() -> TestManager {
         let blockchain = Rc::new(MockBlockchain::new(Rc::new(MockBroadcaster {})));
         let store = Rc::new(MemoryStorage::new());
@@ -2953,6 +3738,34 @@ mod test {
             .expect_err("To reject the second offer message");
     }
 
+    #[test]
+    fn reject_invalid_manager_config() {
+        let invalid_configs = [
+            ManagerConfig {
+                refund_delay: u32::MAX,
+                ..Default::default()
+            },
+            ManagerConfig {
+                cet_nsequence: 0,
+                ..Default::default()
+            },
+            ManagerConfig {
+                cet_nsequence: u16::MAX as u32 + 1,
+                ..Default::default()
+            },
+        ];
+
+        for config in invalid_configs.iter() {
+            config
+                .validate()
+                .expect_err("To reject the invalid configuration");
+        }
+
+        ManagerConfig::default()
+            .validate()
+            .expect("To accept the default configuration");
+    }
+
     #[test]
     fn reject_channel_offer_with_existing_channel_id() {
         let offer_message = Message::Channel(ChannelMessage::Offer(
@@ -2969,4 +3782,503 @@ mod test {
             .on_dlc_message(&offer_message, pubkey())
             .expect_err("To reject the second offer message");
     }
+
+    /// Closes the given contract with a CET broadcast by `alice` at maturity, and
+    /// returns the attestations that `bob` recovered when seeing it on chain.
+    fn close_with_counter_party_cet(
+        blockchain: &MockBlockchain<Rc<MockBroadcaster>>,
+        alice: &TestManager,
+        bob: &TestManager,
+        contract_id: ContractId,
+    ) -> Option<Vec<OracleAttestation>> {
+        mocks::mock_time::set_time(EVENT_MATURITY as u64);
+        periodic_check(&[alice]);
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[bob]);
+
+        match bob.get_store().get_contract(&contract_id).unwrap() {
+            Some(Contract::Closed(c)) => c.attestations,
+            c => panic!("Unexpected contract state {:?}", c),
+        }
+    }
+
+    #[test]
+    fn recover_single_attestation_from_counter_party_cet() {
+        // Bob's view of the oracles does not have their attestations, which can
+        // thus only be recovered from the CET.
+        let bob_oracles: Vec<_> = (0..2).map(|_| get_oracle(None)).collect();
+        let alice_oracles: Vec<_> = bob_oracles
+            .iter()
+            .map(|x| {
+                let mut oracle = (**x).clone();
+                oracle.add_attestation(EVENT_ID, &["a".to_string()]);
+                Rc::new(oracle)
+            })
+            .collect();
+        let (blockchain, alice, bob) =
+            get_counter_parties_with_oracles(&alice_oracles, &bob_oracles);
+        let contract_input = get_enum_contract_input(&bob_oracles, 1);
+        let contract_id = get_confirmed_contract(&blockchain, &alice, &bob, &contract_input);
+
+        let attestations = close_with_counter_party_cet(&blockchain, &alice, &bob, contract_id);
+
+        assert_eq!(
+            Some(vec![alice_oracles[0].get_attestation(EVENT_ID).unwrap()]),
+            attestations
+        );
+    }
+
+    #[test]
+    fn recover_multi_oracle_attestations_from_counter_party_cet() {
+        let oracles: Vec<_> = (0..3).map(|_| get_oracle(Some("a"))).collect();
+        let (blockchain, alice, bob) = get_counter_parties_with_oracles(&oracles, &oracles);
+        let contract_input = get_enum_contract_input(&oracles, 2);
+        let contract_id = get_confirmed_contract(&blockchain, &alice, &bob, &contract_input);
+
+        let attestations = close_with_counter_party_cet(&blockchain, &alice, &bob, contract_id);
+
+        // Alice used the attestations of the first two oracles.
+        let expected = oracles[..2]
+            .iter()
+            .map(|x| x.get_attestation(EVENT_ID).unwrap())
+            .collect();
+        assert_eq!(Some(expected), attestations);
+    }
+
+    #[test]
+    fn recover_numerical_attestation_from_counter_party_cet() {
+        let oracle = get_numerical_oracle(10);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle);
+        let contract_input = get_numerical_contract_input(&oracle);
+        let contract_id = get_confirmed_contract(&blockchain, &alice, &bob, &contract_input);
+
+        let attestations = close_with_counter_party_cet(&blockchain, &alice, &bob, contract_id);
+
+        assert_eq!(
+            Some(vec![oracle.get_attestation(EVENT_ID).unwrap()]),
+            attestations
+        );
+    }
+
+    #[test]
+    fn adaptor_points_are_persisted_and_pruned_on_close() {
+        let oracle = get_numerical_oracle(10);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle);
+        let contract_input = get_numerical_contract_input(&oracle);
+        let contract_id = get_confirmed_contract(&blockchain, &alice, &bob, &contract_input);
+
+        for manager in [&alice, &bob] {
+            assert!(!manager.get_store().get_adaptor_points().unwrap().is_empty());
+        }
+
+        close_with_counter_party_cet(&blockchain, &alice, &bob, contract_id);
+        periodic_check(&[&alice, &bob]);
+
+        for manager in [&alice, &bob] {
+            assert!(manager.get_store().get_adaptor_points().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn contract_execution_events() {
+        let oracle = get_oracle(Some("a"));
+        let (blockchain, alice, bob) = get_counter_parties(&oracle);
+        let contract_id =
+            get_confirmed_contract(&blockchain, &alice, &bob, &get_contract_input(&oracle));
+
+        mocks::mock_time::set_time(EVENT_MATURITY as u64);
+        periodic_check(&[&alice]);
+        let cet = blockchain.get_sent_transactions().pop().unwrap();
+        assert_events(
+            &alice,
+            &[Event::CetBroadcast {
+                contract_id,
+                txid: cet.txid(),
+            }],
+        );
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        // Bob learns about the CET through the spending of the funding output.
+        periodic_check(&[&alice, &bob]);
+        assert_contract_closed_event(&alice, contract_id);
+        assert_contract_closed_event(&bob, contract_id);
+    }
+
+    #[test]
+    fn contract_collaborative_close_events() {
+        let oracle = get_oracle(None);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle);
+        let contract_id =
+            get_confirmed_contract(&blockchain, &alice, &bob, &get_contract_input(&oracle));
+
+        let (close_offer, _) = bob
+            .offer_contract_close(&contract_id, COLLATERAL, 2)
+            .expect("To create a close offer");
+        alice
+            .on_dlc_message(
+                &Message::OnChain(OnChainMessage::CloseOffer(close_offer.clone())),
+                node_id(2),
+            )
+            .expect("To process the close offer");
+        assert_events(
+            &alice,
+            &[Event::ContractCloseOffered {
+                contract_id,
+                counter_party: node_id(2),
+                close_offer: close_offer.clone(),
+            }],
+        );
+
+        let close_accept = alice
+            .accept_contract_close(&close_offer, &node_id(2))
+            .expect("To accept the close offer");
+        let close_tx = blockchain.get_sent_transactions().pop().unwrap();
+        let cet_broadcast = Event::CetBroadcast {
+            contract_id,
+            txid: close_tx.txid(),
+        };
+        assert_events(&alice, &[cet_broadcast.clone()]);
+
+        bob.on_dlc_message(
+            &Message::OnChain(OnChainMessage::CloseAccept(close_accept)),
+            node_id(1),
+        )
+        .expect("To process the close accept");
+        assert_events(&bob, &[cet_broadcast]);
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[&alice, &bob]);
+        assert_contract_closed_event(&alice, contract_id);
+        assert_contract_closed_event(&bob, contract_id);
+    }
+
+    #[test]
+    fn expired_close_offer_is_rejected() {
+        let oracle = get_oracle(None);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle);
+        let contract_id =
+            get_confirmed_contract(&blockchain, &alice, &bob, &get_contract_input(&oracle));
+
+        let (close_offer, _) = bob
+            .offer_contract_close(&contract_id, COLLATERAL, 2)
+            .expect("To create a close offer");
+        alice
+            .on_dlc_message(
+                &Message::OnChain(OnChainMessage::CloseOffer(close_offer.clone())),
+                node_id(2),
+            )
+            .expect("To process the close offer");
+
+        let nb_sent_transactions = blockchain.get_sent_transactions().len();
+        mocks::mock_time::set_time(close_offer.expiry as u64);
+        alice
+            .accept_contract_close(&close_offer, &node_id(2))
+            .expect_err("Expired close offer should not be accepted");
+        alice
+            .on_dlc_message(
+                &Message::OnChain(OnChainMessage::CloseOffer(close_offer)),
+                node_id(2),
+            )
+            .expect_err("Expired close offer should be rejected");
+        assert_eq!(
+            nb_sent_transactions,
+            blockchain.get_sent_transactions().len()
+        );
+        assert!(matches!(
+            alice.get_store().get_contract(&contract_id).unwrap(),
+            Some(Contract::Confirmed(_))
+        ));
+    }
+
+    #[test]
+    fn contract_refund_events() {
+        let oracle = get_oracle(None);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle);
+        let contract_id =
+            get_confirmed_contract(&blockchain, &alice, &bob, &get_contract_input(&oracle));
+
+        mocks::mock_time::set_time(EVENT_MATURITY as u64 + alice.get_config().refund_delay as u64);
+        // The refund transaction is broadcast but only reported once confirmed.
+        periodic_check(&[&alice]);
+        assert_events(&alice, &[]);
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[&alice, &bob]);
+        assert_events(&alice, &[Event::ContractRefunded { contract_id }]);
+        assert_events(&bob, &[Event::ContractRefunded { contract_id }]);
+    }
+
+    #[test]
+    fn prerefunded_contract_rebroadcast_and_refunded() {
+        let oracle = get_oracle(None);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle);
+        let contract_id =
+            get_confirmed_contract(&blockchain, &alice, &bob, &get_contract_input(&oracle));
+
+        mocks::mock_time::set_time(EVENT_MATURITY as u64 + alice.get_config().refund_delay as u64);
+        periodic_check(&[&alice]);
+        let refund = match alice.get_store().get_contract(&contract_id).unwrap() {
+            Some(Contract::PreRefunded(c)) => c.signed_refund,
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+        let refund_txid = refund.txid();
+        let is_broadcast = || {
+            blockchain
+                .get_sent_transactions()
+                .iter()
+                .any(|x| x.txid() == refund_txid)
+        };
+        assert!(is_broadcast());
+        assert_eq!(
+            1,
+            alice.get_store().get_prerefunded_contracts().unwrap().len()
+        );
+
+        // The refund transaction is re-broadcast if dropped from the mempool.
+        blockchain.evict_transaction(&refund_txid);
+        assert!(!is_broadcast());
+        periodic_check(&[&alice]);
+        assert!(is_broadcast());
+        assert_events(&alice, &[]);
+
+        // The contract stays pre-refunded until enough confirmations are reached.
+        blockchain.generate_blocks(NB_CONFIRMATIONS - 1);
+        periodic_check(&[&alice]);
+        assert!(matches!(
+            alice.get_store().get_contract(&contract_id).unwrap(),
+            Some(Contract::PreRefunded(_))
+        ));
+        assert_events(&alice, &[]);
+
+        blockchain.generate_blocks(1);
+        periodic_check(&[&alice]);
+        assert!(matches!(
+            alice.get_store().get_contract(&contract_id).unwrap(),
+            Some(Contract::Refunded(_))
+        ));
+        assert!(alice
+            .get_store()
+            .get_prerefunded_contracts()
+            .unwrap()
+            .is_empty());
+        assert_events(&alice, &[Event::ContractRefunded { contract_id }]);
+    }
+
+    #[test]
+    fn bump_fee_of_unconfirmed_funding_and_cet() {
+        let oracle = get_oracle(Some("a"));
+        let (blockchain, alice, bob) = get_counter_parties_with_config(
+            &[oracle.clone()],
+            &[oracle.clone()],
+            ManagerConfig {
+                nb_confirmations: NB_CONFIRMATIONS,
+                max_fee_rate: 10,
+                ..Default::default()
+            },
+        );
+        let max_fee_rate = 10;
+
+        // Checks that the last sent transaction spends the output of `parent`
+        // paying to `script_pubkey`, that both pay at least `max_fee_rate` and
+        // that the child is recorded in the store.
+        let assert_bumped = |parent: &Transaction, input_value: u64, script_pubkey: &Script| {
+            let child = blockchain.get_sent_transactions().pop().unwrap();
+            let (vout, output) =
+                dlc::util::get_output_for_script_pubkey(parent, script_pubkey).unwrap();
+            assert_eq!(
+                OutPoint {
+                    txid: parent.txid(),
+                    vout: vout as u32
+                },
+                child.input[0].previous_output
+            );
+            let parent_fee = input_value - parent.output.iter().map(|x| x.value).sum::<u64>();
+            let child_fee = output.value - child.output[0].value;
+            let package_fee =
+                dlc::util::weight_to_fee(parent.weight() + child.weight(), max_fee_rate).unwrap();
+            assert!(parent_fee + child_fee >= package_fee);
+            let fee_bump = alice.get_store().get_fee_bump(&parent.txid()).unwrap();
+            assert_eq!(
+                Some(FeeBump {
+                    fee_rate: max_fee_rate,
+                    child_txid: child.txid(),
+                }),
+                fee_bump
+            );
+            child
+        };
+
+        let contract_id = establish_contract(&alice, &bob, &get_contract_input(&oracle));
+        let accepted_contract = match alice.get_store().get_contract(&contract_id).unwrap() {
+            Some(Contract::Signed(c)) => c.accepted_contract,
+            _ => panic!("Expected a signed contract"),
+        };
+        let offer_params = &accepted_contract.offered_contract.offer_params;
+        let dlc_transactions = &accepted_contract.dlc_transactions;
+        let fund_tx = blockchain
+            .get_transaction(&dlc_transactions.fund.txid())
+            .unwrap();
+        let input_value = offer_params.input_amount + accepted_contract.accept_params.input_amount;
+
+        // A first bump to the estimate of 5 sats/vbyte.
+        blockchain.set_est_fee(5 * VBYTES_PER_KILO_WEIGHT);
+        periodic_check(&[&alice]);
+        let first_child = blockchain.get_sent_transactions().pop().unwrap();
+        assert_eq!(fund_tx.txid(), first_child.input[0].previous_output.txid);
+        let fee_bump = alice.get_store().get_fee_bump(&fund_tx.txid()).unwrap();
+        assert_eq!(
+            Some(FeeBump {
+                fee_rate: 5,
+                child_txid: first_child.txid(),
+            }),
+            fee_bump
+        );
+
+        // The estimate of 20 sats/vbyte is capped by `max_fee_rate`, and the
+        // first child is replaced by one paying to the same address.
+        blockchain.set_est_fee(20 * VBYTES_PER_KILO_WEIGHT);
+        periodic_check(&[&alice]);
+        let child = assert_bumped(&fund_tx, input_value, &offer_params.change_script_pubkey);
+        assert_eq!(
+            first_child.output[0].script_pubkey,
+            child.output[0].script_pubkey
+        );
+        assert!(blockchain.get_transaction(&first_child.txid()).is_err());
+
+        // The bump is recorded in the store and not repeated.
+        let nb_sent_transactions = blockchain.get_sent_transactions().len();
+        periodic_check(&[&alice]);
+        assert_eq!(
+            nb_sent_transactions,
+            blockchain.get_sent_transactions().len()
+        );
+
+        // The child is broadcast again if evicted from the mempool.
+        blockchain.evict_transaction(&child.txid());
+        periodic_check(&[&alice]);
+        assert_bumped(&fund_tx, input_value, &offer_params.change_script_pubkey);
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[&alice, &bob]);
+        let fee_bump = alice.get_store().get_fee_bump(&fund_tx.txid()).unwrap();
+        assert_eq!(None, fee_bump);
+
+        mocks::mock_time::set_time(EVENT_MATURITY as u64);
+        periodic_check(&[&alice]);
+        let cet = match alice.get_store().get_contract(&contract_id).unwrap() {
+            Some(Contract::PreClosed(c)) => c.signed_cet,
+            _ => panic!("Expected a pre-closed contract"),
+        };
+        periodic_check(&[&alice]);
+        assert_bumped(
+            &cet,
+            dlc_transactions.get_fund_output().value,
+            &offer_params.payout_script_pubkey,
+        );
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[&alice]);
+        let fee_bump = alice.get_store().get_fee_bump(&cet.txid()).unwrap();
+        assert_eq!(None, fee_bump);
+    }
+
+    #[test]
+    fn channel_events() {
+        let oracle = get_oracle(None);
+        let (blockchain, alice, bob) = get_counter_parties(&oracle);
+
+        let offer = alice
+            .offer_channel(
+                &get_contract_input(&oracle),
+                node_id(2),
+                FeeConfig::EvenSplit,
+                None,
+            )
+            .expect("To create a channel offer");
+        bob.on_dlc_message(
+            &Message::Channel(ChannelMessage::Offer(offer.clone())),
+            node_id(1),
+        )
+        .expect("To process the channel offer");
+        let (accept, channel_id, contract_id, _) = bob
+            .accept_channel(&offer.temporary_channel_id, FeeConfig::EvenSplit)
+            .expect("To accept the channel offer");
+        let sign = alice
+            .on_dlc_message(
+                &Message::Channel(ChannelMessage::Accept(accept)),
+                node_id(2),
+            )
+            .expect("To process the channel accept")
+            .expect("To get a sign message");
+        assert_events(&alice, &[Event::ChannelEstablished { channel_id }]);
+        bob.on_dlc_message(&sign, node_id(1))
+            .expect("To process the channel sign");
+        assert_events(&bob, &[Event::ChannelEstablished { channel_id }]);
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[&alice, &bob]);
+        assert_events(&alice, &[Event::ContractConfirmed { contract_id }]);
+        assert_events(&bob, &[Event::ContractConfirmed { contract_id }]);
+
+        let (settle_offer, _) = alice
+            .settle_offer(&channel_id, COLLATERAL, None)
+            .expect("To create a settle offer");
+        bob.on_dlc_message(
+            &Message::Channel(ChannelMessage::SettleOffer(settle_offer)),
+            node_id(1),
+        )
+        .expect("To process the settle offer");
+        let (settle_accept, _) = bob
+            .accept_settle_offer(&channel_id)
+            .expect("To accept the settle offer");
+        let settle_confirm = alice
+            .on_dlc_message(
+                &Message::Channel(ChannelMessage::SettleAccept(settle_accept)),
+                node_id(2),
+            )
+            .expect("To process the settle accept")
+            .expect("To get a settle confirm message");
+        assert_events(&alice, &[]);
+        let settle_finalize = bob
+            .on_dlc_message(&settle_confirm, node_id(1))
+            .expect("To process the settle confirm")
+            .expect("To get a settle finalize message");
+        let settled_events = [
+            Event::ContractClosed {
+                contract_id,
+                pnl: 0,
+            },
+            Event::ChannelSettled {
+                channel_id,
+                own_payout: COLLATERAL,
+            },
+        ];
+        assert_events(&bob, &settled_events);
+        alice
+            .on_dlc_message(&settle_finalize, node_id(2))
+            .expect("To process the settle finalize");
+        assert_events(&alice, &settled_events);
+
+        let close_offer = alice
+            .offer_collaborative_close(&channel_id, COLLATERAL, None)
+            .expect("To create a collaborative close offer");
+        bob.on_dlc_message(
+            &Message::Channel(ChannelMessage::CollaborativeCloseOffer(close_offer)),
+            node_id(1),
+        )
+        .expect("To process the collaborative close offer");
+        assert_events(&bob, &[]);
+        bob.accept_collaborative_close(&channel_id)
+            .expect("To accept the collaborative close offer");
+        let channel_closed = Event::ChannelClosed {
+            channel_id,
+            closing_txid: blockchain.get_sent_transactions().pop().unwrap().txid(),
+        };
+        assert_events(&bob, &[channel_closed.clone()]);
+
+        blockchain.generate_blocks(NB_CONFIRMATIONS);
+        periodic_check(&[&alice, &bob]);
+        assert_events(&alice, &[channel_closed]);
+        assert_events(&bob, &[]);
+    }
 }
@@ -0,0 +1,812 @@
+//! #Builders of payout functions for common financial products.
+//!
+//! Payouts are expressed in satoshis from the point of view of the offer party,
+//! and outcomes are prices (for example in USD per BTC) in the range
+//! `[0, max_outcome]` that the oracle(s) can attest to.
+
+use super::{
+    Evaluable, HyperbolaPayoutCurvePiece, PayoutFunction, PayoutFunctionPiece, PayoutPoint,
+    PolynomialPayoutCurvePiece, RoundingInterval, RoundingIntervals,
+};
+use crate::error::Error;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// An inverse perpetual swap, where the collateral is denominated in bitcoin and
+/// the contract size in the currency of the price, such that the long party
+/// gains `notional * (1 - entry_price / price)` satoshis.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct InversePerpetual {
+    /// The price at which the position is opened.
+    pub entry_price: u64,
+    /// The numerator of the leverage of the offer party, such that the notional
+    /// of the contract is `offer_collateral * leverage_numerator /
+    /// leverage_denominator` satoshis (rounded down). The leverage of the accept
+    /// party is implied by its collateral. Leverage is expressed as a fraction of
+    /// integers so that both parties derive the exact same payout curve.
+    pub leverage_numerator: u64,
+    /// The denominator of the leverage of the offer party.
+    pub leverage_denominator: u64,
+    /// The collateral of the offer party.
+    pub offer_collateral: u64,
+    /// The collateral of the accept party.
+    pub accept_collateral: u64,
+    /// Whether the offer party is long (or short otherwise).
+    pub offer_is_long: bool,
+}
+
+impl InversePerpetual {
+    /// Returns the payout function and rounding intervals of the contract.
+    pub fn build(
+        &self,
+        max_outcome: u64,
+        rounding_mod: u64,
+    ) -> Result<(PayoutFunction, RoundingIntervals), Error> {
+        let total_collateral = get_total_collateral(self.offer_collateral, self.accept_collateral)?;
+        if self.entry_price == 0 || self.entry_price >= max_outcome {
+            return Err(Error::InvalidParameters(
+                "Entry price must be within the outcome range.".to_string(),
+            ));
+        }
+        let notional = match self.get_notional() {
+            Some(notional) if notional > 0 => notional,
+            _ => {
+                return Err(Error::InvalidParameters(
+                    "Leverage must be strictly positive and keep the notional within range."
+                        .to_string(),
+                ))
+            }
+        };
+
+        let (long_collateral, short_collateral) = if self.offer_is_long {
+            (self.offer_collateral, self.accept_collateral)
+        } else {
+            (self.accept_collateral, self.offer_collateral)
+        };
+        if long_collateral == 0 || short_collateral == 0 {
+            return Err(Error::InvalidParameters(
+                "Both parties must provide collateral.".to_string(),
+            ));
+        }
+
+        // The payout of the long party is `long_collateral + notional - notional * entry_price / price`.
+        let notional = notional as f64;
+        let translate_payout = long_collateral as f64 + notional;
+        let numerator = -notional * self.entry_price as f64;
+        let long_payout = |outcome: u64| inverse_evaluate(translate_payout, numerator, outcome);
+
+        // Price under which the long party is liquidated.
+        let mut start = f64::ceil(notional * self.entry_price as f64 / translate_payout) as u64;
+        while start < max_outcome
+            && !is_payout_in_range(translate_payout, numerator, start, total_collateral)
+        {
+            start += 1;
+        }
+
+        // Price above which the short party is liquidated, if any.
+        let mut end = if translate_payout > total_collateral as f64 {
+            let short_liquidation_price = -numerator / (translate_payout - total_collateral as f64);
+            u64::min(short_liquidation_price.floor() as u64, max_outcome)
+        } else {
+            max_outcome
+        };
+        while end > start && !is_payout_in_range(translate_payout, numerator, end, total_collateral)
+        {
+            end -= 1;
+        }
+
+        if start >= end {
+            return Err(Error::InvalidParameters(
+                "Leverage too high for the given collaterals and entry price.".to_string(),
+            ));
+        }
+
+        let mut builder = PiecesBuilder::new(total_collateral, !self.offer_is_long, 0);
+        builder.constant(start)?;
+        let end_payout = if end < max_outcome {
+            total_collateral
+        } else {
+            clamp_payout(long_payout(end), total_collateral)
+        };
+        builder.inverse(end, end_payout, translate_payout, numerator)?;
+        builder.constant(max_outcome)?;
+
+        builder.build(max_outcome, rounding_mod)
+    }
+
+    /// Returns the notional of the contract in satoshis, or `None` if the
+    /// leverage denominator is zero or the notional does not fit in a `u64`.
+    fn get_notional(&self) -> Option<u64> {
+        if self.leverage_denominator == 0 {
+            return None;
+        }
+        let notional = self.offer_collateral as u128 * self.leverage_numerator as u128
+            / self.leverage_denominator as u128;
+        u64::try_from(notional).ok()
+    }
+}
+
+/// A covered call, where the seller locks bitcoin as collateral and the buyer
+/// the premium, and the buyer receives the value above the strike price in
+/// bitcoin at maturity.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct CoveredCall {
+    /// The strike price of the option.
+    pub strike_price: u64,
+    /// The collateral of the offer party.
+    pub offer_collateral: u64,
+    /// The collateral of the accept party.
+    pub accept_collateral: u64,
+    /// Whether the offer party is the seller of the option (or the buyer
+    /// otherwise). The collateral of the buyer is the premium paid to the seller.
+    pub offer_is_seller: bool,
+}
+
+impl CoveredCall {
+    /// Returns the payout function and rounding intervals of the contract.
+    pub fn build(
+        &self,
+        max_outcome: u64,
+        rounding_mod: u64,
+    ) -> Result<(PayoutFunction, RoundingIntervals), Error> {
+        let total_collateral = get_total_collateral(self.offer_collateral, self.accept_collateral)?;
+        if self.strike_price == 0 || self.strike_price >= max_outcome {
+            return Err(Error::InvalidParameters(
+                "Strike price must be within the outcome range.".to_string(),
+            ));
+        }
+
+        let (seller_collateral, premium) = if self.offer_is_seller {
+            (self.offer_collateral, self.accept_collateral)
+        } else {
+            (self.accept_collateral, self.offer_collateral)
+        };
+        if seller_collateral == 0 {
+            return Err(Error::InvalidParameters(
+                "Seller must provide collateral.".to_string(),
+            ));
+        }
+
+        // Above the strike, the payout of the seller is `premium + seller_collateral * strike_price / price`.
+        let translate_payout = premium as f64;
+        let numerator = seller_collateral as f64 * self.strike_price as f64;
+        let seller_payout = |outcome: u64| inverse_evaluate(translate_payout, numerator, outcome);
+
+        let mut builder =
+            PiecesBuilder::new(total_collateral, !self.offer_is_seller, total_collateral);
+        builder.constant(self.strike_price)?;
+        builder.inverse(
+            max_outcome,
+            clamp_payout(seller_payout(max_outcome), total_collateral),
+            translate_payout,
+            numerator,
+        )?;
+
+        builder.build(max_outcome, rounding_mod)
+    }
+}
+
+/// A binary option, paying the total collateral to one party if the price is
+/// at or above the strike price and to the other party otherwise.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct BinaryOption {
+    /// The strike price of the option.
+    pub strike_price: u64,
+    /// The collateral of the offer party.
+    pub offer_collateral: u64,
+    /// The collateral of the accept party.
+    pub accept_collateral: u64,
+    /// Whether the offer party wins if the price is at or above the strike
+    /// price (or below it otherwise).
+    pub offer_wins_above: bool,
+}
+
+impl BinaryOption {
+    /// Returns the payout function and rounding intervals of the contract.
+    pub fn build(
+        &self,
+        max_outcome: u64,
+        rounding_mod: u64,
+    ) -> Result<(PayoutFunction, RoundingIntervals), Error> {
+        let total_collateral = get_total_collateral(self.offer_collateral, self.accept_collateral)?;
+        if self.strike_price == 0 || self.strike_price > max_outcome {
+            return Err(Error::InvalidParameters(
+                "Strike price must be within the outcome range.".to_string(),
+            ));
+        }
+
+        let mut builder = PiecesBuilder::new(total_collateral, !self.offer_wins_above, 0);
+        builder.constant(self.strike_price - 1)?;
+        builder.linear(self.strike_price, total_collateral)?;
+        builder.constant(max_outcome)?;
+
+        builder.build(max_outcome, rounding_mod)
+    }
+}
+
+/// A collar, protecting the holder of bitcoin against a price drop below the
+/// lower strike price in exchange for giving up the gains above the upper
+/// strike price. The holder keeps the value of its collateral at the lower
+/// strike price under it, as far as the collateral of the counter party allows,
+/// and at the upper strike price above it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct Collar {
+    /// The price under which the holder is protected.
+    pub lower_strike_price: u64,
+    /// The price above which the gains of the holder are given up.
+    pub upper_strike_price: u64,
+    /// The collateral of the offer party.
+    pub offer_collateral: u64,
+    /// The collateral of the accept party.
+    pub accept_collateral: u64,
+    /// Whether the offer party is the holder (or its counter party otherwise).
+    pub offer_is_holder: bool,
+}
+
+impl Collar {
+    /// Returns the payout function and rounding intervals of the contract.
+    pub fn build(
+        &self,
+        max_outcome: u64,
+        rounding_mod: u64,
+    ) -> Result<(PayoutFunction, RoundingIntervals), Error> {
+        let total_collateral = get_total_collateral(self.offer_collateral, self.accept_collateral)?;
+        if self.lower_strike_price == 0
+            || self.lower_strike_price >= self.upper_strike_price
+            || self.upper_strike_price >= max_outcome
+        {
+            return Err(Error::InvalidParameters(
+                "Strike prices must be increasing and within the outcome range.".to_string(),
+            ));
+        }
+
+        let holder_collateral = if self.offer_is_holder {
+            self.offer_collateral
+        } else {
+            self.accept_collateral
+        };
+        if holder_collateral == 0 {
+            return Err(Error::InvalidParameters(
+                "Holder must provide collateral.".to_string(),
+            ));
+        }
+
+        // The payout of the holder is `holder_collateral * strike_price / price`
+        // outside of the strike prices.
+        let lower_numerator = holder_collateral as f64 * self.lower_strike_price as f64;
+        let upper_numerator = holder_collateral as f64 * self.upper_strike_price as f64;
+
+        // Price under which the counter party collateral is exhausted.
+        let mut start = f64::ceil(lower_numerator / total_collateral as f64) as u64;
+        while start < self.lower_strike_price
+            && !is_payout_in_range(0.0, lower_numerator, start, total_collateral)
+        {
+            start += 1;
+        }
+
+        if start >= self.lower_strike_price {
+            return Err(Error::InvalidParameters(
+                "Collateral too low to provide protection under the lower strike price."
+                    .to_string(),
+            ));
+        }
+
+        let mut builder =
+            PiecesBuilder::new(total_collateral, !self.offer_is_holder, total_collateral);
+        builder.constant(start)?;
+        builder.inverse(
+            self.lower_strike_price,
+            holder_collateral,
+            0.0,
+            lower_numerator,
+        )?;
+        builder.constant(self.upper_strike_price)?;
+        builder.inverse(
+            max_outcome,
+            clamp_payout(
+                inverse_evaluate(0.0, upper_numerator, max_outcome),
+                total_collateral,
+            ),
+            0.0,
+            upper_numerator,
+        )?;
+
+        builder.build(max_outcome, rounding_mod)
+    }
+}
+
+fn get_total_collateral(offer_collateral: u64, accept_collateral: u64) -> Result<u64, Error> {
+    match offer_collateral.checked_add(accept_collateral) {
+        Some(total) if total > 0 => Ok(total),
+        _ => Err(Error::InvalidParameters(
+            "Invalid total collateral.".to_string(),
+        )),
+    }
+}
+
+/// Evaluates `translate_payout + numerator / outcome` the same way as
+/// [`HyperbolaPayoutCurvePiece`] created by [`PiecesBuilder::inverse`] does.
+fn inverse_evaluate(translate_payout: f64, numerator: f64, outcome: u64) -> f64 {
+    inverse_piece(0, 0, outcome.max(1), 0, translate_payout, numerator)
+        .map(|piece| piece.evaluate(outcome))
+        .unwrap_or(f64::NAN)
+}
+
+/// Returns whether the payout at `outcome` is within `[0, total_collateral]` as
+/// evaluated from the point of view of both parties.
+fn is_payout_in_range(
+    translate_payout: f64,
+    numerator: f64,
+    outcome: u64,
+    total_collateral: u64,
+) -> bool {
+    let is_in_range = |translate_payout: f64, numerator: f64| {
+        inverse_piece(0, 0, outcome.max(1), 0, translate_payout, numerator)
+            .map(|piece| is_piece_in_range(&piece, outcome, total_collateral))
+            .unwrap_or(false)
+    };
+    is_in_range(translate_payout, numerator)
+        && is_in_range(total_collateral as f64 - translate_payout, -numerator)
+}
+
+/// Returns whether the payout of the piece at `outcome` is within
+/// `[0, total_collateral]` with any [`super::EvaluationMode`]. Parameters that
+/// cannot be evaluated exactly are rejected, as they cannot be used with the
+/// default [`super::EvaluationMode::Exact`].
+fn is_piece_in_range(
+    piece: &HyperbolaPayoutCurvePiece,
+    outcome: u64,
+    total_collateral: u64,
+) -> bool {
+    let float_payout = piece.evaluate(outcome);
+    let is_float_in_range = !float_payout.is_nan()
+        && !float_payout.is_sign_negative()
+        && float_payout.round() <= total_collateral as f64;
+    let is_exact_in_range = match piece.evaluate_exact(outcome) {
+        Some(payout) => {
+            !payout.is_negative()
+                && matches!(payout.round_to_multiple(1), Some(p) if p <= total_collateral as i128)
+        }
+        None => false,
+    };
+    is_float_in_range && is_exact_in_range
+}
+
+fn inverse_piece(
+    start: u64,
+    start_payout: u64,
+    end: u64,
+    end_payout: u64,
+    translate_payout: f64,
+    numerator: f64,
+) -> Result<HyperbolaPayoutCurvePiece, Error> {
+    HyperbolaPayoutCurvePiece::new(
+        get_point(start, start_payout),
+        get_point(end, end_payout),
+        true,
+        0.0,
+        translate_payout,
+        1.0,
+        0.0,
+        0.0,
+        numerator,
+    )
+}
+
+fn clamp_payout(payout: f64, total_collateral: u64) -> u64 {
+    if payout.is_nan() || payout <= 0.0 {
+        0
+    } else {
+        u64::min(payout.floor() as u64, total_collateral)
+    }
+}
+
+fn get_point(event_outcome: u64, outcome_payout: u64) -> PayoutPoint {
+    PayoutPoint {
+        event_outcome,
+        outcome_payout,
+        extra_precision: 0,
+    }
+}
+
+/// Helper to assemble continuous payout function pieces from left to right.
+/// Payouts are given from the point of view of one of the parties, and are
+/// converted to the ones of the other party if `complement` is set.
+struct PiecesBuilder {
+    total_collateral: u64,
+    complement: bool,
+    last_point: PayoutPoint,
+    pieces: Vec<PayoutFunctionPiece>,
+}
+
+impl PiecesBuilder {
+    fn new(total_collateral: u64, complement: bool, start_payout: u64) -> Self {
+        let mut builder = PiecesBuilder {
+            total_collateral,
+            complement,
+            last_point: get_point(0, 0),
+            pieces: Vec::new(),
+        };
+        builder.last_point.outcome_payout = builder.convert_payout(start_payout);
+        builder
+    }
+
+    fn convert_payout(&self, payout: u64) -> u64 {
+        if self.complement {
+            self.total_collateral - payout
+        } else {
+            payout
+        }
+    }
+
+    fn push_polynomial(&mut self, end: u64, end_payout: u64) -> Result<(), Error> {
+        let end_point = get_point(end, self.convert_payout(end_payout));
+        let piece =
+            PolynomialPayoutCurvePiece::new(vec![self.last_point.clone(), end_point.clone()])?;
+        self.pieces
+            .push(PayoutFunctionPiece::PolynomialPayoutCurvePiece(piece));
+        self.last_point = end_point;
+        Ok(())
+    }
+
+    /// Keeps the current payout up to `end`.
+    fn constant(&mut self, end: u64) -> Result<(), Error> {
+        if end == self.last_point.event_outcome {
+            return Ok(());
+        }
+        let payout = self.convert_payout(self.last_point.outcome_payout);
+        self.push_polynomial(end, payout)
+    }
+
+    /// Linearly interpolates the payout up to `end_payout` at `end`.
+    fn linear(&mut self, end: u64, end_payout: u64) -> Result<(), Error> {
+        self.push_polynomial(end, end_payout)
+    }
+
+    /// Adds a piece with payout `translate_payout + numerator / outcome` up to
+    /// `end`, whose payout at `end` is expected to be `end_payout` once rounded.
+    fn inverse(
+        &mut self,
+        end: u64,
+        end_payout: u64,
+        translate_payout: f64,
+        numerator: f64,
+    ) -> Result<(), Error> {
+        let start = self.last_point.event_outcome;
+        if start == 0 {
+            return Err(Error::InvalidParameters(
+                "Inverse payout curve cannot start at zero.".to_string(),
+            ));
+        }
+
+        let (translate_payout, numerator) = if self.complement {
+            (self.total_collateral as f64 - translate_payout, -numerator)
+        } else {
+            (translate_payout, numerator)
+        };
+        let piece = inverse_piece(
+            start,
+            self.last_point.outcome_payout,
+            end,
+            self.convert_payout(end_payout),
+            translate_payout,
+            numerator,
+        )?;
+
+        if !is_piece_in_range(&piece, start, self.total_collateral)
+            || !is_piece_in_range(&piece, end, self.total_collateral)
+        {
+            return Err(Error::InvalidParameters(
+                "Payout curve is out of the collateral range.".to_string(),
+            ));
+        }
+
+        self.last_point = piece.right_end_point.clone();
+        self.pieces
+            .push(PayoutFunctionPiece::HyperbolaPayoutCurvePiece(piece));
+        Ok(())
+    }
+
+    fn build(
+        self,
+        max_outcome: u64,
+        rounding_mod: u64,
+    ) -> Result<(PayoutFunction, RoundingIntervals), Error> {
+        let payout_function = PayoutFunction::new(self.pieces)?;
+        payout_function.validate(max_outcome)?;
+
+        if rounding_mod == 0 {
+            return Err(Error::InvalidParameters(
+                "Rounding modulus must be strictly positive.".to_string(),
+            ));
+        }
+        let rounding_intervals = RoundingIntervals {
+            intervals: vec![RoundingInterval {
+                begin_interval: 0,
+                rounding_mod,
+            }],
+        };
+        rounding_intervals.validate()?;
+
+        Ok((payout_function, rounding_intervals))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use dlc::RangePayout;
+    use proptest::prelude::*;
+
+    const MAX_OUTCOME: u64 = (1 << 15) - 1;
+    /// Computing the range payouts over all the outcomes is slow, so only a few
+    /// cases are generated for each property.
+    const NB_CASES: u32 = 20;
+
+    fn get_range_payouts(
+        function: &(PayoutFunction, RoundingIntervals),
+        total_collateral: u64,
+    ) -> Vec<RangePayout> {
+        let range_payouts = function
+            .0
+            .to_range_payouts(total_collateral, &function.1)
+            .expect("to be able to compute the range payouts");
+
+        // Payouts cover all the outcomes and stay within the total collateral.
+        let mut next_start = 0;
+        for range_payout in &range_payouts {
+            assert_eq!(next_start, range_payout.start);
+            assert!(range_payout.payout.offer <= total_collateral);
+            assert_eq!(
+                total_collateral,
+                range_payout.payout.offer + range_payout.payout.accept
+            );
+            next_start += range_payout.count;
+        }
+        assert_eq!(MAX_OUTCOME as usize + 1, next_start);
+
+        range_payouts
+    }
+
+    fn get_offer_payout(range_payouts: &[RangePayout], outcome: u64) -> u64 {
+        range_payouts
+            .iter()
+            .find(|x| x.start <= outcome as usize && (outcome as usize) < x.start + x.count)
+            .expect("to find a range for the outcome")
+            .payout
+            .offer
+    }
+
+    #[test]
+    fn inverse_perpetual_pays_back_collateral_at_entry_price() {
+        let perpetual = InversePerpetual {
+            entry_price: 10_000,
+            leverage_numerator: 2,
+            leverage_denominator: 1,
+            offer_collateral: 1_000_000,
+            accept_collateral: 2_000_000,
+            offer_is_long: true,
+        };
+        let function = perpetual.build(MAX_OUTCOME, 1).unwrap();
+        let range_payouts = get_range_payouts(&function, 3_000_000);
+
+        assert_eq!(1_000_000, get_offer_payout(&range_payouts, 10_000));
+        // Long liquidated at 10_000 * 2 / 3.
+        assert_eq!(0, get_offer_payout(&range_payouts, 6_666));
+        // Short has 1x leverage and is never liquidated.
+        assert!(get_offer_payout(&range_payouts, MAX_OUTCOME) < 3_000_000);
+
+        // As a short, the long is liquidated at 10_000 * 2 / 4 and the offer
+        // party at 10_000 * 2 / 1.
+        let short = InversePerpetual {
+            offer_is_long: false,
+            ..perpetual
+        };
+        let function = short.build(MAX_OUTCOME, 1).unwrap();
+        let range_payouts = get_range_payouts(&function, 3_000_000);
+        assert_eq!(1_000_000, get_offer_payout(&range_payouts, 10_000));
+        assert_eq!(3_000_000, get_offer_payout(&range_payouts, 4_999));
+        assert_eq!(0, get_offer_payout(&range_payouts, 20_001));
+    }
+
+    #[test]
+    fn collar_keeps_holder_collateral_between_strikes() {
+        let collar = Collar {
+            lower_strike_price: 8_000,
+            upper_strike_price: 12_000,
+            offer_collateral: 1_000_000,
+            accept_collateral: 1_000_000,
+            offer_is_holder: true,
+        };
+        let function = collar.build(MAX_OUTCOME, 1).unwrap();
+        let range_payouts = get_range_payouts(&function, 2_000_000);
+
+        assert_eq!(1_000_000, get_offer_payout(&range_payouts, 10_000));
+        assert_eq!(2_000_000, get_offer_payout(&range_payouts, 4_000));
+        assert_eq!(500_000, get_offer_payout(&range_payouts, 24_000));
+    }
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        assert!(BinaryOption {
+            strike_price: MAX_OUTCOME + 1,
+            offer_collateral: 1,
+            accept_collateral: 1,
+            offer_wins_above: true,
+        }
+        .build(MAX_OUTCOME, 1)
+        .is_err());
+        assert!(Collar {
+            lower_strike_price: 10_000,
+            upper_strike_price: 10_000,
+            offer_collateral: 1,
+            accept_collateral: 1,
+            offer_is_holder: true,
+        }
+        .build(MAX_OUTCOME, 1)
+        .is_err());
+        assert!(InversePerpetual {
+            entry_price: 10_000,
+            leverage_numerator: 0,
+            leverage_denominator: 1,
+            offer_collateral: 1,
+            accept_collateral: 1,
+            offer_is_long: true,
+        }
+        .build(MAX_OUTCOME, 1)
+        .is_err());
+        assert!(InversePerpetual {
+            entry_price: 10_000,
+            leverage_numerator: 1,
+            leverage_denominator: 0,
+            offer_collateral: 1,
+            accept_collateral: 1,
+            offer_is_long: true,
+        }
+        .build(MAX_OUTCOME, 1)
+        .is_err());
+        assert!(CoveredCall {
+            strike_price: 10_000,
+            offer_collateral: 1,
+            accept_collateral: 1,
+            offer_is_seller: true,
+        }
+        .build(MAX_OUTCOME, 0)
+        .is_err());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(NB_CASES))]
+
+        #[test]
+        fn inverse_perpetual_payouts_in_collateral_range(
+            offer_leverage_percent in 100u64..500,
+            accept_leverage_percent in 100u64..500,
+            offer_collateral in 10_000u64..10_000_000,
+            entry_price in 1_000u64..20_000,
+            offer_is_long in any::<bool>(),
+            rounding_mod in 1u64..1_000,
+        ) {
+            let accept_collateral =
+                offer_collateral * offer_leverage_percent / accept_leverage_percent;
+            let perpetual = InversePerpetual {
+                entry_price,
+                leverage_numerator: offer_leverage_percent,
+                leverage_denominator: 100,
+                offer_collateral,
+                accept_collateral,
+                offer_is_long,
+            };
+
+            let function = perpetual
+                .build(MAX_OUTCOME, rounding_mod)
+                .unwrap_or_else(|e| panic!("Could not build {:?}: {}", perpetual, e));
+            get_range_payouts(&function, offer_collateral + accept_collateral);
+        }
+
+        #[test]
+        fn covered_call_payouts_in_collateral_range(
+            strike_price in 1..MAX_OUTCOME,
+            offer_collateral in 1u64..10_000_000,
+            accept_collateral in 1u64..10_000_000,
+            offer_is_seller in any::<bool>(),
+            rounding_mod in 1u64..1_000,
+        ) {
+            let call = CoveredCall {
+                strike_price,
+                offer_collateral,
+                accept_collateral,
+                offer_is_seller,
+            };
+
+            let function = call
+                .build(MAX_OUTCOME, rounding_mod)
+                .unwrap_or_else(|e| panic!("Could not build {:?}: {}", call, e));
+            let total_collateral = offer_collateral + accept_collateral;
+            let range_payouts = get_range_payouts(&function, total_collateral);
+
+            let seller_payout = get_offer_payout(&range_payouts, strike_price / 2);
+            prop_assert_eq!(if offer_is_seller { total_collateral } else { 0 }, seller_payout);
+        }
+
+        #[test]
+        fn binary_option_payouts_in_collateral_range(
+            strike_price in 1..=MAX_OUTCOME,
+            offer_collateral in 0u64..10_000_000,
+            accept_collateral in 1u64..10_000_000,
+            offer_wins_above in any::<bool>(),
+        ) {
+            let option = BinaryOption {
+                strike_price,
+                offer_collateral,
+                accept_collateral,
+                offer_wins_above,
+            };
+            let total_collateral = offer_collateral + accept_collateral;
+
+            let function = option
+                .build(MAX_OUTCOME, 1)
+                .unwrap_or_else(|e| panic!("Could not build {:?}: {}", option, e));
+            let range_payouts = get_range_payouts(&function, total_collateral);
+
+            let (above, below) = if offer_wins_above {
+                (total_collateral, 0)
+            } else {
+                (0, total_collateral)
+            };
+            prop_assert_eq!(above, get_offer_payout(&range_payouts, strike_price));
+            prop_assert_eq!(below, get_offer_payout(&range_payouts, strike_price - 1));
+        }
+
+        #[test]
+        fn collar_payouts_in_collateral_range(
+            lower_strike_price in 1_000u64..10_000,
+            strike_price_spread in 1..MAX_OUTCOME - 10_000,
+            holder_collateral in 10_000u64..10_000_000,
+            // Enough collateral to protect down to half the lower strike price.
+            extra_counter_collateral in 0u64..10_000_000,
+            offer_is_holder in any::<bool>(),
+            rounding_mod in 1u64..1_000,
+        ) {
+            let counter_collateral = holder_collateral + extra_counter_collateral;
+            let (offer_collateral, accept_collateral) = if offer_is_holder {
+                (holder_collateral, counter_collateral)
+            } else {
+                (counter_collateral, holder_collateral)
+            };
+            let collar = Collar {
+                lower_strike_price,
+                upper_strike_price: lower_strike_price + strike_price_spread,
+                offer_collateral,
+                accept_collateral,
+                offer_is_holder,
+            };
+
+            let function = collar
+                .build(MAX_OUTCOME, rounding_mod)
+                .unwrap_or_else(|e| panic!("Could not build {:?}: {}", collar, e));
+            get_range_payouts(&function, offer_collateral + accept_collateral);
+        }
+    }
+}
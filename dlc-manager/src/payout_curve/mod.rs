@@ -1,5 +1,6 @@
 //! #PayoutFunction
 
+use std::convert::TryFrom;
 use std::ops::Deref;
 
 use crate::error::Error;
@@ -7,6 +8,35 @@ use dlc::{Payout, RangePayout};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub mod builders;
+mod rational;
+pub mod rounding;
+
+use self::rational::Rational;
+
+/// The arithmetic used to evaluate a payout function when computing the payouts
+/// of each outcome.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum EvaluationMode {
+    /// Payouts are evaluated and rounded using `f64` arithmetic. Kept to
+    /// reproduce the payouts computed by earlier versions of this library, which
+    /// can differ from the exact ones for outcomes close to a rounding boundary.
+    Float,
+    /// Payouts are evaluated and rounded using exact rational arithmetic, so
+    /// that any implementation computes the same payouts for a given function.
+    /// Hyperbola pieces with a non zero `b` parameter require a square root and
+    /// are evaluated using the (correctly rounded) `f64` operations instead.
+    /// An error is returned if a payout cannot be computed exactly. This is the
+    /// default, as agreeing on the set of CETs requires both parties to compute
+    /// the same payouts whatever their platform.
+    Exact,
+}
+
 /// Contains information to compute the set of payouts based on the outcomes.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(
@@ -53,35 +83,13 @@ impl PayoutFunction {
                 .payout_function_pieces
                 .first()
                 .expect("to have at least one piece");
-            let starts_at_zero = match first {
-                PayoutFunctionPiece::PolynomialPayoutCurvePiece(p) => {
-                    p.payout_points
-                        .first()
-                        .expect("to have at least a point")
-                        .event_outcome
-                        == 0
-                }
-                PayoutFunctionPiece::HyperbolaPayoutCurvePiece(h) => {
-                    h.left_end_point.event_outcome == 0
-                }
-            };
+            let starts_at_zero = first.get_first_point().event_outcome == 0;
 
             let last = self
                 .payout_function_pieces
                 .last()
                 .expect("to have at least one piece");
-            let finishes_at_max = match last {
-                PayoutFunctionPiece::PolynomialPayoutCurvePiece(p) => {
-                    p.payout_points
-                        .last()
-                        .expect("to have at least a point")
-                        .event_outcome
-                        == max_value
-                }
-                PayoutFunctionPiece::HyperbolaPayoutCurvePiece(h) => {
-                    h.right_end_point.event_outcome == max_value
-                }
-            };
+            let finishes_at_max = last.get_last_point().event_outcome == max_value;
 
             starts_at_zero && finishes_at_max
         };
@@ -95,15 +103,33 @@ impl PayoutFunction {
         }
     }
 
-    /// Generate the range payouts from the function.
+    /// Generate the range payouts from the function, using
+    /// [`EvaluationMode::Exact`].
     pub fn to_range_payouts(
         &self,
         total_collateral: u64,
         rounding_intervals: &RoundingIntervals,
+    ) -> Result<Vec<RangePayout>, Error> {
+        self.to_range_payouts_with_mode(total_collateral, rounding_intervals, EvaluationMode::Exact)
+    }
+
+    /// Generate the range payouts from the function using the given evaluation
+    /// mode. Both parties to a contract must use the same mode, otherwise they
+    /// can compute different sets of CETs.
+    pub fn to_range_payouts_with_mode(
+        &self,
+        total_collateral: u64,
+        rounding_intervals: &RoundingIntervals,
+        mode: EvaluationMode,
     ) -> Result<Vec<RangePayout>, Error> {
         let mut range_payouts = Vec::new();
         for piece in &self.payout_function_pieces {
-            piece.to_range_payouts(total_collateral, rounding_intervals, &mut range_payouts)?;
+            piece.to_range_payouts_with_mode(
+                total_collateral,
+                rounding_intervals,
+                mode,
+                &mut range_payouts,
+            )?;
         }
         Ok(range_payouts)
     }
@@ -121,10 +147,14 @@ pub enum PayoutFunctionPiece {
     PolynomialPayoutCurvePiece(PolynomialPayoutCurvePiece),
     /// A function piece represented by an hyperbola.
     HyperbolaPayoutCurvePiece(HyperbolaPayoutCurvePiece),
+    /// A function piece represented by a line, evaluated using integer
+    /// arithmetic only.
+    LinearPayoutCurvePiece(LinearPayoutCurvePiece),
 }
 
 impl PayoutFunctionPiece {
-    /// Generate the range payouts for the function piece.
+    /// Generate the range payouts for the function piece, using
+    /// [`EvaluationMode::Exact`].
     pub fn to_range_payouts(
         &self,
         total_collateral: u64,
@@ -138,20 +168,56 @@ impl PayoutFunctionPiece {
             PayoutFunctionPiece::HyperbolaPayoutCurvePiece(h) => {
                 h.to_range_payouts(rounding_intervals, total_collateral, range_payouts)
             }
+            PayoutFunctionPiece::LinearPayoutCurvePiece(l) => {
+                l.to_range_payouts(rounding_intervals, total_collateral, range_payouts)
+            }
         }
     }
 
-    fn get_first_point(&self) -> &PayoutPoint {
+    /// Generate the range payouts for the function piece using the given
+    /// evaluation mode.
+    pub fn to_range_payouts_with_mode(
+        &self,
+        total_collateral: u64,
+        rounding_intervals: &RoundingIntervals,
+        mode: EvaluationMode,
+        range_payouts: &mut Vec<RangePayout>,
+    ) -> Result<(), Error> {
+        match self {
+            PayoutFunctionPiece::PolynomialPayoutCurvePiece(p) => p.to_range_payouts_with_mode(
+                rounding_intervals,
+                total_collateral,
+                mode,
+                range_payouts,
+            ),
+            PayoutFunctionPiece::HyperbolaPayoutCurvePiece(h) => h.to_range_payouts_with_mode(
+                rounding_intervals,
+                total_collateral,
+                mode,
+                range_payouts,
+            ),
+            PayoutFunctionPiece::LinearPayoutCurvePiece(l) => l.to_range_payouts_with_mode(
+                rounding_intervals,
+                total_collateral,
+                mode,
+                range_payouts,
+            ),
+        }
+    }
+
+    pub(crate) fn get_first_point(&self) -> &PayoutPoint {
         match self {
             PayoutFunctionPiece::PolynomialPayoutCurvePiece(p) => &p.payout_points[0],
             PayoutFunctionPiece::HyperbolaPayoutCurvePiece(h) => &h.left_end_point,
+            PayoutFunctionPiece::LinearPayoutCurvePiece(l) => &l.left_end_point,
         }
     }
 
-    fn get_last_point(&self) -> &PayoutPoint {
+    pub(crate) fn get_last_point(&self) -> &PayoutPoint {
         match self {
             PayoutFunctionPiece::PolynomialPayoutCurvePiece(p) => p.payout_points.last().unwrap(),
             PayoutFunctionPiece::HyperbolaPayoutCurvePiece(h) => &h.right_end_point,
+            PayoutFunctionPiece::LinearPayoutCurvePiece(l) => &l.right_end_point,
         }
     }
 }
@@ -159,11 +225,63 @@ impl PayoutFunctionPiece {
 trait Evaluable {
     fn evaluate(&self, outcome: u64) -> f64;
 
+    /// Returns the exact payout for the given outcome, or `None` if it cannot
+    /// be represented.
+    fn evaluate_exact(&self, outcome: u64) -> Option<Rational>;
+
     fn get_rounded_payout(
         &self,
         outcome: u64,
         rounding_intervals: &RoundingIntervals,
         total_collateral: u64,
+        mode: EvaluationMode,
+    ) -> Result<u64, Error> {
+        match mode {
+            EvaluationMode::Float => {
+                self.get_float_rounded_payout(outcome, rounding_intervals, total_collateral)
+            }
+            EvaluationMode::Exact => {
+                self.get_exact_rounded_payout(outcome, rounding_intervals, total_collateral)
+            }
+        }
+    }
+
+    fn get_exact_rounded_payout(
+        &self,
+        outcome: u64,
+        rounding_intervals: &RoundingIntervals,
+        total_collateral: u64,
+    ) -> Result<u64, Error> {
+        let payout = self.evaluate_exact(outcome).ok_or_else(|| {
+            Error::InvalidParameters(format!(
+                "Could not evaluate function exactly for outcome {}",
+                outcome
+            ))
+        })?;
+        if payout.is_negative() {
+            return Err(Error::InvalidParameters(format!(
+                "Could not evaluate function for outcome {}, result was: {}",
+                outcome,
+                payout.to_f64()
+            )));
+        }
+
+        if !matches!(payout.round_to_multiple(1), Some(p) if p <= total_collateral as i128) {
+            return Err(Error::InvalidParameters(
+                "Computed payout is greater than total collateral".to_string(),
+            ));
+        }
+
+        // Ensure that we never round over the total collateral.
+        let rounded = rounding_intervals.round_exact(outcome, &payout)?;
+        Ok(u64::min(rounded, total_collateral))
+    }
+
+    fn get_float_rounded_payout(
+        &self,
+        outcome: u64,
+        rounding_intervals: &RoundingIntervals,
+        total_collateral: u64,
     ) -> Result<u64, Error> {
         let payout_double = self.evaluate(outcome);
         if payout_double.is_sign_negative() || (payout_double != 0.0 && !payout_double.is_normal())
@@ -196,13 +314,18 @@ trait Evaluable {
         range_payouts: &mut Vec<RangePayout>,
         total_collateral: u64,
         rounding_intervals: &RoundingIntervals,
+        mode: EvaluationMode,
     ) -> Result<RangePayout, Error> {
         let res = match range_payouts.pop() {
             Some(cur) => cur,
             None => {
                 let first_outcome = self.get_first_outcome();
-                let first_payout =
-                    self.get_rounded_payout(first_outcome, rounding_intervals, total_collateral)?;
+                let first_payout = self.get_rounded_payout(
+                    first_outcome,
+                    rounding_intervals,
+                    total_collateral,
+                    mode,
+                )?;
                 RangePayout {
                     start: first_outcome as usize,
                     count: 1,
@@ -222,7 +345,28 @@ trait Evaluable {
         total_collateral: u64,
         range_payouts: &mut Vec<RangePayout>,
     ) -> Result<(), Error> {
-        compute_range_payouts(self, rounding_intervals, total_collateral, range_payouts)
+        self.to_range_payouts_with_mode(
+            rounding_intervals,
+            total_collateral,
+            EvaluationMode::Exact,
+            range_payouts,
+        )
+    }
+
+    fn to_range_payouts_with_mode(
+        &self,
+        rounding_intervals: &RoundingIntervals,
+        total_collateral: u64,
+        mode: EvaluationMode,
+        range_payouts: &mut Vec<RangePayout>,
+    ) -> Result<(), Error> {
+        compute_range_payouts(
+            self,
+            rounding_intervals,
+            total_collateral,
+            mode,
+            range_payouts,
+        )
     }
 }
 
@@ -230,6 +374,7 @@ fn compute_range_payouts<E: Deref>(
     function: E,
     rounding_intervals: &RoundingIntervals,
     total_collateral: u64,
+    mode: EvaluationMode,
     range_payouts: &mut Vec<RangePayout>,
 ) -> Result<(), Error>
 where
@@ -237,7 +382,7 @@ where
 {
     let first_outcome = function.get_first_outcome();
     let mut cur_range =
-        function.get_cur_range(range_payouts, total_collateral, rounding_intervals)?;
+        function.get_cur_range(range_payouts, total_collateral, rounding_intervals, mode)?;
 
     let range_end = function
         .get_last_outcome()
@@ -245,7 +390,8 @@ where
         .unwrap_or(u64::MAX);
 
     for outcome in (first_outcome + 1)..range_end {
-        let payout = function.get_rounded_payout(outcome, rounding_intervals, total_collateral)?;
+        let payout =
+            function.get_rounded_payout(outcome, rounding_intervals, total_collateral, mode)?;
         if payout > total_collateral {
             return Err(Error::InvalidParameters(
                 "Computed payout is greater than total collateral.".to_string(),
@@ -341,6 +487,33 @@ impl Evaluable for PolynomialPayoutCurvePiece {
         result
     }
 
+    fn evaluate_exact(&self, outcome: u64) -> Option<Rational> {
+        if self.payout_points.len() == 2 {
+            return interpolate_linear_exact(
+                &self.payout_points[0],
+                &self.payout_points[1],
+                outcome,
+            );
+        }
+
+        let outcome = outcome as i128;
+        let mut result = Rational::from_integer(0);
+
+        for (i, point_i) in self.payout_points.iter().enumerate() {
+            let mut l = point_i.get_exact_outcome_payout();
+            for (j, point_j) in self.payout_points.iter().enumerate() {
+                if i != j {
+                    let numerator = outcome - point_j.event_outcome as i128;
+                    let denominator = point_i.event_outcome as i128 - point_j.event_outcome as i128;
+                    l = l.checked_mul(Rational::new(numerator, denominator)?)?;
+                }
+            }
+            result = result.checked_add(l)?;
+        }
+
+        Some(result)
+    }
+
     fn get_first_outcome(&self) -> u64 {
         self.payout_points[0].event_outcome
     }
@@ -349,24 +522,172 @@ impl Evaluable for PolynomialPayoutCurvePiece {
         self.payout_points.last().unwrap().event_outcome
     }
 
-    fn to_range_payouts(
+    fn to_range_payouts_with_mode(
         &self,
         rounding_intervals: &RoundingIntervals,
         total_collateral: u64,
+        mode: EvaluationMode,
         range_payouts: &mut Vec<RangePayout>,
     ) -> Result<(), Error> {
         if self.payout_points.len() == 2
             && self.payout_points[0].outcome_payout == self.payout_points[1].outcome_payout
         {
-            let mut cur_range =
-                self.get_cur_range(range_payouts, total_collateral, rounding_intervals)?;
-            cur_range.count += (self.payout_points[1].event_outcome
-                - self.payout_points[0].event_outcome) as usize;
-            range_payouts.push(cur_range);
-            return Ok(());
+            return extend_constant_range(
+                self,
+                rounding_intervals,
+                total_collateral,
+                mode,
+                range_payouts,
+            );
+        }
+
+        compute_range_payouts(
+            self,
+            rounding_intervals,
+            total_collateral,
+            mode,
+            range_payouts,
+        )
+    }
+}
+
+/// Extends the current range payout up to the last outcome of the given
+/// function, which must be constant.
+fn extend_constant_range<E: Deref>(
+    function: E,
+    rounding_intervals: &RoundingIntervals,
+    total_collateral: u64,
+    mode: EvaluationMode,
+    range_payouts: &mut Vec<RangePayout>,
+) -> Result<(), Error>
+where
+    E::Target: Evaluable,
+{
+    let mut cur_range =
+        function.get_cur_range(range_payouts, total_collateral, rounding_intervals, mode)?;
+    cur_range.count += (function.get_last_outcome() - function.get_first_outcome()) as usize;
+    range_payouts.push(cur_range);
+    Ok(())
+}
+
+fn interpolate_linear_exact(
+    left_point: &PayoutPoint,
+    right_point: &PayoutPoint,
+    outcome: u64,
+) -> Option<Rational> {
+    let left_payout = left_point.get_exact_outcome_payout();
+    let right_payout = right_point.get_exact_outcome_payout();
+    let slope = right_payout
+        .checked_sub(left_payout)?
+        .checked_div(Rational::from_integer(
+            right_point.event_outcome as i128 - left_point.event_outcome as i128,
+        ))?;
+    Rational::from_integer(outcome as i128 - left_point.event_outcome as i128)
+        .checked_mul(slope)?
+        .checked_add(left_payout)
+}
+
+/// A function piece represented by a line between two points. Using
+/// [`EvaluationMode::Exact`], payouts are computed using integer arithmetic
+/// only. It is transmitted to the counter party as a polynomial piece with two
+/// points, so using [`EvaluationMode::Float`] it is evaluated as such to
+/// compute the same payouts as the counter party.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct LinearPayoutCurvePiece {
+    /// The left end point of the piece.
+    pub(crate) left_end_point: PayoutPoint,
+    /// The right end point of the piece.
+    pub(crate) right_end_point: PayoutPoint,
+}
+
+impl LinearPayoutCurvePiece {
+    /// Create a new LinearPayoutCurvePiece
+    pub fn new(left_end_point: PayoutPoint, right_end_point: PayoutPoint) -> Result<Self, Error> {
+        if left_end_point.event_outcome >= right_end_point.event_outcome {
+            return Err(Error::InvalidParameters(
+                "Left end point outcome must be strictly less than right end point outcome"
+                    .to_string(),
+            ));
+        }
+
+        Ok(LinearPayoutCurvePiece {
+            left_end_point,
+            right_end_point,
+        })
+    }
+}
+
+impl Evaluable for LinearPayoutCurvePiece {
+    fn evaluate(&self, outcome: u64) -> f64 {
+        self.evaluate_exact(outcome)
+            .map_or(f64::NAN, |payout| payout.to_f64())
+    }
+
+    fn evaluate_exact(&self, outcome: u64) -> Option<Rational> {
+        interpolate_linear_exact(&self.left_end_point, &self.right_end_point, outcome)
+    }
+
+    fn get_rounded_payout(
+        &self,
+        outcome: u64,
+        rounding_intervals: &RoundingIntervals,
+        total_collateral: u64,
+        _: EvaluationMode,
+    ) -> Result<u64, Error> {
+        self.get_exact_rounded_payout(outcome, rounding_intervals, total_collateral)
+    }
+
+    fn get_first_outcome(&self) -> u64 {
+        self.left_end_point.event_outcome
+    }
+
+    fn get_last_outcome(&self) -> u64 {
+        self.right_end_point.event_outcome
+    }
+
+    fn to_range_payouts_with_mode(
+        &self,
+        rounding_intervals: &RoundingIntervals,
+        total_collateral: u64,
+        mode: EvaluationMode,
+        range_payouts: &mut Vec<RangePayout>,
+    ) -> Result<(), Error> {
+        if mode == EvaluationMode::Float {
+            let polynomial = PolynomialPayoutCurvePiece {
+                payout_points: vec![self.left_end_point.clone(), self.right_end_point.clone()],
+            };
+            return polynomial.to_range_payouts_with_mode(
+                rounding_intervals,
+                total_collateral,
+                mode,
+                range_payouts,
+            );
         }
 
-        compute_range_payouts(self, rounding_intervals, total_collateral, range_payouts)
+        if self.left_end_point.outcome_payout == self.right_end_point.outcome_payout
+            && self.left_end_point.extra_precision == self.right_end_point.extra_precision
+        {
+            return extend_constant_range(
+                self,
+                rounding_intervals,
+                total_collateral,
+                mode,
+                range_payouts,
+            );
+        }
+
+        compute_range_payouts(
+            self,
+            rounding_intervals,
+            total_collateral,
+            mode,
+            range_payouts,
+        )
     }
 }
 
@@ -390,6 +711,14 @@ impl PayoutPoint {
     fn get_outcome_payout(&self) -> f64 {
         (self.outcome_payout as f64) + ((self.extra_precision as f64) / ((1 << 16) as f64))
     }
+
+    fn get_exact_outcome_payout(&self) -> Rational {
+        Rational::new(
+            ((self.outcome_payout as i128) << 16) + self.extra_precision as i128,
+            1 << 16,
+        )
+        .expect("denominator to not be zero")
+    }
 }
 
 /// A function piece represented by a hyperbola.
@@ -462,7 +791,10 @@ impl Evaluable for HyperbolaPayoutCurvePiece {
     fn evaluate(&self, outcome: u64) -> f64 {
         let outcome = outcome as f64;
         let translated_outcome = outcome - self.translate_outcome;
-        let sqrt_term_abs_val = (translated_outcome.powi(2) - 4.0 * self.a * self.b).sqrt();
+        // Only use operations that IEEE 754 requires to be correctly rounded so
+        // that the result does not depend on the platform.
+        let sqrt_term_abs_val =
+            (translated_outcome * translated_outcome - 4.0 * self.a * self.b).sqrt();
         let sqrt_term = if self.use_positive_piece {
             sqrt_term_abs_val
         } else {
@@ -474,6 +806,36 @@ impl Evaluable for HyperbolaPayoutCurvePiece {
         first_term + second_term + self.translate_payout
     }
 
+    fn evaluate_exact(&self, outcome: u64) -> Option<Rational> {
+        if self.b != 0.0 {
+            // The square root cannot be computed exactly in general.
+            return Rational::from_f64(self.evaluate(outcome));
+        }
+
+        // With `b` equal to zero, the square root term is the absolute value of
+        // the translated outcome.
+        let translated_outcome = Rational::from_integer(outcome as i128)
+            .checked_sub(Rational::from_f64(self.translate_outcome)?)?;
+        let sqrt_term = if translated_outcome.is_negative() == self.use_positive_piece {
+            translated_outcome.checked_neg()?
+        } else {
+            translated_outcome
+        };
+        let sum = translated_outcome.checked_add(sqrt_term)?;
+
+        let a = Rational::from_f64(self.a)?;
+        let two_a = a.checked_add(a)?;
+        let first_term = Rational::from_f64(self.c)?
+            .checked_mul(sum)?
+            .checked_div(two_a)?;
+        let second_term = two_a
+            .checked_mul(Rational::from_f64(self.d)?)?
+            .checked_div(sum)?;
+        first_term
+            .checked_add(second_term)?
+            .checked_add(Rational::from_f64(self.translate_payout)?)
+    }
+
     fn get_first_outcome(&self) -> u64 {
         self.left_end_point.event_outcome
     }
@@ -514,14 +876,7 @@ impl RoundingIntervals {
     /// Round the given payout based on the rounding modulus matching the given
     /// outcome.
     pub fn round(&self, outcome: u64, payout: f64) -> u64 {
-        let rounding_mod = match self
-            .intervals
-            .binary_search_by(|x| x.begin_interval.cmp(&outcome))
-        {
-            Ok(index) => self.intervals[index].rounding_mod,
-            Err(index) if index != 0 => self.intervals[index - 1].rounding_mod,
-            _ => unreachable!(),
-        } as f64;
+        let rounding_mod = self.get_rounding_mod(outcome) as f64;
 
         let m = if payout >= 0.0 {
             payout % rounding_mod
@@ -536,6 +891,26 @@ impl RoundingIntervals {
         }
     }
 
+    /// Round the given exact payout based on the rounding modulus matching the
+    /// given outcome, the same way as [`RoundingIntervals::round`] does.
+    pub(crate) fn round_exact(&self, outcome: u64, payout: &Rational) -> Result<u64, Error> {
+        payout
+            .round_to_multiple(self.get_rounding_mod(outcome))
+            .and_then(|rounded| u64::try_from(rounded).ok())
+            .ok_or_else(|| Error::InvalidParameters("Could not round payout.".to_string()))
+    }
+
+    fn get_rounding_mod(&self, outcome: u64) -> u64 {
+        match self
+            .intervals
+            .binary_search_by(|x| x.begin_interval.cmp(&outcome))
+        {
+            Ok(index) => self.intervals[index].rounding_mod,
+            Err(index) if index != 0 => self.intervals[index - 1].rounding_mod,
+            _ => unreachable!(),
+        }
+    }
+
     /// Validate that the instance is well formed, meaning non empty and with the
     /// first interval starting at zero.
     pub fn validate(&self) -> Result<(), Error> {
@@ -686,9 +1061,10 @@ mod test {
 
             let mut range_payouts = Vec::new();
             polynomial
-                .to_range_payouts(
+                .to_range_payouts_with_mode(
                     &rounding_intervals,
                     test_case.total_collateral,
+                    EvaluationMode::Float,
                     &mut range_payouts,
                 )
                 .expect("to be able to compute the range payouts");
@@ -759,7 +1135,7 @@ mod test {
         };
 
         hyperbola
-            .to_range_payouts(
+            .to_range_payouts_with_mode(
                 &RoundingIntervals {
                     intervals: vec![RoundingInterval {
                         begin_interval: 0,
@@ -767,6 +1143,7 @@ mod test {
                     }],
                 },
                 200000000,
+                EvaluationMode::Float,
                 &mut Vec::new(),
             )
             .expect_err("Should not tolerate negative payout");
@@ -795,7 +1172,7 @@ mod test {
         };
 
         hyperbola
-            .to_range_payouts(
+            .to_range_payouts_with_mode(
                 &RoundingIntervals {
                     intervals: vec![RoundingInterval {
                         begin_interval: 0,
@@ -803,6 +1180,7 @@ mod test {
                     }],
                 },
                 200000000,
+                EvaluationMode::Float,
                 &mut Vec::new(),
             )
             .expect("to be able to compute the range payouts");
@@ -1290,4 +1668,153 @@ mod test {
         assert_eq!(polynomial.evaluate(0), 10.0);
         assert_eq!(polynomial.evaluate(1), 8.0);
     }
+
+    fn get_point(event_outcome: u64, outcome_payout: u64) -> PayoutPoint {
+        PayoutPoint {
+            event_outcome,
+            outcome_payout,
+            extra_precision: 0,
+        }
+    }
+
+    fn get_offer_payout(range_payouts: &[RangePayout], outcome: u64) -> u64 {
+        range_payouts
+            .iter()
+            .find(|x| x.start <= outcome as usize && (outcome as usize) < x.start + x.count)
+            .expect("to find a range for the outcome")
+            .payout
+            .offer
+    }
+
+    #[test]
+    fn exact_evaluation_test() {
+        let polynomial = PolynomialPayoutCurvePiece {
+            payout_points: vec![get_point(0, 1), get_point(2, 5), get_point(4, 17)],
+        };
+        assert_eq!(
+            Some(Rational::from_integer(101)),
+            polynomial.evaluate_exact(10)
+        );
+        assert_eq!(
+            Some(Rational::from_integer(10)),
+            polynomial.evaluate_exact(3)
+        );
+
+        let with_extra_precision = PolynomialPayoutCurvePiece {
+            payout_points: vec![
+                get_point(0, 0),
+                PayoutPoint {
+                    event_outcome: 2,
+                    outcome_payout: 1,
+                    extra_precision: 1 << 15,
+                },
+            ],
+        };
+        assert_eq!(Rational::new(3, 4), with_extra_precision.evaluate_exact(1));
+
+        let hyperbola = HyperbolaPayoutCurvePiece::new(
+            get_point(1, 0),
+            get_point(1000, 0),
+            true,
+            0.0,
+            10.0,
+            1.0,
+            0.0,
+            0.0,
+            3.0,
+        )
+        .unwrap();
+        assert_eq!(Rational::new(31, 3), hyperbola.evaluate_exact(9));
+    }
+
+    #[test]
+    fn exact_mode_rounds_half_payouts_consistently_test() {
+        // The payout at outcome 7 is exactly 30.5, but evaluates to
+        // 30.499999999999996 using floating point arithmetic.
+        let payout_function =
+            PayoutFunction::new(vec![PayoutFunctionPiece::PolynomialPayoutCurvePiece(
+                PolynomialPayoutCurvePiece::new(vec![get_point(0, 0), get_point(14, 61)]).unwrap(),
+            )])
+            .unwrap();
+        let rounding_intervals = RoundingIntervals {
+            intervals: vec![RoundingInterval {
+                begin_interval: 0,
+                rounding_mod: 1,
+            }],
+        };
+
+        let float_payouts = payout_function
+            .to_range_payouts_with_mode(61, &rounding_intervals, EvaluationMode::Float)
+            .unwrap();
+        assert_eq!(30, get_offer_payout(&float_payouts, 7));
+
+        let exact_payouts = payout_function
+            .to_range_payouts(61, &rounding_intervals)
+            .unwrap();
+        assert_eq!(31, get_offer_payout(&exact_payouts, 7));
+
+        let linear_function =
+            PayoutFunction::new(vec![PayoutFunctionPiece::LinearPayoutCurvePiece(
+                LinearPayoutCurvePiece::new(get_point(0, 0), get_point(14, 61)).unwrap(),
+            )])
+            .unwrap();
+        assert_eq!(
+            float_payouts,
+            linear_function
+                .to_range_payouts_with_mode(61, &rounding_intervals, EvaluationMode::Float)
+                .unwrap()
+        );
+        assert_eq!(
+            exact_payouts,
+            linear_function
+                .to_range_payouts(61, &rounding_intervals)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn linear_piece_matches_integer_computation_test() {
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let left_outcome = rng.next_u64() % 1000;
+            let right_outcome = left_outcome + 1 + rng.next_u64() % 1000;
+            let left_payout = rng.next_u64() % 1_000_000_000;
+            let right_payout = rng.next_u64() % 1_000_000_000;
+            let rounding_mod = 1 + rng.next_u64() % 10_000;
+            let total_collateral = 1_000_000_000;
+            let linear = LinearPayoutCurvePiece::new(
+                get_point(left_outcome, left_payout),
+                get_point(right_outcome, right_payout),
+            )
+            .unwrap();
+            let rounding_intervals = RoundingIntervals {
+                intervals: vec![RoundingInterval {
+                    begin_interval: 0,
+                    rounding_mod,
+                }],
+            };
+
+            let mut range_payouts = Vec::new();
+            linear
+                .to_range_payouts_with_mode(
+                    &rounding_intervals,
+                    total_collateral,
+                    EvaluationMode::Exact,
+                    &mut range_payouts,
+                )
+                .expect("to be able to compute the range payouts");
+
+            let dx = (right_outcome - left_outcome) as i128;
+            let dy = right_payout as i128 - left_payout as i128;
+            for outcome in left_outcome..=right_outcome {
+                // payout = (left_payout * dx + (outcome - left_outcome) * dy) / dx,
+                // rounded to the closest multiple of the rounding modulus.
+                let num = left_payout as i128 * dx + (outcome - left_outcome) as i128 * dy;
+                let divisor = dx * rounding_mod as i128;
+                let expected = (2 * num + divisor) / (2 * divisor) * rounding_mod as i128;
+                let expected = u64::min(expected as u64, total_collateral);
+                assert_eq!(expected, get_offer_payout(&range_payouts, outcome));
+            }
+        }
+    }
 }
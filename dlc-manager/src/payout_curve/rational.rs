@@ -0,0 +1,217 @@
+//! #Rational
+//! Exact rational arithmetic used to evaluate payout curves without the
+//! platform and implementation dependent rounding errors of floating point
+//! arithmetic.
+
+use std::convert::TryFrom;
+
+/// A rational number with a strictly positive denominator, always kept in its
+/// reduced form so that equal values have equal representations. All operations
+/// are checked and return `None` on overflow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Rational {
+    num: i128,
+    den: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    // Only `i128::MIN` has an absolute value that does not fit in an `i128`,
+    // in which case the gcd is a power of two that can be halved.
+    i128::try_from(a).unwrap_or(1 << 126)
+}
+
+impl Rational {
+    /// Creates a new rational equal to `num / den`, returning `None` if `den`
+    /// is zero.
+    pub(crate) fn new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den);
+        let (num, den) = (num / g, den / g);
+        if den < 0 {
+            Some(Rational {
+                num: num.checked_neg()?,
+                den: den.checked_neg()?,
+            })
+        } else {
+            Some(Rational { num, den })
+        }
+    }
+
+    /// Creates a new rational equal to the given integer.
+    pub(crate) fn from_integer(value: i128) -> Self {
+        Rational { num: value, den: 1 }
+    }
+
+    /// Creates a new rational exactly equal to the given floating point value,
+    /// returning `None` if the value is not finite or cannot be represented.
+    pub(crate) fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        let bits = value.to_bits();
+        let is_negative = bits >> 63 == 1;
+        let exponent_bits = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = (bits & ((1 << 52) - 1)) as i128;
+        let (mut mantissa, mut exponent) = if exponent_bits == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | (1 << 52), exponent_bits - 1075)
+        };
+        if mantissa == 0 {
+            return Some(Rational::from_integer(0));
+        }
+        if is_negative {
+            mantissa = -mantissa;
+        }
+
+        if exponent >= 0 {
+            if exponent > 126 - 53 {
+                return None;
+            }
+            return Some(Rational::from_integer(mantissa << exponent));
+        }
+
+        let trailing_zeros = i32::min(mantissa.trailing_zeros() as i32, -exponent);
+        mantissa >>= trailing_zeros;
+        exponent += trailing_zeros;
+        if -exponent > 126 {
+            return None;
+        }
+        Rational::new(mantissa, 1 << -exponent)
+    }
+
+    /// Returns the closest floating point value.
+    pub(crate) fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Returns whether the value is strictly negative.
+    pub(crate) fn is_negative(&self) -> bool {
+        self.num < 0
+    }
+
+    pub(crate) fn checked_add(self, other: Self) -> Option<Self> {
+        let g = gcd(self.den, other.den);
+        let den = (self.den / g).checked_mul(other.den)?;
+        let num = self
+            .num
+            .checked_mul(other.den / g)?
+            .checked_add(other.num.checked_mul(self.den / g)?)?;
+        Rational::new(num, den)
+    }
+
+    pub(crate) fn checked_sub(self, other: Self) -> Option<Self> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    pub(crate) fn checked_neg(self) -> Option<Self> {
+        Some(Rational {
+            num: self.num.checked_neg()?,
+            den: self.den,
+        })
+    }
+
+    pub(crate) fn checked_mul(self, other: Self) -> Option<Self> {
+        let g1 = gcd(self.num, other.den);
+        let g2 = gcd(other.num, self.den);
+        let num = (self.num / g1).checked_mul(other.num / g2)?;
+        let den = (self.den / g2).checked_mul(other.den / g1)?;
+        Rational::new(num, den)
+    }
+
+    pub(crate) fn checked_div(self, other: Self) -> Option<Self> {
+        if other.num == 0 {
+            return None;
+        }
+        self.checked_mul(Rational::new(other.den, other.num)?)
+    }
+
+    /// Rounds the value to the closest multiple of `modulus`, rounding up when
+    /// the value is exactly in between two multiples. Returns `None` if
+    /// `modulus` is zero.
+    pub(crate) fn round_to_multiple(&self, modulus: u64) -> Option<i128> {
+        if modulus == 0 {
+            return None;
+        }
+        let modulus = modulus as i128;
+        let divisor = self.den.checked_mul(modulus)?;
+        let quotient = self.num.div_euclid(divisor);
+        let remainder = self.num.rem_euclid(divisor);
+        let quotient = if remainder.checked_mul(2)? >= divisor {
+            quotient.checked_add(1)?
+        } else {
+            quotient
+        };
+        quotient.checked_mul(modulus)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_f64_is_exact() {
+        assert_eq!(Rational::new(1, 2), Rational::from_f64(0.5));
+        assert_eq!(Rational::new(-3, 4), Rational::from_f64(-0.75));
+        assert_eq!(Some(Rational::from_integer(0)), Rational::from_f64(-0.0));
+        assert_eq!(
+            Rational::new(3602879701896397, 1 << 55),
+            Rational::from_f64(0.1)
+        );
+        assert_eq!(
+            Some(Rational::from_integer(1 << 60)),
+            Rational::from_f64((1u64 << 60) as f64)
+        );
+        assert_eq!(None, Rational::from_f64(f64::NAN));
+        assert_eq!(None, Rational::from_f64(f64::INFINITY));
+        assert_eq!(None, Rational::from_f64(1e300));
+        assert_eq!(None, Rational::from_f64(1e-300));
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        let third = Rational::new(1, 3).unwrap();
+        let sixth = Rational::new(1, 6).unwrap();
+        assert_eq!(Rational::new(1, 2), third.checked_add(sixth));
+        assert_eq!(Some(sixth), third.checked_sub(sixth));
+        assert_eq!(Rational::new(1, 18), third.checked_mul(sixth));
+        assert_eq!(Some(Rational::from_integer(2)), third.checked_div(sixth));
+        assert_eq!(None, third.checked_div(Rational::from_integer(0)));
+        assert_eq!(
+            Some(Rational::from_integer(0)),
+            third.checked_mul(Rational::from_integer(0))
+        );
+        assert_eq!(Rational::new(1, -2), Rational::new(-2, 4));
+        assert!(Rational::new(-1, 2).unwrap().is_negative());
+        assert_eq!(
+            None,
+            Rational::from_integer(i128::MAX).checked_add(Rational::from_integer(1))
+        );
+    }
+
+    #[test]
+    fn round_to_multiple_test() {
+        let round = |num: i128, den: i128, modulus: u64| {
+            Rational::new(num, den)
+                .unwrap()
+                .round_to_multiple(modulus)
+                .unwrap()
+        };
+        assert_eq!(1, round(1, 2, 1));
+        assert_eq!(0, round(49, 100, 1));
+        assert_eq!(0, round(-1, 2, 1));
+        assert_eq!(-1, round(-51, 100, 1));
+        assert_eq!(100, round(50, 1, 100));
+        assert_eq!(0, round(4999, 100, 100));
+        assert_eq!(1000, round(2999, 3, 1000));
+    }
+}
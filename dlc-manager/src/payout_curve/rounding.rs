@@ -0,0 +1,351 @@
+//! #Rounding
+//! Search for rounding intervals keeping the number of CETs and adaptor
+//! signatures required by a payout function within a budget.
+
+use super::{PayoutFunction, RoundingInterval, RoundingIntervals};
+use crate::error::Error;
+use dlc::RangePayout;
+use dlc_trie::combination_iterator::CombinationIterator;
+use dlc_trie::digit_decomposition::group_by_ignoring_digits;
+use dlc_trie::OracleNumericInfo;
+
+/// The maximum number of CETs and adaptor signatures that a contract can
+/// require.
+#[derive(Clone, Debug)]
+pub struct RoundingBudget {
+    /// The maximum number of CETs.
+    pub max_nb_cets: usize,
+    /// The maximum number of adaptor signatures.
+    pub max_nb_adaptor_signatures: usize,
+}
+
+/// Rounding intervals found by [`optimize_rounding_intervals`].
+#[derive(Clone, Debug)]
+pub struct OptimizedRounding {
+    /// The rounding intervals to use.
+    pub rounding_intervals: RoundingIntervals,
+    /// The number of CETs required with the rounding intervals.
+    pub nb_cets: usize,
+    /// The number of adaptor signatures required with the rounding intervals.
+    pub nb_adaptor_signatures: usize,
+    /// The largest rounding modulus, the payout of an outcome differing by at
+    /// most half of it from the one given by the payout function.
+    pub max_rounding_mod: u64,
+}
+
+struct Counter<'a> {
+    payout_function: &'a PayoutFunction,
+    total_collateral: u64,
+    base: usize,
+    nb_digits: usize,
+    max_outcome: usize,
+    nb_combinations: usize,
+    budget: &'a RoundingBudget,
+}
+
+impl<'a> Counter<'a> {
+    /// Returns the number of CETs and adaptor signatures required with the
+    /// given rounding intervals.
+    fn count(&self, rounding_intervals: &RoundingIntervals) -> Result<(usize, usize), Error> {
+        let range_payouts = self
+            .payout_function
+            .to_range_payouts(self.total_collateral, rounding_intervals)?;
+        let nb_groups: usize = range_payouts
+            .iter()
+            .filter(|x| x.start <= self.max_outcome)
+            .map(|x| self.get_nb_groups(x))
+            .sum();
+        Ok((range_payouts.len(), nb_groups * self.nb_combinations))
+    }
+
+    fn get_nb_groups(&self, range_payout: &RangePayout) -> usize {
+        let end = usize::min(
+            range_payout
+                .start
+                .saturating_add(range_payout.count)
+                .saturating_sub(1),
+            self.max_outcome,
+        );
+        group_by_ignoring_digits(range_payout.start, end, self.base, self.nb_digits).len()
+    }
+
+    fn is_within_budget(&self, rounding_intervals: &RoundingIntervals) -> Result<bool, Error> {
+        let (nb_cets, nb_adaptor_signatures) = self.count(rounding_intervals)?;
+        Ok(nb_cets <= self.budget.max_nb_cets
+            && nb_adaptor_signatures <= self.budget.max_nb_adaptor_signatures)
+    }
+}
+
+/// Searches for rounding intervals for which the contract requires at most the
+/// given number of CETs and adaptor signatures, while minimizing the rounding
+/// error. The number of adaptor signatures is computed for a contract with the
+/// given oracles, `threshold` of which are required to close it, and without
+/// allowed difference in outcome between the oracles.
+///
+/// The smallest rounding modulus within the budget is first searched for the
+/// whole outcome range, assuming that the number of CETs decreases as the
+/// modulus increases. The modulus of the outcome ranges of each piece of the
+/// payout function is then halved for as long as the budget allows it, to
+/// reduce the error where possible.
+pub fn optimize_rounding_intervals(
+    payout_function: &PayoutFunction,
+    total_collateral: u64,
+    oracle_numeric_infos: &OracleNumericInfo,
+    threshold: usize,
+    budget: &RoundingBudget,
+) -> Result<OptimizedRounding, Error> {
+    let nb_oracles = oracle_numeric_infos.nb_digits.len();
+    if threshold == 0 || threshold > nb_oracles {
+        return Err(Error::InvalidParameters(
+            "Threshold must be between one and the number of oracles.".to_string(),
+        ));
+    }
+    if oracle_numeric_infos.has_diff_bases() {
+        return Err(Error::InvalidParameters(
+            "Oracles using different bases are not supported.".to_string(),
+        ));
+    }
+    let nb_digits = oracle_numeric_infos.get_min_nb_digits();
+    let max_outcome = oracle_numeric_infos
+        .base
+        .checked_pow(nb_digits as u32)
+        .ok_or_else(|| Error::InvalidParameters("Could not compute max value".to_string()))?
+        - 1;
+    let counter = Counter {
+        payout_function,
+        total_collateral,
+        base: oracle_numeric_infos.base,
+        nb_digits,
+        max_outcome,
+        nb_combinations: CombinationIterator::new(nb_oracles, threshold).count(),
+        budget,
+    };
+
+    let max_rounding_mod = find_uniform_rounding_mod(&counter)?;
+
+    let interval_starts = get_interval_starts(payout_function);
+    let mut rounding_mods = vec![max_rounding_mod; interval_starts.len()];
+    for i in 0..rounding_mods.len() {
+        while rounding_mods[i] > 1 {
+            let mut candidate = rounding_mods.clone();
+            candidate[i] /= 2;
+            if !counter.is_within_budget(&get_rounding_intervals(&interval_starts, &candidate))? {
+                break;
+            }
+            rounding_mods = candidate;
+        }
+    }
+
+    let rounding_intervals = get_rounding_intervals(&interval_starts, &rounding_mods);
+    let (nb_cets, nb_adaptor_signatures) = counter.count(&rounding_intervals)?;
+    Ok(OptimizedRounding {
+        nb_cets,
+        nb_adaptor_signatures,
+        max_rounding_mod: rounding_intervals
+            .intervals
+            .iter()
+            .map(|x| x.rounding_mod)
+            .max()
+            .expect("to have at least one interval"),
+        rounding_intervals,
+    })
+}
+
+/// Returns the smallest rounding modulus within the budget when used over the
+/// whole outcome range.
+fn find_uniform_rounding_mod(counter: &Counter) -> Result<u64, Error> {
+    let is_within_budget = |rounding_mod: u64| {
+        counter.is_within_budget(&get_rounding_intervals(&[0], &[rounding_mod]))
+    };
+
+    if is_within_budget(1)? {
+        return Ok(1);
+    }
+
+    // With a modulus larger than the total collateral, payouts can only be
+    // rounded to zero or to the total collateral.
+    let mut high = counter.total_collateral.saturating_mul(2).max(2);
+    if !is_within_budget(high)? {
+        return Err(Error::InvalidParameters(
+            "Payout function cannot be rounded to fit within the budget.".to_string(),
+        ));
+    }
+
+    let mut low = 1;
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if is_within_budget(mid)? {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    Ok(high)
+}
+
+fn get_interval_starts(payout_function: &PayoutFunction) -> Vec<u64> {
+    let mut starts: Vec<u64> = payout_function
+        .payout_function_pieces
+        .iter()
+        .map(|x| x.get_first_point().event_outcome)
+        .collect();
+    starts.dedup();
+    starts
+}
+
+/// Returns the rounding intervals with the given starts and moduli, merging
+/// consecutive intervals with the same modulus.
+fn get_rounding_intervals(starts: &[u64], rounding_mods: &[u64]) -> RoundingIntervals {
+    let mut intervals: Vec<RoundingInterval> = Vec::new();
+    for (begin_interval, rounding_mod) in starts.iter().zip(rounding_mods) {
+        if intervals.last().map(|x| x.rounding_mod) != Some(*rounding_mod) {
+            intervals.push(RoundingInterval {
+                begin_interval: *begin_interval,
+                rounding_mod: *rounding_mod,
+            });
+        }
+    }
+    RoundingIntervals { intervals }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::payout_curve::builders::{Collar, InversePerpetual};
+
+    fn get_oracle_numeric_infos(nb_oracles: usize) -> OracleNumericInfo {
+        OracleNumericInfo {
+            base: 2,
+            nb_digits: vec![15; nb_oracles],
+            oracle_bases: None,
+        }
+    }
+
+    fn get_perpetual() -> PayoutFunction {
+        InversePerpetual {
+            entry_price: 10_000,
+            leverage_numerator: 2,
+            leverage_denominator: 1,
+            offer_collateral: 1_000_000,
+            accept_collateral: 1_000_000,
+            offer_is_long: true,
+        }
+        .build((1 << 15) - 1, 1)
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn optimized_rounding_is_within_budget() {
+        let payout_function = get_perpetual();
+        for nb_oracles in 1..3 {
+            let oracle_numeric_infos = get_oracle_numeric_infos(nb_oracles);
+            let budget = RoundingBudget {
+                max_nb_cets: 200,
+                max_nb_adaptor_signatures: 1000,
+            };
+            let optimized = optimize_rounding_intervals(
+                &payout_function,
+                2_000_000,
+                &oracle_numeric_infos,
+                nb_oracles,
+                &budget,
+            )
+            .expect("to find rounding intervals");
+
+            assert!(optimized.rounding_intervals.validate().is_ok());
+            assert!(optimized.max_rounding_mod > 1);
+            assert!(optimized.nb_cets <= budget.max_nb_cets);
+            assert!(optimized.nb_adaptor_signatures <= budget.max_nb_adaptor_signatures);
+            let range_payouts = payout_function
+                .to_range_payouts(2_000_000, &optimized.rounding_intervals)
+                .unwrap();
+            assert_eq!(optimized.nb_cets, range_payouts.len());
+
+            // The uniform modulus is the smallest one within the budget.
+            let counter = Counter {
+                payout_function: &payout_function,
+                total_collateral: 2_000_000,
+                base: 2,
+                nb_digits: 15,
+                max_outcome: (1 << 15) - 1,
+                nb_combinations: 1,
+                budget: &budget,
+            };
+            let uniform_mod = find_uniform_rounding_mod(&counter).unwrap();
+            assert!(optimized.max_rounding_mod <= uniform_mod);
+            assert!(!counter
+                .is_within_budget(&get_rounding_intervals(&[0], &[uniform_mod - 1]))
+                .unwrap());
+        }
+    }
+
+    #[test]
+    fn constant_pieces_are_not_rounded() {
+        // The collar is constant between its strike prices, so no rounding is
+        // needed there.
+        let payout_function = Collar {
+            lower_strike_price: 8_000,
+            upper_strike_price: 12_000,
+            offer_collateral: 1_000_000,
+            accept_collateral: 1_000_000,
+            offer_is_holder: true,
+        }
+        .build((1 << 15) - 1, 1)
+        .unwrap()
+        .0;
+        let optimized = optimize_rounding_intervals(
+            &payout_function,
+            2_000_000,
+            &get_oracle_numeric_infos(1),
+            1,
+            &RoundingBudget {
+                max_nb_cets: 100,
+                max_nb_adaptor_signatures: 1000,
+            },
+        )
+        .expect("to find rounding intervals");
+
+        assert!(optimized.rounding_intervals.intervals.len() > 1);
+        let constant_interval = optimized
+            .rounding_intervals
+            .intervals
+            .iter()
+            .rev()
+            .find(|x| x.begin_interval <= 10_000)
+            .unwrap();
+        assert_eq!(1, constant_interval.rounding_mod);
+    }
+
+    #[test]
+    fn small_enough_function_is_not_rounded() {
+        let optimized = optimize_rounding_intervals(
+            &get_perpetual(),
+            2_000_000,
+            &get_oracle_numeric_infos(1),
+            1,
+            &RoundingBudget {
+                max_nb_cets: usize::MAX,
+                max_nb_adaptor_signatures: usize::MAX,
+            },
+        )
+        .expect("to find rounding intervals");
+        assert_eq!(1, optimized.max_rounding_mod);
+    }
+
+    #[test]
+    fn impossible_budget_is_rejected() {
+        optimize_rounding_intervals(
+            &get_perpetual(),
+            2_000_000,
+            &get_oracle_numeric_infos(1),
+            1,
+            &RoundingBudget {
+                max_nb_cets: 1,
+                max_nb_adaptor_signatures: 1,
+            },
+        )
+        .expect_err("budget cannot be met");
+    }
+}
@@ -0,0 +1,201 @@
+//! # Module containing the types used to query the contracts and channels of a
+//! [`crate::Storage`] using filters and cursor based pagination.
+
+use crate::channel::ChannelStateType;
+use crate::contract::ContractStateType;
+use crate::ReferenceId;
+use secp256k1_zkp::PublicKey;
+
+/// A range of unix timestamps in seconds. An unset bound leaves the range
+/// unbounded on that side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    /// The inclusive lower bound of the range.
+    pub from: Option<u64>,
+    /// The exclusive upper bound of the range.
+    pub to: Option<u64>,
+}
+
+impl TimeRange {
+    /// Returns whether the given time is within the range.
+    pub fn contains(&self, time: u64) -> bool {
+        self.from.map_or(true, |from| from <= time) && self.to.map_or(true, |to| time < to)
+    }
+
+    /// Returns whether the range is unbounded on both sides.
+    pub fn is_unbounded(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+}
+
+/// The position of a record in the results of a query. Results are ordered by
+/// the time at which the records were first stored, and then by id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QueryCursor {
+    /// The unix timestamp at which the record was first stored.
+    pub created_at: u64,
+    /// The id of the record.
+    pub id: [u8; 32],
+}
+
+/// A page of the results of a query.
+#[derive(Clone, Debug)]
+pub struct Page<T> {
+    /// The records in the page.
+    pub items: Vec<T>,
+    /// The cursor to set on the query to retrieve the next page, `None` if there
+    /// are no more records matching the query.
+    pub next_cursor: Option<QueryCursor>,
+}
+
+impl<T> Page<T> {
+    /// Creates a page from the records matching a query, sorting them and
+    /// keeping only the ones after `cursor`, up to `limit` of them.
+    pub fn from_matches(
+        mut records: Vec<(QueryCursor, T)>,
+        cursor: Option<QueryCursor>,
+        limit: Option<usize>,
+    ) -> Self {
+        records.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut records = records
+            .into_iter()
+            .filter(|(position, _)| cursor.map_or(true, |c| *position > c))
+            .collect::<Vec<_>>();
+        let next_cursor = match limit {
+            Some(limit) if records.len() > limit => {
+                records.truncate(limit);
+                records.last().map(|(position, _)| *position)
+            }
+            _ => None,
+        };
+        Page {
+            items: records.into_iter().map(|(_, record)| record).collect(),
+            next_cursor,
+        }
+    }
+}
+
+/// A query for contracts. Only the contracts matching all the set filters are
+/// returned.
+#[derive(Clone, Debug, Default)]
+pub struct ContractQuery {
+    /// The states of the contracts to return, contracts in any state are returned
+    /// if empty.
+    pub states: Vec<ContractStateType>,
+    /// Only return the contracts with this counter party.
+    pub counter_party: Option<PublicKey>,
+    /// Only return the contracts first stored within this range.
+    pub created_at: TimeRange,
+    /// Only return the contracts whose maturity, as returned by
+    /// [`crate::contract::Contract::get_maturity`] when they were last stored with
+    /// their contract information, is within this range.
+    pub maturity: TimeRange,
+    /// Only return the contracts positioned after this cursor.
+    pub cursor: Option<QueryCursor>,
+    /// The maximum number of contracts to return, all matching contracts are
+    /// returned if not set.
+    pub limit: Option<usize>,
+}
+
+impl ContractQuery {
+    /// Returns whether a contract with the given properties matches the filters
+    /// of the query. The cursor and limit are not taken into account.
+    pub fn matches(
+        &self,
+        state: ContractStateType,
+        counter_party: &PublicKey,
+        created_at: u64,
+        maturity: Option<u64>,
+    ) -> bool {
+        (self.states.is_empty() || self.states.contains(&state))
+            && self.counter_party.map_or(true, |c| &c == counter_party)
+            && self.created_at.contains(created_at)
+            && (self.maturity.is_unbounded()
+                || maturity.map_or(false, |m| self.maturity.contains(m)))
+    }
+}
+
+/// A query for channels. Only the channels matching all the set filters are
+/// returned.
+#[derive(Clone, Debug, Default)]
+pub struct ChannelQuery {
+    /// The states of the channels to return, channels in any state are returned
+    /// if empty.
+    pub states: Vec<ChannelStateType>,
+    /// Only return the channels with this counter party.
+    pub counter_party: Option<PublicKey>,
+    /// Only return the channels with this reference id.
+    pub reference_id: Option<ReferenceId>,
+    /// Only return the channels first stored within this range.
+    pub created_at: TimeRange,
+    /// Only return the channels positioned after this cursor.
+    pub cursor: Option<QueryCursor>,
+    /// The maximum number of channels to return, all matching channels are
+    /// returned if not set.
+    pub limit: Option<usize>,
+}
+
+impl ChannelQuery {
+    /// Returns whether a channel with the given properties matches the filters of
+    /// the query. The cursor and limit are not taken into account.
+    pub fn matches(
+        &self,
+        state: ChannelStateType,
+        counter_party: &PublicKey,
+        reference_id: Option<&ReferenceId>,
+        created_at: u64,
+    ) -> bool {
+        (self.states.is_empty() || self.states.contains(&state))
+            && self.counter_party.map_or(true, |c| &c == counter_party)
+            && self.reference_id.map_or(true, |r| reference_id == Some(&r))
+            && self.created_at.contains(created_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(created_at: u64, id: u8) -> QueryCursor {
+        QueryCursor {
+            created_at,
+            id: [id; 32],
+        }
+    }
+
+    #[test]
+    fn time_range_contains_test() {
+        let range = TimeRange {
+            from: Some(10),
+            to: Some(20),
+        };
+
+        assert!(!range.contains(9));
+        assert!(range.contains(10));
+        assert!(range.contains(19));
+        assert!(!range.contains(20));
+        assert!(TimeRange::default().contains(0));
+    }
+
+    #[test]
+    fn page_from_matches_is_ordered_and_limited() {
+        let records = vec![
+            (cursor(2, 1), 3),
+            (cursor(1, 2), 2),
+            (cursor(1, 1), 1),
+            (cursor(3, 0), 4),
+        ];
+
+        let page = Page::from_matches(records.clone(), None, Some(2));
+        assert_eq!(vec![1, 2], page.items);
+        assert_eq!(Some(cursor(1, 2)), page.next_cursor);
+
+        let page = Page::from_matches(records.clone(), page.next_cursor, Some(2));
+        assert_eq!(vec![3, 4], page.items);
+        assert_eq!(None, page.next_cursor);
+
+        let page = Page::from_matches(records, None, None);
+        assert_eq!(vec![1, 2, 3, 4], page.items);
+        assert_eq!(None, page.next_cursor);
+    }
+}
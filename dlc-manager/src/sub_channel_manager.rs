@@ -47,15 +47,20 @@ use crate::{
     channel_updater::{
         self, FundingInfo, SubChannelSignInfo, SubChannelSignVerifyInfo, SubChannelVerifyInfo,
     },
-    contract::{contract_input::ContractInput, ClosedContract, Contract, FundingInputInfo},
+    contract::{
+        contract_input::ContractInput, ser::VersionedSerializable, ClosedContract, Contract,
+        FundingInputInfo,
+    },
     error::Error,
-    manager::{get_channel_in_state, get_contract_in_state, Manager, CET_NSEQUENCE},
+    events::Event,
+    manager::{get_channel_in_state, get_contract_in_state, Manager},
     subchannel::{
         generate_temporary_dlc_channel_id, AcceptedSubChannel, CloseAcceptedSubChannel,
         CloseConfirmedSubChannel, CloseOfferedSubChannel, ClosingSubChannel, ConfirmedSubChannel,
         LNChainMonitor, LNChannelManager, LnDlcChannelSigner, LnDlcSignerProvider,
         OfferedSubChannel, ReestablishFlag, SignedSubChannel, SubChannel, SubChannelState,
     },
+    utils::VBYTES_PER_KILO_WEIGHT,
     Blockchain, DlcChannelId, Oracle, Signer, Storage, Time, Wallet,
 };
 
@@ -180,6 +185,8 @@ impl_dlc_writeable_enum!(Action,
     });;
 );
 
+impl VersionedSerializable for Action {}
+
 /// Structure enabling management of DLC channels embedded within Lightning Network channels.
 pub struct SubChannelManager<
     W: Deref,
@@ -400,8 +407,8 @@ where
             contract_input,
             &channel_details.counterparty.node_id,
             oracle_announcements,
-            crate::manager::CET_NSEQUENCE,
-            crate::manager::REFUND_DELAY,
+            self.dlc_channel_manager.get_config().cet_nsequence,
+            self.dlc_channel_manager.get_config().refund_delay,
             self.dlc_channel_manager.get_wallet(),
             self.dlc_channel_manager.get_blockchain(),
             self.dlc_channel_manager.get_time(),
@@ -467,7 +474,7 @@ where
             offer_collateral: offered_contract.offer_params.collateral,
             cet_locktime: offered_contract.cet_locktime,
             refund_locktime: offered_contract.refund_locktime,
-            cet_nsequence: crate::manager::CET_NSEQUENCE,
+            cet_nsequence: offered_channel.cet_nsequence,
             fee_rate_per_vbyte: contract_input.fee_rate,
         };
 
@@ -643,7 +650,7 @@ where
                     },
                     &funding_redeemscript,
                     PackedLockTime::ZERO,
-                    Sequence(crate::manager::CET_NSEQUENCE),
+                    Sequence(offered_channel.cet_nsequence),
                     glue_tx_output_value,
                 );
 
@@ -673,16 +680,19 @@ where
                 };
 
                 let (accepted_channel, mut accepted_contract, accept_channel) =
-                    channel_updater::accept_channel_offer_internal(
-                        self.dlc_channel_manager.get_secp(),
-                        &offered_channel,
-                        &offered_contract,
-                        self.dlc_channel_manager.get_wallet(),
-                        self.dlc_channel_manager.get_blockchain(),
-                        Some(sub_channel_info),
-                        params,
-                        FeeConfig::EvenSplit.into(),
-                    )?;
+                    self.dlc_channel_manager.with_adaptor_point_cache(|adaptor_point_cache| {
+                        channel_updater::accept_channel_offer_internal(
+                            self.dlc_channel_manager.get_secp(),
+                            adaptor_point_cache,
+                            &offered_channel,
+                            &offered_contract,
+                            self.dlc_channel_manager.get_wallet(),
+                            self.dlc_channel_manager.get_blockchain(),
+                            Some(sub_channel_info),
+                            params,
+                            FeeConfig::EvenSplit.into(),
+                        )
+                    })?;
 
                 let ln_glue_signature = dlc::util::get_raw_sig_for_tx_input(
                     self.dlc_channel_manager.get_secp(),
@@ -981,10 +991,11 @@ where
             .get_blockchain()
             .get_transaction_confirmations(&state.signed_sub_channel.split_tx.transaction.txid())?;
 
-        if split_tx_confs < crate::manager::CET_NSEQUENCE {
+        let cet_nsequence = self.dlc_channel_manager.get_config().cet_nsequence;
+        if split_tx_confs < cet_nsequence {
             return Err(Error::InvalidState(format!(
                 "NSequence hasn't elapsed yet, need {} more blocks",
-                crate::manager::CET_NSEQUENCE - split_tx_confs
+                cet_nsequence - split_tx_confs
             )));
         }
 
@@ -1568,6 +1579,10 @@ where
             .get_store()
             .upsert_sub_channel(&signed_sub_channel)?;
 
+        self.dlc_channel_manager.push_event(Event::SubChannelSigned {
+            channel_id: signed_sub_channel.channel_id,
+        });
+
         Ok(())
     }
 
@@ -1826,7 +1841,7 @@ where
             },
             &funding_redeemscript,
             PackedLockTime::ZERO,
-            Sequence(crate::manager::CET_NSEQUENCE),
+            Sequence(offered_channel.cet_nsequence),
             glue_tx_output_value,
         );
 
@@ -1912,18 +1927,20 @@ where
         };
 
         let (signed_channel, signed_contract, sign_channel) =
-            crate::channel_updater::verify_and_sign_accepted_channel_internal(
-                self.dlc_channel_manager.get_secp(),
-                &offered_channel,
-                &offered_contract,
-                &accept_channel,
-                //TODO(tibo): this should be parameterizable.
-                crate::manager::CET_NSEQUENCE,
-                self.dlc_channel_manager.get_wallet(),
-                Some(sub_channel_info),
-                self.dlc_channel_manager.get_chain_monitor(),
-                FeeConfig::EvenSplit.into(),
-            )?;
+            self.dlc_channel_manager.with_adaptor_point_cache(|adaptor_point_cache| {
+                crate::channel_updater::verify_and_sign_accepted_channel_internal(
+                    self.dlc_channel_manager.get_secp(),
+                    adaptor_point_cache,
+                    &offered_channel,
+                    &offered_contract,
+                    &accept_channel,
+                    offered_channel.cet_nsequence,
+                    self.dlc_channel_manager.get_wallet(),
+                    Some(sub_channel_info),
+                    self.dlc_channel_manager.get_chain_monitor(),
+                    FeeConfig::EvenSplit.into(),
+                )
+            })?;
 
         dlc::verify_tx_input_sig(
             self.dlc_channel_manager.get_secp(),
@@ -2112,15 +2129,18 @@ where
                 };
 
                 let (signed_channel, signed_contract, _) =
-                    channel_updater::verify_signed_channel_internal(
-                        self.dlc_channel_manager.get_secp(),
-                        &accepted_channel,
-                        &accepted_contract,
-                        &sign_channel,
-                        self.dlc_channel_manager.get_wallet(),
-                        Some(sub_channel_info),
-                        self.dlc_channel_manager.get_chain_monitor(),
-                    )?;
+                    self.dlc_channel_manager.with_adaptor_point_cache(|adaptor_point_cache| {
+                        channel_updater::verify_signed_channel_internal(
+                            self.dlc_channel_manager.get_secp(),
+                            adaptor_point_cache,
+                            &accepted_channel,
+                            &accepted_contract,
+                            &sign_channel,
+                            self.dlc_channel_manager.get_wallet(),
+                            Some(sub_channel_info),
+                            self.dlc_channel_manager.get_chain_monitor(),
+                        )
+                    })?;
 
                 let split_adaptor_signature = self.get_holder_split_tx_adaptor_signature(
                     sub_channel_confirm.channel_id,
@@ -2282,6 +2302,10 @@ where
                 self.dlc_channel_manager
                     .get_store()
                     .upsert_sub_channel(&confirmed_sub_channel)?;
+
+                self.dlc_channel_manager.push_event(Event::SubChannelSigned {
+                    channel_id: confirmed_sub_channel.channel_id,
+                });
                 Ok(msg)
             },
         )?;
@@ -2330,6 +2354,10 @@ where
                 self.dlc_channel_manager
                     .get_store()
                     .upsert_sub_channel(&confirmed_sub_channel)?;
+
+                self.dlc_channel_manager.push_event(Event::SubChannelSigned {
+                    channel_id: confirmed_sub_channel.channel_id,
+                });
                 Ok(())
             },
         )?;
@@ -2637,7 +2665,7 @@ where
 
                 self.dlc_channel_manager
                     .get_store()
-                    .upsert_channel(dlc_channel, contract)?;
+                    .upsert_channel(dlc_channel, contract.clone())?;
 
                 self.dlc_channel_manager
                     .get_store()
@@ -2646,6 +2674,13 @@ where
                 self.dlc_channel_manager
                     .get_store()
                     .persist_chain_monitor(&chain_monitor)?;
+
+                if let Some(contract) = &contract {
+                    self.dlc_channel_manager.push_contract_event(contract);
+                }
+                self.dlc_channel_manager.push_event(Event::SubChannelClosed {
+                    channel_id: sub_channel.channel_id,
+                });
                 Ok(finalize)
             },
         )?;
@@ -2751,7 +2786,7 @@ where
 
                 self.dlc_channel_manager
                     .get_store()
-                    .upsert_channel(dlc_channel, contract)?;
+                    .upsert_channel(dlc_channel, contract.clone())?;
 
                 self.dlc_channel_manager
                     .get_store()
@@ -2760,6 +2795,13 @@ where
                 self.dlc_channel_manager
                     .get_store()
                     .persist_chain_monitor(&chain_monitor)?;
+
+                if let Some(contract) = &contract {
+                    self.dlc_channel_manager.push_contract_event(contract);
+                }
+                self.dlc_channel_manager.push_event(Event::SubChannelClosed {
+                    channel_id: sub_channel.channel_id,
+                });
                 Ok(())
             },
         )?;
@@ -3155,7 +3197,7 @@ where
                         .get_est_sat_per_1000_weight(
                             lightning::chain::chaininterface::ConfirmationTarget::HighPriority,
                         )
-                        / 250)
+                        / VBYTES_PER_KILO_WEIGHT)
                         .into();
 
                     let signed_tx =
@@ -3181,6 +3223,12 @@ where
                     self.dlc_channel_manager
                         .get_store()
                         .upsert_sub_channel(&sub_channel)?;
+
+                    self.dlc_channel_manager
+                        .push_event(Event::SubChannelPunished {
+                            channel_id: sub_channel.channel_id,
+                            punish_txid: signed_tx.txid(),
+                        });
                 }
             } else if let TxType::CollaborativeClose = channel_info.tx_type {
                 todo!();
@@ -3269,7 +3317,7 @@ where
                             offer_collateral: contract.offer_params.collateral,
                             cet_locktime: contract.cet_locktime,
                             refund_locktime: contract.refund_locktime,
-                            cet_nsequence: crate::manager::CET_NSEQUENCE,
+                            cet_nsequence: self.dlc_channel_manager.get_config().cet_nsequence,
                             fee_rate_per_vbyte: contract.fee_rate_per_vb,
                         };
                         self.actions
@@ -3311,7 +3359,10 @@ where
                                     offer_per_update_seed: None,
                                     is_offer_party: false,
                                     counter_party: dlc_channel.counter_party,
-                                    cet_nsequence: CET_NSEQUENCE,
+                                    cet_nsequence: self
+                                        .dlc_channel_manager
+                                        .get_config()
+                                        .cet_nsequence,
                                     reference_id: None,
                                     fee_config: Some(FeeConfig::EvenSplit)
                                 };
@@ -3388,7 +3439,10 @@ where
                                     is_offer_party: true,
                                     counter_party: dlc_channel.counter_party,
                                     // TODO(tibo): use value from original offer
-                                    cet_nsequence: CET_NSEQUENCE,
+                                    cet_nsequence: self
+                                        .dlc_channel_manager
+                                        .get_config()
+                                        .cet_nsequence,
                                     reference_id: None,
                                     fee_config: Some(FeeConfig::EvenSplit)
                                 };
@@ -3457,7 +3511,10 @@ where
                                             offer_per_update_seed: None,
                                             is_offer_party: false,
                                             counter_party: dlc_channel.counter_party,
-                                            cet_nsequence: CET_NSEQUENCE,
+                                            cet_nsequence: self
+                                                .dlc_channel_manager
+                                                .get_config()
+                                                .cet_nsequence,
                                             reference_id: None,
                                             fee_config: Some(FeeConfig::EvenSplit),
                                         };
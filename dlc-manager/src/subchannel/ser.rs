@@ -1,4 +1,5 @@
 //! Serialization of DLC on Lightning related data structures.
+use crate::contract::ser::VersionedSerializable;
 use dlc::channel::sub_channel::SplitTx;
 use dlc_messages::ser_impls::{read_ecdsa_adaptor_signature, write_ecdsa_adaptor_signature};
 use lightning::ln::msgs::DecodeError;
@@ -28,6 +29,8 @@ impl_dlc_writeable!(SubChannel, {
     (channel_keys_id, option)
 });
 
+impl VersionedSerializable for SubChannel {}
+
 impl_dlc_writeable_enum!(SubChannelState,
     (0, Offered),
     (1, Accepted),
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use bitcoin::{consensus::Encodable, Txid};
+use bitcoin::{consensus::Encodable, Script, Transaction, Txid};
 use dlc::{FeeConfig, util::{cet_or_refund_base_fee, dlc_payout_spk_fee}, PartyParams, TxInputInfo, FUND_TX_BASE_WEIGHT};
 use dlc_messages::{
     oracle_msgs::{OracleAnnouncement, OracleAttestation},
@@ -12,9 +12,14 @@ use secp256k1_zkp::rand::{thread_rng, Rng, RngCore};
 use secp256k1_zkp::{PublicKey, Secp256k1, SecretKey, Signing};
 
 use crate::{
-    channel::party_points::PartyBasePoints, contract::{contract_info::ContractInfo, AdaptorInfo, FundingInputInfo}, error::Error, Blockchain, Wallet
+    channel::party_points::PartyBasePoints, contract::{contract_info::ContractInfo, AdaptorInfo, FundingInputInfo}, error::Error, Blockchain, Utxo, Wallet
 };
 
+/// The number of virtual bytes in 1000 weight units, used to convert the fee
+/// rates of a [`lightning::chain::chaininterface::FeeEstimator`], expressed in
+/// satoshis per 1000 weight units, to satoshis per virtual byte.
+pub(crate) const VBYTES_PER_KILO_WEIGHT: u32 = 250;
+
 macro_rules! get_object_in_state {
     ($manager: expr, $id: expr, $state: ident, $peer_id: expr, $object_type: ident, $get_call: ident) => {{
         let object = $manager.get_store().$get_call($id)?;
@@ -103,88 +108,143 @@ where
     let funding_privkey = wallet.get_new_secret_key()?;
     let funding_pubkey = PublicKey::from_secret_key(secp, &funding_privkey);
 
-    let payout_addr = wallet.get_new_address()?;
-    let payout_spk = payout_addr.script_pubkey();
-    let payout_serial_id = get_new_serial_id();
-    let change_addr = wallet.get_new_address()?;
-    let change_spk = change_addr.script_pubkey();
-    let change_serial_id = get_new_serial_id();
+    let payout_spk = wallet.get_new_address()?.script_pubkey();
+    let change_spk = wallet.get_new_address()?.script_pubkey();
 
-    let mut funding_inputs_info: Vec<FundingInputInfo> = Vec::new();
-    let mut funding_tx_info: Vec<TxInputInfo> = Vec::new();
-    let mut total_input = 0;
+    let mut utxos = Vec::new();
 
     if needs_utxo {
-        let tx_fees = match (fee_config, is_offer) {
-            (FeeConfig::EvenSplit, _) => {
-                let half_of_cet_or_refund_fee = get_half_cet_or_refund_fee(fee_rate)?;
-
-                let payout_spk_fee = dlc_payout_spk_fee(&payout_spk, fee_rate);
-
-                // The extra fee is split evenly between both parties. For simplicity, we allow overshooting
-                // by 1 sat during coin selection
-                let extra_fee_half = extra_fee.div_ceil(2);
-
-                half_of_cet_or_refund_fee + payout_spk_fee + extra_fee_half
-            },
-            (FeeConfig::AllOffer, true) | (FeeConfig::AllAccept, false) => {
-                let cet_or_refund_fee = cet_or_refund_base_fee(fee_rate)?;
-
-                // We assume that both parties use the same kind of SPK.
-                let payout_spk_fees = dlc_payout_spk_fee(&payout_spk, fee_rate) * 2;
-
-                cet_or_refund_fee + payout_spk_fees + extra_fee
-            },
-            (FeeConfig::AllOffer, false) | (FeeConfig::AllAccept, true) => 0,
-        };
+        let tx_fees = get_party_funding_fee(fee_rate, &payout_spk, extra_fee, is_offer, fee_config)?;
 
         let appr_required_amount = own_collateral
             + tx_fees;
 
-        let utxos = wallet.get_utxos_for_amount(
+        for utxo in wallet.get_utxos_for_amount(
             appr_required_amount,
             Some(fee_rate),
             (FUND_TX_BASE_WEIGHT / 2) as u64,
             true,
-        )?;
-        for utxo in utxos {
+        )? {
             let prev_tx = blockchain.get_transaction(&utxo.outpoint.txid)?;
-            let mut writer = Vec::new();
-            prev_tx.consensus_encode(&mut writer)?;
-            let prev_tx_vout = utxo.outpoint.vout;
-            let sequence = 0xffffffff;
-            // TODO(tibo): this assumes P2WPKH with low R
-            let max_witness_len = 107;
-            let funding_input = FundingInput {
-                input_serial_id: get_new_serial_id(),
-                prev_tx: writer,
-                prev_tx_vout,
-                sequence,
-                max_witness_len,
-                redeem_script: utxo.redeem_script,
-            };
-            total_input += prev_tx.output[prev_tx_vout as usize].value;
-            funding_tx_info.push((&funding_input).into());
-            let funding_input_info = FundingInputInfo {
-                funding_input,
-                address: Some(utxo.address.clone()),
-            };
-            funding_inputs_info.push(funding_input_info);
+            utxos.push((utxo, prev_tx));
         }
     }
 
+    let (party_params, funding_inputs_info) = build_party_params(
+        funding_pubkey,
+        own_collateral,
+        payout_spk,
+        change_spk,
+        utxos,
+    )?;
+
+    Ok((party_params, funding_privkey, funding_inputs_info))
+}
+
+/// Builds the [`PartyParams`] of a party providing `own_collateral` using the
+/// given UTXOs, each together with the transaction that created it. Shared by
+/// the [`crate::manager::Manager`] and the asynchronous manager, which retrieve
+/// the keys, addresses and UTXOs differently.
+pub(crate) fn build_party_params(
+    fund_pubkey: PublicKey,
+    own_collateral: u64,
+    payout_script_pubkey: Script,
+    change_script_pubkey: Script,
+    utxos: Vec<(Utxo, Transaction)>,
+) -> Result<(PartyParams, Vec<FundingInputInfo>), Error> {
+    let payout_serial_id = get_new_serial_id();
+    let change_serial_id = get_new_serial_id();
+
+    let mut funding_inputs_info: Vec<FundingInputInfo> = Vec::new();
+    let mut funding_tx_info: Vec<TxInputInfo> = Vec::new();
+    let mut total_input = 0;
+
+    for (utxo, prev_tx) in utxos {
+        total_input += prev_tx
+            .output
+            .get(utxo.outpoint.vout as usize)
+            .ok_or_else(|| {
+                Error::InvalidParameters(format!("Unknown funding output {}", utxo.outpoint))
+            })?
+            .value;
+        let funding_input_info = get_funding_input_info(utxo, &prev_tx)?;
+        funding_tx_info.push((&funding_input_info.funding_input).into());
+        funding_inputs_info.push(funding_input_info);
+    }
+
     let party_params = PartyParams {
-        fund_pubkey: funding_pubkey,
-        change_script_pubkey: change_spk,
+        fund_pubkey,
+        change_script_pubkey,
         change_serial_id,
-        payout_script_pubkey: payout_spk,
+        payout_script_pubkey,
         payout_serial_id,
         inputs: funding_tx_info,
         collateral: own_collateral,
         input_amount: total_input,
     };
 
-    Ok((party_params, funding_privkey, funding_inputs_info))
+    Ok((party_params, funding_inputs_info))
+}
+
+/// Returns the share of the CET or refund transaction fees that a party needs
+/// to provide in addition to its collateral.
+pub(crate) fn get_party_funding_fee(
+    fee_rate: u64,
+    payout_spk: &Script,
+    extra_fee: u64,
+    is_offer: bool,
+    fee_config: FeeConfig,
+) -> Result<u64, Error> {
+    let fee = match (fee_config, is_offer) {
+        (FeeConfig::EvenSplit, _) => {
+            let half_of_cet_or_refund_fee = get_half_cet_or_refund_fee(fee_rate)?;
+
+            let payout_spk_fee = dlc_payout_spk_fee(payout_spk, fee_rate);
+
+            // The extra fee is split evenly between both parties. For simplicity, we allow overshooting
+            // by 1 sat during coin selection
+            let extra_fee_half = extra_fee.div_ceil(2);
+
+            half_of_cet_or_refund_fee + payout_spk_fee + extra_fee_half
+        },
+        (FeeConfig::AllOffer, true) | (FeeConfig::AllAccept, false) => {
+            let cet_or_refund_fee = cet_or_refund_base_fee(fee_rate)?;
+
+            // We assume that both parties use the same kind of SPK.
+            let payout_spk_fees = dlc_payout_spk_fee(payout_spk, fee_rate) * 2;
+
+            cet_or_refund_fee + payout_spk_fees + extra_fee
+        },
+        (FeeConfig::AllOffer, false) | (FeeConfig::AllAccept, true) => 0,
+    };
+
+    Ok(fee)
+}
+
+/// Creates the [`FundingInputInfo`] spending the given [`Utxo`], `prev_tx` being
+/// the transaction that created it.
+pub(crate) fn get_funding_input_info(
+    utxo: Utxo,
+    prev_tx: &Transaction,
+) -> Result<FundingInputInfo, Error> {
+    let mut writer = Vec::new();
+    prev_tx.consensus_encode(&mut writer)?;
+    let prev_tx_vout = utxo.outpoint.vout;
+    let sequence = 0xffffffff;
+    // TODO(tibo): this assumes P2WPKH with low R
+    let max_witness_len = 107;
+    let funding_input = FundingInput {
+        input_serial_id: get_new_serial_id(),
+        prev_tx: writer,
+        prev_tx_vout,
+        sequence,
+        max_witness_len,
+        redeem_script: utxo.redeem_script,
+    };
+    Ok(FundingInputInfo {
+        funding_input,
+        address: Some(utxo.address),
+    })
 }
 
 pub(crate) fn get_party_base_points<C: Signing, W: Deref>(
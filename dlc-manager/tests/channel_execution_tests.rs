@@ -6,7 +6,7 @@ use bitcoin_test_utils::rpc_helpers::init_clients;
 use bitcoincore_rpc::RpcApi;
 use dlc::FeeConfig;
 use dlc_manager::contract::contract_input::ContractInput;
-use dlc_manager::manager::Manager;
+use dlc_manager::manager::{ChannelTimeoutPolicy, Manager};
 use dlc_manager::{channel::Channel, contract::Contract, Blockchain, Oracle, Storage, Wallet};
 use dlc_manager::{ContractId, DlcChannelId};
 use dlc_messages::{ChannelMessage, Message};
@@ -108,6 +108,28 @@ enum TestPath {
     RenewRace,
     RenewEstablishedClose,
     CancelOffer,
+    CollaborativeCloseTimeout,
+}
+
+/// The [`ChannelTimeoutPolicy`] that the managers are configured with.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+enum TestTimeoutPolicy {
+    ForceClose,
+    LogOnly,
+    Callback,
+}
+
+/// Checks that a channel that timed out in `$timed_out` state was handled as
+/// expected by the given [`TestTimeoutPolicy`]: left as is for `LogOnly`,
+/// moved to the given state otherwise.
+macro_rules! assert_timed_out_channel_state {
+    ($d:expr, $id:expr, $policy:expr, $timed_out:ident, $p:ident $(, $s: ident)?) => {{
+        if $policy == TestTimeoutPolicy::LogOnly {
+            assert_channel_state!($d, $id, Signed, $timed_out);
+        } else {
+            assert_channel_state!($d, $id, $p $(, $s)?);
+        }
+    }};
 }
 
 #[test]
@@ -287,7 +309,53 @@ fn channel_offer_reject_test() {
     channel_execution_test(get_enum_test_params(1, 1, None), TestPath::CancelOffer);
 }
 
+/// Generates an ignored test running [`channel_execution_test_with_policy`] with
+/// the given test path and timeout policy for each of the given test names.
+macro_rules! channel_timeout_tests {
+    ($($name:ident: $path:ident, $policy:ident;)*) => {
+        $(
+            #[test]
+            #[ignore]
+            fn $name() {
+                channel_execution_test_with_policy(
+                    get_enum_test_params(1, 1, None),
+                    TestPath::$path,
+                    TestTimeoutPolicy::$policy,
+                );
+            }
+        )*
+    };
+}
+
+channel_timeout_tests! {
+    channel_collaborative_close_timeout_test: CollaborativeCloseTimeout, ForceClose;
+    channel_settle_offer_timeout_log_only_test: SettleOfferTimeout, LogOnly;
+    channel_settle_offer_timeout_callback_test: SettleOfferTimeout, Callback;
+    channel_settle_accept_timeout_log_only_test: SettleAcceptTimeout, LogOnly;
+    channel_settle_accept_timeout_callback_test: SettleAcceptTimeout, Callback;
+    channel_settle_confirm_timeout_log_only_test: SettleConfirmTimeout, LogOnly;
+    channel_settle_confirm_timeout_callback_test: SettleConfirmTimeout, Callback;
+    channel_renew_offer_timeout_log_only_test: RenewOfferTimeout, LogOnly;
+    channel_renew_offer_timeout_callback_test: RenewOfferTimeout, Callback;
+    channel_renew_accept_timeout_log_only_test: RenewAcceptTimeout, LogOnly;
+    channel_renew_accept_timeout_callback_test: RenewAcceptTimeout, Callback;
+    channel_renew_confirm_timeout_log_only_test: RenewConfirmTimeout, LogOnly;
+    channel_renew_confirm_timeout_callback_test: RenewConfirmTimeout, Callback;
+    channel_renew_finalize_timeout_log_only_test: RenewFinalizeTimeout, LogOnly;
+    channel_renew_finalize_timeout_callback_test: RenewFinalizeTimeout, Callback;
+    channel_collaborative_close_timeout_log_only_test: CollaborativeCloseTimeout, LogOnly;
+    channel_collaborative_close_timeout_callback_test: CollaborativeCloseTimeout, Callback;
+}
+
 fn channel_execution_test(test_params: TestParams, path: TestPath) {
+    channel_execution_test_with_policy(test_params, path, TestTimeoutPolicy::ForceClose);
+}
+
+fn channel_execution_test_with_policy(
+    test_params: TestParams,
+    path: TestPath,
+    policy: TestTimeoutPolicy,
+) {
     env_logger::init();
     let (alice_send, bob_receive) = channel::<Option<Message>>();
     let (bob_send, alice_receive) = channel::<Option<Message>>();
@@ -376,32 +444,45 @@ fn channel_execution_test(test_params: TestParams, path: TestPath) {
     refresh_wallet(&alice_wallet, 200000000);
     refresh_wallet(&bob_wallet, 200000000);
 
-    let alice_manager = Arc::new(Mutex::new(
-        Manager::new(
-            Arc::clone(&alice_wallet),
-            Arc::clone(&electrs),
-            alice_store,
-            alice_oracles,
-            Arc::clone(&mock_time),
-            Arc::clone(&electrs),
-        )
-        .unwrap(),
-    ));
+    let timeout_callback_called = Arc::new(AtomicBool::new(false));
+    let timeout_policy = match policy {
+        TestTimeoutPolicy::ForceClose => ChannelTimeoutPolicy::ForceClose,
+        TestTimeoutPolicy::LogOnly => ChannelTimeoutPolicy::LogOnly,
+        TestTimeoutPolicy::Callback => {
+            let timeout_callback_called = timeout_callback_called.clone();
+            ChannelTimeoutPolicy::Callback(Arc::new(move |_| {
+                timeout_callback_called.store(true, Ordering::Relaxed);
+                true
+            }))
+        }
+    };
+
+    let mut alice_manager = Manager::new(
+        Arc::clone(&alice_wallet),
+        Arc::clone(&electrs),
+        alice_store,
+        alice_oracles,
+        Arc::clone(&mock_time),
+        Arc::clone(&electrs),
+    )
+    .unwrap();
+    alice_manager.set_channel_timeout_policy(timeout_policy.clone());
+    let alice_manager = Arc::new(Mutex::new(alice_manager));
 
     let alice_manager_loop = Arc::clone(&alice_manager);
     let alice_manager_send = Arc::clone(&alice_manager);
 
-    let bob_manager = Arc::new(Mutex::new(
-        Manager::new(
-            Arc::clone(&bob_wallet),
-            Arc::clone(&electrs),
-            Arc::clone(&bob_store),
-            bob_oracles,
-            Arc::clone(&mock_time),
-            Arc::clone(&electrs),
-        )
-        .unwrap(),
-    ));
+    let mut bob_manager = Manager::new(
+        Arc::clone(&bob_wallet),
+        Arc::clone(&electrs),
+        Arc::clone(&bob_store),
+        bob_oracles,
+        Arc::clone(&mock_time),
+        Arc::clone(&electrs),
+    )
+    .unwrap();
+    bob_manager.set_channel_timeout_policy(timeout_policy);
+    let bob_manager = Arc::new(Mutex::new(bob_manager));
 
     let bob_manager_loop = Arc::clone(&bob_manager);
     let bob_manager_send = Arc::clone(&bob_manager);
@@ -602,6 +683,16 @@ fn channel_execution_test(test_params: TestParams, path: TestPath) {
                         accept_own_offer
                     );
                 }
+                TestPath::CollaborativeCloseTimeout => {
+                    collaborative_close_timeout(
+                        first,
+                        first_send,
+                        second,
+                        second_receive,
+                        channel_id,
+                        policy,
+                    );
+                }
                 TestPath::SettleOfferTimeout
                 | TestPath::SettleAcceptTimeout
                 | TestPath::SettleConfirmTimeout => {
@@ -613,7 +704,8 @@ fn channel_execution_test(test_params: TestParams, path: TestPath) {
                         second_send,
                         second_receive,
                         channel_id,
-                        path,
+                        path.clone(),
+                        policy,
                     );
                 }
                 TestPath::SettleReject => {
@@ -706,7 +798,8 @@ fn channel_execution_test(test_params: TestParams, path: TestPath) {
                                 second_receive,
                                 channel_id,
                                 &test_params.contract_input,
-                                path,
+                                path.clone(),
+                                policy,
                                 &generate_blocks,
                             );
                         }
@@ -799,6 +892,11 @@ fn channel_execution_test(test_params: TestParams, path: TestPath) {
         }
     }
 
+    assert_eq!(
+        policy == TestTimeoutPolicy::Callback,
+        timeout_callback_called.load(Ordering::Relaxed)
+    );
+
     alice_send.send(None).unwrap();
     bob_send.send(None).unwrap();
 
@@ -1274,6 +1372,7 @@ fn renew_timeout<F: Fn(u64)>(
     channel_id: DlcChannelId,
     contract_input: &ContractInput,
     path: TestPath,
+    policy: TestTimeoutPolicy,
     generate_blocks: &F,
 ) {
     {
@@ -1297,7 +1396,7 @@ fn renew_timeout<F: Fn(u64)>(
             );
             periodic_check(first.clone());
 
-            assert_channel_state!(first, channel_id, Closed);
+            assert_timed_out_channel_state!(first, channel_id, policy, RenewOffered, Closed);
         } else {
             let (renew_accept, _) = second
                 .lock()
@@ -1320,7 +1419,7 @@ fn renew_timeout<F: Fn(u64)>(
                 );
                 periodic_check(second.clone());
 
-                assert_channel_state!(second, channel_id, Closed);
+                assert_timed_out_channel_state!(second, channel_id, policy, RenewAccepted, Closed);
             } else if let TestPath::RenewConfirmTimeout = path {
                 // Process Confirm
                 second_receive.recv().expect("Error synchronizing");
@@ -1329,7 +1428,7 @@ fn renew_timeout<F: Fn(u64)>(
                 );
                 periodic_check(first.clone());
 
-                assert_channel_state!(first, channel_id, Closed);
+                assert_timed_out_channel_state!(first, channel_id, policy, RenewConfirmed, Closed);
             } else if let TestPath::RenewFinalizeTimeout = path {
                 //Process confirm
                 second_receive.recv().expect("Error synchronizing");
@@ -1342,7 +1441,7 @@ fn renew_timeout<F: Fn(u64)>(
                 generate_blocks(289);
                 periodic_check(second.clone());
 
-                assert_channel_state!(second, channel_id, Closed);
+                assert_timed_out_channel_state!(second, channel_id, policy, RenewFinalized, Closed);
             }
         }
     }
@@ -1357,6 +1456,7 @@ fn settle_timeout(
     second_receive: &Receiver<()>,
     channel_id: DlcChannelId,
     path: TestPath,
+    policy: TestTimeoutPolicy,
 ) {
     let (settle_offer, _) = first
         .lock()
@@ -1378,7 +1478,7 @@ fn settle_timeout(
         );
         periodic_check(first.clone());
 
-        assert_channel_state!(first, channel_id, Signed, Closing);
+        assert_timed_out_channel_state!(first, channel_id, policy, SettledOffered, Signed, Closing);
     } else {
         let (settle_accept, _) = second
             .lock()
@@ -1407,7 +1507,14 @@ fn settle_timeout(
                 .get_store()
                 .get_channel(&channel_id)
                 .unwrap();
-            assert_channel_state!(second, channel_id, Signed, Closing);
+            assert_timed_out_channel_state!(
+                second,
+                channel_id,
+                policy,
+                SettledAccepted,
+                Signed,
+                Closing
+            );
         } else if let TestPath::SettleConfirmTimeout = path {
             // Process Confirm
             second_receive.recv().expect("Error synchronizing");
@@ -1416,7 +1523,52 @@ fn settle_timeout(
             );
             periodic_check(first.clone());
 
-            assert_channel_state!(first, channel_id, Signed, Closing);
+            assert_timed_out_channel_state!(
+                first,
+                channel_id,
+                policy,
+                SettledConfirmed,
+                Signed,
+                Closing
+            );
         }
     }
 }
+
+fn collaborative_close_timeout(
+    first: DlcParty,
+    first_send: &Sender<Option<Message>>,
+    second: DlcParty,
+    second_receive: &Receiver<()>,
+    channel_id: DlcChannelId,
+    policy: TestTimeoutPolicy,
+) {
+    let close_offer = first
+        .lock()
+        .unwrap()
+        .offer_collaborative_close(&channel_id, test_utils::ACCEPT_COLLATERAL, None)
+        .expect("to be able to propose a collaborative close");
+    first_send
+        .send(Some(Message::Channel(
+            ChannelMessage::CollaborativeCloseOffer(close_offer),
+        )))
+        .expect("to be able to send collaborative close");
+    second_receive.recv().expect("Error synchronizing");
+
+    mocks::mock_time::set_time((EVENT_MATURITY as u64) + dlc_manager::manager::PEER_TIMEOUT + 2);
+
+    // The party that received the offer is the one expected to reply, so it
+    // should not consider the channel as timed out.
+    periodic_check(second.clone());
+    assert_channel_state!(second, channel_id, Signed, CollaborativeCloseOffered);
+
+    periodic_check(first.clone());
+    assert_timed_out_channel_state!(
+        first,
+        channel_id,
+        policy,
+        CollaborativeCloseOffered,
+        Signed,
+        Closing
+    );
+}
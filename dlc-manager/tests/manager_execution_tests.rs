@@ -17,6 +17,7 @@ use test_utils::*;
 use bitcoin_test_utils::rpc_helpers::init_clients;
 use bitcoincore_rpc::RpcApi;
 use dlc_manager::contract::{numerical_descriptor::DifferenceParams, Contract};
+use dlc_manager::events::Event;
 use dlc_manager::manager::Manager;
 use dlc_manager::{Blockchain, Oracle, Storage, Wallet};
 use dlc_messages::{AcceptDlc, OfferDlc, OnChainMessage, SignDlc};
@@ -97,6 +98,17 @@ macro_rules! periodic_check {
     };
 }
 
+macro_rules! assert_event {
+    ($d:expr, $($pattern:tt)+) => {
+        assert!($d
+            .lock()
+            .unwrap()
+            .get_and_clear_pending_events()
+            .iter()
+            .any(|e| matches!(e, $($pattern)+)));
+    };
+}
+
 fn numerical_common<F>(
     nb_oracles: usize,
     threshold: usize,
@@ -171,6 +183,7 @@ fn numerical_common_diff_nb_digits(
 enum TestPath {
     Close,
     Refund,
+    CollaborativeClose,
     BadAcceptCetSignature,
     BadAcceptRefundSignature,
     BadSignCetSignature,
@@ -282,6 +295,15 @@ fn enum_single_oracle_refund_test() {
     );
 }
 
+#[test]
+#[ignore]
+fn enum_single_oracle_collaborative_close_test() {
+    manager_execution_test(
+        get_enum_test_params(1, 1, None),
+        TestPath::CollaborativeClose,
+    );
+}
+
 #[test]
 #[ignore]
 fn enum_single_oracle_bad_accept_cet_sig_test() {
@@ -657,18 +679,22 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
             periodic_check!(alice_manager_send, contract_id, Confirmed);
             periodic_check!(bob_manager_send, contract_id, Confirmed);
 
+            assert_event!(alice_manager_send, Event::ContractConfirmed { contract_id: id } if *id == contract_id);
+            assert_event!(bob_manager_send, Event::ContractConfirmed { contract_id: id } if *id == contract_id);
+
             mocks::mock_time::set_time((EVENT_MATURITY as u64) + 1);
 
             // Select the first one to close or refund randomly
             let (first, second) = if thread_rng().next_u32() % 2 == 0 {
-                (alice_manager_send, bob_manager_send)
+                (&alice_manager_send, &bob_manager_send)
             } else {
-                (bob_manager_send, alice_manager_send)
+                (&bob_manager_send, &alice_manager_send)
             };
 
             match path {
                 TestPath::Close => {
                     periodic_check!(first, contract_id, PreClosed);
+                    assert_event!(first, Event::CetBroadcast { contract_id: id, .. } if *id == contract_id);
 
                     // Randomly check with or without having the CET mined
                     let case = thread_rng().next_u64() % 3;
@@ -677,6 +703,38 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
                         generate_blocks(6);
                         periodic_check!(first, contract_id, Closed);
                         periodic_check!(second, contract_id, Closed);
+                        assert_event!(first, Event::ContractClosed { contract_id: id, .. } if *id == contract_id);
+
+                        // The second party detected the CET broadcast by the first one
+                        // and recovered the attestations that were used to close it.
+                        let get_closed = |manager: &Arc<Mutex<_>>| {
+                            let contract = manager
+                                .lock()
+                                .unwrap()
+                                .get_store()
+                                .get_contract(&contract_id)
+                                .unwrap();
+                            match contract {
+                                Some(Contract::Closed(c)) => c,
+                                c => panic!("Unexpected contract state {:?}", c),
+                            }
+                        };
+                        let first_contract = get_closed(first);
+                        let second_contract = get_closed(second);
+                        let first_attestations = first_contract
+                            .attestations
+                            .expect("to have the attestations used to close");
+                        let second_attestations = second_contract
+                            .attestations
+                            .expect("to have recovered the attestations from the CET");
+                        assert!(!second_attestations.is_empty());
+                        assert!(second_attestations
+                            .iter()
+                            .all(|x| first_attestations.contains(x)));
+                        assert_eq!(
+                            first_contract.signed_cet.map(|x| x.txid()),
+                            second_contract.signed_cet.map(|x| x.txid())
+                        );
                     } else if case == 1 {
                         // cet is not yet fully confirmed to blockchain
                         generate_blocks(1);
@@ -699,6 +757,7 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
                     generate_blocks(10);
 
                     periodic_check!(first, contract_id, Refunded);
+                    assert_event!(first, Event::ContractRefunded { contract_id: id } if *id == contract_id);
 
                     // Randomly check with or without having the Refund mined.
                     if thread_rng().next_u32() % 2 == 0 {
@@ -707,6 +766,92 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
 
                     periodic_check!(second, contract_id, Refunded);
                 }
+                TestPath::CollaborativeClose => {
+                    let offer_collateral = test_params.contract_input.offer_collateral;
+                    let accept_collateral = test_params.contract_input.accept_collateral;
+                    let alice_payout = accept_collateral / 2;
+                    let counter_party = "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166"
+                        .parse()
+                        .unwrap();
+
+                    let fund_output_value = match bob_manager_send
+                        .lock()
+                        .unwrap()
+                        .get_store()
+                        .get_contract(&contract_id)
+                        .unwrap()
+                    {
+                        Some(Contract::Confirmed(c)) => {
+                            c.accepted_contract.dlc_transactions.get_fund_output().value
+                        }
+                        _ => panic!("Expected a confirmed contract"),
+                    };
+
+                    // Bob offered the contract and proposes to close it early,
+                    // paying the fee of the closing transaction.
+                    let (close_offer, _) = bob_manager_send
+                        .lock()
+                        .unwrap()
+                        .offer_contract_close(&contract_id, alice_payout, 1)
+                        .expect("Error offering contract close");
+                    let close_fee = close_offer.fee;
+                    bob_send
+                        .send(Some(Message::OnChain(OnChainMessage::CloseOffer(close_offer))))
+                        .unwrap();
+                    sync_receive.recv().expect("Error synchronizing");
+
+                    let close_offer = alice_manager_send
+                        .lock()
+                        .unwrap()
+                        .get_and_clear_pending_events()
+                        .into_iter()
+                        .find_map(|e| match e {
+                            Event::ContractCloseOffered { close_offer, .. } => Some(close_offer),
+                            _ => None,
+                        })
+                        .expect("to have received a close offer");
+                    assert_eq!(alice_payout, close_offer.counter_payout);
+
+                    let close_accept = alice_manager_send
+                        .lock()
+                        .unwrap()
+                        .accept_contract_close(&close_offer, &counter_party)
+                        .expect("Error accepting contract close");
+                    assert_contract_state!(alice_manager_send, contract_id, PreClosed);
+
+                    alice_send
+                        .send(Some(Message::OnChain(OnChainMessage::CloseAccept(close_accept))))
+                        .unwrap();
+                    sync_receive.recv().expect("Error synchronizing");
+                    assert_contract_state!(bob_manager_send, contract_id, PreClosed);
+
+                    generate_blocks(6);
+
+                    periodic_check!(alice_manager_send, contract_id, Closed);
+                    periodic_check!(bob_manager_send, contract_id, Closed);
+
+                    let get_pnl = |manager: &Arc<Mutex<_>>| -> i64 {
+                        match manager
+                            .lock()
+                            .unwrap()
+                            .get_store()
+                            .get_contract(&contract_id)
+                            .unwrap()
+                        {
+                            Some(Contract::Closed(c)) => c.pnl,
+                            _ => panic!("Expected a closed contract"),
+                        }
+                    };
+                    assert_eq!(
+                        alice_payout as i64 - accept_collateral as i64,
+                        get_pnl(&alice_manager_send)
+                    );
+                    assert_eq!(
+                        (fund_output_value - close_fee - alice_payout) as i64
+                            - offer_collateral as i64,
+                        get_pnl(&bob_manager_send)
+                    );
+                }
                 _ => unreachable!(),
             }
         }
@@ -332,7 +332,10 @@ pub fn get_enum_contract_descriptor(total_collateral: u64) -> ContractDescriptor
             }
         })
         .collect();
-    ContractDescriptor::Enum(EnumDescriptor { outcome_payouts })
+    ContractDescriptor::Enum(EnumDescriptor {
+        outcome_payouts,
+        oracle_outcome_mappings: None,
+    })
 }
 
 pub fn get_enum_oracle() -> MockOracle {
@@ -390,6 +393,7 @@ pub fn get_enum_test_params_custom_collateral(
         oracles: OracleInput {
             public_keys: oracles.iter().map(|x| x.get_public_key()).collect(),
             event_id: EVENT_ID.to_owned(),
+            event_ids: Vec::new(),
             threshold: threshold as u16,
         },
     };
@@ -486,6 +490,7 @@ pub fn get_numerical_contract_descriptor(
             }],
         },
         oracle_numeric_infos,
+        outcome_params: None,
         difference_params,
     })
 }
@@ -607,6 +612,7 @@ pub fn get_numerical_test_params_custom_collateral(
         oracles: OracleInput {
             public_keys: oracles.iter().map(|x| x.get_public_key()).collect(),
             event_id: EVENT_ID.to_owned(),
+            event_ids: Vec::new(),
             threshold: threshold as u16,
         },
         contract_descriptor,
@@ -639,6 +645,7 @@ pub fn get_enum_and_numerical_test_params(
         oracles: OracleInput {
             public_keys: enum_oracles.iter().map(|x| x.get_public_key()).collect(),
             event_id: EVENT_ID.to_owned(),
+            event_ids: Vec::new(),
             threshold: threshold as u16,
         },
         contract_descriptor: enum_contract_descriptor,
@@ -657,6 +664,7 @@ pub fn get_enum_and_numerical_test_params(
                 .map(|x| x.get_public_key())
                 .collect(),
             event_id: EVENT_ID.to_owned(),
+            event_ids: Vec::new(),
             threshold: threshold as u16,
         },
         contract_descriptor: numerical_contract_descriptor,
@@ -687,6 +695,7 @@ pub fn get_same_num_digits_oracle_numeric_infos(nb_oracles: usize) -> OracleNume
             .take(nb_oracles)
             .collect(),
         base: BASE as usize,
+        oracle_bases: None,
     }
 }
 
@@ -694,6 +703,7 @@ pub fn get_variable_oracle_numeric_infos(nb_digits: &[usize]) -> OracleNumericIn
     OracleNumericInfo {
         base: BASE as usize,
         nb_digits: nb_digits.to_vec(),
+        oracle_bases: None,
     }
 }
 
@@ -1,5 +1,6 @@
 //! Structure containing information about contract details.
 
+use crate::ser_impls::{read_strings, write_strings};
 use lightning::ln::msgs::DecodeError;
 use lightning::util::ser::{Readable, Writeable, Writer};
 use oracle_msgs::OracleInfo;
@@ -126,10 +127,16 @@ pub enum ContractDescriptor {
     EnumeratedContractDescriptor(EnumeratedContractDescriptor),
     /// Used for contract based on numerical outcomes.
     NumericOutcomeContractDescriptor(NumericOutcomeContractDescriptor),
+    /// Used for contract based on enumerated outcomes for which oracles use
+    /// different outcome labels.
+    MappedEnumeratedContractDescriptor(MappedEnumeratedContractDescriptor),
 }
 
 impl_dlc_writeable_enum!(
-    ContractDescriptor, (0, EnumeratedContractDescriptor), (1, NumericOutcomeContractDescriptor);;;
+    ContractDescriptor,
+    (0, EnumeratedContractDescriptor),
+    (1, NumericOutcomeContractDescriptor),
+    (2, MappedEnumeratedContractDescriptor);;;
 );
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -147,6 +154,45 @@ pub struct EnumeratedContractDescriptor {
 
 impl_dlc_writeable!(EnumeratedContractDescriptor, { (payouts, vec) });
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+/// The outcomes attested by an oracle for the outcomes of a contract based on
+/// enumerated outcome events.
+pub struct OracleOutcomeMapping {
+    /// The outcome attested by the oracle for each of the payouts of the
+    /// contract, in the same order.
+    pub outcomes: Vec<String>,
+}
+
+impl_dlc_writeable!(OracleOutcomeMapping, {
+    (outcomes, {cb_writeable, write_strings, read_strings})
+});
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+/// Information about outcomes and payouts for a contract based on enumerated
+/// outcome events, for which each oracle can use different outcome labels.
+pub struct MappedEnumeratedContractDescriptor {
+    /// The payouts for the different outcomes.
+    pub payouts: Vec<ContractOutcome>,
+    /// The outcomes attested by each oracle, in the order of the oracles of the
+    /// contract.
+    pub oracle_outcome_mappings: Vec<OracleOutcomeMapping>,
+}
+
+impl_dlc_writeable!(MappedEnumeratedContractDescriptor, {
+    (payouts, vec),
+    (oracle_outcome_mappings, vec)
+});
+
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(
     feature = "serde",
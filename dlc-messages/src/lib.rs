@@ -76,6 +76,8 @@ macro_rules! impl_type {
 impl_type!(OFFER_TYPE, OfferDlc, 42778);
 impl_type!(ACCEPT_TYPE, AcceptDlc, 42780);
 impl_type!(SIGN_TYPE, SignDlc, 42782);
+impl_type!(CLOSE_OFFER_TYPE, CloseOffer, 42784);
+impl_type!(CLOSE_ACCEPT_TYPE, CloseAccept, 42786);
 impl_type!(OFFER_CHANNEL_TYPE, OfferChannel, 43000);
 impl_type!(ACCEPT_CHANNEL_TYPE, AcceptChannel, 43002);
 impl_type!(SIGN_CHANNEL_TYPE, SignChannel, 43004);
@@ -518,6 +520,88 @@ impl_dlc_writeable!(SignDlc, {
     (funding_signatures, writeable)
 });
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+/// Message used to offer to collaboratively close an on-chain contract before
+/// its maturity, by spending its funding output with the proposed split. Note
+/// that the signature it contains remains valid until the funding output is
+/// spent: `expiry` is only enforced by peers following the protocol.
+pub struct CloseOffer {
+    /// The version of the protocol used by the peer.
+    pub protocol_version: u32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "crate::serde_utils::serialize_hex",
+            deserialize_with = "crate::serde_utils::deserialize_hex_array"
+        )
+    )]
+    /// The id of the contract referred to by this message.
+    pub contract_id: [u8; 32],
+    /// The proposed payout for the receiving party to close the contract with.
+    pub counter_payout: u64,
+    /// The fee of the closing transaction, paid by the sending party out of
+    /// the remainder of the funding output.
+    pub fee: u64,
+    /// The time (UNIX timestamp) from which the offer must not be accepted.
+    pub expiry: u32,
+    /// The signature of the sending party for the closing transaction.
+    pub close_signature: Signature,
+}
+
+impl_dlc_writeable!(CloseOffer, {
+    (protocol_version, writeable),
+    (contract_id, writeable),
+    (counter_payout, writeable),
+    (fee, writeable),
+    (expiry, writeable),
+    (close_signature, writeable)
+});
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+/// Message used to accept a [`CloseOffer`]. It echoes the terms and signature
+/// of the offer so that the offering party can check that they match what it
+/// proposed, and provides the signature of the accepting party.
+pub struct CloseAccept {
+    /// The version of the protocol used by the peer.
+    pub protocol_version: u32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "crate::serde_utils::serialize_hex",
+            deserialize_with = "crate::serde_utils::deserialize_hex_array"
+        )
+    )]
+    /// The id of the contract referred to by this message.
+    pub contract_id: [u8; 32],
+    /// The payout of the accepting party, as proposed in the [`CloseOffer`].
+    pub counter_payout: u64,
+    /// The fee of the closing transaction, as proposed in the [`CloseOffer`].
+    pub fee: u64,
+    /// The signature of the offering party for the closing transaction.
+    pub offer_signature: Signature,
+    /// The signature of the accepting party for the closing transaction.
+    pub accept_signature: Signature,
+}
+
+impl_dlc_writeable!(CloseAccept, {
+    (protocol_version, writeable),
+    (contract_id, writeable),
+    (counter_payout, writeable),
+    (fee, writeable),
+    (offer_signature, writeable),
+    (accept_signature, writeable)
+});
+
 #[allow(missing_docs)]
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -532,6 +616,8 @@ pub enum OnChainMessage {
     Offer(OfferDlc),
     Accept(AcceptDlc),
     Sign(SignDlc),
+    CloseOffer(CloseOffer),
+    CloseAccept(CloseAccept),
 }
 
 #[allow(missing_docs)]
@@ -621,7 +707,9 @@ impl_type_writeable_for_enum!(OnChainMessage,
 {
     Offer,
     Accept,
-    Sign
+    Sign,
+    CloseOffer,
+    CloseAccept
 });
 
 impl_type_writeable_for_enum!(ChannelMessage,
@@ -757,6 +845,58 @@ mod tests {
         roundtrip_test!(SignDlc, input);
     }
 
+    #[test]
+    fn close_msgs_roundtrip() {
+        let sk = secp256k1_zkp::SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let msg = secp256k1_zkp::Message::from_slice(&[2u8; 32]).unwrap();
+        let signature = SECP256K1.sign_ecdsa(&msg, &sk);
+
+        test_roundtrip(CloseOffer {
+            protocol_version: 1,
+            contract_id: [3u8; 32],
+            counter_payout: 100_000,
+            fee: 1_000,
+            expiry: 1_623_133_104,
+            close_signature: signature,
+        });
+        test_roundtrip(CloseAccept {
+            protocol_version: 1,
+            contract_id: [3u8; 32],
+            counter_payout: 100_000,
+            fee: 1_000,
+            offer_signature: signature,
+            accept_signature: signature,
+        });
+    }
+
+    #[test]
+    fn mapped_enum_contract_descriptor_roundtrip() {
+        test_roundtrip(
+            contract_msgs::ContractDescriptor::MappedEnumeratedContractDescriptor(
+                contract_msgs::MappedEnumeratedContractDescriptor {
+                    payouts: vec![
+                        contract_msgs::ContractOutcome {
+                            outcome: "a".to_string(),
+                            offer_payout: 100_000,
+                        },
+                        contract_msgs::ContractOutcome {
+                            outcome: "b".to_string(),
+                            offer_payout: 0,
+                        },
+                    ],
+                    oracle_outcome_mappings: vec![
+                        contract_msgs::OracleOutcomeMapping {
+                            outcomes: vec!["a".to_string(), "b".to_string()],
+                        },
+                        contract_msgs::OracleOutcomeMapping {
+                            outcomes: vec!["yes".to_string(), "no".to_string()],
+                        },
+                    ],
+                },
+            ),
+        );
+    }
+
     #[test]
     fn valid_offer_message_passes_validation() {
         let input = include_str!("./test_inputs/offer_msg.json");
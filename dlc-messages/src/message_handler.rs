@@ -120,7 +120,9 @@ fn read_dlc_message<R: ::std::io::Read>(
             OnChainMessage,
             (OFFER_TYPE, Offer),
             (ACCEPT_TYPE, Accept),
-            (SIGN_TYPE, Sign)
+            (SIGN_TYPE, Sign),
+            (CLOSE_OFFER_TYPE, CloseOffer),
+            (CLOSE_ACCEPT_TYPE, CloseAccept)
         ),
         (
             Channel,
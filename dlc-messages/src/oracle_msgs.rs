@@ -218,7 +218,10 @@ impl OracleEvent {
     pub fn validate(&self) -> Result<(), Error> {
         let expected_nb_nonces = match &self.event_descriptor {
             EventDescriptor::EnumEvent(_) => 1,
-            EventDescriptor::DigitDecompositionEvent(d) => d.nb_digits as usize,
+            // Signed outcomes use an additional nonce to attest to the sign.
+            EventDescriptor::DigitDecompositionEvent(d) => {
+                d.nb_digits as usize + d.is_signed as usize
+            }
         };
 
         if expected_nb_nonces == self.oracle_nonces.len() {
@@ -419,6 +422,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn signed_digit_event_requires_sign_nonce_test() {
+        let mut event = digit_event(10);
+        if let EventDescriptor::DigitDecompositionEvent(d) = &mut event.event_descriptor {
+            d.is_signed = true;
+        }
+        event
+            .validate()
+            .expect_err("signed event should have a nonce for the sign.");
+        event.oracle_nonces.push(some_schnorr_pubkey());
+        event.validate().expect("a valid signed event.");
+    }
+
     #[test]
     fn invalid_oracle_announcement_signature_fails_validation_test() {
         let key_pair = KeyPair::new(SECP256K1, &mut thread_rng());
@@ -14,53 +14,110 @@
 extern crate dlc_manager;
 extern crate sled;
 
+pub use dlc_manager::encryption::EncryptionKey;
+
+use bitcoin::hashes::Hash;
 #[cfg(feature = "wallet")]
-use bitcoin::{Address, Txid};
+use bitcoin::Address;
+use bitcoin::Txid;
 use dlc_manager::chain_monitor::ChainMonitor;
-use dlc_manager::channel::accepted_channel::AcceptedChannel;
 use dlc_manager::channel::offered_channel::OfferedChannel;
 use dlc_manager::channel::signed_channel::{SignedChannel, SignedChannelStateType};
-use dlc_manager::channel::{
-    Channel, ClosedChannel, ClosedPunishedChannel, ClosingChannel, FailedAccept, FailedSign, SettledClosingChannel,
-};
-use dlc_manager::contract::accepted_contract::AcceptedContract;
+use dlc_manager::channel::{Channel, ChannelStateType, SettledClosingChannel};
+use dlc_manager::contract::adaptor_point_cache::CachedAdaptorPoint;
 use dlc_manager::contract::offered_contract::OfferedContract;
-use dlc_manager::contract::ser::Serializable;
+use dlc_manager::contract::ser::{VersionedSerializable, SERIALIZATION_VERSION};
 use dlc_manager::contract::signed_contract::SignedContract;
-use dlc_manager::contract::{
-    ClosedContract, Contract, FailedAcceptContract, FailedSignContract, PreClosedContract,
-};
+use dlc_manager::contract::{Contract, ContractStateType, PreClosedContract, PreRefundedContract};
+use dlc_manager::encryption::Cipher;
+use dlc_manager::query::{ChannelQuery, ContractQuery, Page, QueryCursor, TimeRange};
 use dlc_manager::subchannel::{SubChannel, SubChannelState};
 #[cfg(feature = "wallet")]
 use dlc_manager::Utxo;
-use dlc_manager::{error::Error, ContractId, DlcChannelId, Storage};
+use dlc_manager::{error::Error, ContractId, DlcChannelId, FeeBump, Storage};
 use lightning::ln::ChannelId;
 use lightning::util::ser::{Readable, Writeable};
 #[cfg(feature = "wallet")]
 use secp256k1_zkp::{PublicKey, SecretKey};
 #[cfg(feature = "wallet")]
 use simple_wallet::WalletStorage;
-use sled::transaction::{ConflictableTransactionResult, UnabortableTransactionError};
+use sled::transaction::{
+    ConflictableTransactionError, ConflictableTransactionResult, TransactionalTree,
+    UnabortableTransactionError,
+};
 use sled::{Db, Transactional, Tree};
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const CONTRACT_TREE: u8 = 1;
 const CHANNEL_TREE: u8 = 2;
 const CHAIN_MONITOR_TREE: u8 = 3;
 const CHAIN_MONITOR_KEY: u8 = 4;
-#[cfg(feature = "wallet")]
 const UTXO_TREE: u8 = 5;
-#[cfg(feature = "wallet")]
 const KEY_PAIR_TREE: u8 = 6;
 const SUB_CHANNEL_TREE: u8 = 7;
-#[cfg(feature = "wallet")]
 const ADDRESS_TREE: u8 = 8;
+const ADAPTOR_POINT_CACHE_TREE: u8 = 9;
+const CONTRACT_INDEX_TREE: u8 = 11;
+const CHANNEL_INDEX_TREE: u8 = 12;
+const FEE_BUMP_TREE: u8 = 13;
+/// Identifies the default tree in the associated data of its encrypted values.
+const DEFAULT_TREE: u8 = 0;
 const ACTION_KEY: u8 = 1;
+const VERSION_KEY: u8 = 2;
+const ENCRYPTION_KEY: u8 = 3;
+/// The check of the new key of an ongoing key rotation, empty if the values are
+/// being decrypted.
+const ROTATION_CHECK_KEY: u8 = 4;
+/// The position of the last value rewritten by an ongoing key rotation.
+const ROTATION_POSITION_KEY: u8 = 5;
+/// Encrypted and stored under [`ENCRYPTION_KEY`] to check the key used to open the
+/// database.
+const ENCRYPTION_CHECK: &[u8] = b"dlc-sled-storage-provider";
+/// The maximum number of values rewritten in each transaction of a key rotation.
+const ROTATION_BATCH_SIZE: usize = 500;
+/// The trees whose values are encrypted when encryption is enabled.
+const ENCRYPTED_TREES: [u8; 9] = [
+    CONTRACT_TREE,
+    CHANNEL_TREE,
+    SUB_CHANNEL_TREE,
+    CHAIN_MONITOR_TREE,
+    ADAPTOR_POINT_CACHE_TREE,
+    FEE_BUMP_TREE,
+    UTXO_TREE,
+    KEY_PAIR_TREE,
+    ADDRESS_TREE,
+];
+/// The index trees, whose entries and keys are rewritten when encryption is
+/// enabled or the key is rotated.
+const INDEX_TREES: [u8; 2] = [CONTRACT_INDEX_TREE, CHANNEL_INDEX_TREE];
+/// Prefixes of the keys of the index trees. The [`IndexEntry`] of a record is
+/// stored under its id, and the other keys order the records by creation time,
+/// grouped by one of their properties.
+const INDEX_ENTRY: u8 = 0;
+const BY_CREATION: u8 = 1;
+const BY_STATE: u8 = 2;
+const BY_COUNTER_PARTY: u8 = 3;
+const BY_REFERENCE_ID: u8 = 4;
+
+/// The version of the layout of the database, recorded alongside the data. The
+/// persisted objects are themselves prefixed with the version of their
+/// serialization, [`SERIALIZATION_VERSION`].
+pub const STORAGE_VERSION: u8 = 1;
+
+type Migration = fn(&SledStorageProvider) -> Result<(), Error>;
+
+/// Migrations of the stored data, the one at index `i` migrating the database from
+/// version `i` to version `i + 1`.
+const MIGRATIONS: [Migration; STORAGE_VERSION as usize] = [SledStorageProvider::migrate_v0_to_v1];
 
 /// Implementation of Storage interface using the sled DB backend.
 pub struct SledStorageProvider {
     db: Db,
+    cipher: Option<Cipher>,
 }
 
 macro_rules! convertible_enum {
@@ -115,7 +172,8 @@ convertible_enum!(
         FailedAccept,
         FailedSign,
         Refunded,
-        Rejected,;
+        Rejected,
+        PreRefunded,;
     },
     Contract
 );
@@ -177,6 +235,245 @@ convertible_enum!(
     SubChannelState
 );
 
+impl From<ContractStateType> for ContractPrefix {
+    fn from(state: ContractStateType) -> ContractPrefix {
+        match state {
+            ContractStateType::Offered => ContractPrefix::Offered,
+            ContractStateType::Accepted => ContractPrefix::Accepted,
+            ContractStateType::Signed => ContractPrefix::Signed,
+            ContractStateType::Confirmed => ContractPrefix::Confirmed,
+            ContractStateType::PreClosed => ContractPrefix::PreClosed,
+            ContractStateType::Closed => ContractPrefix::Closed,
+            ContractStateType::PreRefunded => ContractPrefix::PreRefunded,
+            ContractStateType::Refunded => ContractPrefix::Refunded,
+            ContractStateType::FailedAccept => ContractPrefix::FailedAccept,
+            ContractStateType::FailedSign => ContractPrefix::FailedSign,
+            ContractStateType::Rejected => ContractPrefix::Rejected,
+        }
+    }
+}
+
+impl From<ChannelStateType> for ChannelPrefix {
+    fn from(state: ChannelStateType) -> ChannelPrefix {
+        match state {
+            ChannelStateType::Offered => ChannelPrefix::Offered,
+            ChannelStateType::Accepted => ChannelPrefix::Accepted,
+            ChannelStateType::Signed => ChannelPrefix::Signed,
+            ChannelStateType::Closing => ChannelPrefix::Closing,
+            ChannelStateType::SettledClosing => ChannelPrefix::SettledClosing,
+            ChannelStateType::Closed => ChannelPrefix::Closed,
+            ChannelStateType::CounterClosed => ChannelPrefix::CounterClosed,
+            ChannelStateType::ClosedPunished => ChannelPrefix::ClosedPunished,
+            ChannelStateType::CollaborativelyClosed => ChannelPrefix::CollaborativelyClosed,
+            ChannelStateType::FailedAccept => ChannelPrefix::FailedAccept,
+            ChannelStateType::FailedSign => ChannelPrefix::FailedSign,
+            ChannelStateType::Cancelled => ChannelPrefix::Cancelled,
+        }
+    }
+}
+
+/// The properties of a contract or channel used to query them, stored in the
+/// index tree of the record type.
+#[derive(Clone, Debug, PartialEq)]
+struct IndexEntry {
+    created_at: u64,
+    state: u8,
+    counter_party: [u8; 33],
+    reference_id: Option<[u8; 32]>,
+    maturity: Option<u64>,
+}
+
+impl IndexEntry {
+    fn for_contract(contract: &Contract, created_at: u64) -> Self {
+        IndexEntry {
+            created_at,
+            state: ContractPrefix::get_prefix(contract),
+            counter_party: contract.get_counter_party_id().serialize(),
+            reference_id: None,
+            maturity: contract.get_maturity(),
+        }
+    }
+
+    fn for_channel(channel: &Channel, created_at: u64) -> Self {
+        IndexEntry {
+            created_at,
+            state: ChannelPrefix::get_prefix(channel),
+            counter_party: channel.get_counter_party_id().serialize(),
+            reference_id: channel.get_reference_id(),
+            maturity: None,
+        }
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(84);
+        res.extend_from_slice(&self.created_at.to_be_bytes());
+        res.push(self.state);
+        res.extend_from_slice(&self.counter_party);
+        match &self.reference_id {
+            Some(reference_id) => {
+                res.push(1);
+                res.extend_from_slice(reference_id);
+            }
+            None => res.push(0),
+        }
+        match self.maturity {
+            Some(maturity) => {
+                res.push(1);
+                res.extend_from_slice(&maturity.to_be_bytes());
+            }
+            None => res.push(0),
+        }
+        res
+    }
+
+    fn deserialize(buff: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(buff);
+        let created_at = u64::from_be_bytes(read_array(&mut cursor)?);
+        let [state] = read_array::<_, 1>(&mut cursor)?;
+        let counter_party = read_array(&mut cursor)?;
+        let reference_id = match read_array::<_, 1>(&mut cursor)? {
+            [0] => None,
+            _ => Some(read_array(&mut cursor)?),
+        };
+        let maturity = match read_array::<_, 1>(&mut cursor)? {
+            [0] => None,
+            _ => Some(u64::from_be_bytes(read_array(&mut cursor)?)),
+        };
+        Ok(IndexEntry {
+            created_at,
+            state,
+            counter_party,
+            reference_id,
+            maturity,
+        })
+    }
+}
+
+/// The index tree of a record type. When encryption is enabled, the properties
+/// grouping the records are replaced by their keyed hash in the keys of the tree
+/// and the index entries are encrypted.
+#[derive(Clone, Copy)]
+struct Index<'a> {
+    tree_id: u8,
+    cipher: Option<&'a Cipher>,
+}
+
+impl Index<'_> {
+    /// Returns the prefix of the keys ordering the records whose property of the
+    /// given kind has the given value.
+    fn prefix(&self, kind: u8, value: &[u8]) -> Vec<u8> {
+        let mut res = vec![kind];
+        match self.cipher {
+            Some(cipher) => {
+                let mut data = vec![self.tree_id, kind];
+                data.extend_from_slice(value);
+                res.extend_from_slice(&cipher.hash(&data));
+            }
+            None => res.extend_from_slice(value),
+        }
+        res
+    }
+
+    /// Returns the keys ordering the record with the given id in the index tree.
+    fn order_keys(&self, entry: &IndexEntry, id: &[u8; 32]) -> Vec<Vec<u8>> {
+        let mut keys = vec![
+            vec![BY_CREATION],
+            self.prefix(BY_STATE, &[entry.state]),
+            self.prefix(BY_COUNTER_PARTY, &entry.counter_party),
+        ];
+        if let Some(reference_id) = &entry.reference_id {
+            keys.push(self.prefix(BY_REFERENCE_ID, reference_id));
+        }
+        for key in keys.iter_mut() {
+            key.extend_from_slice(&entry.created_at.to_be_bytes());
+            key.extend_from_slice(id);
+        }
+        keys
+    }
+
+    /// Decrypts and deserializes the index entry stored under the given key.
+    fn read_entry(&self, key: &[u8], value: &[u8]) -> Result<IndexEntry, Error> {
+        let context = record_context(self.tree_id, key);
+        IndexEntry::deserialize(&unseal_record(self.cipher, &context, 0, value)?)
+    }
+
+    /// Returns the index entry of the record with the given id.
+    fn get(&self, tree: &Tree, id: &[u8; 32]) -> Result<Option<IndexEntry>, Error> {
+        let key = index_entry_key(id);
+        match tree.get(&key).map_err(to_storage_error)? {
+            Some(value) => Ok(Some(self.read_entry(&key, &value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Indexes the record with the given id, replacing its previous entry and the
+    /// one of the record it replaces under `temporary_id` if any. The creation time
+    /// of the previous entry is kept, as well as its maturity if the new entry has
+    /// none.
+    fn update(
+        &self,
+        db: &TransactionalTree,
+        id: &[u8; 32],
+        temporary_id: Option<&[u8; 32]>,
+        mut entry: IndexEntry,
+    ) -> ConflictableTransactionResult<(), Error> {
+        let mut previous = self.remove(db, id)?;
+        if let Some(temporary_id) = temporary_id {
+            let temporary = self.remove(db, temporary_id)?;
+            previous = previous.or(temporary);
+        }
+        if let Some(previous) = previous {
+            entry.created_at = previous.created_at;
+            entry.maturity = entry.maturity.or(previous.maturity);
+        }
+        self.insert(db, id, &entry)
+    }
+
+    /// Writes the index entry of the record with the given id along with the keys
+    /// ordering it.
+    fn insert(
+        &self,
+        db: &TransactionalTree,
+        id: &[u8; 32],
+        entry: &IndexEntry,
+    ) -> ConflictableTransactionResult<(), Error> {
+        for key in self.order_keys(entry, id) {
+            db.insert(key, Vec::new())?;
+        }
+        let key = index_entry_key(id);
+        let context = record_context(self.tree_id, &key);
+        let value = seal_record(self.cipher, &context, 0, entry.serialize())
+            .map_err(ConflictableTransactionError::Abort)?;
+        db.insert(key, value)?;
+        Ok(())
+    }
+
+    /// Removes the index entry of the record with the given id along with the keys
+    /// ordering it, returning the removed entry.
+    fn remove(
+        &self,
+        db: &TransactionalTree,
+        id: &[u8; 32],
+    ) -> Result<Option<IndexEntry>, UnabortableTransactionError> {
+        let key = index_entry_key(id);
+        let entry = match db.remove(key.clone())? {
+            Some(value) => match self.read_entry(&key, &value) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    log::error!("Failed to deserialize index entry: {e}");
+                    return Ok(None);
+                }
+            },
+            None => return Ok(None),
+        };
+
+        for key in self.order_keys(&entry, id) {
+            db.remove(key)?;
+        }
+        Ok(Some(entry))
+    }
+}
+
 fn to_storage_error<T>(e: T) -> Error
 where
     T: std::fmt::Display,
@@ -185,36 +482,605 @@ where
 }
 
 impl SledStorageProvider {
-    /// Creates a new instance of a SledStorageProvider.
-    pub fn new(path: &str) -> Result<Self, sled::Error> {
-        Ok(SledStorageProvider {
-            db: sled::open(path)?,
-        })
+    /// Creates a new instance of a SledStorageProvider, migrating the data stored
+    /// at the given path to the current [`STORAGE_VERSION`] if required. Fails if
+    /// the database is encrypted.
+    pub fn new(path: &str) -> Result<Self, Error> {
+        Self::open(path, None, None)
+    }
+
+    /// Creates a new instance of a SledStorageProvider encrypting the stored values
+    /// with the given key. Data stored unencrypted is encrypted when first opening
+    /// the database with a key, and opening an encrypted database with a different
+    /// key fails.
+    ///
+    /// The values are encrypted, each being bound to the tree and key under which it
+    /// is stored, and the indexes used to query contracts and channels only store a
+    /// keyed hash of the properties they are queried by.
+    ///
+    /// The ids of the stored objects, the creation times ordering the indexes and the
+    /// addresses and public keys of the wallet are kept in plaintext. So are the
+    /// state prefixes of the contracts, channels and sub channels, which are used to
+    /// filter them without decrypting every record: anyone with access to the files
+    /// of the database can tell how many contracts and channels are in each state,
+    /// and the state of a given contract or channel.
+    pub fn new_encrypted(path: &str, key: EncryptionKey) -> Result<Self, Error> {
+        // Resume the encryption of a plaintext database if it was interrupted.
+        let mut storage = Self::open(path, Some(&key), Some(Some(Cipher::new(&key))))?;
+        if !storage.is_encrypted() {
+            storage.rotate_encryption_key(Some(key))?;
+        }
+        Ok(storage)
+    }
+
+    /// Opens the database at the given path like [`Self::new_encrypted`] with the key
+    /// it is encrypted with, or like [`Self::new`] if `key` is `None`, completing the
+    /// rotation of its key to `new_key` if [`Self::rotate_encryption_key`] was
+    /// interrupted. Fails if the interrupted rotation was to another key.
+    pub fn resume_key_rotation(
+        path: &str,
+        key: Option<EncryptionKey>,
+        new_key: Option<EncryptionKey>,
+    ) -> Result<Self, Error> {
+        Self::open(path, key.as_ref(), Some(new_key.as_ref().map(Cipher::new)))
+    }
+
+    /// Opens the database at the given path, checking the key of encrypted databases
+    /// and completing the interrupted rotation of their key to `new_cipher` if given,
+    /// before migrating them to the current version.
+    fn open(
+        path: &str,
+        key: Option<&EncryptionKey>,
+        new_cipher: Option<Option<Cipher>>,
+    ) -> Result<Self, Error> {
+        let db = sled::open(path).map_err(to_storage_error)?;
+        let cipher = match db.get([ENCRYPTION_KEY]).map_err(to_storage_error)? {
+            Some(check) => {
+                let key = key.ok_or_else(|| {
+                    Error::StorageError(
+                        "The database is encrypted, an encryption key is required to open it"
+                            .to_string(),
+                    )
+                })?;
+                let cipher = Cipher::new(key);
+                if !is_valid_check(Some(&cipher), &check) {
+                    return Err(Error::StorageError(
+                        "Invalid encryption key for the database".to_string(),
+                    ));
+                }
+                Some(cipher)
+            }
+            None => None,
+        };
+        let mut storage = SledStorageProvider { db, cipher };
+        if storage.is_rotating_key()? {
+            let new_cipher = new_cipher.ok_or_else(|| {
+                Error::StorageError(
+                    "The rotation of the encryption key of the database was interrupted, it must be resumed with `SledStorageProvider::resume_key_rotation`"
+                        .to_string(),
+                )
+            })?;
+            storage.rotate_cipher(new_cipher)?;
+        }
+        // Databases could not be encrypted before version 1, so that the migration
+        // from version 0 only reads and writes plaintext records.
+        let version = storage.get_version()?;
+        storage.migrate(version, STORAGE_VERSION)?;
+        Ok(storage)
+    }
+
+    /// Returns whether the values stored by this instance are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Re-encrypts all the stored values with the given key, or decrypts them if
+    /// `None` is given, rebuilding the indexes accordingly, after which the database
+    /// can only be opened with the new key.
+    ///
+    /// The values are rewritten in batches, each in its own transaction along with
+    /// the progress of the rotation, so that the rotation can be resumed with
+    /// [`Self::resume_key_rotation`] if it is interrupted. The database cannot be
+    /// opened otherwise until the rotation completes.
+    pub fn rotate_encryption_key(&mut self, new_key: Option<EncryptionKey>) -> Result<(), Error> {
+        self.rotate_cipher(new_key.as_ref().map(Cipher::new))
+    }
+
+    /// Rotates the key of the database to the one of `new_cipher`, resuming the
+    /// ongoing rotation if any, which must be to the same key.
+    fn rotate_cipher(&mut self, new_cipher: Option<Cipher>) -> Result<(), Error> {
+        let check = match self
+            .db
+            .get([ROTATION_CHECK_KEY])
+            .map_err(to_storage_error)?
+        {
+            Some(check) if is_valid_check(new_cipher.as_ref(), &check) => check.to_vec(),
+            Some(_) => {
+                return Err(Error::StorageError(
+                    "The encryption key of the database is being rotated to another key"
+                        .to_string(),
+                ))
+            }
+            None => self.begin_key_rotation(new_cipher.as_ref())?,
+        };
+        self.reseal_records(new_cipher.as_ref(), check)?;
+        self.cipher = new_cipher;
+        Ok(())
+    }
+
+    /// Returns whether a rotation of the encryption key is ongoing.
+    fn is_rotating_key(&self) -> Result<bool, Error> {
+        self.db
+            .contains_key([ROTATION_CHECK_KEY])
+            .map_err(to_storage_error)
+    }
+
+    /// Records the start of the rotation of the key of the database to the one of
+    /// `new_cipher`, returning the check of the new key.
+    fn begin_key_rotation(&self, new_cipher: Option<&Cipher>) -> Result<Vec<u8>, Error> {
+        let check = match new_cipher {
+            Some(cipher) => cipher.encrypt(&[], ENCRYPTION_CHECK)?,
+            None => Vec::new(),
+        };
+        let mut batch = sled::Batch::default();
+        batch.insert(&[ROTATION_CHECK_KEY], check.clone());
+        batch.insert(&[ROTATION_POSITION_KEY], vec![0]);
+        self.db.apply_batch(batch).map_err(to_storage_error)?;
+        Ok(check)
     }
 
-    fn get_data_with_prefix<T: Serializable>(
+    /// Decrypts all the encrypted values and encrypts them again with `new_cipher`,
+    /// or writes them in plaintext if `None` is given, binding them to the tree and
+    /// key under which they are stored, and rebuilds the indexes for `new_cipher`.
+    /// The trees are rewritten one after the other from the recorded position of
+    /// the rotation, after which the new `check` of the key is written along with
+    /// the actions stored in the default tree, completing the rotation.
+    fn reseal_records(&self, new_cipher: Option<&Cipher>, check: Vec<u8>) -> Result<(), Error> {
+        let position = self
+            .db
+            .get([ROTATION_POSITION_KEY])
+            .map_err(to_storage_error)?
+            .ok_or_else(|| Error::StorageError("Missing key rotation position".to_string()))?;
+        let (stage, last_key) = match position.split_first() {
+            Some((stage, last_key)) => (*stage as usize, last_key),
+            None => {
+                return Err(Error::StorageError(
+                    "Invalid key rotation position".to_string(),
+                ))
+            }
+        };
+
+        let stages = ENCRYPTED_TREES.iter().chain(INDEX_TREES.iter());
+        for (i, tree_id) in stages.enumerate().skip(stage) {
+            let last_key = if i == stage && !last_key.is_empty() {
+                Some(last_key.to_vec())
+            } else {
+                None
+            };
+            if i < ENCRYPTED_TREES.len() {
+                self.reseal_tree(i as u8, *tree_id, new_cipher, last_key)?;
+            } else {
+                self.reindex_tree(i as u8, *tree_id, new_cipher, last_key)?;
+            }
+        }
+
+        let mut batch = sled::Batch::default();
+        if let Some(actions) = self.db.get([ACTION_KEY]).map_err(to_storage_error)? {
+            let context = record_context(DEFAULT_TREE, &[ACTION_KEY]);
+            let record = unseal_record(self.cipher.as_ref(), &context, 1, &actions)?;
+            batch.insert(
+                &[ACTION_KEY],
+                seal_record(new_cipher, &context, 1, record.into_owned())?,
+            );
+        }
+        if new_cipher.is_some() {
+            batch.insert(&[ENCRYPTION_KEY], check);
+        } else {
+            batch.remove(&[ENCRYPTION_KEY]);
+        }
+        batch.remove(&[ROTATION_CHECK_KEY]);
+        batch.remove(&[ROTATION_POSITION_KEY]);
+        self.db.apply_batch(batch).map_err(to_storage_error)?;
+
+        self.db.flush().map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    /// Encrypts the values of the given tree stored after `last_key` with
+    /// `new_cipher`, recording the position of the rotation, identified by `stage`,
+    /// after each batch of values.
+    fn reseal_tree(
         &self,
-        tree: &Tree,
-        prefix: &[u8],
-        consume: Option<u64>,
-    ) -> Result<Vec<T>, Error> {
-        let iter = tree.iter();
-        iter.values()
-            .filter_map(|res| {
-                let value = res.unwrap();
-                let mut cursor = Cursor::new(&value);
-                let mut pref = vec![0u8; prefix.len()];
-                cursor.read_exact(&mut pref).expect("Error reading prefix");
-                if pref == prefix {
-                    if let Some(c) = consume {
-                        cursor.set_position(cursor.position() + c);
+        stage: u8,
+        tree_id: u8,
+        new_cipher: Option<&Cipher>,
+        mut last_key: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        let tree = self.open_tree(&[tree_id])?;
+        loop {
+            let values = match &last_key {
+                Some(last_key) => tree.range::<&[u8], _>((Excluded(&last_key[..]), Unbounded)),
+                None => tree.iter(),
+            };
+            let mut records = Vec::with_capacity(ROTATION_BATCH_SIZE);
+            for res in values.take(ROTATION_BATCH_SIZE) {
+                let (key, value) = res.map_err(to_storage_error)?;
+                let context = record_context(tree_id, &key);
+                let len = header_len(tree_id, &value);
+                let record = unseal_record(self.cipher.as_ref(), &context, len, &value)?;
+                let record = seal_record(new_cipher, &context, len, record.into_owned())?;
+                records.push((key, record));
+            }
+            let last = match records.last() {
+                Some((key, _)) => key.to_vec(),
+                None => return Ok(()),
+            };
+
+            let default_tree: &Tree = &self.db;
+            (&tree, default_tree)
+                .transaction(
+                    |(db, default_db)| -> ConflictableTransactionResult<(), Error> {
+                        for (key, value) in &records {
+                            db.insert(key.clone(), value.clone())?;
+                        }
+                        default_db
+                            .insert(&[ROTATION_POSITION_KEY], rotation_position(stage, &last))?;
+                        Ok(())
+                    },
+                )
+                .map_err(to_storage_error)?;
+            last_key = Some(last);
+        }
+    }
+
+    /// Rewrites the entries of the given index tree stored after `last_key`, along
+    /// with the keys ordering them, for `new_cipher`, recording the position of the
+    /// rotation, identified by `stage`, after each batch of entries.
+    fn reindex_tree(
+        &self,
+        stage: u8,
+        tree_id: u8,
+        new_cipher: Option<&Cipher>,
+        mut last_key: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        let tree = self.open_tree(&[tree_id])?;
+        let current = self.index(tree_id);
+        let new = Index {
+            tree_id,
+            cipher: new_cipher,
+        };
+        let end = [INDEX_ENTRY + 1];
+        loop {
+            let start = match &last_key {
+                Some(last_key) => Excluded(&last_key[..]),
+                None => Included(&[INDEX_ENTRY][..]),
+            };
+            let ids = tree
+                .range::<&[u8], _>((start, Excluded(&end[..])))
+                .take(ROTATION_BATCH_SIZE)
+                .map(|res| {
+                    let (key, _) = res.map_err(to_storage_error)?;
+                    key[1..]
+                        .try_into()
+                        .map_err(|_| Error::StorageError("Invalid index entry key".to_string()))
+                })
+                .collect::<Result<Vec<[u8; 32]>, _>>()?;
+            let last = match ids.last() {
+                Some(id) => index_entry_key(id),
+                None => return Ok(()),
+            };
+
+            let default_tree: &Tree = &self.db;
+            (&tree, default_tree)
+                .transaction(
+                    |(db, default_db)| -> ConflictableTransactionResult<(), Error> {
+                        for id in &ids {
+                            let key = index_entry_key(id);
+                            let entry = match db.get(&key)? {
+                                Some(value) => current
+                                    .read_entry(&key, &value)
+                                    .map_err(ConflictableTransactionError::Abort)?,
+                                None => continue,
+                            };
+                            for key in current.order_keys(&entry, id) {
+                                db.remove(key)?;
+                            }
+                            new.insert(db, id, &entry)?;
+                        }
+                        default_db
+                            .insert(&[ROTATION_POSITION_KEY], rotation_position(stage, &last))?;
+                        Ok(())
+                    },
+                )
+                .map_err(to_storage_error)?;
+            last_key = Some(last);
+        }
+    }
+
+    /// Returns the version of the format of the data stored in the database.
+    /// Databases written before versioning was introduced are at version 0.
+    pub fn get_version(&self) -> Result<u8, Error> {
+        match self.db.get([VERSION_KEY]).map_err(to_storage_error)? {
+            Some(version) if version.len() == 1 => Ok(version[0]),
+            Some(_) => Err(Error::StorageError(
+                "Invalid storage version record".to_string(),
+            )),
+            None => Ok(0),
+        }
+    }
+
+    /// Migrates the data stored in the database from version `from` to version
+    /// `to`, applying each intermediate migration in order. Only forward
+    /// migrations are supported.
+    pub fn migrate(&self, from: u8, to: u8) -> Result<(), Error> {
+        if from > to {
+            return Err(Error::StorageError(format!(
+                "Cannot migrate storage from version {from} to older version {to}"
+            )));
+        }
+        if to > STORAGE_VERSION {
+            return Err(Error::StorageError(format!(
+                "Cannot migrate storage to unknown version {to}"
+            )));
+        }
+        let current = self.get_version()?;
+        if current != from {
+            return Err(Error::StorageError(format!(
+                "Cannot migrate storage from version {from}, database is at version {current}"
+            )));
+        }
+
+        for version in from..to {
+            MIGRATIONS[version as usize](self)?;
+        }
+
+        Ok(())
+    }
+
+    /// Migrates the databases written by releases predating storage versioning,
+    /// whose objects are serialized without version. Their serialization being
+    /// unchanged, the objects are prefixed with version 0 of their serialization,
+    /// written after the state prefixes of their records, and the indexes used to
+    /// query contracts and channels are built. The time at which the existing
+    /// contracts and channels were created is unknown and recorded as 0. Keys and
+    /// addresses of the wallet are stored as raw bytes and are left untouched.
+    fn migrate_v0_to_v1(&self) -> Result<(), Error> {
+        const VERSION: u8 = 1;
+        let tree_ids = [
+            CONTRACT_TREE,
+            CHANNEL_TREE,
+            SUB_CHANNEL_TREE,
+            CHAIN_MONITOR_TREE,
+            UTXO_TREE,
+        ];
+        let trees = tree_ids
+            .iter()
+            .map(|tree_id| self.open_tree(&[*tree_id]))
+            .collect::<Result<Vec<_>, _>>()?;
+        let index_trees = INDEX_TREES
+            .iter()
+            .map(|tree_id| self.open_tree(&[*tree_id]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut contract_entries = Vec::new();
+        let mut channel_entries = Vec::new();
+        let mut records = Vec::with_capacity(trees.len());
+        for (tree_id, tree) in tree_ids.iter().zip(trees.iter()) {
+            let mut tree_records = Vec::new();
+            for res in tree.iter() {
+                let (key, value) = res.map_err(to_storage_error)?;
+                // The records start with the same state prefixes as the current ones,
+                // which are followed by the version of the object in their header.
+                let prefix_len = header_len(*tree_id, &value).saturating_sub(1);
+                let record = with_version(0, prefix_len, &value);
+                match *tree_id {
+                    CONTRACT_TREE => match deserialize_contract(&record) {
+                        Ok(c) => {
+                            contract_entries.push((c.get_id(), IndexEntry::for_contract(&c, 0)))
+                        }
+                        Err(e) => log::error!("Failed to deserialize contract: {e}"),
+                    },
+                    CHANNEL_TREE => match deserialize_channel(&record) {
+                        Ok(c) => channel_entries.push((c.get_id(), IndexEntry::for_channel(&c, 0))),
+                        Err(e) => log::error!("Failed to deserialize channel: {e}"),
+                    },
+                    _ => {}
+                };
+                tree_records.push((key, record));
+            }
+            records.push(tree_records);
+        }
+        let actions = self
+            .db
+            .get([ACTION_KEY])
+            .map_err(to_storage_error)?
+            .map(|actions| with_version(0, 0, &actions));
+        let indexes = [
+            (self.index(CONTRACT_INDEX_TREE), contract_entries),
+            (self.index(CHANNEL_INDEX_TREE), channel_entries),
+        ];
+
+        let default_tree: &Tree = &self.db;
+        let mut all_trees = trees.iter().chain(index_trees.iter()).collect::<Vec<_>>();
+        all_trees.push(default_tree);
+        all_trees[..]
+            .transaction::<_, ()>(|dbs| -> ConflictableTransactionResult<(), Error> {
+                let (default_db, dbs) = dbs.split_last().expect("to have the default tree");
+                let (dbs, index_dbs) = dbs.split_at(tree_ids.len());
+                for (db, tree_records) in dbs.iter().zip(records.iter()) {
+                    for (key, value) in tree_records {
+                        db.insert(key.clone(), value.clone())?;
+                    }
+                }
+
+                for (db, (index, entries)) in index_dbs.iter().zip(indexes.iter()) {
+                    for (id, entry) in entries {
+                        index.insert(db, id, entry)?;
                     }
-                    Some(Ok(T::deserialize(&mut cursor).ok()?))
-                } else {
-                    None
                 }
+
+                if let Some(actions) = &actions {
+                    default_db.insert(&[ACTION_KEY], actions.clone())?;
+                }
+
+                default_db.insert(&[VERSION_KEY], vec![VERSION])?;
+                Ok(())
             })
-            .collect()
+            .map_err(to_storage_error)?;
+
+        self.db.flush().map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    /// Returns the positions of the records of the given index tree matching
+    /// `filter` and created within `created_at`, scanning the records ordered under
+    /// each of the given key prefixes. At most `limit + 1` positions are returned
+    /// for each prefix so that callers can tell whether more records match.
+    fn query_index<F: Fn(&IndexEntry) -> bool>(
+        &self,
+        index: Index,
+        prefixes: Vec<Vec<u8>>,
+        created_at: &TimeRange,
+        cursor: Option<QueryCursor>,
+        limit: Option<usize>,
+        filter: F,
+    ) -> Result<Vec<QueryCursor>, Error> {
+        let max = limit.map(|limit| limit.saturating_add(1));
+        let tree = self.open_tree(&[index.tree_id])?;
+        let mut res = Vec::new();
+
+        for prefix in prefixes {
+            let mut start = prefix.clone();
+            start.extend_from_slice(&created_at.from.unwrap_or(0).to_be_bytes());
+            if let Some(cursor) = &cursor {
+                let mut after_cursor = prefix.clone();
+                after_cursor.extend_from_slice(&cursor.created_at.to_be_bytes());
+                after_cursor.extend_from_slice(&cursor.id);
+                start = start.max(after_cursor);
+            }
+
+            let mut found = 0;
+            for item in tree.range(start..) {
+                let (key, _) = item.map_err(to_storage_error)?;
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                let position = read_position(&key[prefix.len()..])?;
+                if created_at.to.map_or(false, |to| position.created_at >= to) {
+                    break;
+                }
+                if cursor.map_or(false, |cursor| position <= cursor) {
+                    continue;
+                }
+                let entry = match index.get(&tree, &position.id)? {
+                    Some(entry) => entry,
+                    None => continue,
+                };
+                if filter(&entry) {
+                    res.push(position);
+                    found += 1;
+                    if max.map_or(false, |max| found >= max) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        res.sort();
+        res.dedup();
+        Ok(res)
+    }
+
+    /// Reads the records of the given tree at the given sorted positions, keeping at
+    /// most `limit` of them.
+    fn load_page<T>(
+        &self,
+        tree_id: u8,
+        mut positions: Vec<QueryCursor>,
+        limit: Option<usize>,
+        deserialize: fn(&[u8]) -> Result<T, Error>,
+    ) -> Result<Page<T>, Error> {
+        let next_cursor = match limit {
+            Some(limit) if positions.len() > limit => {
+                positions.truncate(limit);
+                positions.last().copied()
+            }
+            _ => None,
+        };
+
+        let tree = self.open_tree(&[tree_id])?;
+        let mut items = Vec::with_capacity(positions.len());
+        for position in positions {
+            let value = match tree.get(position.id).map_err(to_storage_error)? {
+                Some(value) => value,
+                None => continue,
+            };
+            match self
+                .unseal(tree_id, &position.id, &value)
+                .and_then(|record| deserialize(&record))
+            {
+                Ok(item) => items.push(item),
+                Err(e) => log::error!("Failed to deserialize record: {e}"),
+            }
+        }
+
+        Ok(Page { items, next_cursor })
+    }
+
+    fn get_data_with_prefix<T: VersionedSerializable>(
+        &self,
+        tree_id: u8,
+        prefix: &[u8],
+        consume: Option<u64>,
+    ) -> Result<Vec<T>, Error> {
+        let iter = self.open_tree(&[tree_id])?.iter();
+        iter.filter_map(|res| {
+            let (key, value) = res.unwrap();
+            if value.starts_with(prefix) {
+                let len = prefix.len() + consume.unwrap_or(0) as usize;
+                let context = record_context(tree_id, &key);
+                // The version of the object is part of the plaintext header.
+                let record = match unseal_record(self.cipher.as_ref(), &context, len + 1, &value) {
+                    Ok(record) => record,
+                    Err(e) => return Some(Err(e)),
+                };
+                let mut cursor = Cursor::new(&record);
+                cursor.set_position(len as u64);
+                Some(Ok(T::deserialize_versioned(&mut cursor).ok()?))
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    /// Encrypts the record stored under the given key of the given tree if
+    /// encryption is enabled.
+    fn seal(&self, tree_id: u8, key: &[u8], record: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let context = record_context(tree_id, key);
+        seal_record(
+            self.cipher.as_ref(),
+            &context,
+            header_len(tree_id, &record),
+            record,
+        )
+    }
+
+    /// Decrypts the record stored under the given key of the given tree if
+    /// encryption is enabled.
+    fn unseal<'a>(
+        &self,
+        tree_id: u8,
+        key: &[u8],
+        record: &'a [u8],
+    ) -> Result<Cow<'a, [u8]>, Error> {
+        let context = record_context(tree_id, key);
+        unseal_record(
+            self.cipher.as_ref(),
+            &context,
+            header_len(tree_id, record),
+            record,
+        )
     }
 
     fn open_tree(&self, tree_id: &[u8; 1]) -> Result<Tree, Error> {
@@ -234,6 +1100,21 @@ impl SledStorageProvider {
     fn sub_channel_tree(&self) -> Result<Tree, Error> {
         self.open_tree(&[SUB_CHANNEL_TREE])
     }
+
+    fn contract_index_tree(&self) -> Result<Tree, Error> {
+        self.open_tree(&[CONTRACT_INDEX_TREE])
+    }
+
+    fn channel_index_tree(&self) -> Result<Tree, Error> {
+        self.open_tree(&[CHANNEL_INDEX_TREE])
+    }
+
+    fn index(&self, tree_id: u8) -> Index<'_> {
+        Index {
+            tree_id,
+            cipher: self.cipher.as_ref(),
+        }
+    }
 }
 
 #[cfg(feature = "wallet")]
@@ -258,7 +1139,10 @@ impl Storage for SledStorageProvider {
             .get(contract_id)
             .map_err(to_storage_error)?
         {
-            Some(res) => Ok(Some(deserialize_contract(&res)?)),
+            Some(res) => {
+                let record = self.unseal(CONTRACT_TREE, contract_id, &res)?;
+                Ok(Some(deserialize_contract(&record)?))
+            }
             None => Ok(None),
         }
     }
@@ -267,8 +1151,12 @@ impl Storage for SledStorageProvider {
         Ok(self
             .contract_tree()?
             .iter()
-            .values()
-            .filter_map(|x| match deserialize_contract(&x.unwrap()) {
+            .map(|x| {
+                let (key, value) = x.unwrap();
+                self.unseal(CONTRACT_TREE, &key, &value)
+                    .and_then(|record| deserialize_contract(&record))
+            })
+            .filter_map(|res| match res {
                 Ok(contract) => Some(contract),
                 Err(e) => {
                     log::error!("Failed to deserialize contract: {e}");
@@ -279,97 +1167,142 @@ impl Storage for SledStorageProvider {
     }
 
     fn create_contract(&self, contract: &OfferedContract) -> Result<(), Error> {
-        let serialized = serialize_contract(&Contract::Offered(contract.clone()))?;
-        self.contract_tree()?
-            .insert(contract.id, serialized)
-            .map_err(to_storage_error)?;
-        Ok(())
+        self.update_contract(&Contract::Offered(contract.clone()))
     }
 
     fn delete_contract(&self, contract_id: &ContractId) -> Result<(), Error> {
-        self.contract_tree()?
-            .remove(contract_id)
+        let index = self.index(CONTRACT_INDEX_TREE);
+        (&self.contract_tree()?, &self.contract_index_tree()?)
+            .transaction::<_, ()>(
+                |(db, index_db)| -> ConflictableTransactionResult<(), Error> {
+                    db.remove(contract_id)?;
+                    index.remove(index_db, contract_id)?;
+                    Ok(())
+                },
+            )
             .map_err(to_storage_error)?;
         Ok(())
     }
 
     fn update_contract(&self, contract: &Contract) -> Result<(), Error> {
-        let serialized = serialize_contract(contract)?;
-        self.contract_tree()?
-            .transaction::<_, _, UnabortableTransactionError>(|db| {
-                match contract {
-                    a @ Contract::Accepted(_) | a @ Contract::Signed(_) => {
-                        db.remove(&a.get_temporary_id())?;
-                    }
-                    _ => {}
-                };
-
-                db.insert(&contract.get_id(), serialized.clone())?;
-                Ok(())
-            })
+        let serialized = self.seal(
+            CONTRACT_TREE,
+            &contract.get_id(),
+            serialize_contract(contract)?,
+        )?;
+        let timestamp = get_timestamp();
+        let index = self.index(CONTRACT_INDEX_TREE);
+        (&self.contract_tree()?, &self.contract_index_tree()?)
+            .transaction::<_, ()>(
+                |(db, index_db)| -> ConflictableTransactionResult<(), Error> {
+                    insert_contract(db, index_db, index, serialized.clone(), contract, timestamp)?;
+                    Ok(())
+                },
+            )
             .map_err(to_storage_error)?;
         Ok(())
     }
 
     fn get_signed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
-        self.get_data_with_prefix(
-            &self.contract_tree()?,
-            &[ContractPrefix::Signed.into()],
-            None,
-        )
+        self.get_data_with_prefix(CONTRACT_TREE, &[ContractPrefix::Signed.into()], None)
     }
 
     fn get_confirmed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
-        self.get_data_with_prefix(
-            &self.contract_tree()?,
-            &[ContractPrefix::Confirmed.into()],
-            None,
-        )
+        self.get_data_with_prefix(CONTRACT_TREE, &[ContractPrefix::Confirmed.into()], None)
     }
 
     fn get_contract_offers(&self) -> Result<Vec<OfferedContract>, Error> {
-        self.get_data_with_prefix(
-            &self.contract_tree()?,
-            &[ContractPrefix::Offered.into()],
-            None,
-        )
+        self.get_data_with_prefix(CONTRACT_TREE, &[ContractPrefix::Offered.into()], None)
     }
 
     fn get_preclosed_contracts(&self) -> Result<Vec<PreClosedContract>, Error> {
-        self.get_data_with_prefix(
-            &self.contract_tree()?,
-            &[ContractPrefix::PreClosed.into()],
-            None,
-        )
+        self.get_data_with_prefix(CONTRACT_TREE, &[ContractPrefix::PreClosed.into()], None)
+    }
+
+    fn get_prerefunded_contracts(&self) -> Result<Vec<PreRefundedContract>, Error> {
+        self.get_data_with_prefix(CONTRACT_TREE, &[ContractPrefix::PreRefunded.into()], None)
+    }
+
+    fn query_contracts(&self, query: &ContractQuery) -> Result<Page<Contract>, Error> {
+        let states = query
+            .states
+            .iter()
+            .map(|state| ContractPrefix::from(*state).into())
+            .collect::<Vec<u8>>();
+        let counter_party = query.counter_party.map(|c| c.serialize());
+        let index = self.index(CONTRACT_INDEX_TREE);
+        let prefixes = match &counter_party {
+            Some(counter_party) => vec![index.prefix(BY_COUNTER_PARTY, counter_party)],
+            None if !states.is_empty() => states
+                .iter()
+                .map(|state| index.prefix(BY_STATE, &[*state]))
+                .collect(),
+            None => vec![vec![BY_CREATION]],
+        };
+
+        let positions = self.query_index(
+            index,
+            prefixes,
+            &query.created_at,
+            query.cursor,
+            query.limit,
+            |entry| {
+                (states.is_empty() || states.contains(&entry.state))
+                    && counter_party.map_or(true, |c| c == entry.counter_party)
+                    && (query.maturity.is_unbounded()
+                        || entry.maturity.map_or(false, |m| query.maturity.contains(m)))
+            },
+        )?;
+        self.load_page(CONTRACT_TREE, positions, query.limit, deserialize_contract)
     }
 
     fn upsert_channel(&self, channel: Channel, contract: Option<Contract>) -> Result<(), Error> {
-        let serialized = serialize_channel(&channel)?;
+        let serialized = self.seal(
+            CHANNEL_TREE,
+            &channel.get_id(),
+            serialize_channel(&channel)?,
+        )?;
         let serialized_contract = match contract.as_ref() {
-            Some(c) => Some(serialize_contract(c)?),
+            Some(c) => Some(self.seal(CONTRACT_TREE, &c.get_id(), serialize_contract(c)?)?),
             None => None,
         };
+        let timestamp = get_timestamp();
         let channel_tree = self.channel_tree()?;
+        let channel_index_tree = self.channel_index_tree()?;
         let contract_tree = self.contract_tree()?;
-        (&channel_tree, &contract_tree)
+        let contract_index_tree = self.contract_index_tree()?;
+        let channel_index = self.index(CHANNEL_INDEX_TREE);
+        let contract_index = self.index(CONTRACT_INDEX_TREE);
+        (&channel_tree, &channel_index_tree, &contract_tree, &contract_index_tree)
             .transaction::<_, ()>(
-                |(channel_db, contract_db)| -> ConflictableTransactionResult<(), UnabortableTransactionError> {
-                    match &channel {
+                |(channel_db, channel_index_db, contract_db, contract_index_db)| -> ConflictableTransactionResult<(), Error> {
+                    let temporary_id = match &channel {
                         a @ Channel::Accepted(_) | a @ Channel::Signed(_) => {
-                            channel_db.remove(&a.get_temporary_id())?;
+                            let temporary_id = a.get_temporary_id();
+                            channel_db.remove(&temporary_id)?;
+                            Some(temporary_id)
                         }
-                        _ => {}
+                        _ => None,
                     };
 
                     channel_db.insert(&channel.get_id(), serialized.clone())?;
+                    channel_index.update(
+                        channel_index_db,
+                        &channel.get_id(),
+                        temporary_id.as_ref(),
+                        IndexEntry::for_channel(&channel, timestamp),
+                    )?;
 
                     if let Some(c) = contract.as_ref() {
                         insert_contract(
                             contract_db,
+                            contract_index_db,
+                            contract_index,
                             serialized_contract
                                 .clone()
                                 .expect("to have the serialized version"),
                             c,
+                            timestamp,
                         )?;
                     }
                     Ok(())
@@ -382,8 +1315,15 @@ impl Storage for SledStorageProvider {
     }
 
     fn delete_channel(&self, channel_id: &DlcChannelId) -> Result<(), Error> {
-        self.channel_tree()?
-            .remove(channel_id)
+        let index = self.index(CHANNEL_INDEX_TREE);
+        (&self.channel_tree()?, &self.channel_index_tree()?)
+            .transaction::<_, ()>(
+                |(db, index_db)| -> ConflictableTransactionResult<(), Error> {
+                    db.remove(channel_id)?;
+                    index.remove(index_db, channel_id)?;
+                    Ok(())
+                },
+            )
             .map_err(to_storage_error)?;
         Ok(())
     }
@@ -394,7 +1334,10 @@ impl Storage for SledStorageProvider {
             .get(channel_id)
             .map_err(to_storage_error)?
         {
-            Some(res) => Ok(Some(deserialize_channel(&res)?)),
+            Some(res) => {
+                let record = self.unseal(CHANNEL_TREE, channel_id, &res)?;
+                Ok(Some(deserialize_channel(&record)?))
+            }
             None => Ok(None),
         }
     }
@@ -415,48 +1358,136 @@ impl Storage for SledStorageProvider {
             (vec![ChannelPrefix::Signed.into()], Some(1))
         };
 
-        self.get_data_with_prefix(&self.channel_tree()?, &prefix, consume)
+        self.get_data_with_prefix(CHANNEL_TREE, &prefix, consume)
     }
 
     fn get_offered_channels(&self) -> Result<Vec<OfferedChannel>, Error> {
-        self.get_data_with_prefix(
-            &self.channel_tree()?,
-            &[ChannelPrefix::Offered.into()],
-            None,
-        )
+        self.get_data_with_prefix(CHANNEL_TREE, &[ChannelPrefix::Offered.into()], None)
     }
 
     fn get_settled_closing_channels(&self) -> Result<Vec<SettledClosingChannel>, Error> {
-        self.get_data_with_prefix(
-            &self.channel_tree()?,
-            &[ChannelPrefix::SettledClosing.into()],
-            None,
-        )
+        self.get_data_with_prefix(CHANNEL_TREE, &[ChannelPrefix::SettledClosing.into()], None)
+    }
+
+    fn persist_chain_monitor(&self, monitor: &ChainMonitor) -> Result<(), Error> {
+        self.open_tree(&[CHAIN_MONITOR_TREE])?
+            .insert(
+                [CHAIN_MONITOR_KEY],
+                self.seal(
+                    CHAIN_MONITOR_TREE,
+                    &[CHAIN_MONITOR_KEY],
+                    monitor.serialize_versioned()?,
+                )?,
+            )
+            .map_err(|e| Error::StorageError(format!("Error writing chain monitor: {e}")))?;
+        Ok(())
+    }
+    fn get_chain_monitor(&self) -> Result<Option<ChainMonitor>, dlc_manager::error::Error> {
+        let serialized = self
+            .open_tree(&[CHAIN_MONITOR_TREE])?
+            .get([CHAIN_MONITOR_KEY])
+            .map_err(|e| Error::StorageError(format!("Error reading chain monitor: {e}")))?;
+        let deserialized = match serialized {
+            Some(s) => {
+                let record = self.unseal(CHAIN_MONITOR_TREE, &[CHAIN_MONITOR_KEY], &s)?;
+                let mut cursor = ::std::io::Cursor::new(record);
+                Some(ChainMonitor::deserialize_versioned(&mut cursor).map_err(to_storage_error)?)
+            }
+            None => None,
+        };
+        Ok(deserialized)
+    }
+
+    fn upsert_adaptor_points(&self, points: &[CachedAdaptorPoint]) -> Result<(), Error> {
+        let tree = self.open_tree(&[ADAPTOR_POINT_CACHE_TREE])?;
+        let mut batch = sled::Batch::default();
+        for point in points {
+            batch.insert(
+                &point.key[..],
+                self.seal(
+                    ADAPTOR_POINT_CACHE_TREE,
+                    &point.key,
+                    point.serialize_versioned()?,
+                )?,
+            );
+        }
+        tree.apply_batch(batch)
+            .map_err(|e| Error::StorageError(format!("Error writing adaptor points: {e}")))
+    }
+
+    fn get_adaptor_points(&self) -> Result<Vec<CachedAdaptorPoint>, Error> {
+        self.open_tree(&[ADAPTOR_POINT_CACHE_TREE])?
+            .iter()
+            .map(|res| {
+                let (key, value) = res.map_err(|e| {
+                    Error::StorageError(format!("Error reading adaptor points: {e}"))
+                })?;
+                let record = self.unseal(ADAPTOR_POINT_CACHE_TREE, &key, &value)?;
+                let mut cursor = ::std::io::Cursor::new(record);
+                CachedAdaptorPoint::deserialize_versioned(&mut cursor).map_err(to_storage_error)
+            })
+            .collect()
+    }
+
+    fn delete_adaptor_points(&self, keys: &[[u8; 32]]) -> Result<(), Error> {
+        let mut batch = sled::Batch::default();
+        for key in keys {
+            batch.remove(&key[..]);
+        }
+        self.open_tree(&[ADAPTOR_POINT_CACHE_TREE])?
+            .apply_batch(batch)
+            .map_err(|e| Error::StorageError(format!("Error deleting adaptor points: {e}")))
     }
 
-    fn persist_chain_monitor(&self, monitor: &ChainMonitor) -> Result<(), Error> {
-        self.open_tree(&[CHAIN_MONITOR_TREE])?
-            .insert([CHAIN_MONITOR_KEY], monitor.serialize()?)
-            .map_err(|e| Error::StorageError(format!("Error writing chain monitor: {e}")))?;
+    fn upsert_fee_bump(&self, txid: &Txid, fee_bump: &FeeBump) -> Result<(), Error> {
+        let mut serialized = fee_bump.fee_rate.to_be_bytes().to_vec();
+        serialized.extend_from_slice(&fee_bump.child_txid[..]);
+        self.open_tree(&[FEE_BUMP_TREE])?
+            .insert(
+                &txid[..],
+                self.seal(
+                    FEE_BUMP_TREE,
+                    &txid[..],
+                    with_version(SERIALIZATION_VERSION, 0, &serialized),
+                )?,
+            )
+            .map_err(|e| Error::StorageError(format!("Error writing fee bump: {e}")))?;
         Ok(())
     }
-    fn get_chain_monitor(&self) -> Result<Option<ChainMonitor>, dlc_manager::error::Error> {
+
+    fn get_fee_bump(&self, txid: &Txid) -> Result<Option<FeeBump>, Error> {
         let serialized = self
-            .open_tree(&[CHAIN_MONITOR_TREE])?
-            .get([CHAIN_MONITOR_KEY])
-            .map_err(|e| Error::StorageError(format!("Error reading chain monitor: {e}")))?;
+            .open_tree(&[FEE_BUMP_TREE])?
+            .get(&txid[..])
+            .map_err(|e| Error::StorageError(format!("Error reading fee bump: {e}")))?;
         let deserialized = match serialized {
-            Some(s) => Some(
-                ChainMonitor::deserialize(&mut ::std::io::Cursor::new(s))
-                    .map_err(to_storage_error)?,
-            ),
+            Some(s) => {
+                let record = self.unseal(FEE_BUMP_TREE, &txid[..], &s)?;
+                let mut cursor = ::std::io::Cursor::new(record);
+                read_version(&mut cursor)?;
+                Some(FeeBump {
+                    fee_rate: u64::from_be_bytes(read_array(&mut cursor)?),
+                    child_txid: Txid::from_inner(read_array(&mut cursor)?),
+                })
+            }
             None => None,
         };
         Ok(deserialized)
     }
 
+    fn delete_fee_bump(&self, txid: &Txid) -> Result<(), Error> {
+        self.open_tree(&[FEE_BUMP_TREE])?
+            .remove(&txid[..])
+            .map_err(|e| Error::StorageError(format!("Error deleting fee bump: {e}")))?;
+        Ok(())
+    }
+
     fn upsert_sub_channel(&self, subchannel: &SubChannel) -> Result<(), Error> {
-        let serialized = serialize_sub_channel(subchannel)?;
+        let serialized = self.seal(
+            SUB_CHANNEL_TREE,
+            &subchannel.channel_id.0,
+            serialize_sub_channel(subchannel)?,
+        )?;
         self.sub_channel_tree()?
             .insert(subchannel.channel_id.0, serialized)
             .map_err(to_storage_error)?;
@@ -471,7 +1502,10 @@ impl Storage for SledStorageProvider {
             .get(channel_id.0)
             .map_err(to_storage_error)?
         {
-            Some(res) => Ok(Some(deserialize_sub_channel(&res)?)),
+            Some(res) => {
+                let record = self.unseal(SUB_CHANNEL_TREE, &channel_id.0, &res)?;
+                Ok(Some(deserialize_sub_channel(&record)?))
+            }
             None => Ok(None),
         }
     }
@@ -480,8 +1514,12 @@ impl Storage for SledStorageProvider {
         Ok(self
             .sub_channel_tree()?
             .iter()
-            .values()
-            .filter_map(|x| match deserialize_sub_channel(&x.unwrap()) {
+            .map(|x| {
+                let (key, value) = x.unwrap();
+                self.unseal(SUB_CHANNEL_TREE, &key, &value)
+                    .and_then(|record| deserialize_sub_channel(&record))
+            })
+            .filter_map(|res| match res {
                 Ok(sub_channel) => Some(sub_channel),
                 Err(e) => {
                     log::error!("Failed to deserialize subchannel: {e}");
@@ -492,25 +1530,26 @@ impl Storage for SledStorageProvider {
     }
 
     fn get_offered_sub_channels(&self) -> Result<Vec<SubChannel>, Error> {
-        self.get_data_with_prefix(
-            &self.sub_channel_tree()?,
-            &[SubChannelPrefix::Offered.into()],
-            None,
-        )
+        self.get_data_with_prefix(SUB_CHANNEL_TREE, &[SubChannelPrefix::Offered.into()], None)
     }
 
     fn save_sub_channel_actions(
         &self,
         actions: &[dlc_manager::sub_channel_manager::Action],
     ) -> Result<(), Error> {
-        let mut buf = Vec::new();
+        let mut buf = vec![SERIALIZATION_VERSION];
 
         for action in actions {
             action.write(&mut buf)?;
         }
 
+        // Only the version is kept in plaintext.
+        let context = record_context(DEFAULT_TREE, &[ACTION_KEY]);
         self.db
-            .insert([ACTION_KEY], buf)
+            .insert(
+                [ACTION_KEY],
+                seal_record(self.cipher.as_ref(), &context, 1, buf)?,
+            )
             .map_err(to_storage_error)?;
         Ok(())
     }
@@ -518,17 +1557,18 @@ impl Storage for SledStorageProvider {
     fn get_sub_channel_actions(
         &self,
     ) -> Result<Vec<dlc_manager::sub_channel_manager::Action>, Error> {
-        let buf = match self.db.get([ACTION_KEY]).map_err(to_storage_error)? {
-            Some(buf) if buf.len() > 0 => buf,
-            Some(_) | None => return Ok(Vec::new()),
+        let value = match self.db.get([ACTION_KEY]).map_err(to_storage_error)? {
+            Some(value) => value,
+            None => return Ok(Vec::new()),
         };
-
-        debug_assert!(buf.len() > 0);
+        let context = record_context(DEFAULT_TREE, &[ACTION_KEY]);
+        let buf = unseal_record(self.cipher.as_ref(), &context, 1, &value)?;
 
         let len = buf.len();
 
         let mut res = Vec::new();
         let mut cursor = Cursor::new(buf);
+        read_version(&mut cursor)?;
 
         while (cursor.position() as usize) < len - 1 {
             let action = Readable::read(&mut cursor).map_err(to_storage_error)?;
@@ -539,7 +1579,57 @@ impl Storage for SledStorageProvider {
     }
 
     fn get_channels(&self) -> Result<Vec<Channel>, Error> {
-        unimplemented!()
+        Ok(self
+            .channel_tree()?
+            .iter()
+            .map(|x| {
+                let (key, value) = x.unwrap();
+                self.unseal(CHANNEL_TREE, &key, &value)
+                    .and_then(|record| deserialize_channel(&record))
+            })
+            .filter_map(|res| match res {
+                Ok(channel) => Some(channel),
+                Err(e) => {
+                    log::error!("Failed to deserialize channel: {e}");
+                    None
+                }
+            })
+            .collect::<Vec<Channel>>())
+    }
+
+    fn query_channels(&self, query: &ChannelQuery) -> Result<Page<Channel>, Error> {
+        let states = query
+            .states
+            .iter()
+            .map(|state| ChannelPrefix::from(*state).into())
+            .collect::<Vec<u8>>();
+        let counter_party = query.counter_party.map(|c| c.serialize());
+        let index = self.index(CHANNEL_INDEX_TREE);
+        let prefixes = match (&counter_party, &query.reference_id) {
+            (Some(counter_party), _) => vec![index.prefix(BY_COUNTER_PARTY, counter_party)],
+            (None, Some(reference_id)) => vec![index.prefix(BY_REFERENCE_ID, reference_id)],
+            (None, None) if !states.is_empty() => states
+                .iter()
+                .map(|state| index.prefix(BY_STATE, &[*state]))
+                .collect(),
+            (None, None) => vec![vec![BY_CREATION]],
+        };
+
+        let positions = self.query_index(
+            index,
+            prefixes,
+            &query.created_at,
+            query.cursor,
+            query.limit,
+            |entry| {
+                (states.is_empty() || states.contains(&entry.state))
+                    && counter_party.map_or(true, |c| c == entry.counter_party)
+                    && query
+                        .reference_id
+                        .map_or(true, |r| entry.reference_id == Some(r))
+            },
+        )?;
+        self.load_page(CHANNEL_TREE, positions, query.limit, deserialize_channel)
     }
 }
 
@@ -548,8 +1638,8 @@ impl WalletStorage for SledStorageProvider {
     fn upsert_address(&self, address: &Address, privkey: &SecretKey) -> Result<(), Error> {
         let db = self.address_tree()?;
         let key = get_address_key(address);
-        db.insert(key, &privkey.secret_bytes())
-            .map_err(to_storage_error)?;
+        let value = self.seal(ADDRESS_TREE, &key, privkey.secret_bytes().to_vec())?;
+        db.insert(key, value).map_err(to_storage_error)?;
         Ok(())
     }
 
@@ -576,10 +1666,11 @@ impl WalletStorage for SledStorageProvider {
     fn get_priv_key_for_address(&self, address: &Address) -> Result<Option<SecretKey>, Error> {
         let db = self.address_tree()?;
         let key = get_address_key(address);
-        let raw_key = match db.get(key).map_err(to_storage_error)? {
+        let raw_key = match db.get(&key).map_err(to_storage_error)? {
             Some(res) => res,
             None => return Ok(None),
         };
+        let raw_key = self.unseal(ADDRESS_TREE, &key, &raw_key)?;
 
         Ok(Some(
             SecretKey::from_slice(&raw_key).expect("a valid secret key"),
@@ -588,7 +1679,14 @@ impl WalletStorage for SledStorageProvider {
 
     fn upsert_key_pair(&self, public_key: &PublicKey, privkey: &SecretKey) -> Result<(), Error> {
         self.key_pair_tree()?
-            .insert(public_key.serialize(), &privkey.secret_bytes())
+            .insert(
+                public_key.serialize(),
+                self.seal(
+                    KEY_PAIR_TREE,
+                    &public_key.serialize(),
+                    privkey.secret_bytes().to_vec(),
+                )?,
+            )
             .map_err(to_storage_error)?;
         Ok(())
     }
@@ -600,6 +1698,7 @@ impl WalletStorage for SledStorageProvider {
             Some(res) => res,
             None => return Ok(None),
         };
+        let raw_key = self.unseal(KEY_PAIR_TREE, &key, &raw_key)?;
 
         Ok(Some(
             SecretKey::from_slice(&raw_key).expect("a valid secret key"),
@@ -609,9 +1708,8 @@ impl WalletStorage for SledStorageProvider {
     fn upsert_utxo(&self, utxo: &Utxo) -> Result<(), Error> {
         let key = get_utxo_key(&utxo.outpoint.txid, utxo.outpoint.vout);
         let db = self.utxo_tree()?;
-        let mut buf = Vec::new();
-        utxo.write(&mut buf)?;
-        db.insert(key, buf).map_err(to_storage_error)?;
+        let value = self.seal(UTXO_TREE, &key, utxo.serialize_versioned()?)?;
+        db.insert(key, value).map_err(to_storage_error)?;
         Ok(())
     }
 
@@ -631,12 +1729,12 @@ impl WalletStorage for SledStorageProvider {
     fn get_utxos(&self) -> Result<Vec<Utxo>, Error> {
         self.utxo_tree()?
             .iter()
-            .values()
             .map(|x| {
-                let ivec = x.map_err(to_storage_error)?;
-                let mut cursor = Cursor::new(&ivec);
-                let res =
-                    Utxo::read(&mut cursor).map_err(|x| Error::InvalidState(format!("{x}")))?;
+                let (key, ivec) = x.map_err(to_storage_error)?;
+                let record = self.unseal(UTXO_TREE, &key, &ivec)?;
+                let mut cursor = Cursor::new(&record);
+                let res = Utxo::deserialize_versioned(&mut cursor)
+                    .map_err(|x| Error::InvalidState(format!("{x}")))?;
                 Ok(res)
             })
             .collect::<Result<Vec<Utxo>, Error>>()
@@ -646,43 +1744,110 @@ impl WalletStorage for SledStorageProvider {
         let utxo_tree = self.utxo_tree()?;
         let key = get_utxo_key(txid, vout);
         let mut utxo = match utxo_tree.get(&key).map_err(to_storage_error)? {
-            Some(res) => Utxo::read(&mut Cursor::new(&res))
-                .map_err(|_| Error::InvalidState("Could not read UTXO".to_string()))?,
+            Some(res) => {
+                let record = self.unseal(UTXO_TREE, &key, &res)?;
+                let mut cursor = Cursor::new(&record);
+                Utxo::deserialize_versioned(&mut cursor)
+                    .map_err(|_| Error::InvalidState("Could not read UTXO".to_string()))?
+            }
             None => return Err(Error::InvalidState(format!("No utxo for {txid} {vout}"))),
         };
 
         utxo.reserved = false;
-        let mut buf = Vec::new();
-        utxo.write(&mut buf)?;
-        utxo_tree.insert(key, buf).map_err(to_storage_error)?;
+        let value = self.seal(UTXO_TREE, &key, utxo.serialize_versioned()?)?;
+        utxo_tree.insert(key, value).map_err(to_storage_error)?;
         Ok(())
     }
 }
 
 fn insert_contract(
-    db: &sled::transaction::TransactionalTree,
+    db: &TransactionalTree,
+    index_db: &TransactionalTree,
+    index: Index,
     serialized: Vec<u8>,
     contract: &Contract,
-) -> Result<Option<sled::IVec>, UnabortableTransactionError> {
-    match contract {
+    timestamp: u64,
+) -> ConflictableTransactionResult<Option<sled::IVec>, Error> {
+    let temporary_id = match contract {
         a @ Contract::Accepted(_) | a @ Contract::Signed(_) => {
-            db.remove(&a.get_temporary_id())?;
+            let temporary_id = a.get_temporary_id();
+            db.remove(&temporary_id)?;
+            Some(temporary_id)
         }
-        _ => {}
+        _ => None,
     };
 
-    db.insert(&contract.get_id(), serialized)
+    index.update(
+        index_db,
+        &contract.get_id(),
+        temporary_id.as_ref(),
+        IndexEntry::for_contract(contract, timestamp),
+    )?;
+    Ok(db.insert(&contract.get_id(), serialized)?)
+}
+
+/// Returns the position of a key rotation which rewrote the values of the tree or
+/// index of the given stage up to `last_key`.
+fn rotation_position(stage: u8, last_key: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(last_key.len() + 1);
+    res.push(stage);
+    res.extend_from_slice(last_key);
+    res
+}
+
+/// Returns whether `check` is the check of the key of `cipher`, or the empty check
+/// of plaintext databases if `None` is given.
+fn is_valid_check(cipher: Option<&Cipher>, check: &[u8]) -> bool {
+    match cipher {
+        Some(cipher) => {
+            matches!(cipher.decrypt(&[], check), Ok(plaintext) if plaintext == ENCRYPTION_CHECK)
+        }
+        None => check.is_empty(),
+    }
+}
+
+fn index_entry_key(id: &[u8; 32]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(33);
+    res.push(INDEX_ENTRY);
+    res.extend_from_slice(id);
+    res
+}
+
+/// Reads the position of a record from the end of one of its keys in an index
+/// tree.
+fn read_position(buff: &[u8]) -> Result<QueryCursor, Error> {
+    let mut cursor = Cursor::new(buff);
+    Ok(QueryCursor {
+        created_at: u64::from_be_bytes(read_array(&mut cursor)?),
+        id: read_array(&mut cursor)?,
+    })
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N], Error> {
+    let mut res = [0u8; N];
+    reader.read_exact(&mut res)?;
+    Ok(res)
+}
+
+fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Unexpected time before UNIX epoch")
+        .as_secs()
 }
 
 fn serialize_contract(contract: &Contract) -> Result<Vec<u8>, ::std::io::Error> {
     let serialized = match contract {
-        Contract::Offered(o) | Contract::Rejected(o) => o.serialize(),
-        Contract::Accepted(o) => o.serialize(),
-        Contract::Signed(o) | Contract::Confirmed(o) | Contract::Refunded(o) => o.serialize(),
-        Contract::FailedAccept(c) => c.serialize(),
-        Contract::FailedSign(c) => c.serialize(),
-        Contract::PreClosed(c) => c.serialize(),
-        Contract::Closed(c) => c.serialize(),
+        Contract::Offered(o) | Contract::Rejected(o) => o.serialize_versioned(),
+        Contract::Accepted(o) => o.serialize_versioned(),
+        Contract::Signed(o) | Contract::Confirmed(o) | Contract::Refunded(o) => {
+            o.serialize_versioned()
+        }
+        Contract::FailedAccept(c) => c.serialize_versioned(),
+        Contract::FailedSign(c) => c.serialize_versioned(),
+        Contract::PreClosed(c) => c.serialize_versioned(),
+        Contract::PreRefunded(c) => c.serialize_versioned(),
+        Contract::Closed(c) => c.serialize_versioned(),
     };
     let mut serialized = serialized?;
     let mut res = Vec::with_capacity(serialized.len() + 1);
@@ -691,63 +1856,43 @@ fn serialize_contract(contract: &Contract) -> Result<Vec<u8>, ::std::io::Error>
     Ok(res)
 }
 
-fn deserialize_contract(buff: &sled::IVec) -> Result<Contract, Error> {
-    let mut cursor = ::std::io::Cursor::new(buff);
-    let mut prefix = [0u8; 1];
-    cursor.read_exact(&mut prefix)?;
-    let contract_prefix: ContractPrefix = prefix[0].try_into()?;
+fn deserialize_contract(buff: &[u8]) -> Result<Contract, Error> {
+    let mut cursor = Cursor::new(buff);
+    let [prefix] = read_array(&mut cursor)?;
+    let contract_prefix: ContractPrefix = prefix.try_into()?;
     let contract = match contract_prefix {
-        ContractPrefix::Offered => {
-            Contract::Offered(OfferedContract::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ContractPrefix::Accepted => Contract::Accepted(
-            AcceptedContract::deserialize(&mut cursor).map_err(to_storage_error)?,
-        ),
-        ContractPrefix::Signed => {
-            Contract::Signed(SignedContract::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ContractPrefix::Confirmed => {
-            Contract::Confirmed(SignedContract::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ContractPrefix::PreClosed => Contract::PreClosed(
-            PreClosedContract::deserialize(&mut cursor).map_err(to_storage_error)?,
-        ),
-        ContractPrefix::Closed => {
-            Contract::Closed(ClosedContract::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ContractPrefix::FailedAccept => Contract::FailedAccept(
-            FailedAcceptContract::deserialize(&mut cursor).map_err(to_storage_error)?,
-        ),
-        ContractPrefix::FailedSign => Contract::FailedSign(
-            FailedSignContract::deserialize(&mut cursor).map_err(to_storage_error)?,
-        ),
-        ContractPrefix::Refunded => {
-            Contract::Refunded(SignedContract::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ContractPrefix::Rejected => {
-            Contract::Rejected(OfferedContract::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
+        ContractPrefix::Offered => Contract::Offered(read_object(&mut cursor)?),
+        ContractPrefix::Accepted => Contract::Accepted(read_object(&mut cursor)?),
+        ContractPrefix::Signed => Contract::Signed(read_object(&mut cursor)?),
+        ContractPrefix::Confirmed => Contract::Confirmed(read_object(&mut cursor)?),
+        ContractPrefix::PreClosed => Contract::PreClosed(read_object(&mut cursor)?),
+        ContractPrefix::PreRefunded => Contract::PreRefunded(read_object(&mut cursor)?),
+        ContractPrefix::Closed => Contract::Closed(read_object(&mut cursor)?),
+        ContractPrefix::FailedAccept => Contract::FailedAccept(read_object(&mut cursor)?),
+        ContractPrefix::FailedSign => Contract::FailedSign(read_object(&mut cursor)?),
+        ContractPrefix::Refunded => Contract::Refunded(read_object(&mut cursor)?),
+        ContractPrefix::Rejected => Contract::Rejected(read_object(&mut cursor)?),
     };
     Ok(contract)
 }
 
 fn serialize_channel(channel: &Channel) -> Result<Vec<u8>, ::std::io::Error> {
     let serialized = match channel {
-        Channel::Offered(o) => o.serialize(),
-        Channel::Accepted(a) => a.serialize(),
-        Channel::Signed(s) => s.serialize(),
-        Channel::FailedAccept(f) => f.serialize(),
-        Channel::FailedSign(f) => f.serialize(),
-        Channel::Closing(c) => c.serialize(),
-        Channel::SettledClosing(c) => c.serialize(),
+        Channel::Offered(o) => o.serialize_versioned(),
+        Channel::Accepted(a) => a.serialize_versioned(),
+        Channel::Signed(s) => s.serialize_versioned(),
+        Channel::FailedAccept(f) => f.serialize_versioned(),
+        Channel::FailedSign(f) => f.serialize_versioned(),
+        Channel::Closing(c) => c.serialize_versioned(),
+        Channel::SettledClosing(c) => c.serialize_versioned(),
         Channel::Closed(c) | Channel::CounterClosed(c) | Channel::CollaborativelyClosed(c) => {
-            c.serialize()
+            c.serialize_versioned()
         }
-        Channel::ClosedPunished(c) => c.serialize(),
-        Channel::Cancelled(o) => o.serialize(),
+        Channel::ClosedPunished(c) => c.serialize_versioned(),
+        Channel::Cancelled(o) => o.serialize_versioned(),
     };
     let mut serialized = serialized?;
-    let mut res = Vec::with_capacity(serialized.len() + 1);
+    let mut res = Vec::with_capacity(serialized.len() + 2);
     res.push(ChannelPrefix::get_prefix(channel));
     if let Channel::Signed(s) = channel {
         res.push(SignedChannelPrefix::get_prefix(&s.state.get_type()))
@@ -756,54 +1901,37 @@ fn serialize_channel(channel: &Channel) -> Result<Vec<u8>, ::std::io::Error> {
     Ok(res)
 }
 
-fn deserialize_channel(buff: &sled::IVec) -> Result<Channel, Error> {
-    let mut cursor = ::std::io::Cursor::new(buff);
-    let mut prefix = [0u8; 1];
-    cursor.read_exact(&mut prefix)?;
-    let channel_prefix: ChannelPrefix = prefix[0].try_into()?;
+fn deserialize_channel(buff: &[u8]) -> Result<Channel, Error> {
+    let mut cursor = Cursor::new(buff);
+    let [prefix] = read_array(&mut cursor)?;
+    let channel_prefix: ChannelPrefix = prefix.try_into()?;
     let channel = match channel_prefix {
-        ChannelPrefix::Offered => {
-            Channel::Offered(OfferedChannel::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ChannelPrefix::Accepted => {
-            Channel::Accepted(AcceptedChannel::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
+        ChannelPrefix::Offered => Channel::Offered(read_object(&mut cursor)?),
+        ChannelPrefix::Accepted => Channel::Accepted(read_object(&mut cursor)?),
         ChannelPrefix::Signed => {
             // Skip the channel state prefix.
-            cursor.set_position(cursor.position() + 1);
-            Channel::Signed(SignedChannel::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ChannelPrefix::FailedAccept => {
-            Channel::FailedAccept(FailedAccept::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ChannelPrefix::FailedSign => {
-            Channel::FailedSign(FailedSign::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ChannelPrefix::Closing => {
-            Channel::Closing(ClosingChannel::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ChannelPrefix::SettledClosing => {
-            Channel::SettledClosing(SettledClosingChannel::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ChannelPrefix::Closed => {
-            Channel::Closed(ClosedChannel::deserialize(&mut cursor).map_err(to_storage_error)?)
-        }
-        ChannelPrefix::CollaborativelyClosed => Channel::CollaborativelyClosed(
-            ClosedChannel::deserialize(&mut cursor).map_err(to_storage_error)?,
-        ),
-        ChannelPrefix::CounterClosed => Channel::CounterClosed(
-            ClosedChannel::deserialize(&mut cursor).map_err(to_storage_error)?,
-        ),
-        ChannelPrefix::ClosedPunished => Channel::ClosedPunished(
-            ClosedPunishedChannel::deserialize(&mut cursor).map_err(to_storage_error)?,
-        ),
-        ChannelPrefix::Cancelled => Channel::Cancelled(
-            OfferedChannel::deserialize(&mut cursor).map_err(to_storage_error)?,
-        )
+            let _: [u8; 1] = read_array(&mut cursor)?;
+            Channel::Signed(read_object(&mut cursor)?)
+        }
+        ChannelPrefix::FailedAccept => Channel::FailedAccept(read_object(&mut cursor)?),
+        ChannelPrefix::FailedSign => Channel::FailedSign(read_object(&mut cursor)?),
+        ChannelPrefix::Closing => Channel::Closing(read_object(&mut cursor)?),
+        ChannelPrefix::SettledClosing => Channel::SettledClosing(read_object(&mut cursor)?),
+        ChannelPrefix::Closed => Channel::Closed(read_object(&mut cursor)?),
+        ChannelPrefix::CollaborativelyClosed => {
+            Channel::CollaborativelyClosed(read_object(&mut cursor)?)
+        }
+        ChannelPrefix::CounterClosed => Channel::CounterClosed(read_object(&mut cursor)?),
+        ChannelPrefix::ClosedPunished => Channel::ClosedPunished(read_object(&mut cursor)?),
+        ChannelPrefix::Cancelled => Channel::Cancelled(read_object(&mut cursor)?),
     };
     Ok(channel)
 }
 
+fn read_object<T: VersionedSerializable, R: Read>(reader: &mut R) -> Result<T, Error> {
+    T::deserialize_versioned(reader).map_err(to_storage_error)
+}
+
 #[cfg(feature = "wallet")]
 fn get_address_key(address: &Address) -> Vec<u8> {
     address.to_string().into_bytes()
@@ -819,24 +1947,116 @@ fn get_utxo_key(txid: &Txid, vout: u32) -> Vec<u8> {
 
 fn serialize_sub_channel(sub_channel: &SubChannel) -> Result<Vec<u8>, ::std::io::Error> {
     let prefix = SubChannelPrefix::get_prefix(&sub_channel.state);
-    let mut buf = Vec::new();
+    let mut buf = vec![prefix];
 
-    buf.push(prefix);
-    buf.append(&mut sub_channel.serialize()?);
+    buf.append(&mut sub_channel.serialize_versioned()?);
 
     Ok(buf)
 }
 
-fn deserialize_sub_channel(buff: &sled::IVec) -> Result<SubChannel, Error> {
+fn deserialize_sub_channel(buff: &[u8]) -> Result<SubChannel, Error> {
     let mut cursor = ::std::io::Cursor::new(buff);
     // Skip prefix
-    cursor.seek(SeekFrom::Start(1))?;
-    SubChannel::deserialize(&mut cursor).map_err(to_storage_error)
+    cursor.seek(SeekFrom::Current(1))?;
+    SubChannel::deserialize_versioned(&mut cursor).map_err(to_storage_error)
+}
+
+/// Returns the length of the start of a record of the given tree which is kept in
+/// plaintext when encrypting it: the state prefixes used to filter the records
+/// and the version of the object, or nothing for the keys of the wallet.
+fn header_len(tree_id: u8, record: &[u8]) -> usize {
+    match tree_id {
+        CONTRACT_TREE | SUB_CHANNEL_TREE => 2,
+        CHANNEL_TREE if record.first().copied() == Some(ChannelPrefix::Signed.into()) => 3,
+        CHANNEL_TREE => 2,
+        KEY_PAIR_TREE | ADDRESS_TREE => 0,
+        _ => 1,
+    }
+}
+
+/// Returns the location of a record, the id of its tree followed by its length
+/// prefixed key, which is authenticated along with its plaintext header when it is
+/// encrypted so that encrypted values cannot be moved to another key or tree.
+fn record_context(tree_id: u8, key: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(key.len() + 5);
+    res.push(tree_id);
+    res.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    res.extend_from_slice(key);
+    res
+}
+
+/// Encrypts the record after its header of the given length, authenticating the
+/// given context followed by the header as associated data.
+fn seal_record(
+    cipher: Option<&Cipher>,
+    context: &[u8],
+    header_len: usize,
+    record: Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    let cipher = match cipher {
+        Some(cipher) => cipher,
+        None => return Ok(record),
+    };
+    let (header, payload) = record.split_at(header_len.min(record.len()));
+    let mut res = header.to_vec();
+    res.append(&mut cipher.encrypt(&associated_data(context, header), payload)?);
+    Ok(res)
+}
+
+/// Decrypts a record encrypted by [`seal_record`] with the same context.
+fn unseal_record<'a>(
+    cipher: Option<&Cipher>,
+    context: &[u8],
+    header_len: usize,
+    record: &'a [u8],
+) -> Result<Cow<'a, [u8]>, Error> {
+    let cipher = match cipher {
+        Some(cipher) => cipher,
+        None => return Ok(Cow::Borrowed(record)),
+    };
+    if record.len() < header_len {
+        return Err(Error::StorageError("Invalid encrypted record".to_string()));
+    }
+    let (header, data) = record.split_at(header_len);
+    let mut res = header.to_vec();
+    res.append(&mut cipher.decrypt(&associated_data(context, header), data)?);
+    Ok(Cow::Owned(res))
+}
+
+fn associated_data(context: &[u8], header: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(context.len() + header.len());
+    res.extend_from_slice(context);
+    res.extend_from_slice(header);
+    res
+}
+
+/// Inserts the given version after the first `prefix_len` bytes of the record.
+fn with_version(version: u8, prefix_len: usize, record: &[u8]) -> Vec<u8> {
+    let prefix_len = prefix_len.min(record.len());
+    let mut res = Vec::with_capacity(record.len() + 1);
+    res.extend_from_slice(&record[..prefix_len]);
+    res.push(version);
+    res.extend_from_slice(&record[prefix_len..]);
+    res
+}
+
+/// Reads the version of a record which is not the serialization of an object,
+/// which can be decoded as long as it is not newer than [`SERIALIZATION_VERSION`].
+fn read_version<R: Read>(reader: &mut R) -> Result<u8, Error> {
+    let [version] = read_array(reader)?;
+    if version > SERIALIZATION_VERSION {
+        return Err(Error::StorageError(format!(
+            "Unsupported record version {version}, latest supported is {SERIALIZATION_VERSION}"
+        )));
+    }
+    Ok(version)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dlc_manager::channel::accepted_channel::AcceptedChannel;
+    use dlc_manager::contract::ser::Serializable;
 
     macro_rules! sled_test {
         ($name: ident, $body: expr) => {
@@ -1234,4 +2454,516 @@ mod tests {
             .expect("Error getting sub channel actions");
         assert_eq!(actions.len(), 0);
     });
+
+    sled_test!(
+        new_database_is_at_current_version,
+        |storage: SledStorageProvider| {
+            assert_eq!(STORAGE_VERSION, storage.get_version().unwrap());
+        }
+    );
+
+    sled_test!(
+        migrate_to_older_version_fails,
+        |storage: SledStorageProvider| {
+            storage
+                .migrate(STORAGE_VERSION, 0)
+                .expect_err("should not be able to migrate to an older version");
+            storage
+                .migrate(STORAGE_VERSION, STORAGE_VERSION + 1)
+                .expect_err("should not be able to migrate to an unknown version");
+        }
+    );
+
+    /// Copies the database written by the release predating storage versioning to
+    /// the given path, as opening it migrates it in place.
+    fn copy_baseline_database(path: &str) {
+        std::fs::create_dir_all(path).unwrap();
+        for entry in std::fs::read_dir("test_files/baseline_db").unwrap() {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_file() {
+                std::fs::copy(
+                    entry.path(),
+                    std::path::Path::new(path).join(entry.file_name()),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn check_legacy_data(storage: &SledStorageProvider) {
+        assert_eq!(STORAGE_VERSION, storage.get_version().unwrap());
+
+        let serialized = include_bytes!("../test_files/Offered");
+        let offered_contract: OfferedContract = deserialize_object(serialized);
+        if let Some(Contract::Offered(retrieved)) =
+            storage.get_contract(&offered_contract.id).unwrap()
+        {
+            assert_eq!(serialized[..], retrieved.serialize().unwrap()[..]);
+        } else {
+            panic!("Expected to retrieve the offered contract");
+        }
+        // The migrated objects are prefixed with version 0 of their serialization.
+        let raw = storage
+            .contract_tree()
+            .unwrap()
+            .get(offered_contract.id)
+            .unwrap()
+            .expect("to have a stored contract");
+        assert_eq!([ContractPrefix::Offered.into(), 0], raw[..2]);
+        assert_eq!(3, storage.get_contracts().unwrap().len());
+        assert_eq!(1, storage.get_signed_contracts().unwrap().len());
+        assert_eq!(1, storage.get_confirmed_contracts().unwrap().len());
+        let signed_contracts = storage
+            .query_contracts(&ContractQuery {
+                states: vec![ContractStateType::Signed],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(1, signed_contracts.items.len());
+        // The creation time of migrated records is unknown.
+        assert!(storage
+            .query_contracts(&ContractQuery {
+                created_at: TimeRange {
+                    from: Some(1),
+                    to: None,
+                },
+                ..Default::default()
+            })
+            .unwrap()
+            .items
+            .is_empty());
+
+        assert_eq!(2, storage.get_signed_channels(None).unwrap().len());
+        let signed_channels = storage
+            .get_signed_channels(Some(SignedChannelStateType::Established))
+            .unwrap();
+        assert_eq!(1, signed_channels.len());
+        assert!(storage
+            .get_channel(&signed_channels[0].channel_id)
+            .unwrap()
+            .is_some());
+        let counter_party = signed_channels[0].counter_party;
+        let queried_channels = storage
+            .query_channels(&ChannelQuery {
+                counter_party: Some(counter_party),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(2, queried_channels.items.len());
+
+        assert_eq!(1, storage.get_offered_sub_channels().unwrap().len());
+        assert_eq!(1, storage.get_sub_channels().unwrap().len());
+
+        assert_eq!(
+            ChainMonitor::new(123),
+            storage.get_chain_monitor().unwrap().unwrap()
+        );
+
+        assert!(storage.get_sub_channel_actions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn legacy_database_is_migrated() {
+        let path = "test_files/sleddb/legacy_database_is_migrated";
+        copy_baseline_database(path);
+
+        {
+            let storage = SledStorageProvider::new(path).expect("Error opening sled DB");
+            check_legacy_data(&storage);
+        }
+
+        // Opening the database again should not apply the migration a second time.
+        {
+            let storage = SledStorageProvider::new(path).expect("Error opening sled DB");
+            check_legacy_data(&storage);
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn newer_database_version_is_rejected() {
+        let path = "test_files/sleddb/newer_database_version_is_rejected";
+        {
+            let db = sled::open(path).unwrap();
+            db.insert([VERSION_KEY], vec![STORAGE_VERSION + 1]).unwrap();
+            db.flush().unwrap();
+        }
+
+        assert!(SledStorageProvider::new(path).is_err());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    fn get_sub_channel_actions() -> Vec<dlc_manager::sub_channel_manager::Action> {
+        serde_json::from_str(include_str!("../test_files/sub_channel_actions.json")).unwrap()
+    }
+
+    #[test]
+    fn encrypted_values_are_not_stored_in_plaintext() {
+        let path = "test_files/sleddb/encrypted_values_are_not_stored_in_plaintext";
+        {
+            let storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            assert!(storage.is_encrypted());
+
+            let serialized = include_bytes!("../test_files/Offered");
+            let contract: OfferedContract = deserialize_object(serialized);
+            storage
+                .create_contract(&contract)
+                .expect("Error creating contract");
+
+            let raw = storage
+                .contract_tree()
+                .unwrap()
+                .get(contract.id)
+                .unwrap()
+                .expect("to have a stored contract");
+            assert_eq!(
+                [ContractPrefix::Offered.into(), SERIALIZATION_VERSION],
+                raw[..2]
+            );
+            assert!(!raw
+                .windows(serialized.len())
+                .any(|window| window == &serialized[..]));
+
+            let retrieved = storage
+                .get_contract(&contract.id)
+                .expect("Error retrieving contract")
+                .expect("to have found the contract");
+            if let Contract::Offered(retrieved) = retrieved {
+                assert_eq!(serialized[..], retrieved.serialize().unwrap()[..]);
+            } else {
+                panic!("Expected an offered contract");
+            }
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn encrypted_indexes_do_not_store_properties_in_plaintext() {
+        let path = "test_files/sleddb/encrypted_indexes_do_not_store_properties_in_plaintext";
+        {
+            let mut storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            insert_offered_signed_and_confirmed(&mut storage);
+            insert_offered_and_signed_channels(&mut storage);
+
+            let counter_parties = storage
+                .get_contracts()
+                .unwrap()
+                .iter()
+                .map(|c| c.get_counter_party_id().serialize())
+                .collect::<Vec<_>>();
+            let index_trees = [
+                storage.contract_index_tree().unwrap(),
+                storage.channel_index_tree().unwrap(),
+            ];
+            for tree in index_trees.iter() {
+                for res in tree.iter() {
+                    let (key, value) = res.unwrap();
+                    for counter_party in &counter_parties {
+                        assert!(!key
+                            .windows(counter_party.len())
+                            .any(|window| window == &counter_party[..]));
+                        assert!(!value
+                            .windows(counter_party.len())
+                            .any(|window| window == &counter_party[..]));
+                    }
+                }
+            }
+
+            check_counter_party_query(&storage);
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    /// Checks that querying the contracts and channels of the counter party of a
+    /// signed channel returns them.
+    fn check_counter_party_query(storage: &SledStorageProvider) {
+        let channel = &storage.get_signed_channels(None).unwrap()[0];
+        let channels = storage
+            .query_channels(&ChannelQuery {
+                counter_party: Some(channel.counter_party),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(channels
+            .items
+            .iter()
+            .any(|c| c.get_id() == channel.channel_id));
+        assert!(channels
+            .items
+            .iter()
+            .all(|c| c.get_counter_party_id() == channel.counter_party));
+
+        let contract = &storage.get_contracts().unwrap()[0];
+        let contracts = storage
+            .query_contracts(&ContractQuery {
+                counter_party: Some(contract.get_counter_party_id()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(contracts
+            .items
+            .iter()
+            .any(|c| c.get_id() == contract.get_id()));
+    }
+
+    #[test]
+    fn encrypted_values_are_bound_to_their_key() {
+        let path = "test_files/sleddb/encrypted_values_are_bound_to_their_key";
+        {
+            let storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            let contract: OfferedContract =
+                deserialize_object(include_bytes!("../test_files/Offered"));
+            storage
+                .create_contract(&contract)
+                .expect("Error creating contract");
+
+            let tree = storage.contract_tree().unwrap();
+            let raw = tree.get(contract.id).unwrap().expect("to have a contract");
+            let other_id = [0xab; 32];
+            tree.insert(other_id, raw).unwrap();
+
+            assert!(storage.get_contract(&contract.id).unwrap().is_some());
+            assert!(storage.get_contract(&other_id).is_err());
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn encrypted_database_requires_valid_key() {
+        let path = "test_files/sleddb/encrypted_database_requires_valid_key";
+        {
+            let mut storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            insert_offered_signed_and_confirmed(&mut storage);
+        }
+
+        assert!(SledStorageProvider::new(path).is_err());
+        assert!(SledStorageProvider::new_encrypted(path, EncryptionKey::new([2; 32])).is_err());
+
+        {
+            let storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            assert_eq!(6, storage.get_contracts().unwrap().len());
+            assert_eq!(2, storage.get_signed_contracts().unwrap().len());
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn plaintext_database_is_encrypted_when_opened_with_key() {
+        let path = "test_files/sleddb/plaintext_database_is_encrypted_when_opened_with_key";
+        let nb_contracts = {
+            let mut storage = SledStorageProvider::new(path).expect("Error opening sled DB");
+            insert_offered_signed_and_confirmed(&mut storage);
+            insert_offered_and_signed_channels(&mut storage);
+            storage
+                .save_sub_channel_actions(&get_sub_channel_actions())
+                .unwrap();
+            storage.get_contracts().unwrap().len()
+        };
+
+        {
+            let storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            assert_eq!(nb_contracts, storage.get_contracts().unwrap().len());
+            assert_eq!(1, storage.get_offered_channels().unwrap().len());
+            assert_eq!(
+                get_sub_channel_actions(),
+                storage.get_sub_channel_actions().unwrap()
+            );
+        }
+
+        assert!(SledStorageProvider::new(path).is_err());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn rotate_encryption_key_reencrypts_data() {
+        let path = "test_files/sleddb/rotate_encryption_key_reencrypts_data";
+        {
+            let mut storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            insert_offered_and_signed_channels(&mut storage);
+            insert_sub_channels(&mut storage);
+            storage
+                .persist_chain_monitor(&ChainMonitor::new(123))
+                .unwrap();
+            storage
+                .save_sub_channel_actions(&get_sub_channel_actions())
+                .unwrap();
+
+            storage
+                .rotate_encryption_key(Some(EncryptionKey::new([2; 32])))
+                .expect("Error rotating encryption key");
+            assert_eq!(1, storage.get_offered_channels().unwrap().len());
+        }
+
+        assert!(SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32])).is_err());
+
+        {
+            let mut storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([2; 32]))
+                .expect("Error opening sled DB");
+            let check_data = |storage: &SledStorageProvider| {
+                assert_eq!(1, storage.get_offered_channels().unwrap().len());
+                assert_eq!(
+                    1,
+                    storage
+                        .get_signed_channels(Some(SignedChannelStateType::Established))
+                        .unwrap()
+                        .len()
+                );
+                assert_eq!(4, storage.get_sub_channels().unwrap().len());
+                assert_eq!(2, storage.get_offered_sub_channels().unwrap().len());
+                assert_eq!(
+                    Some(ChainMonitor::new(123)),
+                    storage.get_chain_monitor().unwrap()
+                );
+                assert_eq!(
+                    get_sub_channel_actions(),
+                    storage.get_sub_channel_actions().unwrap()
+                );
+                check_counter_party_query(storage);
+            };
+            check_data(&storage);
+
+            storage
+                .rotate_encryption_key(None)
+                .expect("Error removing encryption");
+            assert!(!storage.is_encrypted());
+            check_data(&storage);
+        }
+
+        {
+            let storage = SledStorageProvider::new(path).expect("Error opening sled DB");
+            assert_eq!(4, storage.get_sub_channels().unwrap().len());
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn interrupted_key_rotation_is_resumed() {
+        let path = "test_files/sleddb/interrupted_key_rotation_is_resumed";
+        {
+            let mut storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            insert_offered_signed_and_confirmed(&mut storage);
+            insert_offered_and_signed_channels(&mut storage);
+            storage
+                .save_sub_channel_actions(&get_sub_channel_actions())
+                .unwrap();
+
+            // Stop the rotation once the contracts are rewritten.
+            let new_cipher = Cipher::new(&EncryptionKey::new([2; 32]));
+            storage.begin_key_rotation(Some(&new_cipher)).unwrap();
+            storage
+                .reseal_tree(0, CONTRACT_TREE, Some(&new_cipher), None)
+                .unwrap();
+        }
+
+        assert!(SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32])).is_err());
+        assert!(SledStorageProvider::new_encrypted(path, EncryptionKey::new([2; 32])).is_err());
+        assert!(SledStorageProvider::resume_key_rotation(
+            path,
+            Some(EncryptionKey::new([1; 32])),
+            Some(EncryptionKey::new([3; 32]))
+        )
+        .is_err());
+
+        {
+            let storage = SledStorageProvider::resume_key_rotation(
+                path,
+                Some(EncryptionKey::new([1; 32])),
+                Some(EncryptionKey::new([2; 32])),
+            )
+            .expect("Error resuming key rotation");
+            assert!(storage.is_encrypted());
+            assert_eq!(6, storage.get_contracts().unwrap().len());
+        }
+
+        {
+            let storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([2; 32]))
+                .expect("Error opening sled DB");
+            assert_eq!(6, storage.get_contracts().unwrap().len());
+            assert_eq!(1, storage.get_offered_channels().unwrap().len());
+            assert_eq!(
+                get_sub_channel_actions(),
+                storage.get_sub_channel_actions().unwrap()
+            );
+            check_counter_party_query(&storage);
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[cfg(feature = "wallet")]
+    #[test]
+    fn encrypted_private_keys_roundtrip() {
+        let path = "test_files/sleddb/encrypted_private_keys_roundtrip";
+        {
+            let storage = SledStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening sled DB");
+            let secp = secp256k1_zkp::Secp256k1::new();
+            let secret_key = SecretKey::from_slice(&[3; 32]).unwrap();
+            let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+
+            storage.upsert_key_pair(&public_key, &secret_key).unwrap();
+
+            let raw = storage
+                .key_pair_tree()
+                .unwrap()
+                .get(public_key.serialize())
+                .unwrap()
+                .expect("to have a stored key");
+            assert!(!raw
+                .windows(32)
+                .any(|window| window == &secret_key.secret_bytes()[..]));
+            assert_eq!(
+                Some(secret_key),
+                storage.get_priv_key_for_pubkey(&public_key).unwrap()
+            );
+        }
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    mod conformance {
+        use super::*;
+
+        fn test_path(name: &str) -> String {
+            format!("test_files/sleddb/conformance_{name}")
+        }
+
+        mocks::storage_conformance_tests!(
+            |name: &str| SledStorageProvider::new(&test_path(name)).expect("Error opening sled DB"),
+            |name: &str| std::fs::remove_dir_all(test_path(name)).unwrap()
+        );
+    }
+
+    mod encrypted_conformance {
+        use super::*;
+
+        fn test_path(name: &str) -> String {
+            format!("test_files/sleddb/encrypted_conformance_{name}")
+        }
+
+        mocks::storage_conformance_tests!(
+            |name: &str| SledStorageProvider::new_encrypted(
+                &test_path(name),
+                EncryptionKey::new([1; 32])
+            )
+            .expect("Error opening sled DB"),
+            |name: &str| std::fs::remove_dir_all(test_path(name)).unwrap()
+        );
+    }
 }
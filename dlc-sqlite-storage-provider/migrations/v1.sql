@@ -0,0 +1,90 @@
+CREATE TABLE contracts (
+    id BLOB PRIMARY KEY NOT NULL,
+    state INTEGER NOT NULL,
+    counter_party BLOB NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    maturity INTEGER,
+    data BLOB NOT NULL
+);
+
+CREATE INDEX contracts_state ON contracts (state);
+CREATE INDEX contracts_counter_party ON contracts (counter_party);
+CREATE INDEX contracts_created_at ON contracts (created_at);
+CREATE INDEX contracts_updated_at ON contracts (updated_at);
+CREATE INDEX contracts_maturity ON contracts (maturity);
+
+CREATE TABLE channels (
+    id BLOB PRIMARY KEY NOT NULL,
+    state INTEGER NOT NULL,
+    signed_state INTEGER,
+    counter_party BLOB NOT NULL,
+    reference_id BLOB,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    data BLOB NOT NULL
+);
+
+CREATE INDEX channels_state ON channels (state, signed_state);
+CREATE INDEX channels_counter_party ON channels (counter_party);
+CREATE INDEX channels_reference_id ON channels (reference_id);
+CREATE INDEX channels_created_at ON channels (created_at);
+CREATE INDEX channels_updated_at ON channels (updated_at);
+
+CREATE TABLE sub_channels (
+    id BLOB PRIMARY KEY NOT NULL,
+    state INTEGER NOT NULL,
+    counter_party BLOB NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    data BLOB NOT NULL
+);
+
+CREATE INDEX sub_channels_state ON sub_channels (state);
+CREATE INDEX sub_channels_counter_party ON sub_channels (counter_party);
+CREATE INDEX sub_channels_created_at ON sub_channels (created_at);
+CREATE INDEX sub_channels_updated_at ON sub_channels (updated_at);
+
+CREATE TABLE chain_monitor (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    data BLOB NOT NULL
+);
+
+CREATE TABLE adaptor_points (
+    key BLOB PRIMARY KEY,
+    data BLOB NOT NULL
+);
+
+CREATE TABLE fee_bumps (
+    txid BLOB PRIMARY KEY,
+    fee_rate INTEGER NOT NULL,
+    child_txid BLOB NOT NULL
+);
+
+CREATE TABLE sub_channel_actions (
+    position INTEGER PRIMARY KEY,
+    data BLOB NOT NULL
+);
+
+CREATE TABLE utxos (
+    txid BLOB NOT NULL,
+    vout INTEGER NOT NULL,
+    reserved INTEGER NOT NULL,
+    data BLOB NOT NULL,
+    PRIMARY KEY (txid, vout)
+);
+
+CREATE TABLE key_pairs (
+    public_key BLOB PRIMARY KEY NOT NULL,
+    secret_key BLOB NOT NULL
+);
+
+CREATE TABLE addresses (
+    address TEXT PRIMARY KEY NOT NULL,
+    secret_key BLOB NOT NULL
+);
+
+CREATE TABLE encryption (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    key_check BLOB NOT NULL
+);
@@ -0,0 +1,1769 @@
+//! # dlc-sqlite-storage-provider
+//! Storage provider for dlc-manager using SQLite as underlying storage.
+
+#![crate_name = "dlc_sqlite_storage_provider"]
+// Coding conventions
+#![deny(non_upper_case_globals)]
+#![deny(non_camel_case_types)]
+#![deny(non_snake_case)]
+#![deny(unused_mut)]
+#![deny(dead_code)]
+#![deny(unused_imports)]
+#![deny(missing_docs)]
+
+extern crate dlc_manager;
+extern crate rusqlite;
+
+pub use dlc_manager::encryption::EncryptionKey;
+
+use bitcoin::hashes::Hash;
+#[cfg(feature = "wallet")]
+use bitcoin::Address;
+use bitcoin::Txid;
+use dlc_manager::chain_monitor::ChainMonitor;
+use dlc_manager::channel::offered_channel::OfferedChannel;
+use dlc_manager::channel::signed_channel::{SignedChannel, SignedChannelStateType};
+use dlc_manager::channel::{Channel, ChannelStateType, SettledClosingChannel};
+use dlc_manager::contract::adaptor_point_cache::CachedAdaptorPoint;
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::ser::VersionedSerializable;
+use dlc_manager::contract::signed_contract::SignedContract;
+use dlc_manager::contract::{Contract, ContractStateType, PreClosedContract, PreRefundedContract};
+use dlc_manager::encryption::Cipher;
+use dlc_manager::query::{ChannelQuery, ContractQuery, Page, QueryCursor, TimeRange};
+use dlc_manager::sub_channel_manager::Action;
+use dlc_manager::subchannel::{SubChannel, SubChannelState};
+#[cfg(feature = "wallet")]
+use dlc_manager::Utxo;
+use dlc_manager::{error::Error, ContractId, DlcChannelId, FeeBump, Storage};
+use lightning::ln::ChannelId;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Params};
+#[cfg(feature = "wallet")]
+use secp256k1_zkp::{PublicKey, SecretKey};
+#[cfg(feature = "wallet")]
+use simple_wallet::WalletStorage;
+use std::convert::TryInto;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The version of the database schema, recorded in the `user_version` of the
+/// database.
+pub const SCHEMA_VERSION: u32 = 1;
+
+type Migration = fn(&Connection) -> Result<(), Error>;
+
+/// Functions migrating the database schema, the one at index `i` migrating it
+/// from version `i` to version `i + 1`.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [migrate_v0_to_v1];
+
+/// Encrypted and stored in the `encryption` table to check the key used to open
+/// the database.
+const ENCRYPTION_CHECK: &[u8] = b"dlc-sqlite-storage-provider";
+
+/// The columns encrypted when encryption is enabled, given as the table they belong
+/// to, the columns of the primary key of the table and the encrypted column.
+const ENCRYPTED_COLUMNS: [(&str, &[&str], &str); 9] = [
+    ("contracts", &["id"], "data"),
+    ("channels", &["id"], "data"),
+    ("sub_channels", &["id"], "data"),
+    ("chain_monitor", &["id"], "data"),
+    ("adaptor_points", &["key"], "data"),
+    ("sub_channel_actions", &["position"], "data"),
+    ("utxos", &["txid", "vout"], "data"),
+    ("key_pairs", &["public_key"], "secret_key"),
+    ("addresses", &["address"], "secret_key"),
+];
+
+/// Implementation of Storage interface using the SQLite database.
+pub struct SqliteStorageProvider {
+    connection: Mutex<Connection>,
+    cipher: Option<Cipher>,
+}
+
+macro_rules! convertible_enum {
+    (enum $name:ident {
+        $($vname:ident $(= $val:expr)? $(; $subprefix:ident, $subfield:ident)?,)*;
+        $($tname:ident $(= $tval:expr)?,)*
+    }, $input:ident) => {
+        #[derive(Debug)]
+        enum $name {
+            $($vname $(= $val)?,)*
+            $($tname $(= $tval)?,)*
+        }
+
+        impl From<$name> for u8 {
+            fn from(state: $name) -> u8 {
+                state as u8
+            }
+        }
+
+        impl std::convert::TryFrom<u8> for $name {
+            type Error = Error;
+
+            fn try_from(v: u8) -> Result<Self, Self::Error> {
+                match v {
+                    $(x if x == u8::from($name::$vname) => Ok($name::$vname),)*
+                    $(x if x == u8::from($name::$tname) => Ok($name::$tname),)*
+                    x => Err(Error::StorageError(format!("Unknown state {}", x))),
+                }
+            }
+        }
+
+        impl $name {
+            fn get_state(input: &$input) -> u8 {
+                let state = match input {
+                    $($input::$vname(_) => $name::$vname,)*
+                    $($input::$tname{..} => $name::$tname,)*
+                };
+                state.into()
+            }
+        }
+    }
+}
+
+convertible_enum!(
+    enum ContractStateId {
+        Offered = 1,
+        Accepted,
+        Signed,
+        Confirmed,
+        PreClosed,
+        Closed,
+        FailedAccept,
+        FailedSign,
+        Refunded,
+        Rejected,
+        PreRefunded,;
+    },
+    Contract
+);
+
+convertible_enum!(
+    enum ChannelStateId {
+        Offered = 1,
+        Accepted,
+        Signed; SignedChannelStateId, state,
+        Closing,
+        Closed,
+        CounterClosed,
+        ClosedPunished,
+        CollaborativelyClosed,
+        FailedAccept,
+        FailedSign,
+        Cancelled,
+        SettledClosing,;
+    },
+    Channel
+);
+
+convertible_enum!(
+    enum SignedChannelStateId {;
+        Established = 1,
+        SettledOffered,
+        SettledReceived,
+        SettledAccepted,
+        SettledConfirmed,
+        Settled,
+        Closing,
+        CollaborativeCloseOffered,
+        RenewAccepted,
+        RenewOffered,
+        RenewConfirmed,
+        RenewFinalized,
+        SettledClosing,
+    },
+    SignedChannelStateType
+);
+
+convertible_enum!(
+    enum SubChannelStateId {;
+        Offered = 1,
+        Accepted,
+        Confirmed,
+        Finalized,
+        Signed,
+        Closing,
+        OnChainClosed,
+        CounterOnChainClosed,
+        CloseOffered,
+        CloseAccepted,
+        CloseConfirmed,
+        OffChainClosed,
+        ClosedPunished,
+        Rejected,
+    },
+    SubChannelState
+);
+
+impl From<ContractStateType> for ContractStateId {
+    fn from(state: ContractStateType) -> ContractStateId {
+        match state {
+            ContractStateType::Offered => ContractStateId::Offered,
+            ContractStateType::Accepted => ContractStateId::Accepted,
+            ContractStateType::Signed => ContractStateId::Signed,
+            ContractStateType::Confirmed => ContractStateId::Confirmed,
+            ContractStateType::PreClosed => ContractStateId::PreClosed,
+            ContractStateType::Closed => ContractStateId::Closed,
+            ContractStateType::PreRefunded => ContractStateId::PreRefunded,
+            ContractStateType::Refunded => ContractStateId::Refunded,
+            ContractStateType::FailedAccept => ContractStateId::FailedAccept,
+            ContractStateType::FailedSign => ContractStateId::FailedSign,
+            ContractStateType::Rejected => ContractStateId::Rejected,
+        }
+    }
+}
+
+impl From<ChannelStateType> for ChannelStateId {
+    fn from(state: ChannelStateType) -> ChannelStateId {
+        match state {
+            ChannelStateType::Offered => ChannelStateId::Offered,
+            ChannelStateType::Accepted => ChannelStateId::Accepted,
+            ChannelStateType::Signed => ChannelStateId::Signed,
+            ChannelStateType::Closing => ChannelStateId::Closing,
+            ChannelStateType::SettledClosing => ChannelStateId::SettledClosing,
+            ChannelStateType::Closed => ChannelStateId::Closed,
+            ChannelStateType::CounterClosed => ChannelStateId::CounterClosed,
+            ChannelStateType::ClosedPunished => ChannelStateId::ClosedPunished,
+            ChannelStateType::CollaborativelyClosed => ChannelStateId::CollaborativelyClosed,
+            ChannelStateType::FailedAccept => ChannelStateId::FailedAccept,
+            ChannelStateType::FailedSign => ChannelStateId::FailedSign,
+            ChannelStateType::Cancelled => ChannelStateId::Cancelled,
+        }
+    }
+}
+
+/// Builds the conditions of a query along with their parameters.
+#[derive(Default)]
+struct Filter {
+    conditions: Vec<String>,
+    params: Vec<Value>,
+}
+
+impl Filter {
+    fn add(&mut self, condition: String, params: Vec<Value>) {
+        self.conditions.push(condition);
+        self.params.extend(params);
+    }
+
+    fn add_in(&mut self, column: &str, values: Vec<u8>) {
+        if values.is_empty() {
+            return;
+        }
+        let placeholders = vec!["?"; values.len()].join(", ");
+        self.add(
+            format!("{column} IN ({placeholders})"),
+            values
+                .into_iter()
+                .map(|v| Value::Integer(v.into()))
+                .collect(),
+        );
+    }
+
+    fn add_time_range(&mut self, column: &str, range: &TimeRange) {
+        if let Some(from) = range.from {
+            self.add(format!("{column} >= ?"), vec![to_sql_time(from)]);
+        }
+        if let Some(to) = range.to {
+            self.add(format!("{column} < ?"), vec![to_sql_time(to)]);
+        }
+    }
+
+    fn add_cursor(&mut self, cursor: &Option<QueryCursor>) {
+        if let Some(cursor) = cursor {
+            self.add(
+                "(created_at > ? OR (created_at = ? AND id > ?))".to_string(),
+                vec![
+                    to_sql_time(cursor.created_at),
+                    to_sql_time(cursor.created_at),
+                    Value::Blob(cursor.id.to_vec()),
+                ],
+            );
+        }
+    }
+}
+
+fn to_sql_time(time: u64) -> Value {
+    Value::Integer(time.try_into().unwrap_or(i64::MAX))
+}
+
+fn to_storage_error<T>(e: T) -> Error
+where
+    T: std::fmt::Display,
+{
+    Error::StorageError(e.to_string())
+}
+
+fn get_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Unexpected time before UNIX epoch")
+        .as_secs() as i64
+}
+
+fn migrate_v0_to_v1(connection: &Connection) -> Result<(), Error> {
+    connection
+        .execute_batch(include_str!("../migrations/v1.sql"))
+        .map_err(to_storage_error)
+}
+
+fn read_schema_version(connection: &Connection) -> Result<u32, Error> {
+    connection
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(to_storage_error)
+}
+
+impl SqliteStorageProvider {
+    /// Opens the database at the given path, creating it if it does not exist
+    /// and migrating its schema to the current [`SCHEMA_VERSION`] if required.
+    /// Fails if the database is encrypted.
+    pub fn new(path: &str) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path).map_err(to_storage_error)?, None)
+    }
+
+    /// Opens the database at the given path like [`Self::new`], encrypting the
+    /// stored values with the given key. Data stored unencrypted is encrypted when
+    /// first opening the database with a key, and opening an encrypted database
+    /// with a different key fails.
+    ///
+    /// Only the serialized objects and the private keys of the wallet are
+    /// encrypted. The columns used to query them are kept in plaintext: the ids,
+    /// states, counter parties, reference ids, timestamps and maturities of the
+    /// contracts, channels and sub channels, the fee bumps, the addresses and
+    /// public keys of the wallet and the outpoints of its UTXOs.
+    pub fn new_encrypted(path: &str, key: EncryptionKey) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path).map_err(to_storage_error)?, Some(key))
+    }
+
+    /// Creates a new instance of a SqliteStorageProvider backed by an in-memory
+    /// database.
+    pub fn new_in_memory() -> Result<Self, Error> {
+        Self::from_connection(
+            Connection::open_in_memory().map_err(to_storage_error)?,
+            None,
+        )
+    }
+
+    /// Creates or migrates the schema of the database if required, before checking
+    /// the key of encrypted databases which is recorded by the schema. Plaintext
+    /// databases are encrypted if a key is given.
+    fn from_connection(
+        mut connection: Connection,
+        key: Option<EncryptionKey>,
+    ) -> Result<Self, Error> {
+        let version = read_schema_version(&connection)?;
+        if version > SCHEMA_VERSION {
+            return Err(Error::StorageError(format!(
+                "Database schema version {version} is newer than supported version {SCHEMA_VERSION}"
+            )));
+        }
+
+        for from in version..SCHEMA_VERSION {
+            let transaction = connection.transaction().map_err(to_storage_error)?;
+            MIGRATIONS[from as usize](&transaction)?;
+            transaction
+                .pragma_update(None, "user_version", from + 1)
+                .map_err(to_storage_error)?;
+            transaction.commit().map_err(to_storage_error)?;
+        }
+
+        let check: Option<Vec<u8>> = connection
+            .query_row("SELECT key_check FROM encryption WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(to_storage_error)?;
+        let cipher = match check {
+            Some(check) => {
+                let key = key.as_ref().ok_or_else(|| {
+                    Error::StorageError(
+                        "The database is encrypted, an encryption key is required to open it"
+                            .to_string(),
+                    )
+                })?;
+                let cipher = Cipher::new(key);
+                match cipher.decrypt(&value_context("encryption", &[]), &check) {
+                    Ok(plaintext) if plaintext == ENCRYPTION_CHECK => Some(cipher),
+                    _ => {
+                        return Err(Error::StorageError(
+                            "Invalid encryption key for the database".to_string(),
+                        ))
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut storage = SqliteStorageProvider {
+            connection: Mutex::new(connection),
+            cipher,
+        };
+        if key.is_some() && !storage.is_encrypted() {
+            storage.rotate_encryption_key(key)?;
+        }
+        Ok(storage)
+    }
+
+    /// Returns whether the values stored by this instance are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Re-encrypts all the stored values with the given key, or decrypts them if
+    /// `None` is given. All the values are rewritten in a single transaction, after
+    /// which the database can only be opened with the new key. Unlike sled, SQLite
+    /// writes the pending changes of a transaction to its journal rather than
+    /// keeping them in memory, so that the size of the database does not limit it.
+    pub fn rotate_encryption_key(&mut self, new_key: Option<EncryptionKey>) -> Result<(), Error> {
+        let new_cipher = new_key.as_ref().map(Cipher::new);
+        {
+            let mut connection = self.connection();
+            let transaction = connection.transaction().map_err(to_storage_error)?;
+            for (table, key_columns, column) in ENCRYPTED_COLUMNS {
+                let rows = {
+                    let mut statement = transaction
+                        .prepare(&format!(
+                            "SELECT rowid, {}, {column} FROM {table}",
+                            key_columns.join(", ")
+                        ))
+                        .map_err(to_storage_error)?;
+                    let rows = statement
+                        .query_map([], |row| {
+                            let key = (0..key_columns.len())
+                                .map(|i| row.get::<_, Value>(i + 1))
+                                .collect::<Result<Vec<_>, _>>()?;
+                            Ok((
+                                row.get::<_, i64>(0)?,
+                                row_key(&key),
+                                row.get::<_, Vec<u8>>(key_columns.len() + 1)?,
+                            ))
+                        })
+                        .map_err(to_storage_error)?;
+                    rows.collect::<Result<Vec<_>, _>>()
+                        .map_err(to_storage_error)?
+                };
+                for (rowid, key, value) in rows {
+                    let value = unseal_value(self.cipher.as_ref(), table, &key, value)?;
+                    transaction
+                        .execute(
+                            &format!("UPDATE {table} SET {column} = ?1 WHERE rowid = ?2"),
+                            params![seal_value(new_cipher.as_ref(), table, &key, value)?, rowid],
+                        )
+                        .map_err(to_storage_error)?;
+                }
+            }
+
+            let res = match &new_cipher {
+                Some(cipher) => transaction.execute(
+                    "INSERT OR REPLACE INTO encryption (id, key_check) VALUES (0, ?1)",
+                    [cipher.encrypt(&value_context("encryption", &[]), ENCRYPTION_CHECK)?],
+                ),
+                None => transaction.execute("DELETE FROM encryption", []),
+            };
+            res.map_err(to_storage_error)?;
+            transaction.commit().map_err(to_storage_error)?;
+        }
+
+        self.cipher = new_cipher;
+        Ok(())
+    }
+
+    /// Returns the version of the schema of the database.
+    pub fn get_schema_version(&self) -> Result<u32, Error> {
+        read_schema_version(&self.connection())
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .expect("Could not get connection lock")
+    }
+
+    /// Returns the objects stored in the rows of `table` selected by the given
+    /// query, which returns the key and data of each row.
+    fn get_data<T: VersionedSerializable, P: Params>(
+        &self,
+        table: &str,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<T>, Error> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(sql).map_err(to_storage_error)?;
+        let rows = statement
+            .query_map(params, |row| {
+                Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .map_err(to_storage_error)?;
+
+        let mut res = Vec::new();
+        for row in rows {
+            let (key, data) = row.map_err(to_storage_error)?;
+            match unseal_value(self.cipher.as_ref(), table, &key, data)
+                .and_then(|data| read_object(&mut data.as_slice(), false))
+            {
+                Ok(value) => res.push(value),
+                Err(e) => log::error!("Failed to deserialize record: {e}"),
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Returns a page of the records of `table` matching `filter`, ordered by
+    /// creation time and id.
+    fn get_page<T>(
+        &self,
+        table: &str,
+        mut filter: Filter,
+        cursor: &Option<QueryCursor>,
+        limit: Option<usize>,
+        deserialize: fn(u8, &[u8]) -> Result<T, Error>,
+    ) -> Result<Page<T>, Error> {
+        filter.add_cursor(cursor);
+        let mut sql = format!("SELECT id, state, created_at, data FROM {table}");
+        if !filter.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&filter.conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY created_at, id");
+        if let Some(limit) = limit {
+            // Fetch one more record to know whether there is a next page.
+            sql.push_str(" LIMIT ?");
+            filter
+                .params
+                .push(Value::Integer((limit as i64).saturating_add(1)));
+        }
+
+        let connection = self.connection();
+        let mut statement = connection.prepare(&sql).map_err(to_storage_error)?;
+        let rows = statement
+            .query_map(params_from_iter(filter.params), |row| {
+                Ok((
+                    row.get::<_, Vec<u8>>(0)?,
+                    row.get::<_, u8>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, Vec<u8>>(3)?,
+                ))
+            })
+            .map_err(to_storage_error)?;
+
+        let mut res = Vec::new();
+        for row in rows {
+            let (id, state, created_at, data) = row.map_err(to_storage_error)?;
+            let data = unseal_value(self.cipher.as_ref(), table, &id, data);
+            let id = id
+                .as_slice()
+                .try_into()
+                .map_err(|_| Error::StorageError("Invalid record id".to_string()))?;
+            match data.and_then(|data| deserialize(state, &data)) {
+                Ok(value) => res.push((
+                    QueryCursor {
+                        created_at: created_at as u64,
+                        id,
+                    },
+                    value,
+                )),
+                Err(e) => log::error!("Failed to deserialize record: {e}"),
+            }
+        }
+
+        Ok(Page::from_matches(res, None, limit))
+    }
+
+    /// Returns the object stored in the row of `table` with the given key,
+    /// selected by the given query returning its data.
+    fn get_single<T: VersionedSerializable, P: Params>(
+        &self,
+        table: &str,
+        key: &[u8],
+        sql: &str,
+        params: P,
+    ) -> Result<Option<T>, Error> {
+        let data: Option<Vec<u8>> = self
+            .connection()
+            .query_row(sql, params, |row| row.get(0))
+            .optional()
+            .map_err(to_storage_error)?;
+        data.map(|d| {
+            let d = unseal_value(self.cipher.as_ref(), table, key, d)?;
+            read_object(&mut d.as_slice(), false)
+        })
+        .transpose()
+    }
+}
+
+impl Storage for SqliteStorageProvider {
+    fn get_contract(&self, contract_id: &ContractId) -> Result<Option<Contract>, Error> {
+        let row: Option<(u8, Vec<u8>)> = self
+            .connection()
+            .query_row(
+                "SELECT state, data FROM contracts WHERE id = ?1",
+                [contract_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(to_storage_error)?;
+        match row {
+            Some((state, data)) => {
+                let data = unseal_value(self.cipher.as_ref(), "contracts", contract_id, data)?;
+                Ok(Some(deserialize_contract(state, &data)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn get_contracts(&self) -> Result<Vec<Contract>, Error> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT id, state, data FROM contracts")
+            .map_err(to_storage_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, Vec<u8>>(0)?,
+                    row.get::<_, u8>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                ))
+            })
+            .map_err(to_storage_error)?;
+
+        let mut res = Vec::new();
+        for row in rows {
+            let (id, state, data) = row.map_err(to_storage_error)?;
+            match unseal_value(self.cipher.as_ref(), "contracts", &id, data)
+                .and_then(|data| deserialize_contract(state, &data))
+            {
+                Ok(contract) => res.push(contract),
+                Err(e) => log::error!("Failed to deserialize contract: {e}"),
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn create_contract(&self, contract: &OfferedContract) -> Result<(), Error> {
+        upsert_contract(
+            &self.connection(),
+            self.cipher.as_ref(),
+            &Contract::Offered(contract.clone()),
+        )
+    }
+
+    fn delete_contract(&self, contract_id: &ContractId) -> Result<(), Error> {
+        self.connection()
+            .execute("DELETE FROM contracts WHERE id = ?1", [contract_id])
+            .map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn update_contract(&self, contract: &Contract) -> Result<(), Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(to_storage_error)?;
+        upsert_contract(&transaction, self.cipher.as_ref(), contract)?;
+        transaction.commit().map_err(to_storage_error)
+    }
+
+    fn get_contract_offers(&self) -> Result<Vec<OfferedContract>, Error> {
+        self.get_data(
+            "contracts",
+            "SELECT id, data FROM contracts WHERE state = ?1",
+            [u8::from(ContractStateId::Offered)],
+        )
+    }
+
+    fn get_signed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
+        self.get_data(
+            "contracts",
+            "SELECT id, data FROM contracts WHERE state = ?1",
+            [u8::from(ContractStateId::Signed)],
+        )
+    }
+
+    fn get_confirmed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
+        self.get_data(
+            "contracts",
+            "SELECT id, data FROM contracts WHERE state = ?1",
+            [u8::from(ContractStateId::Confirmed)],
+        )
+    }
+
+    fn get_preclosed_contracts(&self) -> Result<Vec<PreClosedContract>, Error> {
+        self.get_data(
+            "contracts",
+            "SELECT id, data FROM contracts WHERE state = ?1",
+            [u8::from(ContractStateId::PreClosed)],
+        )
+    }
+
+    fn get_prerefunded_contracts(&self) -> Result<Vec<PreRefundedContract>, Error> {
+        self.get_data(
+            "contracts",
+            "SELECT id, data FROM contracts WHERE state = ?1",
+            [u8::from(ContractStateId::PreRefunded)],
+        )
+    }
+
+    fn query_contracts(&self, query: &ContractQuery) -> Result<Page<Contract>, Error> {
+        let mut filter = Filter::default();
+        filter.add_in(
+            "state",
+            query
+                .states
+                .iter()
+                .map(|s| ContractStateId::from(*s).into())
+                .collect(),
+        );
+        if let Some(counter_party) = &query.counter_party {
+            filter.add(
+                "counter_party = ?".to_string(),
+                vec![Value::Blob(counter_party.serialize().to_vec())],
+            );
+        }
+        filter.add_time_range("created_at", &query.created_at);
+        filter.add_time_range("maturity", &query.maturity);
+        self.get_page(
+            "contracts",
+            filter,
+            &query.cursor,
+            query.limit,
+            deserialize_contract,
+        )
+    }
+
+    fn upsert_channel(&self, channel: Channel, contract: Option<Contract>) -> Result<(), Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(to_storage_error)?;
+        upsert_channel(&transaction, self.cipher.as_ref(), &channel)?;
+        if let Some(c) = contract.as_ref() {
+            upsert_contract(&transaction, self.cipher.as_ref(), c)?;
+        }
+        transaction.commit().map_err(to_storage_error)
+    }
+
+    fn delete_channel(&self, channel_id: &DlcChannelId) -> Result<(), Error> {
+        self.connection()
+            .execute("DELETE FROM channels WHERE id = ?1", [channel_id])
+            .map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn get_channel(&self, channel_id: &DlcChannelId) -> Result<Option<Channel>, Error> {
+        let row: Option<(u8, Vec<u8>)> = self
+            .connection()
+            .query_row(
+                "SELECT state, data FROM channels WHERE id = ?1",
+                [channel_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(to_storage_error)?;
+        match row {
+            Some((state, data)) => {
+                let data = unseal_value(self.cipher.as_ref(), "channels", channel_id, data)?;
+                Ok(Some(deserialize_channel(state, &data)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn get_channels(&self) -> Result<Vec<Channel>, Error> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT id, state, data FROM channels")
+            .map_err(to_storage_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, Vec<u8>>(0)?,
+                    row.get::<_, u8>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                ))
+            })
+            .map_err(to_storage_error)?;
+
+        let mut res = Vec::new();
+        for row in rows {
+            let (id, state, data) = row.map_err(to_storage_error)?;
+            match unseal_value(self.cipher.as_ref(), "channels", &id, data)
+                .and_then(|data| deserialize_channel(state, &data))
+            {
+                Ok(channel) => res.push(channel),
+                Err(e) => log::error!("Failed to deserialize channel: {e}"),
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn query_channels(&self, query: &ChannelQuery) -> Result<Page<Channel>, Error> {
+        let mut filter = Filter::default();
+        filter.add_in(
+            "state",
+            query
+                .states
+                .iter()
+                .map(|s| ChannelStateId::from(*s).into())
+                .collect(),
+        );
+        if let Some(counter_party) = &query.counter_party {
+            filter.add(
+                "counter_party = ?".to_string(),
+                vec![Value::Blob(counter_party.serialize().to_vec())],
+            );
+        }
+        if let Some(reference_id) = &query.reference_id {
+            filter.add(
+                "reference_id = ?".to_string(),
+                vec![Value::Blob(reference_id.to_vec())],
+            );
+        }
+        filter.add_time_range("created_at", &query.created_at);
+        self.get_page(
+            "channels",
+            filter,
+            &query.cursor,
+            query.limit,
+            deserialize_channel,
+        )
+    }
+
+    fn get_signed_channels(
+        &self,
+        channel_state: Option<SignedChannelStateType>,
+    ) -> Result<Vec<SignedChannel>, Error> {
+        match &channel_state {
+            Some(state) => self.get_data(
+                "channels",
+                "SELECT id, data FROM channels WHERE state = ?1 AND signed_state = ?2",
+                [
+                    u8::from(ChannelStateId::Signed),
+                    SignedChannelStateId::get_state(state),
+                ],
+            ),
+            None => self.get_data(
+                "channels",
+                "SELECT id, data FROM channels WHERE state = ?1",
+                [u8::from(ChannelStateId::Signed)],
+            ),
+        }
+    }
+
+    fn get_offered_channels(&self) -> Result<Vec<OfferedChannel>, Error> {
+        self.get_data(
+            "channels",
+            "SELECT id, data FROM channels WHERE state = ?1",
+            [u8::from(ChannelStateId::Offered)],
+        )
+    }
+
+    fn get_settled_closing_channels(&self) -> Result<Vec<SettledClosingChannel>, Error> {
+        self.get_data(
+            "channels",
+            "SELECT id, data FROM channels WHERE state = ?1",
+            [u8::from(ChannelStateId::SettledClosing)],
+        )
+    }
+
+    fn persist_chain_monitor(&self, monitor: &ChainMonitor) -> Result<(), Error> {
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO chain_monitor (id, data) VALUES (0, ?1)",
+                [seal_value(
+                    self.cipher.as_ref(),
+                    "chain_monitor",
+                    &row_key(&[Value::Integer(0)]),
+                    monitor.serialize_versioned()?,
+                )?],
+            )
+            .map_err(|e| Error::StorageError(format!("Error writing chain monitor: {e}")))?;
+        Ok(())
+    }
+
+    fn get_chain_monitor(&self) -> Result<Option<ChainMonitor>, Error> {
+        self.get_single(
+            "chain_monitor",
+            &row_key(&[Value::Integer(0)]),
+            "SELECT data FROM chain_monitor WHERE id = 0",
+            [],
+        )
+    }
+
+    fn upsert_adaptor_points(&self, points: &[CachedAdaptorPoint]) -> Result<(), Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(to_storage_error)?;
+        for point in points {
+            transaction
+                .execute(
+                    "INSERT OR REPLACE INTO adaptor_points (key, data) VALUES (?1, ?2)",
+                    params![
+                        &point.key[..],
+                        seal_value(
+                            self.cipher.as_ref(),
+                            "adaptor_points",
+                            &point.key,
+                            point.serialize_versioned()?,
+                        )?,
+                    ],
+                )
+                .map_err(|e| Error::StorageError(format!("Error writing adaptor points: {e}")))?;
+        }
+        transaction.commit().map_err(to_storage_error)
+    }
+
+    fn get_adaptor_points(&self) -> Result<Vec<CachedAdaptorPoint>, Error> {
+        self.get_data("adaptor_points", "SELECT key, data FROM adaptor_points", [])
+    }
+
+    fn delete_adaptor_points(&self, keys: &[[u8; 32]]) -> Result<(), Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(to_storage_error)?;
+        for key in keys {
+            transaction
+                .execute("DELETE FROM adaptor_points WHERE key = ?1", [&key[..]])
+                .map_err(to_storage_error)?;
+        }
+        transaction.commit().map_err(to_storage_error)
+    }
+
+    fn upsert_fee_bump(&self, txid: &Txid, fee_bump: &FeeBump) -> Result<(), Error> {
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO fee_bumps (txid, fee_rate, child_txid) VALUES (?1, ?2, ?3)",
+                params![
+                    &txid[..],
+                    fee_bump.fee_rate as i64,
+                    &fee_bump.child_txid[..]
+                ],
+            )
+            .map_err(|e| Error::StorageError(format!("Error writing fee bump: {e}")))?;
+        Ok(())
+    }
+
+    fn get_fee_bump(&self, txid: &Txid) -> Result<Option<FeeBump>, Error> {
+        let fee_bump: Option<(i64, Vec<u8>)> = self
+            .connection()
+            .query_row(
+                "SELECT fee_rate, child_txid FROM fee_bumps WHERE txid = ?1",
+                [&txid[..]],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(to_storage_error)?;
+        fee_bump
+            .map(|(fee_rate, child_txid)| {
+                Ok(FeeBump {
+                    fee_rate: fee_rate as u64,
+                    child_txid: Txid::from_slice(&child_txid).map_err(to_storage_error)?,
+                })
+            })
+            .transpose()
+    }
+
+    fn delete_fee_bump(&self, txid: &Txid) -> Result<(), Error> {
+        self.connection()
+            .execute("DELETE FROM fee_bumps WHERE txid = ?1", [&txid[..]])
+            .map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn upsert_sub_channel(&self, subchannel: &SubChannel) -> Result<(), Error> {
+        let timestamp = get_timestamp();
+        self.connection()
+            .execute(
+                "INSERT INTO sub_channels (id, state, counter_party, created_at, updated_at, data)
+                VALUES (?1, ?2, ?3, ?4, ?4, ?5)
+                ON CONFLICT (id) DO UPDATE SET
+                    state = excluded.state,
+                    counter_party = excluded.counter_party,
+                    updated_at = excluded.updated_at,
+                    data = excluded.data",
+                params![
+                    subchannel.channel_id.0,
+                    SubChannelStateId::get_state(&subchannel.state),
+                    subchannel.counter_party.serialize(),
+                    timestamp,
+                    seal_value(
+                        self.cipher.as_ref(),
+                        "sub_channels",
+                        &subchannel.channel_id.0,
+                        subchannel.serialize_versioned()?,
+                    )?,
+                ],
+            )
+            .map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn get_sub_channel(&self, channel_id: ChannelId) -> Result<Option<SubChannel>, Error> {
+        self.get_single(
+            "sub_channels",
+            &channel_id.0,
+            "SELECT data FROM sub_channels WHERE id = ?1",
+            [channel_id.0],
+        )
+    }
+
+    fn get_sub_channels(&self) -> Result<Vec<SubChannel>, Error> {
+        self.get_data("sub_channels", "SELECT id, data FROM sub_channels", [])
+    }
+
+    fn get_offered_sub_channels(&self) -> Result<Vec<SubChannel>, Error> {
+        self.get_data(
+            "sub_channels",
+            "SELECT id, data FROM sub_channels WHERE state = ?1",
+            [u8::from(SubChannelStateId::Offered)],
+        )
+    }
+
+    fn save_sub_channel_actions(&self, actions: &[Action]) -> Result<(), Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(to_storage_error)?;
+        transaction
+            .execute("DELETE FROM sub_channel_actions", [])
+            .map_err(to_storage_error)?;
+        for (position, action) in actions.iter().enumerate() {
+            transaction
+                .execute(
+                    "INSERT INTO sub_channel_actions (position, data) VALUES (?1, ?2)",
+                    params![
+                        position as i64,
+                        seal_value(
+                            self.cipher.as_ref(),
+                            "sub_channel_actions",
+                            &row_key(&[Value::Integer(position as i64)]),
+                            action.serialize_versioned()?,
+                        )?,
+                    ],
+                )
+                .map_err(to_storage_error)?;
+        }
+        transaction.commit().map_err(to_storage_error)
+    }
+
+    fn get_sub_channel_actions(&self) -> Result<Vec<Action>, Error> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT position, data FROM sub_channel_actions ORDER BY position")
+            .map_err(to_storage_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .map_err(to_storage_error)?;
+
+        let mut res = Vec::new();
+        for row in rows {
+            let (position, data) = row.map_err(to_storage_error)?;
+            let key = row_key(&[Value::Integer(position)]);
+            let data = unseal_value(self.cipher.as_ref(), "sub_channel_actions", &key, data)?;
+            res.push(read_object(&mut data.as_slice(), false)?);
+        }
+
+        Ok(res)
+    }
+}
+
+#[cfg(feature = "wallet")]
+impl WalletStorage for SqliteStorageProvider {
+    fn upsert_address(&self, address: &Address, privkey: &SecretKey) -> Result<(), Error> {
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO addresses (address, secret_key) VALUES (?1, ?2)",
+                params![
+                    address.to_string(),
+                    seal_value(
+                        self.cipher.as_ref(),
+                        "addresses",
+                        address.to_string().as_bytes(),
+                        privkey.secret_bytes().to_vec(),
+                    )?,
+                ],
+            )
+            .map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn delete_address(&self, address: &Address) -> Result<(), Error> {
+        self.connection()
+            .execute(
+                "DELETE FROM addresses WHERE address = ?1",
+                [address.to_string()],
+            )
+            .map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn get_addresses(&self) -> Result<Vec<Address>, Error> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT address FROM addresses")
+            .map_err(to_storage_error)?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(to_storage_error)?;
+
+        let mut res = Vec::new();
+        for address in rows {
+            res.push(
+                address
+                    .map_err(to_storage_error)?
+                    .parse::<Address>()
+                    .map_err(|e| Error::InvalidState(format!("Could not read address {e}")))?,
+            );
+        }
+
+        Ok(res)
+    }
+
+    fn get_priv_key_for_address(&self, address: &Address) -> Result<Option<SecretKey>, Error> {
+        let raw_key: Option<Vec<u8>> = self
+            .connection()
+            .query_row(
+                "SELECT secret_key FROM addresses WHERE address = ?1",
+                [address.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(to_storage_error)?;
+
+        raw_key
+            .map(|k| {
+                let k = unseal_value(
+                    self.cipher.as_ref(),
+                    "addresses",
+                    address.to_string().as_bytes(),
+                    k,
+                )?;
+                Ok(SecretKey::from_slice(&k).expect("a valid secret key"))
+            })
+            .transpose()
+    }
+
+    fn upsert_key_pair(&self, public_key: &PublicKey, privkey: &SecretKey) -> Result<(), Error> {
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO key_pairs (public_key, secret_key) VALUES (?1, ?2)",
+                params![
+                    public_key.serialize(),
+                    seal_value(
+                        self.cipher.as_ref(),
+                        "key_pairs",
+                        &public_key.serialize(),
+                        privkey.secret_bytes().to_vec(),
+                    )?,
+                ],
+            )
+            .map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn get_priv_key_for_pubkey(&self, public_key: &PublicKey) -> Result<Option<SecretKey>, Error> {
+        let raw_key: Option<Vec<u8>> = self
+            .connection()
+            .query_row(
+                "SELECT secret_key FROM key_pairs WHERE public_key = ?1",
+                [public_key.serialize()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(to_storage_error)?;
+
+        raw_key
+            .map(|k| {
+                let k = unseal_value(
+                    self.cipher.as_ref(),
+                    "key_pairs",
+                    &public_key.serialize(),
+                    k,
+                )?;
+                Ok(SecretKey::from_slice(&k).expect("a valid secret key"))
+            })
+            .transpose()
+    }
+
+    fn upsert_utxo(&self, utxo: &Utxo) -> Result<(), Error> {
+        write_utxo(&self.connection(), self.cipher.as_ref(), utxo)
+    }
+
+    fn has_utxo(&self, utxo: &Utxo) -> Result<bool, Error> {
+        self.connection()
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM utxos WHERE txid = ?1 AND vout = ?2)",
+                params![&utxo.outpoint.txid[..], utxo.outpoint.vout],
+                |row| row.get(0),
+            )
+            .map_err(to_storage_error)
+    }
+
+    fn delete_utxo(&self, utxo: &Utxo) -> Result<(), Error> {
+        self.connection()
+            .execute(
+                "DELETE FROM utxos WHERE txid = ?1 AND vout = ?2",
+                params![&utxo.outpoint.txid[..], utxo.outpoint.vout],
+            )
+            .map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn get_utxos(&self) -> Result<Vec<Utxo>, Error> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT txid, vout, data FROM utxos")
+            .map_err(to_storage_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                let key = row_key(&[row.get(0)?, row.get(1)?]);
+                Ok((key, row.get::<_, Vec<u8>>(2)?))
+            })
+            .map_err(to_storage_error)?;
+
+        let mut res = Vec::new();
+        for row in rows {
+            let (key, data) = row.map_err(to_storage_error)?;
+            let data = unseal_value(self.cipher.as_ref(), "utxos", &key, data)?;
+            res.push(
+                Utxo::deserialize_versioned(&mut data.as_slice())
+                    .map_err(|x| Error::InvalidState(format!("{x}")))?,
+            );
+        }
+
+        Ok(res)
+    }
+
+    fn unreserve_utxo(&self, txid: &Txid, vout: u32) -> Result<(), Error> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(to_storage_error)?;
+        let data: Option<Vec<u8>> = transaction
+            .query_row(
+                "SELECT data FROM utxos WHERE txid = ?1 AND vout = ?2",
+                params![&txid[..], vout],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(to_storage_error)?;
+        let mut utxo = match data {
+            Some(data) => {
+                let data =
+                    unseal_value(self.cipher.as_ref(), "utxos", &utxo_key(txid, vout), data)?;
+                Utxo::deserialize_versioned(&mut data.as_slice())
+                    .map_err(|_| Error::InvalidState("Could not read UTXO".to_string()))?
+            }
+            None => return Err(Error::InvalidState(format!("No utxo for {txid} {vout}"))),
+        };
+
+        utxo.reserved = false;
+        write_utxo(&transaction, self.cipher.as_ref(), &utxo)?;
+        transaction.commit().map_err(to_storage_error)
+    }
+}
+
+#[cfg(feature = "wallet")]
+fn write_utxo(connection: &Connection, cipher: Option<&Cipher>, utxo: &Utxo) -> Result<(), Error> {
+    let key = utxo_key(&utxo.outpoint.txid, utxo.outpoint.vout);
+    connection
+        .execute(
+            "INSERT OR REPLACE INTO utxos (txid, vout, reserved, data) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                &utxo.outpoint.txid[..],
+                utxo.outpoint.vout,
+                utxo.reserved,
+                seal_value(cipher, "utxos", &key, utxo.serialize_versioned()?)?
+            ],
+        )
+        .map_err(to_storage_error)?;
+    Ok(())
+}
+
+#[cfg(feature = "wallet")]
+fn utxo_key(txid: &Txid, vout: u32) -> Vec<u8> {
+    row_key(&[Value::Blob(txid[..].to_vec()), Value::Integer(vout.into())])
+}
+
+/// Removes the record stored under the temporary id of the given one if any,
+/// returning its creation time.
+fn remove_temporary(
+    connection: &Connection,
+    table: &str,
+    temporary_id: &[u8; 32],
+) -> Result<Option<i64>, Error> {
+    let created_at = connection
+        .query_row(
+            &format!("SELECT created_at FROM {table} WHERE id = ?1"),
+            [temporary_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(to_storage_error)?;
+    connection
+        .execute(
+            &format!("DELETE FROM {table} WHERE id = ?1"),
+            [temporary_id],
+        )
+        .map_err(to_storage_error)?;
+    Ok(created_at)
+}
+
+fn upsert_contract(
+    connection: &Connection,
+    cipher: Option<&Cipher>,
+    contract: &Contract,
+) -> Result<(), Error> {
+    let created_at = match contract {
+        a @ Contract::Accepted(_) | a @ Contract::Signed(_) => {
+            remove_temporary(connection, "contracts", &a.get_temporary_id())?
+        }
+        _ => None,
+    };
+    let timestamp = get_timestamp();
+
+    connection
+        .execute(
+            "INSERT INTO contracts (id, state, counter_party, created_at, updated_at, maturity, data)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT (id) DO UPDATE SET
+                state = excluded.state,
+                counter_party = excluded.counter_party,
+                updated_at = excluded.updated_at,
+                maturity = COALESCE(excluded.maturity, contracts.maturity),
+                data = excluded.data",
+            params![
+                contract.get_id(),
+                ContractStateId::get_state(contract),
+                contract.get_counter_party_id().serialize(),
+                created_at.unwrap_or(timestamp),
+                timestamp,
+                contract.get_maturity().map(|m| m as i64),
+                seal_value(
+                    cipher,
+                    "contracts",
+                    &contract.get_id(),
+                    serialize_contract(contract)?
+                )?,
+            ],
+        )
+        .map_err(to_storage_error)?;
+    Ok(())
+}
+
+fn upsert_channel(
+    connection: &Connection,
+    cipher: Option<&Cipher>,
+    channel: &Channel,
+) -> Result<(), Error> {
+    let created_at = match channel {
+        a @ Channel::Accepted(_) | a @ Channel::Signed(_) => {
+            remove_temporary(connection, "channels", &a.get_temporary_id())?
+        }
+        _ => None,
+    };
+    let timestamp = get_timestamp();
+    let signed_state = match channel {
+        Channel::Signed(s) => Some(SignedChannelStateId::get_state(&s.state.get_type())),
+        _ => None,
+    };
+
+    connection
+        .execute(
+            "INSERT INTO channels
+                (id, state, signed_state, counter_party, reference_id, created_at, updated_at, data)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (id) DO UPDATE SET
+                state = excluded.state,
+                signed_state = excluded.signed_state,
+                counter_party = excluded.counter_party,
+                reference_id = excluded.reference_id,
+                updated_at = excluded.updated_at,
+                data = excluded.data",
+            params![
+                channel.get_id(),
+                ChannelStateId::get_state(channel),
+                signed_state,
+                channel.get_counter_party_id().serialize(),
+                channel.get_reference_id(),
+                created_at.unwrap_or(timestamp),
+                timestamp,
+                seal_value(
+                    cipher,
+                    "channels",
+                    &channel.get_id(),
+                    serialize_channel(channel)?
+                )?,
+            ],
+        )
+        .map_err(to_storage_error)?;
+    Ok(())
+}
+
+/// Returns the key of a row given the values of the columns of its primary key,
+/// integers being encoded in big endian.
+fn row_key(values: &[Value]) -> Vec<u8> {
+    let mut res = Vec::new();
+    for value in values {
+        match value {
+            Value::Integer(i) => res.extend_from_slice(&i.to_be_bytes()),
+            Value::Real(r) => res.extend_from_slice(&r.to_be_bytes()),
+            Value::Text(t) => res.extend_from_slice(t.as_bytes()),
+            Value::Blob(b) => res.extend_from_slice(b),
+            Value::Null => {}
+        }
+    }
+    res
+}
+
+/// Returns the location of a value, the name of its table followed by the key of
+/// its row, which is authenticated along with it when it is encrypted so that
+/// encrypted values cannot be moved to another row or table.
+fn value_context(table: &str, key: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(table.len() + key.len() + 1);
+    res.push(table.len() as u8);
+    res.extend_from_slice(table.as_bytes());
+    res.extend_from_slice(key);
+    res
+}
+
+/// Encrypts the value stored in the row of `table` with the given key if
+/// encryption is enabled.
+fn seal_value(
+    cipher: Option<&Cipher>,
+    table: &str,
+    key: &[u8],
+    value: Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(&value_context(table, key), &value),
+        None => Ok(value),
+    }
+}
+
+/// Decrypts a value encrypted by [`seal_value`] for the same row.
+fn unseal_value(
+    cipher: Option<&Cipher>,
+    table: &str,
+    key: &[u8],
+    value: Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    match cipher {
+        Some(cipher) => cipher.decrypt(&value_context(table, key), &value),
+        None => Ok(value),
+    }
+}
+
+fn serialize_contract(contract: &Contract) -> Result<Vec<u8>, ::std::io::Error> {
+    match contract {
+        Contract::Offered(o) | Contract::Rejected(o) => o.serialize_versioned(),
+        Contract::Accepted(o) => o.serialize_versioned(),
+        Contract::Signed(o) | Contract::Confirmed(o) | Contract::Refunded(o) => {
+            o.serialize_versioned()
+        }
+        Contract::FailedAccept(c) => c.serialize_versioned(),
+        Contract::FailedSign(c) => c.serialize_versioned(),
+        Contract::PreClosed(c) => c.serialize_versioned(),
+        Contract::PreRefunded(c) => c.serialize_versioned(),
+        Contract::Closed(c) => c.serialize_versioned(),
+    }
+}
+
+fn deserialize_contract(state: u8, data: &[u8]) -> Result<Contract, Error> {
+    let mut cursor = data;
+    let contract_state: ContractStateId = state.try_into()?;
+    let contract = match contract_state {
+        ContractStateId::Offered => Contract::Offered(read_object(&mut cursor)?),
+        ContractStateId::Accepted => Contract::Accepted(read_object(&mut cursor)?),
+        ContractStateId::Signed => Contract::Signed(read_object(&mut cursor)?),
+        ContractStateId::Confirmed => Contract::Confirmed(read_object(&mut cursor)?),
+        ContractStateId::PreClosed => Contract::PreClosed(read_object(&mut cursor)?),
+        ContractStateId::PreRefunded => Contract::PreRefunded(read_object(&mut cursor)?),
+        ContractStateId::Closed => Contract::Closed(read_object(&mut cursor)?),
+        ContractStateId::FailedAccept => Contract::FailedAccept(read_object(&mut cursor)?),
+        ContractStateId::FailedSign => Contract::FailedSign(read_object(&mut cursor)?),
+        ContractStateId::Refunded => Contract::Refunded(read_object(&mut cursor)?),
+        ContractStateId::Rejected => Contract::Rejected(read_object(&mut cursor)?),
+    };
+    Ok(contract)
+}
+
+fn serialize_channel(channel: &Channel) -> Result<Vec<u8>, ::std::io::Error> {
+    match channel {
+        Channel::Offered(o) => o.serialize_versioned(),
+        Channel::Accepted(a) => a.serialize_versioned(),
+        Channel::Signed(s) => s.serialize_versioned(),
+        Channel::FailedAccept(f) => f.serialize_versioned(),
+        Channel::FailedSign(f) => f.serialize_versioned(),
+        Channel::Closing(c) => c.serialize_versioned(),
+        Channel::SettledClosing(c) => c.serialize_versioned(),
+        Channel::Closed(c) | Channel::CounterClosed(c) | Channel::CollaborativelyClosed(c) => {
+            c.serialize_versioned()
+        }
+        Channel::ClosedPunished(c) => c.serialize_versioned(),
+        Channel::Cancelled(o) => o.serialize_versioned(),
+    }
+}
+
+fn deserialize_channel(state: u8, data: &[u8]) -> Result<Channel, Error> {
+    let mut cursor = data;
+    let channel_state: ChannelStateId = state.try_into()?;
+    let channel = match channel_state {
+        ChannelStateId::Offered => Channel::Offered(read_object(&mut cursor)?),
+        ChannelStateId::Accepted => Channel::Accepted(read_object(&mut cursor)?),
+        ChannelStateId::Signed => Channel::Signed(read_object(&mut cursor)?),
+        ChannelStateId::FailedAccept => Channel::FailedAccept(read_object(&mut cursor)?),
+        ChannelStateId::FailedSign => Channel::FailedSign(read_object(&mut cursor)?),
+        ChannelStateId::Closing => Channel::Closing(read_object(&mut cursor)?),
+        ChannelStateId::SettledClosing => Channel::SettledClosing(read_object(&mut cursor)?),
+        ChannelStateId::Closed => Channel::Closed(read_object(&mut cursor)?),
+        ChannelStateId::CollaborativelyClosed => {
+            Channel::CollaborativelyClosed(read_object(&mut cursor)?)
+        }
+        ChannelStateId::CounterClosed => Channel::CounterClosed(read_object(&mut cursor)?),
+        ChannelStateId::ClosedPunished => Channel::ClosedPunished(read_object(&mut cursor)?),
+        ChannelStateId::Cancelled => Channel::Cancelled(read_object(&mut cursor)?),
+    };
+    Ok(channel)
+}
+
+fn read_object<T: VersionedSerializable>(cursor: &mut &[u8]) -> Result<T, Error> {
+    T::deserialize_versioned(cursor).map_err(to_storage_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dlc_manager::contract::ser::Serializable;
+
+    macro_rules! sqlite_test {
+        ($name: ident, $body: expr) => {
+            #[test]
+            fn $name() {
+                let storage =
+                    SqliteStorageProvider::new_in_memory().expect("Error opening SQLite DB");
+                #[allow(clippy::redundant_closure_call)]
+                $body(storage);
+            }
+        };
+    }
+
+    fn deserialize_object<T>(serialized: &[u8]) -> T
+    where
+        T: Serializable,
+    {
+        let mut cursor = std::io::Cursor::new(&serialized);
+        T::deserialize(&mut cursor).unwrap()
+    }
+
+    mocks::storage_conformance_tests!(
+        |_: &str| SqliteStorageProvider::new_in_memory().expect("Error opening SQLite DB")
+    );
+
+    mod encrypted_conformance {
+        use super::*;
+
+        fn test_path(name: &str) -> std::path::PathBuf {
+            std::env::temp_dir().join(format!("dlc_sqlite_encrypted_conformance_{name}.db"))
+        }
+
+        mocks::storage_conformance_tests!(
+            |name: &str| {
+                let _ = std::fs::remove_file(test_path(name));
+                SqliteStorageProvider::new_encrypted(
+                    test_path(name).to_str().unwrap(),
+                    EncryptionKey::new([1; 32]),
+                )
+                .expect("Error opening SQLite DB")
+            },
+            |name: &str| std::fs::remove_file(test_path(name)).unwrap()
+        );
+    }
+
+    sqlite_test!(
+        new_database_is_at_current_schema_version,
+        |storage: SqliteStorageProvider| {
+            assert_eq!(SCHEMA_VERSION, storage.get_schema_version().unwrap());
+        }
+    );
+
+    #[test]
+    fn data_is_persisted_across_connections() {
+        let path = std::env::temp_dir().join("dlc_sqlite_data_is_persisted_across_connections.db");
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+
+        let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+        let contract: OfferedContract = deserialize_object(serialized);
+        {
+            let storage = SqliteStorageProvider::new(path).expect("Error opening SQLite DB");
+            storage
+                .create_contract(&contract)
+                .expect("Error creating contract");
+        }
+
+        {
+            let storage = SqliteStorageProvider::new(path).expect("Error opening SQLite DB");
+            assert!(storage
+                .get_contract(&contract.id)
+                .expect("Error retrieving contract")
+                .is_some());
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn newer_schema_version_is_rejected() {
+        let path = std::env::temp_dir().join("dlc_sqlite_newer_schema_version_is_rejected.db");
+        let _ = std::fs::remove_file(&path);
+        {
+            let connection = Connection::open(&path).unwrap();
+            connection
+                .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+                .unwrap();
+        }
+
+        assert!(SqliteStorageProvider::new(path.to_str().unwrap()).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn encrypted_values_are_not_stored_in_plaintext() {
+        let path = std::env::temp_dir().join("dlc_sqlite_encrypted_values_are_not_stored.db");
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+
+        let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+        let contract: OfferedContract = deserialize_object(serialized);
+        {
+            let storage = SqliteStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening SQLite DB");
+            assert!(storage.is_encrypted());
+            storage
+                .create_contract(&contract)
+                .expect("Error creating contract");
+
+            let raw: Vec<u8> = storage
+                .connection()
+                .query_row(
+                    "SELECT data FROM contracts WHERE id = ?1",
+                    [contract.id],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert!(!raw
+                .windows(serialized.len())
+                .any(|window| window == &serialized[..]));
+
+            let retrieved = storage
+                .get_contract(&contract.id)
+                .expect("Error retrieving contract")
+                .expect("to have found the contract");
+            if let Contract::Offered(retrieved) = retrieved {
+                assert_eq!(serialized[..], retrieved.serialize().unwrap()[..]);
+            } else {
+                panic!("Expected an offered contract");
+            }
+
+            // Values cannot be moved to another row.
+            let other_id = [0xab; 32];
+            storage
+                .connection()
+                .execute(
+                    "INSERT INTO contracts (id, state, counter_party, created_at, updated_at, data)
+                    SELECT ?1, state, counter_party, created_at, updated_at, data FROM contracts",
+                    [other_id],
+                )
+                .unwrap();
+            assert!(storage.get_contract(&other_id).is_err());
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn encrypted_database_requires_valid_key() {
+        let path = std::env::temp_dir().join("dlc_sqlite_encrypted_database_requires_valid_key.db");
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+
+        let contract: OfferedContract = deserialize_object(include_bytes!(
+            "../../dlc-sled-storage-provider/test_files/Offered"
+        ));
+        {
+            let storage = SqliteStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening SQLite DB");
+            storage
+                .create_contract(&contract)
+                .expect("Error creating contract");
+        }
+
+        assert!(SqliteStorageProvider::new(path).is_err());
+        assert!(SqliteStorageProvider::new_encrypted(path, EncryptionKey::new([2; 32])).is_err());
+
+        {
+            let storage = SqliteStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                .expect("Error opening SQLite DB");
+            assert_eq!(1, storage.get_contract_offers().unwrap().len());
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rotate_encryption_key_reencrypts_data() {
+        let path = std::env::temp_dir().join("dlc_sqlite_rotate_encryption_key_reencrypts_data.db");
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+
+        let contract: OfferedContract = deserialize_object(include_bytes!(
+            "../../dlc-sled-storage-provider/test_files/Offered"
+        ));
+        let check_data = |storage: &SqliteStorageProvider| {
+            assert_eq!(1, storage.get_contracts().unwrap().len());
+            assert_eq!(
+                Some(ChainMonitor::new(123)),
+                storage.get_chain_monitor().unwrap()
+            );
+        };
+        {
+            let storage = SqliteStorageProvider::new(path).expect("Error opening SQLite DB");
+            storage
+                .create_contract(&contract)
+                .expect("Error creating contract");
+            storage
+                .persist_chain_monitor(&ChainMonitor::new(123))
+                .unwrap();
+        }
+
+        {
+            let mut storage =
+                SqliteStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32]))
+                    .expect("Error opening SQLite DB");
+            assert!(storage.is_encrypted());
+            check_data(&storage);
+
+            storage
+                .rotate_encryption_key(Some(EncryptionKey::new([2; 32])))
+                .expect("Error rotating encryption key");
+            check_data(&storage);
+        }
+
+        assert!(SqliteStorageProvider::new_encrypted(path, EncryptionKey::new([1; 32])).is_err());
+
+        {
+            let mut storage =
+                SqliteStorageProvider::new_encrypted(path, EncryptionKey::new([2; 32]))
+                    .expect("Error opening SQLite DB");
+            check_data(&storage);
+
+            storage
+                .rotate_encryption_key(None)
+                .expect("Error removing encryption");
+            assert!(!storage.is_encrypted());
+        }
+
+        {
+            let storage = SqliteStorageProvider::new(path).expect("Error opening SQLite DB");
+            check_data(&storage);
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -21,7 +21,7 @@ extern crate secp256k1_zkp;
 extern crate serde;
 
 use bitcoin::{Script, Transaction};
-use dlc::{Error, RangePayout};
+use dlc::{AdaptorSigVerificationMode, Error, RangePayout};
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use secp256k1_zkp::{All, EcdsaAdaptorSignature, PublicKey, Secp256k1, SecretKey};
@@ -31,6 +31,7 @@ use serde::{Deserialize, Serialize};
 pub mod combination_iterator;
 pub mod digit_decomposition;
 pub mod digit_trie;
+pub mod multi_base_oracle_trie;
 pub mod multi_oracle;
 pub mod multi_oracle_trie;
 pub mod multi_oracle_trie_with_diff;
@@ -86,6 +87,11 @@ pub struct OracleNumericInfo {
     pub base: usize,
     /// The number of digits that each oracle will use to represent the outcome value.
     pub nb_digits: Vec<usize>,
+    /// The base in which each oracle will represent the outcome value, if
+    /// oracles don't all use the same one. `base` is then the one of the first
+    /// oracle.
+    #[cfg_attr(feature = "use-serde", serde(default))]
+    pub oracle_bases: Option<Vec<usize>>,
 }
 
 impl OracleNumericInfo {
@@ -94,6 +100,39 @@ impl OracleNumericInfo {
         *self.nb_digits.iter().min().unwrap()
     }
 
+    /// Returns the base used by the oracle at the given index.
+    pub fn get_base(&self, oracle_index: usize) -> usize {
+        match &self.oracle_bases {
+            Some(bases) => bases[oracle_index],
+            None => self.base,
+        }
+    }
+
+    /// Returns whether oracles use different bases.
+    pub fn has_diff_bases(&self) -> bool {
+        match &self.oracle_bases {
+            Some(bases) => bases.iter().any(|x| *x != self.base),
+            None => false,
+        }
+    }
+
+    /// Returns the largest value that the oracle at the given index can attest
+    /// to, or `None` if it cannot be represented.
+    pub fn get_oracle_max_value(&self, oracle_index: usize) -> Option<usize> {
+        self.get_base(oracle_index)
+            .checked_pow(self.nb_digits[oracle_index] as u32)
+            .map(|x| x - 1)
+    }
+
+    /// Returns the largest value that all oracles can attest to, or `None` if
+    /// it cannot be represented. Outcomes larger than this value are considered
+    /// equal to it.
+    pub fn get_max_value(&self) -> Option<usize> {
+        (0..self.nb_digits.len())
+            .filter_map(|i| self.get_oracle_max_value(i))
+            .min()
+    }
+
     /// Returns whether oracles have varying number of digits.
     pub fn has_diff_nb_digits(&self) -> bool {
         self.nb_digits
@@ -103,6 +142,40 @@ impl OracleNumericInfo {
     }
 }
 
+/// Provides the adaptor points used to create and verify the adaptor signatures
+/// of a [`DlcTrie`].
+pub trait AdaptorPointProvider: Sync {
+    /// Returns the adaptor point for the oracles at the given indexes attesting
+    /// to outcomes starting with the digits of the corresponding paths.
+    fn get_adaptor_point(
+        &self,
+        indexes: &[usize],
+        paths: &[Vec<usize>],
+    ) -> Result<PublicKey, Error>;
+}
+
+/// Computes adaptor points from the signature points of each digit value of
+/// each oracle.
+impl AdaptorPointProvider for [Vec<Vec<PublicKey>>] {
+    fn get_adaptor_point(
+        &self,
+        indexes: &[usize],
+        paths: &[Vec<usize>],
+    ) -> Result<PublicKey, Error> {
+        utils::get_adaptor_point_for_indexed_paths(indexes, paths, self)
+    }
+}
+
+impl AdaptorPointProvider for Vec<Vec<Vec<PublicKey>>> {
+    fn get_adaptor_point(
+        &self,
+        indexes: &[usize],
+        paths: &[Vec<usize>],
+    ) -> Result<PublicKey, Error> {
+        self.as_slice().get_adaptor_point(indexes, paths)
+    }
+}
+
 /// A common trait for trie data structures that store DLC adaptor signature
 /// information.
 pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
@@ -119,7 +192,7 @@ pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
     fn iter(&'a self) -> TrieIterator;
 
     /// Generate the trie while verifying the provided adaptor signatures.
-    fn generate_verify(
+    fn generate_verify<P: AdaptorPointProvider + ?Sized>(
         &'a mut self,
         secp: &Secp256k1<secp256k1_zkp::All>,
         fund_pubkey: &PublicKey,
@@ -127,7 +200,7 @@ pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
         fund_output_value: u64,
         outcomes: &[RangePayout],
         cets: &[Transaction],
-        precomputed_points: &[Vec<Vec<PublicKey>>],
+        adaptor_points: &P,
         adaptor_sigs: &[EcdsaAdaptorSignature],
         adaptor_index_start: usize,
     ) -> Result<usize, Error> {
@@ -139,13 +212,13 @@ pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
             fund_pubkey,
             funding_script_pubkey,
             fund_output_value,
-            precomputed_points,
+            adaptor_points,
             trie_info.into_iter(),
         )
     }
 
     /// Generate the trie while creating the set of adaptor signatures.
-    fn generate_sign(
+    fn generate_sign<P: AdaptorPointProvider + ?Sized>(
         &'a mut self,
         secp: &Secp256k1<All>,
         fund_privkey: &SecretKey,
@@ -153,7 +226,7 @@ pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
         fund_output_value: u64,
         outcomes: &[RangePayout],
         cets: &[Transaction],
-        precomputed_points: &[Vec<Vec<PublicKey>>],
+        adaptor_points: &P,
         adaptor_index_start: usize,
     ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
         let trie_info = self.generate(adaptor_index_start, outcomes)?;
@@ -163,14 +236,14 @@ pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
             fund_privkey,
             funding_script_pubkey,
             fund_output_value,
-            precomputed_points,
+            adaptor_points,
             trie_info.into_iter(),
         )
     }
 
     /// Verify that the provided signatures are valid with respect to the
     /// information stored in the trie.
-    fn verify(
+    fn verify<P: AdaptorPointProvider + ?Sized>(
         &'a self,
         secp: &Secp256k1<All>,
         fund_pubkey: &PublicKey,
@@ -178,7 +251,7 @@ pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
         fund_output_value: u64,
         adaptor_sigs: &[EcdsaAdaptorSignature],
         cets: &[Transaction],
-        precomputed_points: &[Vec<Vec<PublicKey>>],
+        adaptor_points: &P,
     ) -> Result<usize, Error> {
         verify_helper(
             secp,
@@ -187,20 +260,20 @@ pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
             fund_pubkey,
             funding_script_pubkey,
             fund_output_value,
-            precomputed_points,
+            adaptor_points,
             self.iter(),
         )
     }
 
     /// Produce the set of adaptor signatures for the trie.
-    fn sign(
+    fn sign<P: AdaptorPointProvider + ?Sized>(
         &'a self,
         secp: &Secp256k1<All>,
         fund_privkey: &SecretKey,
         funding_script_pubkey: &Script,
         fund_output_value: u64,
         cets: &[Transaction],
-        precomputed_points: &[Vec<Vec<PublicKey>>],
+        adaptor_points: &P,
     ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
         let trie_info = self.iter();
         sign_helper(
@@ -209,7 +282,7 @@ pub trait DlcTrie<'a, TrieIterator: Iterator<Item = TrieIterInfo>> {
             fund_privkey,
             funding_script_pubkey,
             fund_output_value,
-            precomputed_points,
+            adaptor_points,
             trie_info,
         )
     }
@@ -224,22 +297,18 @@ pub struct TrieIterInfo {
 }
 
 #[cfg(not(feature = "parallel"))]
-fn sign_helper<T: Iterator<Item = TrieIterInfo>>(
+fn sign_helper<T: Iterator<Item = TrieIterInfo>, P: AdaptorPointProvider + ?Sized>(
     secp: &Secp256k1<All>,
     cets: &[Transaction],
     fund_privkey: &SecretKey,
     funding_script_pubkey: &Script,
     fund_output_value: u64,
-    precomputed_points: &[Vec<Vec<PublicKey>>],
+    adaptor_points: &P,
     trie_info: T,
 ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
     let mut unsorted = trie_info
         .map(|x| {
-            let adaptor_point = utils::get_adaptor_point_for_indexed_paths(
-                &x.indexes,
-                &x.paths,
-                precomputed_points,
-            )?;
+            let adaptor_point = adaptor_points.get_adaptor_point(&x.indexes, &x.paths)?;
             let adaptor_sig = dlc::create_cet_adaptor_sig_from_point(
                 secp,
                 &cets[x.value.cet_index],
@@ -256,24 +325,20 @@ fn sign_helper<T: Iterator<Item = TrieIterInfo>>(
 }
 
 #[cfg(feature = "parallel")]
-fn sign_helper<T: Iterator<Item = TrieIterInfo>>(
+fn sign_helper<T: Iterator<Item = TrieIterInfo>, P: AdaptorPointProvider + ?Sized>(
     secp: &Secp256k1<All>,
     cets: &[Transaction],
     fund_privkey: &SecretKey,
     funding_script_pubkey: &Script,
     fund_output_value: u64,
-    precomputed_points: &[Vec<Vec<PublicKey>>],
+    adaptor_points: &P,
     trie_info: T,
 ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
     let trie_info: Vec<TrieIterInfo> = trie_info.collect();
     let mut unsorted = trie_info
         .par_iter()
         .map(|x| {
-            let adaptor_point = utils::get_adaptor_point_for_indexed_paths(
-                &x.indexes,
-                &x.paths,
-                precomputed_points,
-            )?;
+            let adaptor_point = adaptor_points.get_adaptor_point(&x.indexes, &x.paths)?;
             let adaptor_sig = dlc::create_cet_adaptor_sig_from_point(
                 secp,
                 &cets[x.value.cet_index],
@@ -289,70 +354,57 @@ fn sign_helper<T: Iterator<Item = TrieIterInfo>>(
     Ok(unsorted.into_iter().map(|(_, y)| y).collect())
 }
 
-#[cfg(not(feature = "parallel"))]
-fn verify_helper<T: Iterator<Item = TrieIterInfo>>(
+fn verify_helper<T: Iterator<Item = TrieIterInfo>, P: AdaptorPointProvider + ?Sized>(
     secp: &Secp256k1<All>,
     cets: &[Transaction],
     adaptor_sigs: &[EcdsaAdaptorSignature],
     fund_pubkey: &PublicKey,
     funding_script_pubkey: &Script,
     fund_output_value: u64,
-    precomputed_points: &[Vec<Vec<PublicKey>>],
-    trie_info: T,
-) -> Result<usize, Error> {
-    let mut max_adaptor_index = 0;
-    for x in trie_info {
-        let adaptor_point =
-            utils::get_adaptor_point_for_indexed_paths(&x.indexes, &x.paths, precomputed_points)?;
-        let adaptor_sig = adaptor_sigs[x.value.adaptor_index];
-        let cet = &cets[x.value.cet_index];
-        if x.value.adaptor_index > max_adaptor_index {
-            max_adaptor_index = x.value.adaptor_index;
-        }
-        dlc::verify_cet_adaptor_sig_from_point(
-            secp,
-            &adaptor_sig,
-            cet,
-            &adaptor_point,
-            fund_pubkey,
-            funding_script_pubkey,
-            fund_output_value,
-        )?;
-    }
-    Ok(max_adaptor_index + 1)
-}
-
-#[cfg(feature = "parallel")]
-fn verify_helper<T: Iterator<Item = TrieIterInfo>>(
-    secp: &Secp256k1<All>,
-    cets: &[Transaction],
-    adaptor_sigs: &[EcdsaAdaptorSignature],
-    fund_pubkey: &PublicKey,
-    funding_script_pubkey: &Script,
-    fund_output_value: u64,
-    precomputed_points: &[Vec<Vec<PublicKey>>],
+    adaptor_points: &P,
     trie_info: T,
 ) -> Result<usize, Error> {
     let trie_info: Vec<TrieIterInfo> = trie_info.collect();
     let max_adaptor_index = trie_info
         .iter()
-        .max_by(|x, y| x.value.adaptor_index.cmp(&y.value.adaptor_index))
-        .unwrap();
-    trie_info.par_iter().try_for_each(|x| {
-        let adaptor_point =
-            utils::get_adaptor_point_for_indexed_paths(&x.indexes, &x.paths, precomputed_points)?;
-        let adaptor_sig = adaptor_sigs[x.value.adaptor_index];
-        let cet = &cets[x.value.cet_index];
-        dlc::verify_cet_adaptor_sig_from_point(
-            secp,
-            &adaptor_sig,
-            cet,
-            &adaptor_point,
-            fund_pubkey,
-            funding_script_pubkey,
-            fund_output_value,
-        )
+        .map(|x| x.value.adaptor_index)
+        .max()
+        .unwrap_or(0);
+
+    #[cfg(not(feature = "parallel"))]
+    let iter = trie_info.iter();
+    #[cfg(feature = "parallel")]
+    let iter = trie_info.par_iter();
+    let inputs = iter
+        .map(|x| {
+            let adaptor_point = adaptor_points.get_adaptor_point(&x.indexes, &x.paths)?;
+            let adaptor_sig = adaptor_sigs
+                .get(x.value.adaptor_index)
+                .ok_or_else(|| Error::InvalidArgument("Missing adaptor signature".to_string()))?;
+            Ok((x.value.cet_index, adaptor_point, *adaptor_sig))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // Report the indexes of the invalid signatures within `adaptor_sigs`
+    // rather than within `inputs`.
+    dlc::verify_cet_adaptor_sigs_from_points(
+        secp,
+        cets,
+        &inputs,
+        fund_pubkey,
+        funding_script_pubkey,
+        fund_output_value,
+        AdaptorSigVerificationMode::EarlyAbort,
+    )
+    .map_err(|e| match e {
+        Error::InvalidAdaptorSignatures(indexes) => Error::InvalidAdaptorSignatures(
+            indexes
+                .into_iter()
+                .map(|i| trie_info[i].value.adaptor_index)
+                .collect(),
+        ),
+        e => e,
     })?;
 
-    Ok(max_adaptor_index.value.adaptor_index + 1)
+    Ok(max_adaptor_index + 1)
 }
@@ -0,0 +1,399 @@
+//! # MultiBaseOracleTrie
+//! Data structure and functions used to store adaptor signature information
+//! for numerical outcome DLC with t of n oracles representing outcome values
+//! using different bases, where at least t oracles need to attest to the same
+//! outcome, or to outcomes within the allowed difference, for the contract to be
+//! able to close.
+
+use crate::combination_iterator::CombinationIterator;
+use crate::multi_oracle::{
+    compute_multi_base_outcome_combinations, compute_multi_base_outcome_combinations_with_diff,
+};
+use crate::{DlcTrie, IndexedPath, OracleNumericInfo, RangeInfo, TrieIterInfo};
+use dlc::{Error, RangePayout};
+
+/// Information about an adaptor signature stored in a [`MultiBaseOracleTrie`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiBaseTrieEntry {
+    /// The indexes of the oracles whose outcomes are used for the adaptor
+    /// signature.
+    pub indexes: Vec<usize>,
+    /// The outcome prefix of each oracle, in the same order as `indexes`.
+    pub paths: Vec<Vec<usize>>,
+    /// The indexes of the CET and of the adaptor signature.
+    pub range_info: RangeInfo,
+}
+
+/// Data structure used to store adaptor signature information for numerical
+/// outcome DLC with t of n oracles using different bases. As the prefixes of
+/// the outcomes of such oracles cannot be shared, the prefixes of each oracle
+/// are stored for every adaptor signature. When differences are allowed, the
+/// first oracle of each combination determines the outcome of the contract.
+#[derive(Clone)]
+pub struct MultiBaseOracleTrie {
+    threshold: usize,
+    oracle_numeric_infos: OracleNumericInfo,
+    difference_exps: Option<(usize, usize)>,
+    entries: Vec<MultiBaseTrieEntry>,
+}
+
+/// Container for a dump of a MultiBaseOracleTrie used for serialization purpose.
+pub struct MultiBaseOracleTrieDump {
+    /// The required number of oracles for this trie.
+    pub threshold: usize,
+    /// Information about each oracle numerical representation.
+    pub oracle_numeric_infos: OracleNumericInfo,
+    /// The adaptor signature information stored in the trie.
+    pub entries: Vec<MultiBaseTrieEntry>,
+    /// The exponents of the minimum support and of the maximum error of the
+    /// differences allowed between the outcomes of the oracles, if any.
+    pub difference_exps: Option<(usize, usize)>,
+}
+
+impl MultiBaseOracleTrie {
+    /// Creates a new MultiBaseOracleTrie
+    pub fn new(oracle_numeric_infos: &OracleNumericInfo, threshold: usize) -> Result<Self, Error> {
+        Self::new_inner(oracle_numeric_infos, threshold, None)
+    }
+
+    /// Creates a new MultiBaseOracleTrie allowing the outcomes of the oracles
+    /// to differ. Outcomes differing by at most `2^min_support_exp` are always
+    /// supported, and outcomes differing by `2^max_error_exp` or more never
+    /// are.
+    pub fn new_with_diff(
+        oracle_numeric_infos: &OracleNumericInfo,
+        threshold: usize,
+        min_support_exp: usize,
+        max_error_exp: usize,
+    ) -> Result<Self, Error> {
+        let max_exp = std::mem::size_of::<usize>() * 8 - 1;
+        if min_support_exp >= max_error_exp || max_error_exp > max_exp {
+            return Err(Error::InvalidArgument(
+                "Invalid difference parameters".to_string(),
+            ));
+        }
+        Self::new_inner(
+            oracle_numeric_infos,
+            threshold,
+            Some((min_support_exp, max_error_exp)),
+        )
+    }
+
+    fn new_inner(
+        oracle_numeric_infos: &OracleNumericInfo,
+        threshold: usize,
+        difference_exps: Option<(usize, usize)>,
+    ) -> Result<Self, Error> {
+        let nb_oracles = oracle_numeric_infos.nb_digits.len();
+        if nb_oracles == 0 || threshold == 0 || threshold > nb_oracles {
+            return Err(Error::InvalidArgument("Invalid nb oracles".to_string()));
+        }
+        if oracle_numeric_infos
+            .oracle_bases
+            .as_ref()
+            .map_or(false, |x| x.len() != nb_oracles)
+        {
+            return Err(Error::InvalidArgument(
+                "Expected a base for each oracle".to_string(),
+            ));
+        }
+        if oracle_numeric_infos.get_max_value().is_none() {
+            return Err(Error::InvalidArgument(
+                "Could not compute max value".to_string(),
+            ));
+        }
+        Ok(MultiBaseOracleTrie {
+            threshold,
+            oracle_numeric_infos: oracle_numeric_infos.clone(),
+            difference_exps,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Dump the trie information.
+    pub fn dump(&self) -> MultiBaseOracleTrieDump {
+        MultiBaseOracleTrieDump {
+            threshold: self.threshold,
+            oracle_numeric_infos: self.oracle_numeric_infos.clone(),
+            entries: self.entries.clone(),
+            difference_exps: self.difference_exps,
+        }
+    }
+
+    /// Recover a MultiBaseOracleTrie from a dump.
+    pub fn from_dump(dump: MultiBaseOracleTrieDump) -> MultiBaseOracleTrie {
+        let MultiBaseOracleTrieDump {
+            threshold,
+            oracle_numeric_infos,
+            entries,
+            difference_exps,
+        } = dump;
+        MultiBaseOracleTrie {
+            threshold,
+            oracle_numeric_infos,
+            difference_exps,
+            entries,
+        }
+    }
+
+    /// Lookup for the adaptor signature that can be decrypted using the given
+    /// outcomes of each oracle, if at least `threshold` of them attested to the
+    /// same value once converted from their respective bases, or to values
+    /// within the allowed difference.
+    pub fn look_up(&self, paths: &[(usize, Vec<usize>)]) -> Option<(RangeInfo, Vec<IndexedPath>)> {
+        let mut valid_paths: Vec<&(usize, Vec<usize>)> = paths
+            .iter()
+            .filter(|(index, outcome)| {
+                *index < self.oracle_numeric_infos.nb_digits.len()
+                    && outcome.len() == self.oracle_numeric_infos.nb_digits[*index]
+                    && outcome
+                        .iter()
+                        .all(|x| *x < self.oracle_numeric_infos.get_base(*index))
+            })
+            .collect();
+        valid_paths.sort_by_key(|x| x.0);
+        valid_paths.dedup_by_key(|x| x.0);
+        if valid_paths.len() < self.threshold {
+            return None;
+        }
+
+        for selector in CombinationIterator::new(valid_paths.len(), self.threshold) {
+            let oracles: Vec<&(usize, Vec<usize>)> =
+                selector.iter().map(|i| valid_paths[*i]).collect();
+            let indexes: Vec<usize> = oracles.iter().map(|x| x.0).collect();
+            let entry = self.entries.iter().find(|x| {
+                x.indexes == indexes
+                    && x.paths
+                        .iter()
+                        .zip(oracles.iter())
+                        .all(|(prefix, (_, outcome))| outcome.starts_with(prefix))
+            });
+            if let Some(entry) = entry {
+                return Some((
+                    entry.range_info.clone(),
+                    indexes.into_iter().zip(entry.paths.clone()).collect(),
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> DlcTrie<'a, MultiBaseOracleTrieIter<'a>> for MultiBaseOracleTrie {
+    fn generate(
+        &mut self,
+        adaptor_index_start: usize,
+        outcomes: &[RangePayout],
+    ) -> Result<Vec<TrieIterInfo>, Error> {
+        let max_value = self
+            .oracle_numeric_infos
+            .get_max_value()
+            .ok_or_else(|| Error::InvalidArgument("Could not compute max value".to_string()))?;
+        let nb_oracles = self.oracle_numeric_infos.nb_digits.len();
+        let mut adaptor_index = adaptor_index_start;
+        let mut trie_infos = Vec::new();
+        for (cet_index, outcome) in outcomes.iter().enumerate() {
+            let end = outcome.start + outcome.count - 1;
+            for indexes in CombinationIterator::new(nb_oracles, self.threshold) {
+                let oracle_infos: Vec<(usize, usize)> = indexes
+                    .iter()
+                    .map(|i| {
+                        (
+                            self.oracle_numeric_infos.get_base(*i),
+                            self.oracle_numeric_infos.nb_digits[*i],
+                        )
+                    })
+                    .collect();
+                let combinations = match self.difference_exps {
+                    Some((min_support_exp, max_error_exp)) => {
+                        compute_multi_base_outcome_combinations_with_diff(
+                            &oracle_infos,
+                            outcome.start,
+                            end,
+                            max_value,
+                            min_support_exp,
+                            max_error_exp,
+                            true,
+                        )
+                    }
+                    None => compute_multi_base_outcome_combinations(
+                        &oracle_infos,
+                        outcome.start,
+                        end,
+                        max_value,
+                    ),
+                };
+                for paths in combinations {
+                    let range_info = RangeInfo {
+                        cet_index,
+                        adaptor_index,
+                    };
+                    adaptor_index += 1;
+                    trie_infos.push(TrieIterInfo {
+                        indexes: indexes.clone(),
+                        paths: paths.clone(),
+                        value: range_info.clone(),
+                    });
+                    self.entries.push(MultiBaseTrieEntry {
+                        indexes: indexes.clone(),
+                        paths,
+                        range_info,
+                    });
+                }
+            }
+        }
+
+        Ok(trie_infos)
+    }
+
+    fn iter(&'a self) -> MultiBaseOracleTrieIter<'a> {
+        MultiBaseOracleTrieIter {
+            entries: self.entries.iter(),
+        }
+    }
+}
+
+/// Iterator for a MultiBaseOracleTrie.
+pub struct MultiBaseOracleTrieIter<'a> {
+    entries: std::slice::Iter<'a, MultiBaseTrieEntry>,
+}
+
+impl<'a> Iterator for MultiBaseOracleTrieIter<'a> {
+    type Item = TrieIterInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|x| TrieIterInfo {
+            indexes: x.indexes.clone(),
+            paths: x.paths.clone(),
+            value: x.range_info.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dlc::{Payout, RangePayout};
+
+    use super::{MultiBaseOracleTrie, MultiBaseTrieEntry};
+    use crate::digit_decomposition::decompose_value;
+    use crate::{DlcTrie, OracleNumericInfo};
+
+    fn get_oracle_numeric_infos() -> OracleNumericInfo {
+        // Both oracles can represent values up to 999.
+        OracleNumericInfo {
+            base: 10,
+            nb_digits: vec![3, 10],
+            oracle_bases: Some(vec![10, 2]),
+        }
+    }
+
+    fn get_range_payouts() -> Vec<RangePayout> {
+        [(0, 200), (200, 600), (800, 200)]
+            .iter()
+            .map(|(start, count)| RangePayout {
+                start: *start,
+                count: *count,
+                payout: Payout {
+                    offer: *start as u64,
+                    accept: 1000 - *start as u64,
+                },
+            })
+            .collect()
+    }
+
+    fn get_outcomes(value: usize, binary_value: usize) -> Vec<(usize, Vec<usize>)> {
+        vec![
+            (0, decompose_value(value, 10, 3)),
+            (1, decompose_value(binary_value, 2, 10)),
+        ]
+    }
+
+    #[test]
+    fn all_outcomes_are_covered_once() {
+        let oracle_numeric_infos = get_oracle_numeric_infos();
+        let mut trie = MultiBaseOracleTrie::new(&oracle_numeric_infos, 2).unwrap();
+        let range_payouts = get_range_payouts();
+        let trie_infos = trie.generate(0, &range_payouts).unwrap();
+        assert_eq!(trie_infos.len(), trie.iter().count());
+
+        for value in 0..1024 {
+            let cet_index = range_payouts
+                .iter()
+                .position(|x| x.start + x.count > value.min(999))
+                .unwrap();
+            let (range_info, paths) = trie
+                .look_up(&get_outcomes(value.min(999), value))
+                .expect("to find the outcome");
+            assert_eq!(cet_index, range_info.cet_index);
+            let matching: Vec<&MultiBaseTrieEntry> = trie
+                .entries
+                .iter()
+                .filter(|x| {
+                    x.paths
+                        .iter()
+                        .zip(get_outcomes(value.min(999), value))
+                        .all(|(prefix, (_, outcome))| outcome.starts_with(prefix))
+                })
+                .collect();
+            assert_eq!(1, matching.len());
+            assert_eq!(
+                matching[0].paths,
+                paths.into_iter().map(|x| x.1).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn disagreeing_outcomes_are_not_found() {
+        let mut trie = MultiBaseOracleTrie::new(&get_oracle_numeric_infos(), 2).unwrap();
+        trie.generate(0, &get_range_payouts()).unwrap();
+        assert!(trie.look_up(&get_outcomes(150, 151)).is_none());
+        assert!(trie.look_up(&get_outcomes(998, 1000)).is_none());
+    }
+
+    #[test]
+    fn outcomes_within_allowed_difference_are_found() {
+        let mut trie =
+            MultiBaseOracleTrie::new_with_diff(&get_oracle_numeric_infos(), 2, 2, 4).unwrap();
+        let range_payouts = get_range_payouts();
+        trie.generate(0, &range_payouts).unwrap();
+
+        for value in (0..1000).step_by(7) {
+            let cet_index = range_payouts
+                .iter()
+                .position(|x| x.start + x.count > value)
+                .unwrap();
+            for binary_value in value.saturating_sub(20)..usize::min(value + 20, 1024) {
+                let diff = (value as i64 - usize::min(binary_value, 999) as i64).abs();
+                let res = trie.look_up(&get_outcomes(value, binary_value));
+                if diff <= 4 {
+                    let (range_info, _) = res.expect("to find the outcome");
+                    assert_eq!(cet_index, range_info.cet_index);
+                } else if diff >= 16 {
+                    assert!(res.is_none());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_oracle_outcomes_are_found_with_threshold() {
+        let oracle_numeric_infos = OracleNumericInfo {
+            base: 10,
+            nb_digits: vec![3, 10, 10],
+            oracle_bases: Some(vec![10, 2, 2]),
+        };
+        let mut trie = MultiBaseOracleTrie::new(&oracle_numeric_infos, 2).unwrap();
+        trie.generate(0, &get_range_payouts()).unwrap();
+        let (range_info, paths) = trie
+            .look_up(&[
+                (0, decompose_value(500, 10, 3)),
+                (1, decompose_value(12, 2, 10)),
+                (2, decompose_value(500, 2, 10)),
+            ])
+            .expect("to find the outcome");
+        assert_eq!(1, range_info.cet_index);
+        assert_eq!(vec![0, 2], paths.iter().map(|x| x.0).collect::<Vec<_>>());
+    }
+}
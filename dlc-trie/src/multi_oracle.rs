@@ -1,7 +1,7 @@
 //! Utility functions to compute outcome combinations to work with
 //! multi oracle DLC.
 
-use digit_decomposition::{compose_value, decompose_value};
+use digit_decomposition::{compose_value, decompose_value, group_by_ignoring_digits};
 
 use crate::utils::pre_pad_vec;
 
@@ -398,6 +398,205 @@ pub fn compute_outcome_combinations(
     res
 }
 
+/// Compute the prefix combinations required for oracles representing outcome
+/// values with the given bases and number of digits (in this order) to be able
+/// to sign any outcome within the interval [start, end]. Each combination
+/// contains a prefix for each oracle, the intervals covered by the prefixes
+/// of a combination overlapping, so that the oracles can be converted to a
+/// common representation. Outcomes larger than `max_value` are considered equal
+/// to it, so if the interval contains `max_value` the combinations also cover
+/// the outcomes of each oracle that are larger than it.
+pub fn compute_multi_base_outcome_combinations(
+    oracle_infos: &[(usize, usize)],
+    start: usize,
+    end: usize,
+    max_value: usize,
+) -> Vec<Vec<Vec<usize>>> {
+    let mut res = Vec::new();
+    if start > max_value {
+        return res;
+    }
+
+    // Outcomes smaller than `max_value` have to be attested by all oracles,
+    // so we only need the prefixes of each oracle covering the intersection
+    // of the intervals of the prefixes of the other oracles.
+    let main_end = if end >= max_value {
+        max_value.checked_sub(1)
+    } else {
+        Some(end)
+    };
+    if let Some(main_end) = main_end.filter(|x| *x >= start) {
+        let groups: Vec<Vec<(Vec<usize>, usize)>> = oracle_infos
+            .iter()
+            .map(|(base, nb_digits)| {
+                group_by_ignoring_digits(start, main_end, *base, *nb_digits)
+                    .into_iter()
+                    .map(|prefix| {
+                        let (_, prefix_end) =
+                            compute_interval_from_prefix(&prefix, *nb_digits, *base);
+                        (prefix, prefix_end)
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut positions = vec![0; groups.len()];
+        loop {
+            res.push(
+                groups
+                    .iter()
+                    .zip(positions.iter())
+                    .map(|(group, i)| group[*i].0.clone())
+                    .collect(),
+            );
+            let intersection_end = groups
+                .iter()
+                .zip(positions.iter())
+                .map(|(group, i)| group[*i].1)
+                .min()
+                .expect("to have at least one oracle");
+            if intersection_end >= main_end {
+                break;
+            }
+            for (group, i) in groups.iter().zip(positions.iter_mut()) {
+                if group[*i].1 == intersection_end {
+                    *i += 1;
+                }
+            }
+        }
+    }
+
+    // Any outcome of an oracle larger or equal to `max_value` can be combined
+    // with any outcome larger or equal to it of the others.
+    if end >= max_value {
+        let mut combinations: Vec<Vec<Vec<usize>>> = vec![Vec::new()];
+        for (base, nb_digits) in oracle_infos {
+            let oracle_max_value = base.pow(*nb_digits as u32) - 1;
+            let prefixes = group_by_ignoring_digits(max_value, oracle_max_value, *base, *nb_digits);
+            combinations = extend_combinations(combinations, &prefixes);
+        }
+        res.append(&mut combinations);
+    }
+
+    res
+}
+
+/// Compute the prefix combinations required for oracles representing outcome
+/// values with the given bases and number of digits (in this order) to be able
+/// to sign any outcome within the interval [start, end], allowing the outcomes
+/// of the other oracles to differ from the one of the first oracle, which is
+/// the one used to determine the outcome of the contract. Outcomes differing by
+/// at most `2^min_support_exp` from the one of the first oracle are always
+/// covered, and outcomes differing by `2^max_error_exp` or more never are. If
+/// `maximize_coverage` is true, outcomes in between are covered as much as
+/// possible. Outcomes larger than `max_value` are considered equal to it.
+pub fn compute_multi_base_outcome_combinations_with_diff(
+    oracle_infos: &[(usize, usize)],
+    start: usize,
+    end: usize,
+    max_value: usize,
+    min_support_exp: usize,
+    max_error_exp: usize,
+    maximize_coverage: bool,
+) -> Vec<Vec<Vec<usize>>> {
+    let mut res = Vec::new();
+    if start > max_value {
+        return res;
+    }
+
+    let (main_base, main_nb_digits) = oracle_infos[0];
+    let max_error = 1 << max_error_exp;
+    let min_support = 1 << min_support_exp;
+
+    // The outcomes of the other oracles are covered around the interval of
+    // each prefix of the first oracle, so the size of these intervals has to
+    // be small enough for the minimum support to be covered without reaching
+    // the maximum error.
+    let mut main_intervals = Vec::new();
+    let main_end = if end >= max_value {
+        max_value.checked_sub(1)
+    } else {
+        Some(end)
+    };
+    if let Some(main_end) = main_end.filter(|x| *x >= start) {
+        for prefix in group_by_ignoring_digits(start, main_end, main_base, main_nb_digits) {
+            for prefix in split_prefix(prefix, main_base, main_nb_digits, max_error - min_support) {
+                let (interval_start, interval_end) =
+                    compute_interval_from_prefix(&prefix, main_nb_digits, main_base);
+                main_intervals.push((prefix, interval_start, interval_end));
+            }
+        }
+    }
+    if end >= max_value {
+        let main_max_value = main_base.pow(main_nb_digits as u32) - 1;
+        for prefix in group_by_ignoring_digits(max_value, main_max_value, main_base, main_nb_digits)
+        {
+            main_intervals.push((prefix, max_value, max_value));
+        }
+    }
+
+    for (main_prefix, interval_start, interval_end) in main_intervals {
+        let diff = if maximize_coverage {
+            max_error - 1 - (interval_end - interval_start)
+        } else {
+            min_support
+        };
+        let covered_start = interval_start.saturating_sub(diff);
+        let covered_end = interval_end.saturating_add(diff);
+        let mut combinations = vec![vec![main_prefix]];
+        for (base, nb_digits) in oracle_infos.iter().skip(1) {
+            let oracle_end = if covered_end >= max_value {
+                base.pow(*nb_digits as u32) - 1
+            } else {
+                covered_end
+            };
+            let prefixes = group_by_ignoring_digits(covered_start, oracle_end, *base, *nb_digits);
+            combinations = extend_combinations(combinations, &prefixes);
+        }
+        res.append(&mut combinations);
+    }
+
+    res
+}
+
+/// Splits the given prefix into prefixes covering the same outcomes, each of
+/// them covering at most `max_size` outcomes.
+fn split_prefix(
+    prefix: Vec<usize>,
+    base: usize,
+    nb_digits: usize,
+    max_size: usize,
+) -> Vec<Vec<usize>> {
+    let size = base.checked_pow((nb_digits - prefix.len()) as u32);
+    if size.map_or(false, |x| x <= max_size) {
+        return vec![prefix];
+    }
+    (0..base)
+        .flat_map(|digit| {
+            let mut prefix = prefix.clone();
+            prefix.push(digit);
+            split_prefix(prefix, base, nb_digits, max_size)
+        })
+        .collect()
+}
+
+/// Returns the combinations obtained by appending each of the given prefixes
+/// to each of the given combinations.
+fn extend_combinations(
+    combinations: Vec<Vec<Vec<usize>>>,
+    prefixes: &[Vec<usize>],
+) -> Vec<Vec<Vec<usize>>> {
+    combinations
+        .into_iter()
+        .flat_map(|combination| {
+            prefixes.iter().map(move |prefix| {
+                let mut combination = combination.clone();
+                combination.push(prefix.clone());
+                combination
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -837,4 +1036,92 @@ mod tests {
             assert_eq!(case.expected_min, min);
         }
     }
+    #[test]
+    fn multi_base_outcome_combinations_test() {
+        let oracle_infos = [(10, 1), (2, 4)];
+        assert_eq!(
+            vec![
+                vec![vec![3], vec![0, 0, 1, 1]],
+                vec![vec![4], vec![0, 1, 0]],
+                vec![vec![5], vec![0, 1, 0]],
+            ],
+            compute_multi_base_outcome_combinations(&oracle_infos, 3, 5, 9)
+        );
+
+        let combinations = compute_multi_base_outcome_combinations(&oracle_infos, 0, 9, 9);
+        assert_eq!(12, combinations.len());
+        // Outcomes of the second oracle larger than 9 are considered equal to 9.
+        for value in 0..16 {
+            let outcomes = [
+                decompose_value(usize::min(value, 9), 10, 1),
+                decompose_value(value, 2, 4),
+            ];
+            let nb_covering = combinations
+                .iter()
+                .filter(|x| {
+                    x.iter()
+                        .zip(outcomes.iter())
+                        .all(|(prefix, outcome)| outcome.starts_with(prefix))
+                })
+                .count();
+            assert_eq!(1, nb_covering);
+        }
+
+        assert!(compute_multi_base_outcome_combinations(&oracle_infos, 10, 12, 9).is_empty());
+    }
+
+    #[test]
+    fn multi_base_outcome_combinations_with_diff_test() {
+        let oracle_infos = [(10, 2), (2, 7)];
+        let min_support = 1 << 1;
+        let max_error = 1 << 3;
+        for maximize_coverage in [false, true].iter() {
+            let combinations = compute_multi_base_outcome_combinations_with_diff(
+                &oracle_infos,
+                0,
+                99,
+                99,
+                1,
+                3,
+                *maximize_coverage,
+            );
+            for main_value in 0..100 {
+                for value in 0..128 {
+                    let outcomes = [
+                        decompose_value(main_value, 10, 2),
+                        decompose_value(value, 2, 7),
+                    ];
+                    let nb_covering = combinations
+                        .iter()
+                        .filter(|x| {
+                            x.iter()
+                                .zip(outcomes.iter())
+                                .all(|(prefix, outcome)| outcome.starts_with(prefix))
+                        })
+                        .count();
+                    // Outcomes of the second oracle larger than 99 are
+                    // considered equal to 99.
+                    let diff = (main_value as i64 - usize::min(value, 99) as i64).abs();
+                    assert!(nb_covering <= 1);
+                    if diff <= min_support {
+                        assert_eq!(1, nb_covering);
+                    }
+                    if diff >= max_error {
+                        assert_eq!(0, nb_covering);
+                    }
+                }
+            }
+        }
+
+        assert!(compute_multi_base_outcome_combinations_with_diff(
+            &oracle_infos,
+            100,
+            120,
+            99,
+            1,
+            3,
+            true
+        )
+        .is_empty());
+    }
 }
@@ -170,6 +170,11 @@ impl MultiOracleTrie {
         if oracle_numeric_infos.nb_digits.is_empty() {
             return Err(Error::InvalidArgument("Oracle numeric infos nb digits is empty".to_string()));
         }
+        if oracle_numeric_infos.has_diff_bases() {
+            return Err(Error::InvalidArgument(
+                "Oracles using different bases require a MultiBaseOracleTrie".to_string(),
+            ));
+        }
         let digit_trie = DigitTrie::new(oracle_numeric_infos.base);
         let extra_cover_trie = if oracle_numeric_infos.has_diff_nb_digits() {
             // The support and coverage parameters don't matter as we only use this trie for coverage of
@@ -35,6 +35,13 @@ impl MultiOracleTrieWithDiff {
         if !is_valid {
             return Err(Error::InvalidArgument("Invalid nb oracles".to_string()));
         }
+        // The computation of the outcome combinations covering the allowed
+        // differences between oracles relies on binary decomposition.
+        if oracle_numeric_infos.base != 2 || oracle_numeric_infos.has_diff_bases() {
+            return Err(Error::InvalidArgument(
+                "Oracles must use base 2 when allowing outcome differences".to_string(),
+            ));
+        }
         let multi_trie = MultiTrie::new(
             oracle_numeric_infos,
             threshold,
@@ -8,6 +8,7 @@ pub(crate) fn same_num_digits_oracle_numeric_infos(
     OracleNumericInfo {
         nb_digits: std::iter::repeat(nb_digits).take(nb_oracles).collect(),
         base,
+        oracle_bases: None,
     }
 }
 
@@ -18,5 +19,6 @@ pub(crate) fn get_variable_oracle_numeric_infos(
     OracleNumericInfo {
         base,
         nb_digits: nb_digits.to_vec(),
+        oracle_bases: None,
     }
 }
@@ -23,6 +23,7 @@ mod benches {
 
     const SINGLE_NB_ORACLES: usize = 1;
     const SINGLE_NB_NONCES: usize = 10;
+    const MULTI_NB_CETS: usize = 100;
     const ALL_NB_ORACLES: usize = 1;
     const ALL_NB_NONCES: usize = 10;
     const ALL_BASE: usize = 2;
@@ -170,6 +171,61 @@ mod benches {
         })
     }
 
+    /// Create the adaptor signatures of a set of CETs directly from their aggregated
+    /// anticipation points (in parallel if the `parallel` feature is enabled).
+    #[bench]
+    fn bench_create_multiple_adaptor_sigs_from_aggregated_points(b: &mut Bencher) {
+        let seckey = SecretKey::new(&mut thread_rng());
+        let cet = cet();
+        let funding_script_pubkey = funding_script_pubkey();
+        let adaptor_points: Vec<PublicKey> = (0..MULTI_NB_CETS)
+            .map(|_| PublicKey::from_secret_key(SECP256K1, &SecretKey::new(&mut thread_rng())))
+            .collect();
+        let inputs: Vec<(&Transaction, &PublicKey)> =
+            adaptor_points.iter().map(|x| (&cet, x)).collect();
+
+        b.iter(|| {
+            black_box(
+                create_cet_adaptor_sigs_from_points(
+                    SECP256K1,
+                    &inputs,
+                    &seckey,
+                    &funding_script_pubkey,
+                    cet.output[0].value,
+                )
+                .unwrap(),
+            )
+        })
+    }
+
+    /// Create the adaptor signatures of a set of CETs including the aggregated
+    /// anticipation points (in parallel if the `parallel` feature is enabled).
+    #[bench]
+    fn bench_create_multiple_adaptor_sigs_including_aggregated_points(b: &mut Bencher) {
+        let oracle_infos = generate_oracle_infos(SINGLE_NB_ORACLES, SINGLE_NB_NONCES);
+        let seckey = SecretKey::new(&mut thread_rng());
+        let cets = vec![cet(); MULTI_NB_CETS];
+        let funding_script_pubkey = funding_script_pubkey();
+        let msgs: Vec<Vec<Vec<Message>>> = (0..MULTI_NB_CETS)
+            .map(|_| generate_single_outcome_messages(SINGLE_NB_ORACLES, SINGLE_NB_NONCES))
+            .collect();
+
+        b.iter(|| {
+            black_box(
+                create_cet_adaptor_sigs_from_oracle_info(
+                    SECP256K1,
+                    &cets,
+                    &oracle_infos,
+                    &seckey,
+                    &funding_script_pubkey,
+                    cets[0].output[0].value,
+                    &msgs,
+                )
+                .unwrap(),
+            )
+        })
+    }
+
     /// Create only the aggregated anticipation point.
     #[bench]
     fn bench_compute_aggregated_point(b: &mut Bencher) {
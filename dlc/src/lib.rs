@@ -15,6 +15,8 @@
 extern crate bitcoin;
 extern crate core;
 extern crate miniscript;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 extern crate secp256k1_sys;
 extern crate secp256k1_zkp;
 #[cfg(feature = "serde")]
@@ -29,6 +31,8 @@ use bitcoin::{
     },
     PackedLockTime, Sequence, Witness,
 };
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use secp256k1_zkp::schnorr::Signature as SchnorrSignature;
 use secp256k1_zkp::{
     ecdsa::Signature, EcdsaAdaptorSignature, Message, PublicKey, Secp256k1, SecretKey,
@@ -187,6 +191,9 @@ pub enum Error {
     InvalidArgument(String),
     /// An error occurred in miniscript
     Miniscript(miniscript::Error),
+    /// Some of the adaptor signatures given for verification are invalid, the
+    /// indexes of which are provided.
+    InvalidAdaptorSignatures(Vec<usize>),
 }
 
 impl From<secp256k1_zkp::Error> for Error {
@@ -220,6 +227,9 @@ impl fmt::Display for Error {
             Error::InvalidArgument(message) => write!(f, "Invalid argument {message}"),
             Error::Sighash(_) => write!(f, "Error while computing sighash"),
             Error::Miniscript(_) => write!(f, "Error within miniscript"),
+            Error::InvalidAdaptorSignatures(indexes) => {
+                write!(f, "Invalid adaptor signatures at indexes {indexes:?}")
+            }
         }
     }
 }
@@ -231,6 +241,7 @@ impl std::error::Error for Error {
             Error::Sighash(e) => Some(e),
             Error::InvalidArgument(_) => None,
             Error::Miniscript(e) => Some(e),
+            Error::InvalidAdaptorSignatures(_) => None,
         }
     }
 }
@@ -729,6 +740,63 @@ pub fn create_refund_transaction(
     }
 }
 
+/// The weight of a transaction with a single P2WPKH input and a single output,
+/// excluding the output script pubkey, computed as: (version(4) + input count(1)
+/// + output count(1) + locktime(4) + value(8) + script pubkey size(1)) * 4
+/// + segwit marker and flag(2) + input base weight + P2WPKH witness size.
+const CPFP_TX_BASE_WEIGHT: usize = 19 * 4 + 2 + TX_INPUT_BASE_WEIGHT + P2WPKH_WITNESS_SIZE;
+
+/// Create a transaction spending the output at index `vout` of the given
+/// unconfirmed `parent` transaction to `destination`, paying a fee such that
+/// the parent and the child together pay `fee_rate_per_vb`, so that miners are
+/// incentivized to include the parent (child pays for parent). The spent output
+/// is expected to be a P2WPKH one, and `parent_fee` to be the fee already paid
+/// by the parent. The input of the child signals replaceability so that it can
+/// be replaced if the fee needs to be bumped again.
+pub fn create_cpfp_transaction(
+    parent: &Transaction,
+    parent_fee: u64,
+    vout: u32,
+    destination: &Script,
+    fee_rate_per_vb: u64,
+) -> Result<Transaction, Error> {
+    let parent_output = parent.output.get(vout as usize).ok_or_else(|| {
+        Error::InvalidArgument(format!("Parent transaction has no output {}", vout))
+    })?;
+
+    let child_weight = CPFP_TX_BASE_WEIGHT + destination.len() * 4;
+    let package_fee = util::weight_to_fee(parent.weight() + child_weight, fee_rate_per_vb)?;
+    let child_fee = u64::max(
+        package_fee.saturating_sub(parent_fee),
+        util::tx_weight_to_fee(child_weight, fee_rate_per_vb)?,
+    );
+
+    if parent_output.value < child_fee + DUST_LIMIT {
+        return Err(Error::InvalidArgument(format!(
+            "Output value {} is too low to pay a fee of {}",
+            parent_output.value, child_fee
+        )));
+    }
+
+    Ok(Transaction {
+        version: TX_VERSION,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: parent.txid(),
+                vout,
+            },
+            script_sig: Script::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: parent_output.value - child_fee,
+            script_pubkey: destination.clone(),
+        }],
+    })
+}
+
 /// Create the multisig redeem script for the funding output
 pub fn make_funding_redeemscript(a: &PublicKey, b: &PublicKey) -> Script {
     let (first, second) = if a <= b { (a, b) } else { (b, a) };
@@ -742,7 +810,12 @@ pub fn make_funding_redeemscript(a: &PublicKey, b: &PublicKey) -> Script {
         .into_script()
 }
 
-fn get_oracle_sig_point<C: secp256k1_zkp::Verification>(
+/// Get the signature point of the given oracle for the given messages, which
+/// is the adaptor point of a contract using a single oracle. Adaptor points of
+/// contracts using several oracles are the sum of the signature points of the
+/// oracles, so the signature points can be computed once and shared between
+/// the adaptor points of all the combinations of oracles.
+pub fn get_oracle_sig_point<C: secp256k1_zkp::Verification>(
     secp: &Secp256k1<C>,
     oracle_info: &OracleInfo,
     msgs: &[Message],
@@ -823,7 +896,8 @@ pub fn create_cet_adaptor_sig_from_oracle_info(
     )
 }
 
-/// Crerate a set of adaptor signatures for the given cet/message pairs.
+/// Crerate a set of adaptor signatures for the given cet/message pairs. The
+/// signatures are created in parallel if the `parallel` feature is enabled.
 pub fn create_cet_adaptor_sigs_from_points<C: secp256k1_zkp::Signing>(
     secp: &secp256k1_zkp::Secp256k1<C>,
     inputs: &[(&Transaction, &PublicKey)],
@@ -831,8 +905,11 @@ pub fn create_cet_adaptor_sigs_from_points<C: secp256k1_zkp::Signing>(
     funding_script_pubkey: &Script,
     fund_output_value: u64,
 ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
+    #[cfg(not(feature = "parallel"))]
+    let inputs = inputs.iter();
+    #[cfg(feature = "parallel")]
+    let inputs = inputs.par_iter();
     inputs
-        .iter()
         .map(|(cet, adaptor_point)| {
             create_cet_adaptor_sig_from_point(
                 secp,
@@ -846,7 +923,8 @@ pub fn create_cet_adaptor_sigs_from_points<C: secp256k1_zkp::Signing>(
         .collect()
 }
 
-/// Crerate a set of adaptor signatures for the given cet/message pairs.
+/// Crerate a set of adaptor signatures for the given cet/message pairs. The
+/// signatures are created in parallel if the `parallel` feature is enabled.
 pub fn create_cet_adaptor_sigs_from_oracle_info(
     secp: &secp256k1_zkp::Secp256k1<secp256k1_zkp::All>,
     cets: &[Transaction],
@@ -860,8 +938,11 @@ pub fn create_cet_adaptor_sigs_from_oracle_info(
         return Err(Error::InvalidArgument("length of msgs is not equal to length of cets".to_string()));
     }
 
-    cets.iter()
-        .zip(msgs.iter())
+    #[cfg(not(feature = "parallel"))]
+    let inputs = cets.iter().zip(msgs.iter());
+    #[cfg(feature = "parallel")]
+    let inputs = cets.par_iter().zip(msgs.par_iter());
+    inputs
         .map(|(cet, msg)| {
             create_cet_adaptor_sig_from_oracle_info(
                 secp,
@@ -876,7 +957,9 @@ pub fn create_cet_adaptor_sigs_from_oracle_info(
         .collect()
 }
 
-fn signatures_to_secret(signatures: &[Vec<SchnorrSignature>]) -> Result<SecretKey, Error> {
+/// Returns the adaptor secret corresponding to the given oracle signatures,
+/// that is the sum of their `s` values.
+pub fn signatures_to_secret(signatures: &[Vec<SchnorrSignature>]) -> Result<SecretKey, Error> {
     let s_values = signatures
         .iter()
         .flatten()
@@ -941,6 +1024,92 @@ pub fn verify_cet_adaptor_sig_from_point(
     Ok(())
 }
 
+/// How [`verify_cet_adaptor_sigs_from_points`] handles invalid signatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdaptorSigVerificationMode {
+    /// Stop at the first invalid signature found and only report it.
+    EarlyAbort,
+    /// Verify all the signatures and report all the invalid ones.
+    Complete,
+}
+
+/// Verify a set of adaptor signatures, each given with the index of the cet it
+/// signs within `cets` and its adaptor point. This is not a batch verification:
+/// each signature is verified separately (in parallel if the `parallel`
+/// feature is enabled), but the signature hash of each cet is computed only
+/// once and reused for all the adaptor signatures of a cet covering multiple
+/// outcomes. If some signatures are invalid, an
+/// [`Error::InvalidAdaptorSignatures`] is returned with their indexes within
+/// `inputs`: the first one with [`AdaptorSigVerificationMode::EarlyAbort`] or
+/// all of them with [`AdaptorSigVerificationMode::Complete`].
+pub fn verify_cet_adaptor_sigs_from_points(
+    secp: &Secp256k1<secp256k1_zkp::All>,
+    cets: &[Transaction],
+    inputs: &[(usize, PublicKey, EcdsaAdaptorSignature)],
+    pubkey: &PublicKey,
+    funding_script_pubkey: &Script,
+    total_collateral: u64,
+    mode: AdaptorSigVerificationMode,
+) -> Result<(), Error> {
+    let mut sig_hashes = vec![None; cets.len()];
+    for (cet_index, _, _) in inputs {
+        let cet = cets
+            .get(*cet_index)
+            .ok_or_else(|| Error::InvalidArgument("cet index out of range".to_string()))?;
+        if sig_hashes[*cet_index].is_none() {
+            sig_hashes[*cet_index] = Some(util::get_sig_hash_msg(
+                cet,
+                0,
+                funding_script_pubkey,
+                total_collateral,
+            )?);
+        }
+    }
+
+    let is_invalid = |input: &(usize, PublicKey, EcdsaAdaptorSignature)| {
+        let (cet_index, adaptor_point, adaptor_sig) = input;
+        let sig_hash = sig_hashes[*cet_index]
+            .as_ref()
+            .expect("to have computed the signature hash of every cet");
+        adaptor_sig
+            .verify(secp, sig_hash, pubkey, adaptor_point)
+            .is_err()
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    let invalid_indexes: Vec<usize> = match mode {
+        AdaptorSigVerificationMode::EarlyAbort => {
+            inputs.iter().position(is_invalid).into_iter().collect()
+        }
+        AdaptorSigVerificationMode::Complete => inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| is_invalid(*x))
+            .map(|(i, _)| i)
+            .collect(),
+    };
+    #[cfg(feature = "parallel")]
+    let invalid_indexes: Vec<usize> = match mode {
+        AdaptorSigVerificationMode::EarlyAbort => inputs
+            .par_iter()
+            .position_first(is_invalid)
+            .into_iter()
+            .collect(),
+        AdaptorSigVerificationMode::Complete => inputs
+            .par_iter()
+            .enumerate()
+            .filter(|(_, x)| is_invalid(*x))
+            .map(|(i, _)| i)
+            .collect(),
+    };
+
+    if invalid_indexes.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidAdaptorSignatures(invalid_indexes))
+    }
+}
+
 /// Verify that a given adaptor signature for a given cet is valid with respect
 /// to an oracle public key, nonce and a given message.
 pub fn verify_cet_adaptor_sig_from_oracle_info(
@@ -998,6 +1167,7 @@ mod tests {
     use bitcoin::blockdata::transaction::{EcdsaSighashType, OutPoint};
     use bitcoin::consensus::encode::Encodable;
     use bitcoin::hashes::hex::FromHex;
+    use bitcoin::hashes::Hash;
     use bitcoin::{network::constants::Network, Address, Txid};
     use secp256k1_zkp::{
         rand::{Rng, RngCore},
@@ -1063,6 +1233,46 @@ mod tests {
         assert_eq!(3, refund_transaction.input[0].sequence.0);
     }
 
+    #[test]
+    fn create_cpfp_transaction_test() {
+        let (offer, accept, funding) = create_test_tx_io();
+        let parent = create_refund_transaction(
+            TxOut {
+                value: 100_000,
+                ..offer
+            },
+            accept,
+            funding,
+            0,
+        );
+        let destination = Script::new_v0_p2wpkh(&bitcoin::WPubkeyHash::hash(&[1u8; 33]));
+        let parent_fee = 100;
+        let fee_rate = 20;
+
+        let child =
+            create_cpfp_transaction(&parent, parent_fee, 0, &destination, fee_rate).unwrap();
+
+        assert_eq!(parent.txid(), child.input[0].previous_output.txid);
+        assert_eq!(0, child.input[0].previous_output.vout);
+        assert!(child.input[0].sequence.is_rbf());
+        assert_eq!(destination, child.output[0].script_pubkey);
+
+        let child_fee = 100_000 - child.output[0].value;
+        let child_weight = CPFP_TX_BASE_WEIGHT + destination.len() * 4;
+        let package_vbytes = (parent.weight() + child_weight + 3) / 4;
+        assert!(parent_fee + child_fee >= package_vbytes as u64 * fee_rate);
+    }
+
+    #[test]
+    fn create_cpfp_transaction_output_too_low_test() {
+        let (offer, accept, funding) = create_test_tx_io();
+        let parent = create_refund_transaction(offer, accept, funding, 0);
+        let destination = Script::new_v0_p2wpkh(&bitcoin::WPubkeyHash::hash(&[1u8; 33]));
+
+        create_cpfp_transaction(&parent, 0, 0, &destination, 100)
+            .expect_err("should not be able to pay the fee");
+    }
+
     #[test]
     fn create_funding_transaction_test() {
         let (pk, pk1) = create_multi_party_pub_keys();
@@ -1508,6 +1718,88 @@ mod tests {
         .expect("Invalid decrypted adaptor signature");
     }
 
+    #[test]
+    fn verify_cet_adaptor_sigs_from_points_test() {
+        let secp = Secp256k1::new();
+        let mut rng = secp256k1_zkp::rand::thread_rng();
+        let (offer_party_params, offer_fund_sk) = get_party_params(1000000000, 100000000, None);
+        let (accept_party_params, _) = get_party_params(1000000000, 100000000, None);
+
+        let dlc_txs = create_dlc_transactions(
+            &offer_party_params,
+            &accept_party_params,
+            &payouts(),
+            100,
+            4,
+            10,
+            10,
+            0,
+            FeeConfig::EvenSplit,
+        )
+        .unwrap();
+        let funding_script_pubkey = make_funding_redeemscript(
+            &offer_party_params.fund_pubkey,
+            &accept_party_params.fund_pubkey,
+        );
+        let fund_output_value = dlc_txs.fund.output[0].value;
+        let cets = dlc_txs.cets;
+
+        let adaptor_points: Vec<PublicKey> = (0..4)
+            .map(|_| PublicKey::from_secret_key(&secp, &SecretKey::new(&mut rng)))
+            .collect();
+        let inputs: Vec<(&Transaction, &PublicKey)> = adaptor_points
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (&cets[i % cets.len()], x))
+            .collect();
+        let adaptor_sigs = create_cet_adaptor_sigs_from_points(
+            &secp,
+            &inputs,
+            &offer_fund_sk,
+            &funding_script_pubkey,
+            fund_output_value,
+        )
+        .unwrap();
+        let mut to_verify: Vec<(usize, PublicKey, EcdsaAdaptorSignature)> = adaptor_points
+            .iter()
+            .zip(adaptor_sigs.iter())
+            .enumerate()
+            .map(|(i, (point, sig))| (i % cets.len(), *point, *sig))
+            .collect();
+
+        let verify = |to_verify: &[(usize, PublicKey, EcdsaAdaptorSignature)], mode| {
+            verify_cet_adaptor_sigs_from_points(
+                &secp,
+                &cets,
+                to_verify,
+                &offer_party_params.fund_pubkey,
+                &funding_script_pubkey,
+                fund_output_value,
+                mode,
+            )
+        };
+
+        verify(&to_verify, AdaptorSigVerificationMode::EarlyAbort).expect("to be valid");
+        verify(&to_verify, AdaptorSigVerificationMode::Complete).expect("to be valid");
+
+        to_verify[1].2 = adaptor_sigs[0];
+        to_verify[3].2 = adaptor_sigs[0];
+        match verify(&to_verify, AdaptorSigVerificationMode::EarlyAbort) {
+            Err(Error::InvalidAdaptorSignatures(indexes)) => assert_eq!(vec![1], indexes),
+            res => panic!("Unexpected result {:?}", res),
+        }
+        match verify(&to_verify, AdaptorSigVerificationMode::Complete) {
+            Err(Error::InvalidAdaptorSignatures(indexes)) => assert_eq!(vec![1, 3], indexes),
+            res => panic!("Unexpected result {:?}", res),
+        }
+
+        to_verify[0].0 = cets.len();
+        match verify(&to_verify, AdaptorSigVerificationMode::Complete) {
+            Err(Error::InvalidArgument(_)) => {}
+            res => panic!("Unexpected result {:?}", res),
+        }
+    }
+
     #[test]
     fn input_output_ordering_test() {
         struct OrderingCase {
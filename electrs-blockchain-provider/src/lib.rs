@@ -164,6 +164,131 @@ impl Blockchain for ElectrsBlockchainProvider {
     }
 }
 
+#[cfg(feature = "async")]
+fn to_io_error(e: reqwest::Error) -> Error {
+    Error::IOError(std::io::Error::new(std::io::ErrorKind::Other, e))
+}
+
+#[cfg(feature = "async")]
+impl ElectrsBlockchainProvider {
+    async fn get_async_text(&self, sub_url: &str) -> Result<String, Error> {
+        self.get_async(sub_url)
+            .await
+            .map_err(to_io_error)?
+            .text()
+            .await
+            .map_err(to_io_error)
+    }
+
+    async fn get_async_bytes(&self, sub_url: &str) -> Result<Vec<u8>, Error> {
+        let bytes = self
+            .get_async(sub_url)
+            .await
+            .map_err(to_io_error)?
+            .bytes()
+            .await
+            .map_err(|e| Error::BlockchainError(e.to_string()))?;
+        Ok(bytes.to_vec())
+    }
+
+    async fn get_async_from_json<T>(&self, sub_url: &str) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.get_async(sub_url)
+            .await
+            .map_err(to_io_error)?
+            .json::<T>()
+            .await
+            .map_err(|e| Error::BlockchainError(e.to_string()))
+    }
+}
+
+/// Native asynchronous implementation, not blocking the executor while waiting
+/// for the Electrs server.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl dlc_manager::AsyncBlockchain for ElectrsBlockchainProvider {
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<(), Error> {
+        let res = self
+            .async_client
+            .post(format!("{}tx", self.host))
+            .body(tx_to_string(transaction))
+            .send()
+            .await
+            .map_err(to_io_error)?;
+        if let Err(error) = res.error_for_status_ref() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(Error::InvalidParameters(format!(
+                "Server returned error: {error} {body}"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn get_network(&self) -> Result<Network, Error> {
+        Ok(self.network)
+    }
+
+    async fn get_blockchain_height(&self) -> Result<u64, Error> {
+        self.get_async_text("blocks/tip/height")
+            .await?
+            .parse()
+            .map_err(|e: std::num::ParseIntError| Error::BlockchainError(e.to_string()))
+    }
+
+    async fn get_block_at_height(&self, height: u64) -> Result<Block, Error> {
+        let hash_at_height = self
+            .get_async_text(&format!("block-height/{height}"))
+            .await?;
+        let raw_block = self
+            .get_async_bytes(&format!("block/{hash_at_height}/raw"))
+            .await?;
+        Block::consensus_decode(&mut std::io::Cursor::new(&*raw_block))
+            .map_err(|e| Error::BlockchainError(e.to_string()))
+    }
+
+    async fn get_transaction(&self, tx_id: &Txid) -> Result<Transaction, Error> {
+        let raw_tx = self.get_async_bytes(&format!("tx/{tx_id}/raw")).await?;
+        Transaction::consensus_decode(&mut std::io::Cursor::new(&*raw_tx))
+            .map_err(|e| Error::BlockchainError(e.to_string()))
+    }
+
+    async fn get_transaction_confirmations(&self, tx_id: &Txid) -> Result<u32, Error> {
+        let tx_status = self
+            .get_async_from_json::<TxStatus>(&format!("tx/{tx_id}/status"))
+            .await?;
+        if tx_status.confirmed {
+            let block_chain_height =
+                dlc_manager::AsyncBlockchain::get_blockchain_height(self).await?;
+            if let Some(block_height) = tx_status.block_height {
+                return Ok((block_chain_height - block_height + 1) as u32);
+            }
+        }
+
+        Ok(0)
+    }
+
+    async fn get_txo_confirmations(&self, txo: &OutPoint) -> Result<Option<(u32, Txid)>, Error> {
+        let out_spend = self
+            .get_async_from_json::<OutSpendResp>(&format!("tx/{}/outspend/{}", txo.txid, txo.vout))
+            .await?;
+        match out_spend {
+            OutSpendResp::Spent(OutSpendInfo {
+                txid,
+                status: UtxoStatus::Confirmed { block_height, .. },
+                ..
+            }) => {
+                let block_chain_height =
+                    dlc_manager::AsyncBlockchain::get_blockchain_height(self).await?;
+                Ok(Some(((block_chain_height - block_height + 1) as u32, txid)))
+            }
+            OutSpendResp::Spent(OutSpendInfo { txid, .. }) => Ok(Some((0, txid))),
+            OutSpendResp::Unspent { .. } => Ok(None),
+        }
+    }
+}
+
 impl simple_wallet::WalletBlockchainProvider for ElectrsBlockchainProvider {
     fn get_utxos_for_address(&self, address: &bitcoin::Address) -> Result<Vec<Utxo>, Error> {
         let utxos: Vec<UtxoResp> = self.get_from_json(&format!("address/{address}/utxo"))?;
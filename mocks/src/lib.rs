@@ -3,6 +3,8 @@ pub mod mock_blockchain;
 pub mod mock_oracle_provider;
 pub mod mock_time;
 pub mod mock_wallet;
+pub mod storage_conformance;
+pub mod test_utils;
 
 pub use dlc_manager;
 pub use simple_wallet;
@@ -6,18 +6,30 @@ use dlc_manager::channel::{
     Channel,
     SettledClosingChannel,
 };
+use dlc_manager::contract::ser::VersionedSerializable;
 use dlc_manager::contract::{
-    offered_contract::OfferedContract, signed_contract::SignedContract, Contract, PreClosedContract,
+    adaptor_point_cache::CachedAdaptorPoint, offered_contract::OfferedContract, signed_contract::SignedContract, Contract, PreClosedContract,
+    PreRefundedContract,
 };
+use dlc_manager::query::{ChannelQuery, ContractQuery, Page, QueryCursor};
 use dlc_manager::sub_channel_manager::Action;
 use dlc_manager::subchannel::{SubChannel, SubChannelState};
 use dlc_manager::Storage;
-use dlc_manager::{error::Error as DaemonError, ContractId, DlcChannelId, Utxo};
+use dlc_manager::{error::Error as DaemonError, ContractId, DlcChannelId, FeeBump, Utxo};
 use lightning::ln::ChannelId;
 use secp256k1_zkp::{PublicKey, SecretKey};
 use simple_wallet::WalletStorage;
 use std::collections::HashMap;
 use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Information about a stored record that is not part of the record itself,
+/// used to answer queries.
+#[derive(Clone, Copy, Default)]
+struct RecordInfo {
+    created_at: u64,
+    maturity: Option<u64>,
+}
 
 pub struct MemoryStorage {
     contracts: RwLock<HashMap<ContractId, Contract>>,
@@ -30,6 +42,11 @@ pub struct MemoryStorage {
     utxos: RwLock<HashMap<OutPoint, Utxo>>,
     key_pairs: RwLock<HashMap<PublicKey, SecretKey>>,
     actions: RwLock<Vec<Action>>,
+    adaptor_points: RwLock<HashMap<[u8; 32], CachedAdaptorPoint>>,
+    chain_monitor: RwLock<Option<Vec<u8>>>,
+    fee_bumps: RwLock<HashMap<Txid, FeeBump>>,
+    contract_infos: RwLock<HashMap<ContractId, RecordInfo>>,
+    channel_infos: RwLock<HashMap<DlcChannelId, RecordInfo>>,
 }
 
 impl MemoryStorage {
@@ -45,6 +62,11 @@ impl MemoryStorage {
             utxos: RwLock::new(HashMap::new()),
             key_pairs: RwLock::new(HashMap::new()),
             actions: RwLock::new(Vec::new()),
+            adaptor_points: RwLock::new(HashMap::new()),
+            chain_monitor: RwLock::new(None),
+            fee_bumps: RwLock::new(HashMap::new()),
+            contract_infos: RwLock::new(HashMap::new()),
+            channel_infos: RwLock::new(HashMap::new()),
         }
     }
 
@@ -95,6 +117,36 @@ impl MemoryStorage {
     }
 }
 
+/// Records the information of a stored record, keeping the creation time of the
+/// record previously stored under `id` or `temporary_id` as well as its maturity
+/// if none is given.
+fn update_record_info(
+    infos: &RwLock<HashMap<[u8; 32], RecordInfo>>,
+    id: [u8; 32],
+    temporary_id: [u8; 32],
+    maturity: Option<u64>,
+) {
+    let mut infos = infos.write().expect("Could not get write lock");
+    let previous = match infos.get(&id) {
+        Some(info) => Some(*info),
+        None => infos.remove(&temporary_id),
+    };
+    let info = match previous {
+        Some(previous) => RecordInfo {
+            created_at: previous.created_at,
+            maturity: maturity.or(previous.maturity),
+        },
+        None => RecordInfo {
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Unexpected time error")
+                .as_secs(),
+            maturity,
+        },
+    };
+    infos.insert(id, info);
+}
+
 impl Default for MemoryStorage {
     fn default() -> Self {
         Self::new()
@@ -121,7 +173,15 @@ impl Storage for MemoryStorage {
         let mut map = self.contracts.write().expect("Could not get write lock");
         let res = map.insert(contract.id, Contract::Offered(contract.clone()));
         match res {
-            None => Ok(()),
+            None => {
+                update_record_info(
+                    &self.contract_infos,
+                    contract.id,
+                    contract.id,
+                    map[&contract.id].get_maturity(),
+                );
+                Ok(())
+            }
             Some(_) => Err(DaemonError::StorageError(
                 "Contract already exists".to_string(),
             )),
@@ -131,6 +191,10 @@ impl Storage for MemoryStorage {
     fn delete_contract(&self, id: &ContractId) -> Result<(), DaemonError> {
         let mut map = self.contracts.write().expect("Could not get write lock");
         map.remove(id);
+        self.contract_infos
+            .write()
+            .expect("Could not get write lock")
+            .remove(id);
         Ok(())
     }
 
@@ -143,6 +207,12 @@ impl Storage for MemoryStorage {
             _ => {}
         };
         map.insert(contract.get_id(), contract.clone());
+        update_record_info(
+            &self.contract_infos,
+            contract.get_id(),
+            contract.get_temporary_id(),
+            contract.get_maturity(),
+        );
         Ok(())
     }
 
@@ -200,6 +270,48 @@ impl Storage for MemoryStorage {
         }
         Ok(res)
     }
+
+    fn get_prerefunded_contracts(&self) -> Result<Vec<PreRefundedContract>, DaemonError> {
+        let map = self.contracts.read().expect("Could not get read lock");
+
+        let mut res: Vec<PreRefundedContract> = Vec::new();
+
+        for (_, val) in map.iter() {
+            if let Contract::PreRefunded(c) = val {
+                res.push(c.clone());
+            }
+        }
+        Ok(res)
+    }
+
+    fn query_contracts(&self, query: &ContractQuery) -> Result<Page<Contract>, DaemonError> {
+        let map = self.contracts.read().expect("Could not get read lock");
+        let infos = self.contract_infos.read().expect("Could not get read lock");
+
+        let matches = map
+            .iter()
+            .filter_map(|(id, contract)| {
+                let info = infos.get(id).copied().unwrap_or_default();
+                if query.matches(
+                    contract.get_state_type(),
+                    &contract.get_counter_party_id(),
+                    info.created_at,
+                    info.maturity,
+                ) {
+                    let cursor = QueryCursor {
+                        created_at: info.created_at,
+                        id: *id,
+                    };
+                    Some((cursor, contract.clone()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Ok(Page::from_matches(matches, query.cursor, query.limit))
+    }
+
     fn upsert_channel(
         &self,
         channel: Channel,
@@ -213,6 +325,12 @@ impl Storage for MemoryStorage {
                 }
                 _ => {}
             };
+            update_record_info(
+                &self.channel_infos,
+                channel.get_id(),
+                channel.get_temporary_id(),
+                None,
+            );
             map.insert(channel.get_id(), channel);
         }
         if let Some(c) = contract {
@@ -224,6 +342,10 @@ impl Storage for MemoryStorage {
     fn delete_channel(&self, channel_id: &DlcChannelId) -> Result<(), DaemonError> {
         let mut map = self.channels.write().expect("Could not get write lock");
         map.remove(channel_id);
+        self.channel_infos
+            .write()
+            .expect("Could not get write lock")
+            .remove(channel_id);
         Ok(())
     }
 
@@ -284,13 +406,85 @@ impl Storage for MemoryStorage {
         Ok(res)
     }
 
-    fn persist_chain_monitor(&self, _: &ChainMonitor) -> Result<(), DaemonError> {
-        // No need to persist for mocks
+    fn persist_chain_monitor(&self, monitor: &ChainMonitor) -> Result<(), DaemonError> {
+        // The chain monitor is not cloneable so we keep its serialized form.
+        let serialized = monitor
+            .serialize_versioned()
+            .map_err(|e| DaemonError::StorageError(e.to_string()))?;
+        *self
+            .chain_monitor
+            .write()
+            .expect("Could not get write lock") = Some(serialized);
         Ok(())
     }
 
     fn get_chain_monitor(&self) -> Result<Option<ChainMonitor>, DaemonError> {
-        Ok(None)
+        self.chain_monitor
+            .read()
+            .expect("Could not get read lock")
+            .as_ref()
+            .map(|serialized| {
+                ChainMonitor::deserialize_versioned(&mut std::io::Cursor::new(serialized))
+                    .map_err(|e| DaemonError::StorageError(format!("{:?}", e)))
+            })
+            .transpose()
+    }
+
+    fn upsert_adaptor_points(&self, points: &[CachedAdaptorPoint]) -> Result<(), DaemonError> {
+        let mut map = self
+            .adaptor_points
+            .write()
+            .expect("Could not get write lock");
+        for point in points {
+            map.insert(point.key, point.clone());
+        }
+        Ok(())
+    }
+
+    fn get_adaptor_points(&self) -> Result<Vec<CachedAdaptorPoint>, DaemonError> {
+        Ok(self
+            .adaptor_points
+            .read()
+            .expect("Could not get read lock")
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    fn delete_adaptor_points(&self, keys: &[[u8; 32]]) -> Result<(), DaemonError> {
+        let mut map = self
+            .adaptor_points
+            .write()
+            .expect("Could not get write lock");
+        for key in keys {
+            map.remove(key);
+        }
+        Ok(())
+    }
+
+    fn upsert_fee_bump(&self, txid: &Txid, fee_bump: &FeeBump) -> Result<(), DaemonError> {
+        self.fee_bumps
+            .write()
+            .expect("Could not get write lock")
+            .insert(*txid, *fee_bump);
+        Ok(())
+    }
+
+    fn get_fee_bump(&self, txid: &Txid) -> Result<Option<FeeBump>, DaemonError> {
+        Ok(self
+            .fee_bumps
+            .read()
+            .expect("Could not get read lock")
+            .get(txid)
+            .copied())
+    }
+
+    fn delete_fee_bump(&self, txid: &Txid) -> Result<(), DaemonError> {
+        self.fee_bumps
+            .write()
+            .expect("Could not get write lock")
+            .remove(txid);
+        Ok(())
     }
 
     fn upsert_sub_channel(&self, subchannel: &SubChannel) -> Result<(), DaemonError> {
@@ -334,8 +528,7 @@ impl Storage for MemoryStorage {
     }
 
     fn save_sub_channel_actions(&self, actions: &[Action]) -> Result<(), DaemonError> {
-        let mut vec = self.actions.write().expect("Could not get write lock");
-        vec.append(&mut actions.to_vec());
+        *self.actions.write().expect("Could not get write lock") = actions.to_vec();
         Ok(())
     }
 
@@ -348,7 +541,41 @@ impl Storage for MemoryStorage {
     }
 
     fn get_channels(&self) -> Result<Vec<Channel>, DaemonError> {
-        unimplemented!()
+        Ok(self
+            .channels
+            .read()
+            .expect("Could not get read lock")
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    fn query_channels(&self, query: &ChannelQuery) -> Result<Page<Channel>, DaemonError> {
+        let map = self.channels.read().expect("Could not get read lock");
+        let infos = self.channel_infos.read().expect("Could not get read lock");
+
+        let matches = map
+            .iter()
+            .filter_map(|(id, channel)| {
+                let info = infos.get(id).copied().unwrap_or_default();
+                if query.matches(
+                    channel.get_state_type(),
+                    &channel.get_counter_party_id(),
+                    channel.get_reference_id().as_ref(),
+                    info.created_at,
+                ) {
+                    let cursor = QueryCursor {
+                        created_at: info.created_at,
+                        id: *id,
+                    };
+                    Some((cursor, channel.clone()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Ok(Page::from_matches(matches, query.cursor, query.limit))
     }
 }
 
@@ -465,3 +692,10 @@ impl WalletStorage for MemoryStorage {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryStorage;
+
+    crate::storage_conformance_tests!(|_: &str| MemoryStorage::new());
+}
@@ -1,6 +1,6 @@
 use std::{ops::Deref, sync::Mutex};
 
-use bitcoin::{hashes::Hash, OutPoint, Transaction, Txid};
+use bitcoin::{OutPoint, Transaction, Txid};
 use dlc_manager::error::Error;
 use lightning::chain::chaininterface::BroadcasterInterface;
 use simple_wallet::WalletBlockchainProvider;
@@ -13,6 +13,10 @@ where
     discard: Mutex<bool>,
     discard_ids: Mutex<Vec<Txid>>,
     est_fee: Mutex<u32>,
+    /// The transactions sent through [`dlc_manager::Blockchain::send_transaction`]
+    /// together with their number of confirmations.
+    transactions: Mutex<Vec<(Transaction, u32)>>,
+    height: Mutex<u64>,
 }
 
 impl<T: Deref> MockBlockchain<T>
@@ -25,6 +29,8 @@ where
             discard: Mutex::new(false),
             discard_ids: Mutex::new(Vec::new()),
             est_fee: Mutex::new(500),
+            transactions: Mutex::new(Vec::new()),
+            height: Mutex::new(10),
         }
     }
 
@@ -39,6 +45,33 @@ where
     pub fn set_est_fee(&self, est_fee: u32) {
         *self.est_fee.lock().unwrap() = est_fee;
     }
+
+    /// Mines `nb_blocks` blocks, confirming all the transactions sent so far.
+    pub fn generate_blocks(&self, nb_blocks: u32) {
+        *self.height.lock().unwrap() += nb_blocks as u64;
+        for (_, confirmations) in self.transactions.lock().unwrap().iter_mut() {
+            *confirmations += nb_blocks;
+        }
+    }
+
+    /// Returns the transactions sent so far, in the order they were sent.
+    pub fn get_sent_transactions(&self) -> Vec<Transaction> {
+        self.transactions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(tx, _)| tx.clone())
+            .collect()
+    }
+
+    /// Forgets an unconfirmed transaction, as if it was evicted from the
+    /// mempool.
+    pub fn evict_transaction(&self, txid: &Txid) {
+        self.transactions
+            .lock()
+            .unwrap()
+            .retain(|(tx, confirmations)| *confirmations > 0 || tx.txid() != *txid);
+    }
 }
 
 impl<T: Deref> BroadcasterInterface for MockBlockchain<T>
@@ -100,10 +133,36 @@ impl<T: Deref> dlc_manager::Blockchain for MockBlockchain<T>
 where
     T::Target: BroadcasterInterface,
 {
-    fn send_transaction(
-        &self,
-        _transaction: &Transaction,
-    ) -> Result<(), dlc_manager::error::Error> {
+    fn send_transaction(&self, transaction: &Transaction) -> Result<(), Error> {
+        let mut transactions = self.transactions.lock().unwrap();
+        let txid = transaction.txid();
+        if transactions.iter().any(|(tx, _)| tx.txid() == txid) {
+            return Ok(());
+        }
+        let is_conflicting = |tx: &Transaction| {
+            tx.input.iter().any(|x| {
+                transaction
+                    .input
+                    .iter()
+                    .any(|y| x.previous_output == y.previous_output)
+            })
+        };
+        // Only unconfirmed transactions signaling replaceability (BIP 125) can
+        // be replaced by a conflicting one.
+        let is_replaceable = |tx: &Transaction, confirmations: u32| {
+            confirmations == 0 && tx.input.iter().any(|x| x.sequence.is_rbf())
+        };
+        if transactions
+            .iter()
+            .any(|(tx, confirmations)| is_conflicting(tx) && !is_replaceable(tx, *confirmations))
+        {
+            return Err(Error::BlockchainError(format!(
+                "Transaction {} conflicts with a known transaction",
+                txid
+            )));
+        }
+        transactions.retain(|(tx, _)| !is_conflicting(tx));
+        transactions.push((transaction.clone(), 0));
         Ok(())
     }
     fn get_network(
@@ -112,18 +171,39 @@ where
         Ok(bitcoin::Network::Regtest)
     }
     fn get_blockchain_height(&self) -> Result<u64, Error> {
-        Ok(10)
+        Ok(*self.height.lock().unwrap())
     }
     fn get_block_at_height(&self, _height: u64) -> Result<bitcoin::Block, Error> {
         unimplemented!();
     }
-    fn get_transaction(&self, _tx_id: &Txid) -> Result<Transaction, Error> {
-        unimplemented!();
-    }
-    fn get_transaction_confirmations(&self, _tx_id: &Txid) -> Result<u32, Error> {
-        Ok(6)
-    }
-    fn get_txo_confirmations(&self, _txo: &OutPoint) -> Result<Option<(u32, Txid)>, Error> {
-        Ok(Some((6, Txid::all_zeros())))
+    fn get_transaction(&self, tx_id: &Txid) -> Result<Transaction, Error> {
+        self.transactions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(tx, _)| tx.txid() == *tx_id)
+            .map(|(tx, _)| tx.clone())
+            .ok_or_else(|| Error::BlockchainError(format!("Unknown transaction {}", tx_id)))
+    }
+    fn get_transaction_confirmations(&self, tx_id: &Txid) -> Result<u32, Error> {
+        Ok(self
+            .transactions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(tx, _)| tx.txid() == *tx_id)
+            .map(|(_, confirmations)| *confirmations)
+            .unwrap_or(0))
+    }
+    fn get_txo_confirmations(&self, txo: &OutPoint) -> Result<Option<(u32, Txid)>, Error> {
+        Ok(self
+            .transactions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(tx, confirmations)| {
+                *confirmations > 0 && tx.input.iter().any(|x| x.previous_output == *txo)
+            })
+            .map(|(tx, confirmations)| (*confirmations, tx.txid())))
     }
 }
@@ -1,28 +1,35 @@
-use std::{ops::Deref, rc::Rc};
+use std::{collections::HashMap, ops::Deref, sync::Mutex};
 
 use bitcoin::psbt::PartiallySignedTransaction;
-use bitcoin::{Address, OutPoint, PackedLockTime, Script, Transaction, TxOut};
+use bitcoin::{Address, OutPoint, PackedLockTime, Script, Transaction, TxOut, Witness};
 use dlc_manager::{error::Error, Blockchain, Signer, Utxo, Wallet};
 use lightning::chain::chaininterface::BroadcasterInterface;
-use secp256k1_zkp::{rand::seq::SliceRandom, SecretKey};
+use secp256k1_zkp::{
+    rand::{seq::SliceRandom, thread_rng},
+    PublicKey, SecretKey,
+};
 
 use crate::mock_blockchain::MockBlockchain;
 
 pub struct MockWallet {
     utxos: Vec<Utxo>,
+    secret_key: SecretKey,
+    secret_keys: Mutex<HashMap<PublicKey, SecretKey>>,
 }
 
 impl MockWallet {
-    pub fn new<T: Deref>(blockchain: &Rc<MockBlockchain<T>>, nb_utxo: u16) -> Self
+    pub fn new<T: Deref>(blockchain: &MockBlockchain<T>, nb_utxo: u16) -> Self
     where
         T::Target: BroadcasterInterface,
     {
+        let secret_key = SecretKey::new(&mut thread_rng());
+        let address = get_address(&secret_key);
         let mut utxos = Vec::with_capacity(nb_utxo as usize);
 
         for i in 0..nb_utxo {
             let tx_out = TxOut {
                 value: 1000000 * i as u64,
-                script_pubkey: Script::default(),
+                script_pubkey: address.script_pubkey(),
             };
             let tx = Transaction {
                 version: 2,
@@ -37,7 +44,7 @@ impl MockWallet {
                     txid: tx.txid(),
                     vout: 0,
                 },
-                address: get_address(),
+                address: address.clone(),
                 redeem_script: Script::default(),
                 reserved: false,
             };
@@ -45,34 +52,50 @@ impl MockWallet {
             utxos.push(utxo);
         }
 
-        Self { utxos }
+        Self {
+            utxos,
+            secret_key,
+            secret_keys: Mutex::new(HashMap::new()),
+        }
     }
 }
 
 impl Signer for MockWallet {
     fn sign_psbt_input(
         &self,
-        _psbt: &mut PartiallySignedTransaction,
-        _idx: usize,
+        psbt: &mut PartiallySignedTransaction,
+        idx: usize,
     ) -> Result<(), Error> {
+        // Signatures are not verified by the mocks, so a placeholder witness
+        // of the size of a P2WPKH one is enough.
+        psbt.inputs[idx].final_script_witness =
+            Some(Witness::from_vec(vec![vec![0; 72], vec![0; 33]]));
         Ok(())
     }
 
     fn get_secret_key_for_pubkey(
         &self,
-        _pubkey: &secp256k1_zkp::PublicKey,
+        pubkey: &PublicKey,
     ) -> Result<SecretKey, dlc_manager::error::Error> {
-        Ok(get_secret_key())
+        self.secret_keys
+            .lock()
+            .unwrap()
+            .get(pubkey)
+            .cloned()
+            .ok_or_else(|| Error::InvalidParameters("Unknown public key".to_string()))
     }
 }
 
 impl Wallet for MockWallet {
     fn get_new_address(&self) -> Result<Address, dlc_manager::error::Error> {
-        Ok(get_address())
+        Ok(get_address(&self.secret_key))
     }
 
     fn get_new_secret_key(&self) -> Result<SecretKey, dlc_manager::error::Error> {
-        Ok(get_secret_key())
+        let secret_key = SecretKey::new(&mut thread_rng());
+        let pubkey = PublicKey::from_secret_key(secp256k1_zkp::SECP256K1, &secret_key);
+        self.secret_keys.lock().unwrap().insert(pubkey, secret_key);
+        Ok(secret_key)
     }
 
     fn get_utxos_for_amount(
@@ -118,21 +141,13 @@ impl Wallet for MockWallet {
     }
 }
 
-fn get_address() -> Address {
+fn get_address(secret_key: &SecretKey) -> Address {
     Address::p2wpkh(
         &bitcoin::PublicKey::from_private_key(
             secp256k1_zkp::SECP256K1,
-            &bitcoin::PrivateKey::new(get_secret_key(), bitcoin::Network::Regtest),
+            &bitcoin::PrivateKey::new(*secret_key, bitcoin::Network::Regtest),
         ),
         bitcoin::Network::Regtest,
     )
     .unwrap()
 }
-
-pub fn get_secret_key() -> SecretKey {
-    SecretKey::from_slice(&[
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        0, 1,
-    ])
-    .unwrap()
-}
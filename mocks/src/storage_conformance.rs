@@ -0,0 +1,1129 @@
+//! Checks of the behavior expected from implementations of the [`Storage`] trait.
+//! Each check is a function taking an empty storage, and the
+//! [`storage_conformance_tests`](crate::storage_conformance_tests) macro generates a test
+//! for each of them.
+
+use bitcoin::hashes::Hash;
+use bitcoin::Txid;
+use dlc_manager::chain_monitor::ChainMonitor;
+use dlc_manager::channel::accepted_channel::AcceptedChannel;
+use dlc_manager::channel::offered_channel::OfferedChannel;
+use dlc_manager::channel::signed_channel::{SignedChannelState, SignedChannelStateType};
+use dlc_manager::channel::{Channel, ChannelStateType};
+use dlc_manager::contract::adaptor_point_cache::CachedAdaptorPoint;
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::ser::Serializable;
+use dlc_manager::contract::signed_contract::SignedContract;
+use dlc_manager::contract::{Contract, ContractStateType, PreRefundedContract};
+use dlc_manager::query::{ChannelQuery, ContractQuery, TimeRange};
+use dlc_manager::sub_channel_manager::Action;
+use dlc_manager::subchannel::SubChannel;
+use dlc_manager::{FeeBump, Storage};
+use lightning::ln::ChannelId;
+use secp256k1_zkp::{PublicKey, SecretKey, SECP256K1};
+
+/// Generates a test for each of the storage conformance checks. The first
+/// expression is called with the name of the test and must return an empty
+/// storage. The optional second one is called with the same name once the
+/// storage has been dropped, to clean up any resources it used.
+#[macro_export]
+macro_rules! storage_conformance_tests {
+    (@tests $create_storage: expr, $cleanup: expr, $($test: ident),*) => {
+        $(
+            #[test]
+            fn $test() {
+                let name = std::stringify!($test);
+                {
+                    #[allow(clippy::redundant_closure_call)]
+                    let storage = ($create_storage)(name);
+                    $crate::storage_conformance::$test(&storage);
+                }
+                #[allow(clippy::redundant_closure_call)]
+                ($cleanup)(name);
+            }
+        )*
+    };
+    ($create_storage: expr) => {
+        $crate::storage_conformance_tests!($create_storage, |_: &str| {});
+    };
+    ($create_storage: expr, $cleanup: expr) => {
+        $crate::storage_conformance_tests!(@tests $create_storage, $cleanup,
+            create_contract_can_be_retrieved,
+            update_contract_is_updated,
+            delete_contract_is_deleted,
+            get_contracts_all_returned,
+            get_signed_contracts_only_signed,
+            get_confirmed_contracts_only_confirmed,
+            get_offered_contracts_only_offered,
+            get_preclosed_contracts_only_preclosed,
+            get_prerefunded_contracts_only_prerefunded,
+            upsert_channel_stores_channel_and_contract,
+            upsert_channel_removes_temporary_channel,
+            get_channel_by_id_returns_correct_channel,
+            delete_channel_is_not_returned,
+            get_channels_all_returned,
+            get_offered_channels_only_offered,
+            get_signed_channels_filtered_by_state,
+            get_settled_closing_channels_only_settled_closing,
+            persist_chain_monitor_roundtrip,
+            adaptor_points_roundtrip,
+            fee_bump_roundtrip,
+            get_sub_channel_by_id_returns_correct_sub_channel,
+            get_sub_channels_all_returned,
+            get_offered_sub_channels_only_offered,
+            upsert_sub_channel_is_updated,
+            save_actions_roundtrip,
+            save_actions_replaces_previous,
+            get_actions_unset_is_empty,
+            get_empty_actions_is_empty,
+            query_contracts_filtered_by_state,
+            query_contracts_filtered_by_counter_party,
+            query_contracts_filtered_by_maturity,
+            query_contracts_filtered_by_creation_time,
+            query_contracts_paginated,
+            query_contracts_excludes_replaced_and_deleted,
+            query_channels_filtered_by_state,
+            query_channels_filtered_by_counter_party_and_reference_id,
+            query_channels_paginated,
+            query_channels_excludes_deleted
+        );
+    };
+}
+
+fn deserialize_object<T>(serialized: &[u8]) -> T
+where
+    T: Serializable,
+{
+    let mut cursor = std::io::Cursor::new(&serialized);
+    T::deserialize(&mut cursor).unwrap()
+}
+
+fn insert_offered_signed_and_confirmed<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+    let offered_contract = deserialize_object(serialized);
+    storage
+        .create_contract(&offered_contract)
+        .expect("Error creating contract");
+
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Signed");
+    let signed_contract = Contract::Signed(deserialize_object(serialized));
+    storage
+        .update_contract(&signed_contract)
+        .expect("Error creating contract");
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Signed1");
+    let signed_contract = Contract::Signed(deserialize_object(serialized));
+    storage
+        .update_contract(&signed_contract)
+        .expect("Error creating contract");
+
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Confirmed");
+    let confirmed_contract = Contract::Confirmed(deserialize_object(serialized));
+    storage
+        .update_contract(&confirmed_contract)
+        .expect("Error creating contract");
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Confirmed1");
+    let confirmed_contract = Contract::Confirmed(deserialize_object(serialized));
+    storage
+        .update_contract(&confirmed_contract)
+        .expect("Error creating contract");
+
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/PreClosed");
+    let preclosed_contract = Contract::PreClosed(deserialize_object(serialized));
+    storage
+        .update_contract(&preclosed_contract)
+        .expect("Error creating contract");
+}
+
+fn insert_offered_and_signed_channels<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+    let offered_contract = deserialize_object(serialized);
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/OfferedChannel");
+    let offered_channel = deserialize_object(serialized);
+    storage
+        .upsert_channel(
+            Channel::Offered(offered_channel),
+            Some(Contract::Offered(offered_contract)),
+        )
+        .expect("Error creating channel");
+
+    let serialized =
+        include_bytes!("../../dlc-sled-storage-provider/test_files/SignedChannelEstablished");
+    let signed_channel = Channel::Signed(deserialize_object(serialized));
+    storage
+        .upsert_channel(signed_channel, None)
+        .expect("Error creating channel");
+
+    let serialized =
+        include_bytes!("../../dlc-sled-storage-provider/test_files/SignedChannelSettled");
+    let signed_channel = Channel::Signed(deserialize_object(serialized));
+    storage
+        .upsert_channel(signed_channel, None)
+        .expect("Error creating channel");
+}
+
+fn insert_sub_channels<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/OfferedSubChannel");
+    let offered_sub_channel = deserialize_object(serialized);
+    storage
+        .upsert_sub_channel(&offered_sub_channel)
+        .expect("Error inserting sub channel");
+    let serialized =
+        include_bytes!("../../dlc-sled-storage-provider/test_files/OfferedSubChannel1");
+    let offered_sub_channel = deserialize_object(serialized);
+    storage
+        .upsert_sub_channel(&offered_sub_channel)
+        .expect("Error inserting sub channel");
+
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/SignedSubChannel");
+    let signed_sub_channel = deserialize_object(serialized);
+    storage
+        .upsert_sub_channel(&signed_sub_channel)
+        .expect("Error inserting sub channel");
+
+    let serialized =
+        include_bytes!("../../dlc-sled-storage-provider/test_files/AcceptedSubChannel");
+    let accepted_sub_channel = deserialize_object(serialized);
+    storage
+        .upsert_sub_channel(&accepted_sub_channel)
+        .expect("Error inserting sub channel");
+}
+
+/// Retrieves all the pages of the results of the given contract query.
+fn query_all_contracts<S: Storage>(storage: &S, mut query: ContractQuery) -> Vec<Contract> {
+    let mut res = Vec::new();
+    loop {
+        let page = storage
+            .query_contracts(&query)
+            .expect("Error querying contracts");
+        if let Some(limit) = query.limit {
+            assert!(page.items.len() <= limit);
+        }
+        res.extend(page.items);
+        match page.next_cursor {
+            Some(cursor) => query.cursor = Some(cursor),
+            None => return res,
+        }
+    }
+}
+
+/// Retrieves all the pages of the results of the given channel query.
+fn query_all_channels<S: Storage>(storage: &S, mut query: ChannelQuery) -> Vec<Channel> {
+    let mut res = Vec::new();
+    loop {
+        let page = storage
+            .query_channels(&query)
+            .expect("Error querying channels");
+        if let Some(limit) = query.limit {
+            assert!(page.items.len() <= limit);
+        }
+        res.extend(page.items);
+        match page.next_cursor {
+            Some(cursor) => query.cursor = Some(cursor),
+            None => return res,
+        }
+    }
+}
+
+fn get_actions() -> Vec<Action> {
+    vec![
+        Action::ForceSign(ChannelId([1; 32])),
+        Action::ReAcceptCloseOffer {
+            channel_id: ChannelId([2; 32]),
+            own_balance: 100000,
+        },
+    ]
+}
+
+/// Checks that an offered contract can be retrieved after being created.
+pub fn create_contract_can_be_retrieved<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+    let contract: OfferedContract = deserialize_object(serialized);
+
+    storage
+        .create_contract(&contract)
+        .expect("Error creating contract");
+
+    let retrieved = storage
+        .get_contract(&contract.id)
+        .expect("Error retrieving contract.");
+
+    if let Some(Contract::Offered(retrieved_offer)) = retrieved {
+        assert_eq!(serialized[..], retrieved_offer.serialize().unwrap()[..]);
+    } else {
+        panic!("Expected to retrieve the offered contract");
+    }
+}
+
+/// Checks that updating a contract replaces the record stored under its
+/// temporary id.
+pub fn update_contract_is_updated<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+    let offered_contract: OfferedContract = deserialize_object(serialized);
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Accepted");
+    let accepted_contract = Contract::Accepted(deserialize_object(serialized));
+
+    storage
+        .create_contract(&offered_contract)
+        .expect("Error creating contract");
+
+    storage
+        .update_contract(&accepted_contract)
+        .expect("Error updating contract.");
+    let retrieved = storage
+        .get_contract(&accepted_contract.get_id())
+        .expect("Error retrieving contract.");
+
+    assert!(matches!(retrieved, Some(Contract::Accepted(_))));
+    assert!(storage
+        .get_contract(&accepted_contract.get_temporary_id())
+        .expect("Error retrieving contract.")
+        .is_none());
+}
+
+/// Checks that a deleted contract is not returned anymore.
+pub fn delete_contract_is_deleted<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+    let contract: OfferedContract = deserialize_object(serialized);
+    storage
+        .create_contract(&contract)
+        .expect("Error creating contract");
+
+    storage
+        .delete_contract(&contract.id)
+        .expect("Error deleting contract");
+
+    assert!(storage
+        .get_contract(&contract.id)
+        .expect("Error querying contract")
+        .is_none());
+    assert!(storage
+        .get_contract_offers()
+        .expect("Error querying contracts")
+        .is_empty());
+}
+
+/// Checks that all the stored contracts are returned.
+pub fn get_contracts_all_returned<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let contracts = storage.get_contracts().expect("Error retrieving contracts");
+
+    assert_eq!(6, contracts.len());
+}
+
+/// Checks that only contracts in the signed state are returned.
+pub fn get_signed_contracts_only_signed<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let signed_contracts = storage
+        .get_signed_contracts()
+        .expect("Error retrieving signed contracts");
+
+    assert_eq!(2, signed_contracts.len());
+}
+
+/// Checks that only contracts in the confirmed state are returned.
+pub fn get_confirmed_contracts_only_confirmed<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let confirmed_contracts = storage
+        .get_confirmed_contracts()
+        .expect("Error retrieving confirmed contracts");
+
+    assert_eq!(2, confirmed_contracts.len());
+}
+
+/// Checks that only contracts in the offered state are returned.
+pub fn get_offered_contracts_only_offered<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let offered_contracts = storage
+        .get_contract_offers()
+        .expect("Error retrieving offered contracts");
+
+    assert_eq!(1, offered_contracts.len());
+}
+
+/// Checks that only contracts in the pre-closed state are returned.
+pub fn get_preclosed_contracts_only_preclosed<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let preclosed_contracts = storage
+        .get_preclosed_contracts()
+        .expect("Error retrieving preclosed contracts");
+
+    assert_eq!(1, preclosed_contracts.len());
+}
+
+/// Checks that only contracts in the pre-refunded state are returned.
+pub fn get_prerefunded_contracts_only_prerefunded<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    assert!(storage
+        .get_prerefunded_contracts()
+        .expect("Error retrieving prerefunded contracts")
+        .is_empty());
+
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Signed");
+    let signed_contract: SignedContract = deserialize_object(serialized);
+    let signed_refund = signed_contract
+        .accepted_contract
+        .dlc_transactions
+        .refund
+        .clone();
+    let prerefunded_contract = Contract::PreRefunded(PreRefundedContract {
+        signed_contract,
+        signed_refund,
+    });
+    storage
+        .update_contract(&prerefunded_contract)
+        .expect("Error updating contract");
+
+    let prerefunded_contracts = storage
+        .get_prerefunded_contracts()
+        .expect("Error retrieving prerefunded contracts");
+    assert_eq!(1, prerefunded_contracts.len());
+    assert_eq!(
+        1,
+        storage
+            .get_signed_contracts()
+            .expect("Error retrieving signed contracts")
+            .len()
+    );
+}
+
+/// Checks that upserting a channel along with a contract stores both.
+pub fn upsert_channel_stores_channel_and_contract<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+    let offered_contract: OfferedContract = deserialize_object(serialized);
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/OfferedChannel");
+    let offered_channel: OfferedChannel = deserialize_object(serialized);
+    let channel_id = offered_channel.temporary_channel_id;
+    let contract_id = offered_contract.id;
+
+    storage
+        .upsert_channel(
+            Channel::Offered(offered_channel),
+            Some(Contract::Offered(offered_contract)),
+        )
+        .expect("Error upserting channel");
+
+    assert!(matches!(
+        storage.get_channel(&channel_id).unwrap(),
+        Some(Channel::Offered(_))
+    ));
+    assert!(matches!(
+        storage.get_contract(&contract_id).unwrap(),
+        Some(Contract::Offered(_))
+    ));
+}
+
+/// Checks that upserting an accepted channel replaces the record stored under its
+/// temporary id.
+pub fn upsert_channel_removes_temporary_channel<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/AcceptedChannel");
+    let accepted_channel: AcceptedChannel = deserialize_object(serialized);
+    let temporary_id = accepted_channel.temporary_channel_id;
+    let channel_id = accepted_channel.channel_id;
+    let mut offered_channel: OfferedChannel = deserialize_object(include_bytes!(
+        "../../dlc-sled-storage-provider/test_files/OfferedChannel"
+    ));
+    offered_channel.temporary_channel_id = temporary_id;
+
+    storage
+        .upsert_channel(Channel::Offered(offered_channel), None)
+        .expect("Error upserting channel");
+    storage
+        .upsert_channel(Channel::Accepted(accepted_channel), None)
+        .expect("Error upserting channel");
+
+    assert!(storage.get_channel(&temporary_id).unwrap().is_none());
+    assert!(matches!(
+        storage.get_channel(&channel_id).unwrap(),
+        Some(Channel::Accepted(_))
+    ));
+}
+
+/// Checks that a channel can be retrieved using its id.
+pub fn get_channel_by_id_returns_correct_channel<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/AcceptedChannel");
+    let accepted_channel: AcceptedChannel = deserialize_object(serialized);
+    let channel_id = accepted_channel.channel_id;
+    storage
+        .upsert_channel(Channel::Accepted(accepted_channel), None)
+        .expect("Error creating channel");
+
+    let retrieved = storage
+        .get_channel(&channel_id)
+        .expect("error retrieving previously inserted channel.")
+        .expect("to have found the previously inserted channel.");
+    assert_eq!(channel_id, retrieved.get_id());
+}
+
+/// Checks that a deleted channel is not returned anymore.
+pub fn delete_channel_is_not_returned<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/AcceptedChannel");
+    let accepted_channel: AcceptedChannel = deserialize_object(serialized);
+    let channel_id = accepted_channel.channel_id;
+    storage
+        .upsert_channel(Channel::Accepted(accepted_channel), None)
+        .expect("Error creating channel");
+
+    storage
+        .get_channel(&channel_id)
+        .expect("could not retrieve previously inserted channel.");
+
+    storage
+        .delete_channel(&channel_id)
+        .expect("to be able to delete the channel");
+
+    assert!(storage
+        .get_channel(&channel_id)
+        .expect("error getting channel.")
+        .is_none());
+}
+
+/// Checks that all the stored channels are returned.
+pub fn get_channels_all_returned<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let channels = storage.get_channels().expect("Error retrieving channels");
+    let offered_channels = storage
+        .get_offered_channels()
+        .expect("Error retrieving offered channels");
+    let signed_channels = storage
+        .get_signed_channels(None)
+        .expect("Error retrieving signed channels");
+
+    assert!(!channels.is_empty());
+    assert_eq!(
+        offered_channels.len() + signed_channels.len(),
+        channels.len()
+    );
+}
+
+/// Checks that only channels in the offered state are returned.
+pub fn get_offered_channels_only_offered<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let offered_channels = storage
+        .get_offered_channels()
+        .expect("Error retrieving offered channels");
+    assert_eq!(1, offered_channels.len());
+}
+
+/// Checks that signed channels are filtered by the type of their state.
+pub fn get_signed_channels_filtered_by_state<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let signed_channels = storage
+        .get_signed_channels(None)
+        .expect("Error retrieving signed channels");
+    assert!(!signed_channels.is_empty());
+
+    let established_channels = storage
+        .get_signed_channels(Some(SignedChannelStateType::Established))
+        .expect("Error retrieving established channels");
+    assert_eq!(1, established_channels.len());
+    assert!(established_channels
+        .iter()
+        .all(|c| matches!(c.state, SignedChannelState::Established { .. })));
+
+    let settled_channels = storage
+        .get_signed_channels(Some(SignedChannelStateType::Settled))
+        .expect("Error retrieving settled channels");
+    assert!(settled_channels
+        .iter()
+        .all(|c| matches!(c.state, SignedChannelState::Settled { .. })));
+
+    assert!(storage
+        .get_signed_channels(Some(SignedChannelStateType::RenewOffered))
+        .expect("Error retrieving renew offered channels")
+        .is_empty());
+
+    for channel in established_channels.iter().chain(settled_channels.iter()) {
+        assert!(storage
+            .get_channel(&channel.channel_id)
+            .expect("Error retrieving channel")
+            .is_some());
+    }
+}
+
+/// Checks that only channels in the settled closing state are returned.
+pub fn get_settled_closing_channels_only_settled_closing<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    assert!(storage
+        .get_settled_closing_channels()
+        .expect("Error retrieving settled closing channels")
+        .is_empty());
+}
+
+/// Checks that the chain monitor can be retrieved after being persisted.
+pub fn persist_chain_monitor_roundtrip<S: Storage>(storage: &S) {
+    assert!(storage
+        .get_chain_monitor()
+        .expect("to be able to retrieve the chain monitor.")
+        .is_none());
+
+    let chain_monitor = ChainMonitor::new(123);
+
+    storage
+        .persist_chain_monitor(&chain_monitor)
+        .expect("to be able to persist the chain monitor.");
+
+    let retrieved = storage
+        .get_chain_monitor()
+        .expect("to be able to retrieve the chain monitor.")
+        .expect("to have a persisted chain monitor.");
+
+    assert_eq!(chain_monitor, retrieved);
+}
+
+fn get_cached_adaptor_point(key: u8, announcements_hash: u8) -> CachedAdaptorPoint {
+    CachedAdaptorPoint {
+        key: [key; 32],
+        announcements_hash: [announcements_hash; 32],
+        point: PublicKey::from_secret_key(SECP256K1, &SecretKey::from_slice(&[key; 32]).unwrap()),
+    }
+}
+
+fn get_sorted_adaptor_points<S: Storage>(storage: &S) -> Vec<CachedAdaptorPoint> {
+    let mut points = storage
+        .get_adaptor_points()
+        .expect("to be able to retrieve the adaptor points.");
+    points.sort_by_key(|x| x.key);
+    points
+}
+
+/// Checks that adaptor points can be persisted, replaced and deleted.
+pub fn adaptor_points_roundtrip<S: Storage>(storage: &S) {
+    assert!(get_sorted_adaptor_points(storage).is_empty());
+
+    let points = (1..=3)
+        .map(|i| get_cached_adaptor_point(i, 1))
+        .collect::<Vec<_>>();
+    storage
+        .upsert_adaptor_points(&points)
+        .expect("to be able to persist adaptor points.");
+    assert_eq!(points, get_sorted_adaptor_points(storage));
+
+    let replaced = get_cached_adaptor_point(2, 2);
+    let added = get_cached_adaptor_point(4, 2);
+    storage
+        .upsert_adaptor_points(&[replaced.clone(), added.clone()])
+        .expect("to be able to persist adaptor points.");
+    assert_eq!(
+        vec![
+            points[0].clone(),
+            replaced,
+            points[2].clone(),
+            added.clone()
+        ],
+        get_sorted_adaptor_points(storage)
+    );
+
+    storage
+        .delete_adaptor_points(&[[1; 32], [2; 32], [3; 32]])
+        .expect("to be able to delete adaptor points.");
+    assert_eq!(vec![added], get_sorted_adaptor_points(storage));
+}
+
+/// Checks that fee bumps can be recorded, updated and deleted.
+pub fn fee_bump_roundtrip<S: Storage>(storage: &S) {
+    let txid = Txid::from_inner([1; 32]);
+    let other_txid = Txid::from_inner([2; 32]);
+    let fee_bump = |fee_rate: u64, child: u8| FeeBump {
+        fee_rate,
+        child_txid: Txid::from_inner([child; 32]),
+    };
+    assert_eq!(None, storage.get_fee_bump(&txid).unwrap());
+
+    storage
+        .upsert_fee_bump(&txid, &fee_bump(10, 3))
+        .expect("to be able to record a fee bump.");
+    storage
+        .upsert_fee_bump(&other_txid, &fee_bump(20, 4))
+        .expect("to be able to record a fee bump.");
+    assert_eq!(Some(fee_bump(10, 3)), storage.get_fee_bump(&txid).unwrap());
+
+    storage
+        .upsert_fee_bump(&txid, &fee_bump(15, 5))
+        .expect("to be able to update a fee bump.");
+    assert_eq!(Some(fee_bump(15, 5)), storage.get_fee_bump(&txid).unwrap());
+
+    storage
+        .delete_fee_bump(&txid)
+        .expect("to be able to delete a fee bump.");
+    assert_eq!(None, storage.get_fee_bump(&txid).unwrap());
+    assert_eq!(
+        Some(fee_bump(20, 4)),
+        storage.get_fee_bump(&other_txid).unwrap()
+    );
+}
+
+/// Checks that a sub channel can be retrieved using its id.
+pub fn get_sub_channel_by_id_returns_correct_sub_channel<S: Storage>(storage: &S) {
+    insert_sub_channels(storage);
+
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/SignedSubChannel");
+    let signed_sub_channel: SubChannel = deserialize_object(serialized);
+
+    let retrieved = storage
+        .get_sub_channel(signed_sub_channel.channel_id)
+        .expect("Error retrieving sub channel")
+        .expect("to have found the previously inserted sub channel.");
+    assert_eq!(signed_sub_channel.channel_id, retrieved.channel_id);
+    assert!(storage
+        .get_sub_channel(ChannelId([0; 32]))
+        .expect("Error retrieving sub channel")
+        .is_none());
+}
+
+/// Checks that all the stored sub channels are returned.
+pub fn get_sub_channels_all_returned<S: Storage>(storage: &S) {
+    insert_sub_channels(storage);
+
+    let sub_channels = storage
+        .get_sub_channels()
+        .expect("Error retrieving sub channels");
+    assert_eq!(4, sub_channels.len());
+}
+
+/// Checks that only sub channels in the offered state are returned.
+pub fn get_offered_sub_channels_only_offered<S: Storage>(storage: &S) {
+    insert_sub_channels(storage);
+
+    let offered_sub_channels = storage
+        .get_offered_sub_channels()
+        .expect("Error retrieving offered sub channels");
+    assert_eq!(2, offered_sub_channels.len());
+}
+
+/// Checks that upserting a sub channel replaces the previous record with the same
+/// id.
+pub fn upsert_sub_channel_is_updated<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/OfferedSubChannel");
+    let offered_sub_channel: SubChannel = deserialize_object(serialized);
+    storage
+        .upsert_sub_channel(&offered_sub_channel)
+        .expect("Error inserting sub channel");
+
+    let mut rejected_sub_channel = offered_sub_channel.clone();
+    rejected_sub_channel.state = dlc_manager::subchannel::SubChannelState::Rejected;
+    storage
+        .upsert_sub_channel(&rejected_sub_channel)
+        .expect("Error updating sub channel");
+
+    assert_eq!(
+        1,
+        storage
+            .get_sub_channels()
+            .expect("Error retrieving sub channels")
+            .len()
+    );
+    assert!(storage
+        .get_offered_sub_channels()
+        .expect("Error retrieving offered sub channels")
+        .is_empty());
+}
+
+/// Checks that sub channel actions can be retrieved after being saved.
+pub fn save_actions_roundtrip<S: Storage>(storage: &S) {
+    let actions = get_actions();
+    storage
+        .save_sub_channel_actions(&actions)
+        .expect("Error saving sub channel actions");
+    let recovered = storage
+        .get_sub_channel_actions()
+        .expect("Error getting sub channel actions");
+    assert_eq!(actions, recovered);
+}
+
+/// Checks that saving sub channel actions replaces the previously saved ones.
+pub fn save_actions_replaces_previous<S: Storage>(storage: &S) {
+    let actions = get_actions();
+    storage
+        .save_sub_channel_actions(&actions)
+        .expect("Error saving sub channel actions");
+    storage
+        .save_sub_channel_actions(&actions[1..])
+        .expect("Error saving sub channel actions");
+    let recovered = storage
+        .get_sub_channel_actions()
+        .expect("Error getting sub channel actions");
+    assert_eq!(actions[1..], recovered[..]);
+}
+
+/// Checks that no sub channel actions are returned if none were saved.
+pub fn get_actions_unset_is_empty<S: Storage>(storage: &S) {
+    let actions = storage
+        .get_sub_channel_actions()
+        .expect("Error getting sub channel actions");
+    assert!(actions.is_empty());
+}
+
+/// Checks that no sub channel actions are returned after saving an empty list.
+pub fn get_empty_actions_is_empty<S: Storage>(storage: &S) {
+    storage
+        .save_sub_channel_actions(&get_actions())
+        .expect("Error saving sub channel actions");
+    storage
+        .save_sub_channel_actions(&[])
+        .expect("Error saving sub channel actions");
+    let actions = storage
+        .get_sub_channel_actions()
+        .expect("Error getting sub channel actions");
+    assert!(actions.is_empty());
+}
+
+/// Checks that contracts are filtered by the type of their state.
+pub fn query_contracts_filtered_by_state<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let signed = storage
+        .get_signed_contracts()
+        .expect("Error retrieving signed contracts");
+    let confirmed = storage
+        .get_confirmed_contracts()
+        .expect("Error retrieving confirmed contracts");
+
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            states: vec![ContractStateType::Signed],
+            ..Default::default()
+        },
+    );
+    assert_eq!(signed.len(), contracts.len());
+    assert!(contracts.iter().all(|c| matches!(c, Contract::Signed(_))));
+
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            states: vec![ContractStateType::Signed, ContractStateType::Confirmed],
+            ..Default::default()
+        },
+    );
+    assert_eq!(signed.len() + confirmed.len(), contracts.len());
+
+    assert!(query_all_contracts(
+        storage,
+        ContractQuery {
+            states: vec![ContractStateType::Refunded],
+            ..Default::default()
+        },
+    )
+    .is_empty());
+
+    let all = storage.get_contracts().expect("Error retrieving contracts");
+    assert_eq!(
+        all.len(),
+        query_all_contracts(storage, ContractQuery::default()).len()
+    );
+}
+
+/// Checks that contracts are filtered by counter party.
+pub fn query_contracts_filtered_by_counter_party<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let all = storage.get_contracts().expect("Error retrieving contracts");
+    let counter_party = all[0].get_counter_party_id();
+    let expected = all
+        .iter()
+        .filter(|c| c.get_counter_party_id() == counter_party)
+        .count();
+
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            counter_party: Some(counter_party),
+            ..Default::default()
+        },
+    );
+    assert_eq!(expected, contracts.len());
+    assert!(contracts
+        .iter()
+        .all(|c| c.get_counter_party_id() == counter_party));
+
+    let signed = all
+        .iter()
+        .filter(|c| c.get_counter_party_id() == counter_party && matches!(c, Contract::Signed(_)))
+        .count();
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            counter_party: Some(counter_party),
+            states: vec![ContractStateType::Signed],
+            ..Default::default()
+        },
+    );
+    assert_eq!(signed, contracts.len());
+}
+
+/// Checks that contracts are filtered by the maturity of their oracle events.
+pub fn query_contracts_filtered_by_maturity<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let all = storage.get_contracts().expect("Error retrieving contracts");
+    let maturity = all
+        .iter()
+        .find_map(|c| c.get_maturity())
+        .expect("to have a contract with a maturity");
+
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            maturity: TimeRange {
+                from: Some(maturity),
+                to: Some(maturity + 1),
+            },
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        all.iter()
+            .filter(|c| c.get_maturity() == Some(maturity))
+            .count(),
+        contracts.len()
+    );
+    assert!(contracts.iter().all(|c| c.get_maturity() == Some(maturity)));
+
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            maturity: TimeRange {
+                from: None,
+                to: Some(maturity),
+            },
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        all.iter()
+            .filter(|c| c.get_maturity().map_or(false, |m| m < maturity))
+            .count(),
+        contracts.len()
+    );
+}
+
+/// Checks that contracts are filtered by the time at which they were first stored.
+pub fn query_contracts_filtered_by_creation_time<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let all = storage.get_contracts().expect("Error retrieving contracts");
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            created_at: TimeRange {
+                from: Some(1),
+                to: None,
+            },
+            ..Default::default()
+        },
+    );
+    assert_eq!(all.len(), contracts.len());
+
+    assert!(query_all_contracts(
+        storage,
+        ContractQuery {
+            created_at: TimeRange {
+                from: None,
+                to: Some(1),
+            },
+            ..Default::default()
+        },
+    )
+    .is_empty());
+}
+
+/// Checks that paginating through the contracts returns each of them once.
+pub fn query_contracts_paginated<S: Storage>(storage: &S) {
+    insert_offered_signed_and_confirmed(storage);
+
+    let all = storage.get_contracts().expect("Error retrieving contracts");
+    assert!(all.len() > 2);
+
+    let first_page = storage
+        .query_contracts(&ContractQuery {
+            limit: Some(2),
+            ..Default::default()
+        })
+        .expect("Error querying contracts");
+    assert_eq!(2, first_page.items.len());
+    assert!(first_page.next_cursor.is_some());
+
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            limit: Some(2),
+            ..Default::default()
+        },
+    );
+    let mut ids = contracts.iter().map(|c| c.get_id()).collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(all.len(), ids.len());
+    assert_eq!(all.len(), contracts.len());
+
+    let signed = storage
+        .get_signed_contracts()
+        .expect("Error retrieving signed contracts");
+    let contracts = query_all_contracts(
+        storage,
+        ContractQuery {
+            states: vec![ContractStateType::Signed],
+            limit: Some(1),
+            ..Default::default()
+        },
+    );
+    assert_eq!(signed.len(), contracts.len());
+}
+
+/// Checks that contracts replaced by one with a new id or deleted are not returned.
+pub fn query_contracts_excludes_replaced_and_deleted<S: Storage>(storage: &S) {
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Offered");
+    let offered_contract = deserialize_object(serialized);
+    let serialized = include_bytes!("../../dlc-sled-storage-provider/test_files/Accepted");
+    let accepted_contract = Contract::Accepted(deserialize_object(serialized));
+
+    storage
+        .create_contract(&offered_contract)
+        .expect("Error creating contract");
+    storage
+        .update_contract(&accepted_contract)
+        .expect("Error updating contract");
+
+    let contracts = query_all_contracts(storage, ContractQuery::default());
+    assert_eq!(1, contracts.len());
+    assert!(matches!(contracts[0], Contract::Accepted(_)));
+    assert!(query_all_contracts(
+        storage,
+        ContractQuery {
+            states: vec![ContractStateType::Offered],
+            ..Default::default()
+        },
+    )
+    .is_empty());
+
+    storage
+        .delete_contract(&accepted_contract.get_id())
+        .expect("Error deleting contract");
+    assert!(query_all_contracts(storage, ContractQuery::default()).is_empty());
+}
+
+/// Checks that channels are filtered by the type of their state.
+pub fn query_channels_filtered_by_state<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let signed = storage
+        .get_signed_channels(None)
+        .expect("Error retrieving signed channels");
+    let channels = query_all_channels(
+        storage,
+        ChannelQuery {
+            states: vec![ChannelStateType::Signed],
+            ..Default::default()
+        },
+    );
+    assert_eq!(signed.len(), channels.len());
+    assert!(channels.iter().all(|c| matches!(c, Channel::Signed(_))));
+
+    let channels = query_all_channels(
+        storage,
+        ChannelQuery {
+            states: vec![ChannelStateType::Offered],
+            ..Default::default()
+        },
+    );
+    assert_eq!(1, channels.len());
+
+    let all = storage.get_channels().expect("Error retrieving channels");
+    assert_eq!(
+        all.len(),
+        query_all_channels(storage, ChannelQuery::default()).len()
+    );
+}
+
+/// Checks that channels are filtered by counter party and reference id.
+pub fn query_channels_filtered_by_counter_party_and_reference_id<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let all = storage.get_channels().expect("Error retrieving channels");
+    let counter_party = all[0].get_counter_party_id();
+    let channels = query_all_channels(
+        storage,
+        ChannelQuery {
+            counter_party: Some(counter_party),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        all.iter()
+            .filter(|c| c.get_counter_party_id() == counter_party)
+            .count(),
+        channels.len()
+    );
+    assert!(channels
+        .iter()
+        .all(|c| c.get_counter_party_id() == counter_party));
+
+    let mut reference_ids = all
+        .iter()
+        .filter_map(|c| c.get_reference_id())
+        .collect::<Vec<_>>();
+    reference_ids.push([7; 32]);
+    for reference_id in reference_ids {
+        let channels = query_all_channels(
+            storage,
+            ChannelQuery {
+                reference_id: Some(reference_id),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            all.iter()
+                .filter(|c| c.get_reference_id() == Some(reference_id))
+                .count(),
+            channels.len()
+        );
+    }
+}
+
+/// Checks that paginating through the channels returns each of them once.
+pub fn query_channels_paginated<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let all = storage.get_channels().expect("Error retrieving channels");
+    let channels = query_all_channels(
+        storage,
+        ChannelQuery {
+            limit: Some(1),
+            ..Default::default()
+        },
+    );
+    let mut ids = channels.iter().map(|c| c.get_id()).collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(all.len(), ids.len());
+    assert_eq!(all.len(), channels.len());
+}
+
+/// Checks that deleted channels are not returned.
+pub fn query_channels_excludes_deleted<S: Storage>(storage: &S) {
+    insert_offered_and_signed_channels(storage);
+
+    let all = storage.get_channels().expect("Error retrieving channels");
+    let deleted = all[0].get_id();
+    storage
+        .delete_channel(&deleted)
+        .expect("Error deleting channel");
+
+    let channels = query_all_channels(storage, ChannelQuery::default());
+    assert_eq!(all.len() - 1, channels.len());
+    assert!(channels.iter().all(|c| c.get_id() != deleted));
+}
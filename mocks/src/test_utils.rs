@@ -0,0 +1,331 @@
+//! Fixtures shared by the tests of the [`Manager`], setting up two managers
+//! using the mocks of this crate and establishing contracts between them.
+
+use crate::memory_storage_provider::MemoryStorage;
+use crate::mock_blockchain::{MockBlockchain, MockBroadcaster};
+use crate::mock_oracle_provider::MockOracle;
+use crate::mock_time::{self, MockTime};
+use crate::mock_wallet::MockWallet;
+use dlc::{EnumerationPayout, Payout};
+use dlc_manager::contract::contract_input::{ContractInput, ContractInputInfo, OracleInput};
+use dlc_manager::contract::enum_descriptor::EnumDescriptor;
+use dlc_manager::contract::numerical_descriptor::NumericalDescriptor;
+use dlc_manager::contract::ContractDescriptor;
+use dlc_manager::events::Event;
+use dlc_manager::manager::{Manager, ManagerConfig};
+use dlc_manager::payout_curve::{
+    PayoutFunction, PayoutFunctionPiece, PayoutPoint, PolynomialPayoutCurvePiece, RoundingInterval,
+    RoundingIntervals,
+};
+use dlc_manager::{ContractId, Oracle};
+use dlc_messages::oracle_msgs::{
+    DigitDecompositionEventDescriptor, EnumEventDescriptor, EventDescriptor,
+};
+use dlc_messages::{Message, OnChainMessage};
+use dlc_trie::OracleNumericInfo;
+use secp256k1_zkp::{PublicKey, SecretKey, SECP256K1};
+use std::rc::Rc;
+
+pub const EVENT_ID: &str = "event";
+pub const EVENT_MATURITY: u32 = 1623133104;
+pub const COLLATERAL: u64 = 1000000;
+pub const NB_CONFIRMATIONS: u32 = 6;
+
+/// A manager using the mocks of this crate, sharing its blockchain with its
+/// counter party.
+pub type TestManager = Manager<
+    Rc<MockWallet>,
+    Rc<MockBlockchain<Rc<MockBroadcaster>>>,
+    Rc<MemoryStorage>,
+    Rc<MockOracle>,
+    Rc<MockTime>,
+    Rc<MockBlockchain<Rc<MockBroadcaster>>>,
+>;
+
+/// Returns the node id derived from a secret key made of the given byte.
+pub fn node_id(seed: u8) -> PublicKey {
+    PublicKey::from_secret_key(SECP256K1, &SecretKey::from_slice(&[seed; 32]).unwrap())
+}
+
+/// Returns an oracle announcing an event with outcomes "a" and "b", and
+/// attesting to `outcome` if given.
+pub fn get_oracle(outcome: Option<&str>) -> Rc<MockOracle> {
+    let mut oracle = MockOracle::new();
+    let event = EnumEventDescriptor {
+        outcomes: vec!["a".to_string(), "b".to_string()],
+    };
+    oracle.add_event(EVENT_ID, &EventDescriptor::EnumEvent(event), EVENT_MATURITY);
+    if let Some(outcome) = outcome {
+        oracle.add_attestation(EVENT_ID, &[outcome.to_string()]);
+    }
+    Rc::new(oracle)
+}
+
+/// Returns an oracle attesting to the given value, between 0 and 15, in
+/// binary.
+pub fn get_numerical_oracle(value: usize) -> Rc<MockOracle> {
+    let mut oracle = MockOracle::new();
+    let event = DigitDecompositionEventDescriptor {
+        base: 2,
+        is_signed: false,
+        unit: "sats/sec".to_string(),
+        precision: 0,
+        nb_digits: 4,
+    };
+    oracle.add_event(
+        EVENT_ID,
+        &EventDescriptor::DigitDecompositionEvent(event),
+        EVENT_MATURITY,
+    );
+    let outcomes: Vec<_> = (0..4)
+        .rev()
+        .map(|i| ((value >> i) & 1).to_string())
+        .collect();
+    oracle.add_attestation(EVENT_ID, &outcomes);
+    Rc::new(oracle)
+}
+
+/// Returns a shared blockchain and two managers using it, the first one
+/// being the one offering contracts and channels to the second one.
+pub fn get_counter_parties(
+    oracle: &Rc<MockOracle>,
+) -> (
+    Rc<MockBlockchain<Rc<MockBroadcaster>>>,
+    TestManager,
+    TestManager,
+) {
+    get_counter_parties_with_oracles(&[oracle.clone()], &[oracle.clone()])
+}
+
+/// Same as [`get_counter_parties`] but with each manager having its own view
+/// of the oracles.
+pub fn get_counter_parties_with_oracles(
+    alice_oracles: &[Rc<MockOracle>],
+    bob_oracles: &[Rc<MockOracle>],
+) -> (
+    Rc<MockBlockchain<Rc<MockBroadcaster>>>,
+    TestManager,
+    TestManager,
+) {
+    get_counter_parties_with_config(
+        alice_oracles,
+        bob_oracles,
+        ManagerConfig {
+            nb_confirmations: NB_CONFIRMATIONS,
+            ..Default::default()
+        },
+    )
+}
+
+/// Same as [`get_counter_parties_with_oracles`] but with the given
+/// configuration for both managers.
+pub fn get_counter_parties_with_config(
+    alice_oracles: &[Rc<MockOracle>],
+    bob_oracles: &[Rc<MockOracle>],
+    config: ManagerConfig,
+) -> (
+    Rc<MockBlockchain<Rc<MockBroadcaster>>>,
+    TestManager,
+    TestManager,
+) {
+    let blockchain = Rc::new(MockBlockchain::new(Rc::new(MockBroadcaster {})));
+    let create_manager = |oracles: &[Rc<MockOracle>]| {
+        let oracles = oracles
+            .iter()
+            .map(|x| (x.get_public_key(), x.clone()))
+            .collect();
+        Manager::new_with_config(
+            Rc::new(MockWallet::new(&blockchain, 10)),
+            blockchain.clone(),
+            Rc::new(MemoryStorage::new()),
+            oracles,
+            Rc::new(MockTime {}),
+            blockchain.clone(),
+            config,
+        )
+        .unwrap()
+    };
+    let alice = create_manager(alice_oracles);
+    let bob = create_manager(bob_oracles);
+
+    mock_time::set_time(EVENT_MATURITY as u64 - 1);
+
+    (blockchain, alice, bob)
+}
+
+/// Returns a contract paying the whole collateral to the offer party if
+/// "a" is attested, and to the accept party if "b" is.
+pub fn get_contract_input(oracle: &Rc<MockOracle>) -> ContractInput {
+    get_enum_contract_input(&[oracle.clone()], 1)
+}
+
+/// Same as [`get_contract_input`] but on a `threshold` of the given oracles.
+pub fn get_enum_contract_input(oracles: &[Rc<MockOracle>], threshold: u16) -> ContractInput {
+    let outcome_payouts = vec![
+        EnumerationPayout {
+            outcome: "a".to_string(),
+            payout: Payout {
+                offer: 2 * COLLATERAL,
+                accept: 0,
+            },
+        },
+        EnumerationPayout {
+            outcome: "b".to_string(),
+            payout: Payout {
+                offer: 0,
+                accept: 2 * COLLATERAL,
+            },
+        },
+    ];
+    ContractInput {
+        offer_collateral: COLLATERAL,
+        accept_collateral: COLLATERAL,
+        fee_rate: 2,
+        contract_infos: vec![ContractInputInfo {
+            contract_descriptor: ContractDescriptor::Enum(EnumDescriptor {
+                outcome_payouts,
+                oracle_outcome_mappings: None,
+            }),
+            oracles: OracleInput {
+                public_keys: oracles.iter().map(|x| x.get_public_key()).collect(),
+                event_id: EVENT_ID.to_string(),
+                event_ids: Vec::new(),
+                threshold,
+            },
+        }],
+    }
+}
+
+/// Returns a contract on a value between 0 and 15 attested in binary, with
+/// the payout of the offer party growing linearly with the value.
+pub fn get_numerical_contract_input(oracle: &MockOracle) -> ContractInput {
+    let payout_function =
+        PayoutFunction::new(vec![PayoutFunctionPiece::PolynomialPayoutCurvePiece(
+            PolynomialPayoutCurvePiece::new(vec![
+                PayoutPoint {
+                    event_outcome: 0,
+                    outcome_payout: 0,
+                    extra_precision: 0,
+                },
+                PayoutPoint {
+                    event_outcome: 15,
+                    outcome_payout: 2 * COLLATERAL,
+                    extra_precision: 0,
+                },
+            ])
+            .unwrap(),
+        )])
+        .unwrap();
+    ContractInput {
+        offer_collateral: COLLATERAL,
+        accept_collateral: COLLATERAL,
+        fee_rate: 2,
+        contract_infos: vec![ContractInputInfo {
+            contract_descriptor: ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function,
+                rounding_intervals: RoundingIntervals {
+                    intervals: vec![RoundingInterval {
+                        begin_interval: 0,
+                        rounding_mod: 1,
+                    }],
+                },
+                difference_params: None,
+                oracle_numeric_infos: OracleNumericInfo {
+                    base: 2,
+                    nb_digits: vec![4],
+                    oracle_bases: None,
+                },
+                outcome_params: None,
+            }),
+            oracles: OracleInput {
+                public_keys: vec![oracle.get_public_key()],
+                event_id: EVENT_ID.to_string(),
+                event_ids: Vec::new(),
+                threshold: 1,
+            },
+        }],
+    }
+}
+
+/// Has `alice` offer the contract to `bob` and exchanges the messages until
+/// both sign it, returning its id.
+pub fn establish_contract(
+    alice: &TestManager,
+    bob: &TestManager,
+    contract_input: &ContractInput,
+) -> ContractId {
+    let offer = alice
+        .send_offer(contract_input, node_id(2))
+        .expect("To create an offer");
+    bob.on_dlc_message(
+        &Message::OnChain(OnChainMessage::Offer(offer.clone())),
+        node_id(1),
+    )
+    .expect("To process the offer");
+    let (contract_id, _, accept) = bob
+        .accept_contract_offer(&offer.temporary_contract_id)
+        .expect("To accept the offer");
+    let sign = alice
+        .on_dlc_message(
+            &Message::OnChain(OnChainMessage::Accept(accept)),
+            node_id(2),
+        )
+        .expect("To process the accept")
+        .expect("To get a sign message");
+    bob.on_dlc_message(&sign, node_id(1))
+        .expect("To process the sign");
+
+    contract_id
+}
+
+/// Runs the periodic check of each of the given managers.
+pub fn periodic_check(managers: &[&TestManager]) {
+    for manager in managers {
+        manager
+            .periodic_check()
+            .expect("To be able to do the periodic check");
+    }
+}
+
+/// Checks that exactly the `expected` events are pending and that they are
+/// cleared once retrieved.
+pub fn assert_events(manager: &TestManager, expected: &[Event]) {
+    assert_eq!(expected, manager.get_and_clear_pending_events().as_slice());
+    assert!(manager.get_and_clear_pending_events().is_empty());
+}
+
+/// Checks that a single [`Event::ContractClosed`] event is pending for the
+/// given contract.
+pub fn assert_contract_closed_event(manager: &TestManager, contract_id: ContractId) {
+    let events = manager.get_and_clear_pending_events();
+    assert!(
+        matches!(
+            events.as_slice(),
+            [Event::ContractClosed { contract_id: id, .. }] if *id == contract_id
+        ),
+        "Unexpected events {:?}",
+        events
+    );
+    assert!(manager.get_and_clear_pending_events().is_empty());
+}
+
+/// Establishes a contract and confirms its fund transaction.
+pub fn get_confirmed_contract(
+    blockchain: &MockBlockchain<Rc<MockBroadcaster>>,
+    alice: &TestManager,
+    bob: &TestManager,
+    contract_input: &ContractInput,
+) -> ContractId {
+    let contract_id = establish_contract(alice, bob, contract_input);
+
+    periodic_check(&[alice, bob]);
+    assert_events(alice, &[]);
+    assert_events(bob, &[]);
+
+    blockchain.generate_blocks(NB_CONFIRMATIONS);
+    periodic_check(&[alice, bob]);
+    assert_events(alice, &[Event::ContractConfirmed { contract_id }]);
+    assert_events(bob, &[Event::ContractConfirmed { contract_id }]);
+
+    contract_id
+}
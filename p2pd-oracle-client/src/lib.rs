@@ -83,6 +83,21 @@ where
         .map_err(|e| dlc_manager::error::Error::OracleError(e.to_string()))
 }
 
+#[cfg(feature = "async")]
+async fn get_async<T>(path: &str) -> Result<T, DlcManagerError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    reqwest::get(path)
+        .await
+        .map_err(|x| {
+            dlc_manager::error::Error::IOError(std::io::Error::new(std::io::ErrorKind::Other, x))
+        })?
+        .json::<T>()
+        .await
+        .map_err(|e| dlc_manager::error::Error::OracleError(e.to_string()))
+}
+
 fn pubkey_path(host: &str) -> String {
     format!("{}{}", host, "oracle/publickey")
 }
@@ -124,6 +139,20 @@ impl P2PDOracleClient {
         let public_key = get::<PublicKeyResponse>(&path)?.public_key;
         Ok(P2PDOracleClient { host, public_key })
     }
+
+    fn to_oracle_attestation(&self, attestation: AttestationResponse) -> OracleAttestation {
+        let AttestationResponse {
+            event_id: _,
+            signatures,
+            values,
+        } = attestation;
+
+        OracleAttestation {
+            oracle_public_key: self.public_key,
+            signatures,
+            outcomes: values,
+        }
+    }
 }
 
 fn parse_event_id(event_id: &str) -> Result<(String, DateTime<Utc>), DlcManagerError> {
@@ -156,17 +185,35 @@ impl Oracle for P2PDOracleClient {
     ) -> Result<OracleAttestation, dlc_manager::error::Error> {
         let (asset_id, date_time) = parse_event_id(event_id)?;
         let path = attestation_path(&self.host, &asset_id, &date_time);
-        let AttestationResponse {
-            event_id: _,
-            signatures,
-            values,
-        } = get::<AttestationResponse>(&path)?;
+        let attestation = get::<AttestationResponse>(&path)?;
+        Ok(self.to_oracle_attestation(attestation))
+    }
+}
 
-        Ok(OracleAttestation {
-            oracle_public_key: self.public_key,
-            signatures,
-            outcomes: values,
-        })
+/// Native asynchronous implementation, not blocking the executor while waiting
+/// for the oracle server.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl dlc_manager::AsyncOracle for P2PDOracleClient {
+    fn get_public_key(&self) -> XOnlyPublicKey {
+        self.public_key
+    }
+
+    async fn get_announcement(
+        &self,
+        event_id: &str,
+    ) -> Result<OracleAnnouncement, DlcManagerError> {
+        let (asset_id, date_time) = parse_event_id(event_id)?;
+        let path = announcement_path(&self.host, &asset_id, &date_time);
+        let announcement = get_async(&path).await?;
+        Ok(announcement)
+    }
+
+    async fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, DlcManagerError> {
+        let (asset_id, date_time) = parse_event_id(event_id)?;
+        let path = attestation_path(&self.host, &asset_id, &date_time);
+        let attestation = get_async::<AttestationResponse>(&path).await?;
+        Ok(self.to_oracle_attestation(attestation))
     }
 }
 
@@ -249,4 +296,33 @@ mod tests {
             .get_attestation("btcusd1624943400")
             .expect("Error getting attestation");
     }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn get_attestation_async_test() {
+        let path: &str = &attestation_path(
+            "/",
+            "btcusd",
+            &DateTime::parse_from_rfc3339("2021-06-29T05:10:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        let _m = mock("GET", path)
+            .with_body(r#"{"eventId":"btcusd1624943400","signatures":[],"values":[]}"#)
+            .create();
+        // The blocking client used by `P2PDOracleClient::new` cannot be used
+        // within the runtime.
+        let client = P2PDOracleClient {
+            host: format!("{}/", mockito::server_url()),
+            public_key: "ce4b7ad2b45de01f0897aa716f67b4c2f596e54506431e693f898712fe7e9bf3"
+                .parse()
+                .unwrap(),
+        };
+
+        let attestation = dlc_manager::AsyncOracle::get_attestation(&client, "btcusd1624943400")
+            .await
+            .expect("Error getting attestation");
+
+        assert_eq!(client.public_key, attestation.oracle_public_key);
+    }
 }
@@ -9,6 +9,7 @@ use dlc_manager::channel::signed_channel::SignedChannelState;
 use dlc_manager::channel::signed_channel::SignedChannelStateType;
 use dlc_manager::contract::contract_input::ContractInput;
 use dlc_manager::contract::Contract;
+use dlc_manager::query::ContractQuery;
 use dlc_manager::Storage;
 use dlc_messages::ChannelMessage;
 use dlc_messages::Message as DlcMessage;
@@ -259,48 +260,63 @@ pub(crate) async fn poll_for_user_input(
                             .unwrap()
                             .periodic_check()
                             .expect("Error doing periodic check.");
-                        let contracts = manager_clone
-                            .lock()
-                            .unwrap()
-                            .get_store()
-                            .get_contracts()
-                            .expect("Error retrieving contract list.");
-                        for contract in contracts {
-                            let id = hex_str(&contract.get_id());
-                            match contract {
-                                Contract::Offered(_) => {
-                                    println!("Offered contract: {}", id);
-                                }
-                                Contract::Accepted(_) => {
-                                    println!("Accepted contract: {}", id);
-                                }
-                                Contract::Confirmed(_) => {
-                                    println!("Confirmed contract: {}", id);
-                                }
-                                Contract::Signed(_) => {
-                                    println!("Signed contract: {}", id);
-                                }
-                                Contract::Closed(closed) => {
-                                    println!("Closed contract: {}", id);
-                                    if let Some(attestations) = closed.attestations {
-                                        println!(
-                                            "Outcomes: {:?}",
-                                            attestations
-                                                .iter()
-                                                .map(|x| x.outcomes.clone())
-                                                .collect::<Vec<_>>()
-                                        );
+                        let mut query = ContractQuery {
+                            limit: Some(100),
+                            ..Default::default()
+                        };
+                        loop {
+                            let page = manager_clone
+                                .lock()
+                                .unwrap()
+                                .get_store()
+                                .query_contracts(&query)
+                                .expect("Error retrieving contract list.");
+                            for contract in page.items {
+                                let id = hex_str(&contract.get_id());
+                                match contract {
+                                    Contract::Offered(_) => {
+                                        println!("Offered contract: {}", id);
+                                    }
+                                    Contract::Accepted(_) => {
+                                        println!("Accepted contract: {}", id);
+                                    }
+                                    Contract::Confirmed(_) => {
+                                        println!("Confirmed contract: {}", id);
+                                    }
+                                    Contract::Signed(_) => {
+                                        println!("Signed contract: {}", id);
+                                    }
+                                    Contract::Closed(closed) => {
+                                        println!("Closed contract: {}", id);
+                                        if let Some(attestations) = closed.attestations {
+                                            println!(
+                                                "Outcomes: {:?}",
+                                                attestations
+                                                    .iter()
+                                                    .map(|x| x.outcomes.clone())
+                                                    .collect::<Vec<_>>()
+                                            );
+                                        }
+                                        println!("PnL: {} sats", closed.pnl)
+                                    }
+                                    Contract::Refunded(_) => {
+                                        println!("Refunded contract: {}", id);
+                                    }
+                                    Contract::FailedAccept(_) | Contract::FailedSign(_) => {
+                                        println!("Failed contract: {}", id);
+                                    }
+                                    Contract::Rejected(_) => println!("Rejected contract: {}", id),
+                                    Contract::PreClosed(_) => {
+                                        println!("Pre-closed contract: {}", id)
+                                    }
+                                    Contract::PreRefunded(_) => {
+                                        println!("Pre-refunded contract: {}", id)
                                     }
-                                    println!("PnL: {} sats", closed.pnl)
-                                }
-                                Contract::Refunded(_) => {
-                                    println!("Refunded contract: {}", id);
-                                }
-                                Contract::FailedAccept(_) | Contract::FailedSign(_) => {
-                                    println!("Failed contract: {}", id);
                                 }
-                                Contract::Rejected(_) => println!("Rejected contract: {}", id),
-                                Contract::PreClosed(_) => println!("Pre-closed contract: {}", id),
+                            }
+                            match page.next_cursor {
+                                Some(cursor) => query.cursor = Some(cursor),
+                                None => break,
                             }
                         }
                     })